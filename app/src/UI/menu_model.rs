@@ -21,11 +21,9 @@ pub struct MenuItem { pub label: String, pub action: Option<MenuAction> }
 #[derive(Clone, Debug)]
 pub struct MenuTop { pub label: String, pub action: Option<MenuAction>, pub submenu: Option<Vec<MenuItem>> }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct MenuState { pub open: bool, pub top_index: usize, pub submenu_index: Option<usize> }
 
-impl Default for MenuState { fn default() -> Self { Self { open: false, top_index: 0, submenu_index: None } } }
-
 pub struct MenuModel;
 
 impl MenuModel {
@@ -47,11 +45,11 @@ impl MenuModel {
 impl MenuState {
     pub fn selected_action(&self, model: &(Vec<MenuTop>, ())) -> Option<MenuAction> {
         model.0.get(self.top_index).and_then(|top| {
-            if let Some(si) = self.submenu_index { top.submenu.as_ref().and_then(|s| s.get(si)).and_then(|it| it.action.clone()) } else { None }
+            if let Some(si) = self.submenu_index { top.submenu.as_ref().and_then(|s| s.get(si)).and_then(|it| it.action) } else { None }
         })
     }
 
-    pub fn index_for_x(col: u16, width: u16, labels: &Vec<&str>) -> usize {
+    pub fn index_for_x(col: u16, width: u16, labels: &[&str]) -> usize {
         let n = labels.len();
         if n == 0 { return 0; }
         let step = (width as usize).saturating_div(n.max(1));
@@ -16,6 +16,10 @@ pub mod widgets {
     pub mod file_list;
     pub mod preview;
     pub mod progress_bar;
+    pub mod jobs;
+    pub mod conflict;
+    pub mod trash;
+    pub mod batch_rename;
     pub mod panel;
 }
 
@@ -26,14 +26,45 @@ pub fn compute_scrollbar_thumb(height: u16, total: usize, visible: usize, offset
     let h = height as u32; let tot = total as u32; let vis = visible as u32; let off = offset as u32;
     let mut size = (vis * h / tot) as u16; if size == 0 { size = 1; }
     let denom = tot.saturating_sub(vis);
-    let mut start = if denom == 0 { 0 } else { ((off * (h - size as u32)) / denom) as u16 };
+    let mut start = (off * (h - size as u32)).checked_div(denom).unwrap_or(0) as u16;
     if start as u32 + size as u32 > h { start = h.saturating_sub(size as u32) as u16; }
     (start, size)
 }
 
-use crate::app::Entry;
+use crate::app::{Entry, EntryKind};
 pub fn format_entry_line(e: &Entry) -> String {
     let time = e.modified.as_ref().map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| "-".into());
-    let size = if e.is_dir { "<dir>".into() } else { format!("{}", e.size) };
-    format!("{}  {}  {}", e.name, size, time)
+    let size = if e.is_dir || e.points_to_dir { "<dir>".into() } else { format!("{}", e.size) };
+    let name = match &e.link_target {
+        Some(target) => format!("{} -> {}", display_name(e), target.display()),
+        None => display_name(e),
+    };
+    format!("{}  {}  {}", name, size, time)
+}
+
+/// The name shown for `e` in the listing, after the type marker Midnight
+/// Commander uses (see `type_marker`).
+pub fn display_name(e: &Entry) -> String {
+    match type_marker(e) {
+        Some(marker) => format!("{}{}", marker, e.name),
+        None => e.name.clone(),
+    }
+}
+
+/// `~` for a symlink to a directory, `!` for a broken symlink, `@` for
+/// any other symlink, `|` for a FIFO, `=` for a socket, `-` and `+` for
+/// character and block devices, `*` for an executable file. Directories
+/// and plain files have none.
+pub fn type_marker(e: &Entry) -> Option<char> {
+    match e.kind {
+        EntryKind::Symlink if e.broken_link => Some('!'),
+        EntryKind::Symlink if e.points_to_dir => Some('~'),
+        EntryKind::Symlink => Some('@'),
+        EntryKind::Fifo => Some('|'),
+        EntryKind::Socket => Some('='),
+        EntryKind::CharDevice => Some('-'),
+        EntryKind::BlockDevice => Some('+'),
+        EntryKind::File if e.unix_mode.is_some_and(|m| m & 0o111 != 0) => Some('*'),
+        EntryKind::File | EntryKind::Dir => None,
+    }
 }
@@ -72,4 +72,37 @@ pub fn ui(f: &mut Frame, app: &CoreApp) {
         }
     }
     crate::ui::widgets::footer::render(f, chunks[chunks.len() - 1], &state, &theme);
+
+    if let crate::app::Mode::Jobs { selected } = &app.mode {
+        crate::ui::widgets::jobs::render(f, chunks[2], &app.jobs.list(), *selected);
+    }
+
+    if let crate::app::Mode::Trash { items, selected } = &app.mode {
+        crate::ui::widgets::trash::render(f, chunks[2], items, *selected);
+    }
+
+    if let crate::app::Mode::BatchRename { fields, focus, preview, scroll } = &app.mode {
+        let area = crate::ui::modal::centered_rect(size, size.width.saturating_sub(4), size.height.saturating_sub(2));
+        crate::ui::widgets::batch_rename::render(f, area, fields, *focus, preview, *scroll);
+    }
+
+    if let crate::app::Mode::EditNames { text, scroll, .. } = &app.mode {
+        let area = crate::ui::modal::centered_rect(size, size.width.saturating_sub(4), size.height.saturating_sub(2));
+        crate::ui::widgets::conflict::render_diff(f, area, "Apply these changes? Enter apply", text, *scroll);
+    }
+
+    if let crate::app::Mode::Progress { title, processed, total, message, bytes, .. } = &app.mode {
+        let area = crate::ui::modal::centered_rect(size, 70, crate::ui::widgets::progress_bar::OPERATION_HEIGHT);
+        crate::ui::widgets::progress_bar::render_operation(f, area, title, message, *processed, *total, bytes.as_ref());
+    }
+
+    if let crate::app::Mode::Conflict { conflict, selected, apply_all } = &app.mode {
+        let area = crate::ui::modal::centered_rect(size, 90, crate::ui::widgets::conflict::HEIGHT);
+        crate::ui::widgets::conflict::render(f, area, conflict, *selected, *apply_all);
+    }
+
+    if let crate::app::Mode::Diff { title, text, scroll, .. } = &app.mode {
+        let area = crate::ui::modal::centered_rect(size, size.width.saturating_sub(4), size.height.saturating_sub(2));
+        crate::ui::widgets::conflict::render_diff(f, area, title, text, *scroll);
+    }
 }
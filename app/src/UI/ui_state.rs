@@ -15,29 +15,8 @@ pub struct UIState {
     pub menu_sub_selected: Option<usize>,
     pub preview_text: Option<String>,
     pub progress: u16,
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn from_core_maps_menu_state() {
-        // Construct a minimal App and manipulate menu state so we can
-        // verify the UIState mapping behaves as expected.
-        let mut app = crate::app::core::App::with_options(&crate::app::StartOptions::default()).expect("create app");
-        app.menu_index = 2;
-        app.menu_focused = true;
-        app.menu_state.open = true;
-        app.menu_state.submenu_index = Some(1);
-
-        let state = UIState::from_core(&app);
-
-        assert_eq!(state.menu_selected, 2);
-        assert!(state.menu_focused);
-        assert!(state.menu_open);
-        assert_eq!(state.menu_sub_selected, Some(1));
-    }
+    /// Footer summary of unfinished background jobs, if any.
+    pub jobs: Option<String>,
 }
 
 impl UIState {
@@ -53,6 +32,7 @@ impl UIState {
             menu_sub_selected: None,
             preview_text: Some("preview".into()),
             progress: 25,
+            jobs: None,
         }
     }
 
@@ -60,42 +40,42 @@ impl UIState {
 
     /// Build a UIState view-model from the core App so UI rendering shows real data.
     pub fn from_core(app: &crate::app::core::App) -> Self {
-        use crate::ui::panels::format_entry_line;
+        use crate::ui::panels::{display_name, format_entry_line};
 
         // Build left/right lists depending on each panel's display mode.
         let left_list = match app.left.mode {
-            crate::app::core::panel::PanelMode::Full => app.left.entries.iter().map(|e| format_entry_line(e)).collect(),
-            crate::app::core::panel::PanelMode::Brief => app.left.entries.iter().map(|e| e.name.clone()).collect(),
-            crate::app::core::panel::PanelMode::QuickView => app.left.entries.iter().map(|e| format!("{}  {}", e.name, if e.is_dir { "<dir>".to_string() } else { format!("{}", e.size) })).collect(),
+            crate::app::core::panel::PanelMode::Full => app.left.entries.iter().map(format_entry_line).collect(),
+            crate::app::core::panel::PanelMode::Brief => app.left.entries.iter().map(display_name).collect(),
+            crate::app::core::panel::PanelMode::QuickView => app.left.entries.iter().map(|e| format!("{}  {}", display_name(e), if e.is_dir { "<dir>".to_string() } else { format!("{}", e.size) })).collect(),
             crate::app::core::panel::PanelMode::Tree => {
                 // Build a shallow tree view (max depth 3).
                 match app.left.tree_entries(3) {
-                    Ok(vec) => vec.into_iter().map(|(e, d)| format!("{}{}", "  ".repeat(d), e.name)).collect(),
-                    Err(_) => app.left.entries.iter().map(|e| e.name.clone()).collect(),
+                    Ok(vec) => vec.into_iter().map(|(e, d)| format!("{}{}", "  ".repeat(d), display_name(&e))).collect(),
+                    Err(_) => app.left.entries.iter().map(display_name).collect(),
                 }
             }
             crate::app::core::panel::PanelMode::Flat => {
                 match app.left.flat_entries(3) {
-                    Ok(vec) => vec.into_iter().map(|e| e.name).collect(),
-                    Err(_) => app.left.entries.iter().map(|e| e.name.clone()).collect(),
+                    Ok(vec) => vec.iter().map(display_name).collect(),
+                    Err(_) => app.left.entries.iter().map(display_name).collect(),
                 }
             }
         };
 
         let right_list = match app.right.mode {
-            crate::app::core::panel::PanelMode::Full => app.right.entries.iter().map(|e| format_entry_line(e)).collect(),
-            crate::app::core::panel::PanelMode::Brief => app.right.entries.iter().map(|e| e.name.clone()).collect(),
-            crate::app::core::panel::PanelMode::QuickView => app.right.entries.iter().map(|e| format!("{}  {}", e.name, if e.is_dir { "<dir>".to_string() } else { format!("{}", e.size) })).collect(),
+            crate::app::core::panel::PanelMode::Full => app.right.entries.iter().map(format_entry_line).collect(),
+            crate::app::core::panel::PanelMode::Brief => app.right.entries.iter().map(display_name).collect(),
+            crate::app::core::panel::PanelMode::QuickView => app.right.entries.iter().map(|e| format!("{}  {}", display_name(e), if e.is_dir { "<dir>".to_string() } else { format!("{}", e.size) })).collect(),
             crate::app::core::panel::PanelMode::Tree => {
                 match app.right.tree_entries(3) {
-                    Ok(vec) => vec.into_iter().map(|(e, d)| format!("{}{}", "  ".repeat(d), e.name)).collect(),
-                    Err(_) => app.right.entries.iter().map(|e| e.name.clone()).collect(),
+                    Ok(vec) => vec.into_iter().map(|(e, d)| format!("{}{}", "  ".repeat(d), display_name(&e))).collect(),
+                    Err(_) => app.right.entries.iter().map(display_name).collect(),
                 }
             }
             crate::app::core::panel::PanelMode::Flat => {
                 match app.right.flat_entries(3) {
-                    Ok(vec) => vec.into_iter().map(|e| e.name).collect(),
-                    Err(_) => app.right.entries.iter().map(|e| e.name.clone()).collect(),
+                    Ok(vec) => vec.iter().map(display_name).collect(),
+                    Err(_) => app.right.entries.iter().map(display_name).collect(),
                 }
             }
         };
@@ -114,6 +94,7 @@ impl UIState {
                 }
             },
             progress: 0,
+            jobs: crate::ui::widgets::jobs::indicator(&app.jobs.list()),
             menu_selected: app.menu_index,
             menu_focused: app.menu_focused,
             menu_open: app.menu_state.open,
@@ -121,3 +102,26 @@ impl UIState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_core_maps_menu_state() {
+        // Construct a minimal App and manipulate menu state so we can
+        // verify the UIState mapping behaves as expected.
+        let mut app = crate::app::core::App::with_options(&crate::app::StartOptions::default()).expect("create app");
+        app.menu_index = 2;
+        app.menu_focused = true;
+        app.menu_state.open = true;
+        app.menu_state.submenu_index = Some(1);
+
+        let state = UIState::from_core(&app);
+
+        assert_eq!(state.menu_selected, 2);
+        assert!(state.menu_focused);
+        assert!(state.menu_open);
+        assert_eq!(state.menu_sub_selected, Some(1));
+    }
+}
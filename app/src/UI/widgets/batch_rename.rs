@@ -0,0 +1,74 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table},
+    Frame,
+};
+use crate::fs_op::batch_rename::PreviewRow;
+
+/// Labels of the dialog's fields, in `Mode::BatchRename::fields` order.
+pub const FIELDS: [&str; 3] = ["Mask", "Find", "Replace"];
+
+/// Mask tokens, listed under the fields.
+pub const TOKENS: &str = "* name  [N] name without extension  [E] extension  [C] [C:3] [C10+5] counter  [Y][M][D][h][m][s] modified  [U][L][F][n] case  [[ literal [";
+
+/// Key hints shown on the last line.
+pub const HINTS: &str = "Tab next field · Up/Down scroll · Enter rename · Esc cancel";
+
+/// Render the batch rename dialog: the three fields with the focused one
+/// highlighted, the token reference, then the preview table of old and new
+/// names from row `scroll` on, with flagged rows in red and their problem
+/// in the last column. A template that does not parse replaces the table
+/// with its error.
+pub fn render(f: &mut Frame, area: Rect, fields: &[String; 3], focus: usize, preview: &Result<Vec<PreviewRow>, String>, scroll: usize) {
+    let colors = crate::ui::colors::current();
+    let title = match preview {
+        Ok(rows) => format!("Rename {} items", rows.len()),
+        Err(_) => "Rename".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title).style(colors.panel_block_style);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(2), Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)])
+        .split(inner);
+
+    let field_lines: Vec<Line> = FIELDS
+        .iter()
+        .zip(fields)
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let style = if i == focus { colors.panel_selected_style } else { Style::default() };
+            Line::from(vec![Span::raw(format!("{:<8} ", format!("{}:", label))), Span::styled(format!("{}_", value), style)])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(field_lines), parts[0]);
+    f.render_widget(Paragraph::new(TOKENS).style(Style::default().fg(Color::DarkGray)), parts[1]);
+
+    let status = match preview {
+        Ok(rows) => {
+            let flagged = rows.iter().filter(|r| r.problem.is_some()).count();
+            let table_rows: Vec<Row> = rows
+                .iter()
+                .skip(scroll)
+                .map(|r| {
+                    let style = if r.problem.is_some() { Style::default().fg(Color::Red) } else { Style::default() };
+                    Row::new(vec![r.old.clone(), r.new.clone(), r.problem.clone().unwrap_or_default()]).style(style)
+                })
+                .collect();
+            let table = Table::new(table_rows, [Constraint::Percentage(40), Constraint::Percentage(40), Constraint::Percentage(20)])
+                .header(Row::new(vec!["Old name", "New name", ""]).style(colors.header_style));
+            f.render_widget(table, parts[2]);
+            match flagged {
+                0 => String::new(),
+                n => format!("{} name(s) flagged; fix them to rename", n),
+            }
+        }
+        Err(e) => e.clone(),
+    };
+    f.render_widget(Paragraph::new(status).style(Style::default().fg(Color::Red)), parts[3]);
+    f.render_widget(Paragraph::new(HINTS), parts[4]);
+}
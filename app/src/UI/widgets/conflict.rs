@@ -0,0 +1,108 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table},
+    Frame,
+};
+use crate::app::types::Entry;
+use crate::fs_op::permissions::format_unix_rwx;
+use crate::runner::progress::{Conflict, ConflictPolicy};
+use crate::ui::widgets::progress_bar::format_bytes;
+
+/// Buttons of the conflict dialog: one per `ConflictPolicy::CHOICES`, then
+/// Cancel, then Compare when both sides are files.
+pub fn buttons(conflict: &Conflict) -> Vec<&'static str> {
+    let mut buttons: Vec<&'static str> = ConflictPolicy::CHOICES.iter().map(|p| p.label()).collect();
+    buttons.push("Cancel");
+    if conflict.src.is_some() && conflict.both_files() {
+        buttons.push("Compare");
+    }
+    buttons
+}
+
+/// The comparison table: one `[label, source, existing]` row for size,
+/// modification time, permissions and owner, and a last row saying
+/// whether the contents are the same.
+pub fn rows(conflict: &Conflict) -> Vec<[String; 3]> {
+    let side = |entry: &Option<Entry>, field: fn(&Entry) -> String| entry.as_ref().map(field).unwrap_or_else(|| "-".to_string());
+    let size = |e: &Entry| if e.is_dir { "directory".to_string() } else { format_bytes(e.size) };
+    let modified = |e: &Entry| e.modified.map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| "-".to_string());
+    let mode = |e: &Entry| format_unix_rwx(e.unix_mode);
+    let owner = |e: &Entry| match (&e.owner, &e.group) {
+        (Some(owner), Some(group)) => format!("{}:{}", owner, group),
+        _ => match (e.uid, e.gid) {
+            (Some(uid), Some(gid)) => format!("{}:{}", uid, gid),
+            _ => "-".to_string(),
+        },
+    };
+    let mut rows = Vec::new();
+    for (label, field) in [("Size", size as fn(&Entry) -> String), ("Modified", modified), ("Permissions", mode), ("Owner", owner)] {
+        rows.push([label.to_string(), side(&conflict.src_entry, field), side(&conflict.target_entry, field)]);
+    }
+    let content = match conflict.same_content {
+        Some(true) => "identical",
+        Some(false) => "different",
+        None => "not compared",
+    };
+    rows.push(["Content".to_string(), content.to_string(), String::new()]);
+    rows
+}
+
+/// Rows needed by `render`, borders included.
+pub const HEIGHT: u16 = 14;
+
+/// Render the conflict dialog: both paths, the comparison table (see
+/// `rows`), the "apply to all" toggle and the buttons with the selected
+/// one in brackets.
+pub fn render(f: &mut Frame, area: Rect, conflict: &Conflict, selected: usize, apply_all: bool) {
+    let colors = crate::ui::colors::current();
+    let block = Block::default().borders(Borders::ALL).title("File exists").style(colors.panel_block_style);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Length(6), Constraint::Min(0), Constraint::Length(1), Constraint::Length(2)])
+        .split(inner);
+
+    let source = conflict.src.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "(new file)".to_string());
+    let paths = format!("Source:   {}\nExisting: {}", source, conflict.target.display());
+    f.render_widget(Paragraph::new(paths), parts[0]);
+
+    let header = Row::new(["", "Source", "Existing"]).style(colors.panel_selected_style);
+    let table = Table::new(rows(conflict).into_iter().map(Row::new), [Constraint::Length(12), Constraint::Percentage(45), Constraint::Percentage(45)]).header(header);
+    f.render_widget(table, parts[1]);
+
+    let toggle = format!("[{}] apply to all conflicts (a)", if apply_all { "x" } else { " " });
+    f.render_widget(Paragraph::new(toggle), parts[3]);
+
+    let labels: Vec<String> = buttons(conflict)
+        .iter()
+        .enumerate()
+        .map(|(i, b)| if i == selected { format!("[{}]", b) } else { b.to_string() })
+        .collect();
+    f.render_widget(Paragraph::new(labels.join("  ")).wrap(ratatui::widgets::Wrap { trim: true }), parts[4]);
+}
+
+/// Render a unified diff with added lines in green and removed lines in
+/// red, starting at line `scroll`.
+pub fn render_diff(f: &mut Frame, area: Rect, title: &str, text: &str, scroll: usize) {
+    let colors = crate::ui::colors::current();
+    let block = Block::default().borders(Borders::ALL).title(format!("{} · Esc close", title)).style(colors.panel_block_style);
+    let lines: Vec<Line> = text
+        .lines()
+        .skip(scroll)
+        .map(|line| {
+            let style = match line.chars().next() {
+                Some('+') if !line.starts_with("+++") => Style::default().fg(Color::Green),
+                Some('-') if !line.starts_with("---") => Style::default().fg(Color::Red),
+                Some('@') => Style::default().fg(Color::Cyan),
+                _ => Style::default(),
+            };
+            Line::from(Span::styled(line.to_string(), style))
+        })
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -3,7 +3,11 @@ use crate::ui::{UIState, Theme};
 use crate::ui::colors::current as current_colors;
 
 pub fn render(f: &mut Frame, area: Rect, state: &UIState, _theme: &Theme) {
-    let content = format!("Progress: {}% | {} items", state.progress, state.left_list.len());
+    let mut content = format!("Progress: {}% | {} items", state.progress, state.left_list.len());
+    if let Some(jobs) = &state.jobs {
+        content.push_str(" | ");
+        content.push_str(jobs);
+    }
     let colors = current_colors();
     let p = Paragraph::new(content).block(Block::default().borders(Borders::ALL).style(colors.footer_style));
     f.render_widget(p, area);
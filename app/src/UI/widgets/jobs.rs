@@ -0,0 +1,66 @@
+use ratatui::{layout::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph}, Frame};
+use crate::runner::job_queue::{JobId, JobKind, JobState, JobStatus};
+
+/// Key hints shown under the job list.
+pub const HINTS: &str = "Enter follow · p pause · c cancel · r retry · f retry failed · +/- move · Esc close";
+
+/// Render the jobs view: one row per job (see `format_row`) with the
+/// selected one highlighted, and the key hints on the last line.
+pub fn render(f: &mut Frame, area: Rect, jobs: &[(JobId, JobKind, JobState)], selected: usize) {
+    let colors = crate::ui::colors::current();
+    let block = Block::default().borders(Borders::ALL).title("Jobs").style(colors.panel_block_style);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if jobs.is_empty() {
+        f.render_widget(Paragraph::new("No jobs"), rows[0]);
+    } else {
+        let items: Vec<ListItem> = jobs.iter().map(|(_, kind, state)| ListItem::new(format_row(kind, state))).collect();
+        let list = List::new(items).highlight_style(colors.panel_selected_style);
+        let mut state = ListState::default();
+        state.select(Some(selected.min(jobs.len() - 1)));
+        f.render_stateful_widget(list, rows[0], &mut state);
+    }
+    f.render_widget(Paragraph::new(HINTS), rows[1]);
+}
+
+/// One row of the jobs view, e.g.
+/// `Running    42%  Copy 3 items to /backup  Copying /src/a.iso`, followed
+/// by ` · 2 failed` once items have been skipped after failing.
+pub fn format_row(kind: &JobKind, state: &JobState) -> String {
+    let detail = match &state.status {
+        JobStatus::Failed(e) => e.as_str(),
+        _ => state.message.as_str(),
+    };
+    let mut row = format!("{:<9} {:>3}%  {}  {}", state.status.label(), state.progress, kind.summary(), detail);
+    if !state.failures.is_empty() {
+        row.push_str(&format!(" · {} failed", state.failures.len()));
+    }
+    row
+}
+
+/// Compact footer summary of unfinished jobs, for example
+/// `Jobs: 3 active · 45% · 1 paused · 1 waiting`; `None` when every job
+/// has finished. "Waiting" jobs need a conflict or failed-item decision.
+pub fn indicator(jobs: &[(JobId, JobKind, JobState)]) -> Option<String> {
+    let active: Vec<&JobState> = jobs.iter().map(|(_, _, s)| s).filter(|s| !s.status.is_finished()).collect();
+    if active.is_empty() {
+        return None;
+    }
+    let percent = active.iter().map(|s| s.progress as usize).sum::<usize>() / active.len();
+    let mut out = format!("Jobs: {} active · {}%", active.len(), percent);
+    let paused = active.iter().filter(|s| s.status == JobStatus::Paused).count();
+    if paused > 0 {
+        out.push_str(&format!(" · {} paused", paused));
+    }
+    let waiting = active.iter().filter(|s| matches!(s.message.as_str(), "Conflict" | "Error")).count();
+    if waiting > 0 {
+        out.push_str(&format!(" · {} waiting", waiting));
+    }
+    Some(out)
+}
@@ -1,7 +1,88 @@
-use ratatui::{layout::Rect, widgets::{Block, Gauge, Borders}, Frame};
+use ratatui::{layout::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Gauge, Paragraph}, Frame};
+use crate::runner::progress::ByteProgress;
 use crate::ui::UIState;
 
 pub fn render(f: &mut Frame, area: Rect, state: &UIState) {
-    let g = Gauge::default().block(Block::default().borders(Borders::ALL)).percent(state.progress as u16);
+    let g = Gauge::default().block(Block::default().borders(Borders::ALL)).percent(state.progress);
     f.render_widget(g, area);
 }
+
+/// Rows needed by `render_operation`, borders included.
+pub const OPERATION_HEIGHT: u16 = 7;
+
+/// Render the dialog for a running file operation: the current message, a
+/// gauge for the current file and one for the whole operation, and a
+/// summary line with counts, throughput and ETA (see `summary`).
+///
+/// Without byte counters the overall gauge follows the item counts and the
+/// file gauge stays empty.
+pub fn render_operation(f: &mut Frame, area: Rect, title: &str, message: &str, processed: usize, total: usize, bytes: Option<&ByteProgress>) {
+    let block = Block::default().borders(Borders::ALL).title(title.to_string());
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    f.render_widget(Paragraph::new(message.to_string()), rows[0]);
+
+    let file_pct = bytes.and_then(|b| b.file_percent()).unwrap_or(0.0);
+    let file_label = match bytes {
+        Some(b) if b.file_total > 0 => format!("file {} / {}", format_bytes(b.file_done), format_bytes(b.file_total)),
+        _ => "file".to_string(),
+    };
+    f.render_widget(Gauge::default().ratio(file_pct / 100.0).label(file_label), rows[1]);
+
+    let total_pct = bytes
+        .and_then(|b| b.percent())
+        .or_else(|| (total > 0).then(|| processed.min(total) as f64 * 100.0 / total as f64))
+        .unwrap_or(0.0);
+    f.render_widget(Gauge::default().ratio(total_pct / 100.0).label(format!("total {:.0}%", total_pct)), rows[2]);
+
+    f.render_widget(Paragraph::new(summary(processed, total, bytes)), rows[3]);
+}
+
+/// One-line summary of an operation's progress, for example
+/// `3/10 items · 1.2 GiB / 40.0 GiB · 85.3 MiB/s · ETA 7m 42s`.
+/// While the pre-scan runs it reports what has been found so far.
+pub fn summary(processed: usize, total: usize, bytes: Option<&ByteProgress>) -> String {
+    let items = format!("{}/{} items", processed, total);
+    let Some(b) = bytes else { return items };
+    if b.scanning {
+        return format!("Scanning · {} found", format_bytes(b.total));
+    }
+    let mut parts = vec![items, format!("{} / {}", format_bytes(b.done), format_bytes(b.total))];
+    if b.rate > 0 {
+        parts.push(format!("{}/s", format_bytes(b.rate)));
+    }
+    if let Some(eta) = b.eta_secs {
+        parts.push(format!("ETA {}", format_eta(eta)));
+    }
+    parts.join(" · ")
+}
+
+/// Format a byte count with binary units: `512 B`, `1.5 KiB`, `40.0 GiB`.
+pub fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut value = n as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Format a duration in seconds compactly: `42s`, `7m 42s`, `1h 05m`.
+pub fn format_eta(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {:02}s", s / 60, s % 60),
+        s => format!("{}h {:02}m", s / 3600, (s % 3600) / 60),
+    }
+}
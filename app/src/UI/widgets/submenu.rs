@@ -4,7 +4,7 @@ use crate::ui::colors::current as current_colors;
 /// Render a small vertical submenu inside the header area when a top label
 /// is open. The submenu will display items stacked top->down and highlight
 /// the selected submenu index when present.
-pub fn render(f: &mut Frame, area: Rect, labels: &Vec<String>, selected: Option<usize>) {
+pub fn render(f: &mut Frame, area: Rect, labels: &[String], selected: Option<usize>) {
     if labels.is_empty() { return; }
 
     // Render the submenu as a simple list inside the supplied area. If the
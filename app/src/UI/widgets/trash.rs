@@ -0,0 +1,37 @@
+use ratatui::{layout::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph}, Frame};
+use crate::fs_op::trash::TrashedItem;
+
+/// Key hints shown under the trash list.
+pub const HINTS: &str = "Enter/r restore · x delete permanently · E empty trash · Esc close";
+
+/// Render the trash view: one row per item (see `format_row`) with the
+/// selected one highlighted, and the key hints on the last line.
+pub fn render(f: &mut Frame, area: Rect, items: &[TrashedItem], selected: usize) {
+    let colors = crate::ui::colors::current();
+    let block = Block::default().borders(Borders::ALL).title("Trash").style(colors.panel_block_style);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if items.is_empty() {
+        f.render_widget(Paragraph::new("Trash is empty"), rows[0]);
+    } else {
+        let list_items: Vec<ListItem> = items.iter().map(|item| ListItem::new(format_row(item))).collect();
+        let list = List::new(list_items).highlight_style(colors.panel_selected_style);
+        let mut state = ListState::default();
+        state.select(Some(selected.min(items.len() - 1)));
+        f.render_stateful_widget(list, rows[0], &mut state);
+    }
+    f.render_widget(Paragraph::new(HINTS), rows[1]);
+}
+
+/// One row of the trash view: when the item was deleted and where it
+/// came from, e.g. `2024-05-01 14:03  /home/me/notes.txt`.
+pub fn format_row(item: &TrashedItem) -> String {
+    let deleted = item.deleted.map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| "-".repeat(16));
+    format!("{}  {}", deleted, item.original.display())
+}
@@ -35,6 +35,6 @@ pub struct StartOptions {
 
 pub use core::panel::Panel;
 pub use core::App;
-pub use types::{Action, Entry, InputKind, Mode, Side, SortKey};
+pub use types::{Action, Entry, EntryKind, InputKind, Mode, Side, SortKey};
 // Deprecated compatibility shim: keep `crate::app::path` working for older code/tests.
 pub use crate::fs_op::path;
@@ -37,5 +37,7 @@ pub(crate) fn with_cwd(cwd: PathBuf) -> App {
         drag_start: None,
         drag_current: None,
         drag_button: None,
+        jobs: Default::default(),
+        op_job: None,
     }
 }
@@ -6,7 +6,7 @@
 
 use std::io;
 
-use super::{init, App, Panel, Mode, Side, SortKey};
+use super::{init, App, Mode, Side, SortKey};
 
 impl App {
     // Helper: refresh only the active panel
@@ -24,9 +24,10 @@ impl App {
     /// Construct an App instance with default initial values but without
     /// performing filesystem I/O. This is useful for tests or callers that
     /// want to initialise state and control when `refresh` runs.
-    // `with_cwd` moved to `app::core::init` to be reusable across core
-    // submodules and tests. Use `super::init::with_cwd` when constructing
-    // an App from a known working directory.
+    pub fn with_cwd(cwd: std::path::PathBuf) -> Self {
+        init::with_cwd(cwd)
+    }
+
     /// Create an App with explicit startup options (for example a start
     /// directory or initial mouse setting). This mirrors `new` but uses
     /// `StartOptions` when provided so callers can control initial state
@@ -37,30 +38,7 @@ impl App {
         } else {
             std::env::current_dir()?
         };
-        let mut app = App {
-            left: Panel::new(cwd.clone()),
-            right: Panel::new(cwd),
-            active: Side::Left,
-            mode: Mode::Normal,
-            sort: SortKey::Name,
-            sort_order: crate::app::types::SortOrder::Ascending,
-            menu_index: 0,
-            menu_focused: false,
-            menu_state: crate::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-            command_line: None,
-            settings: crate::app::settings::write_settings::Settings::default(),
-            op_progress_rx: None,
-            op_cancel_flag: None,
-            op_decision_tx: None,
-            last_mouse_click_time: None,
-            last_mouse_click_pos: None,
-            drag_active: false,
-            drag_start: None,
-            drag_current: None,
-            drag_button: None,
-        };
+        let mut app = init::with_cwd(cwd);
         // Apply any immediate overrides requested by CLI options. Persisted
         // settings (loaded later) will be applied afterwards; callers that
         // want CLI to override persisted settings should reapply after
@@ -98,32 +76,78 @@ impl App {
         // most-recent one. If the channel closes we clear the receiver.
         if let Some(rx) = self.op_progress_rx.as_ref() {
             let mut last: Option<crate::runner::progress::ProgressUpdate> = None;
-            while let Ok(update) = rx.try_recv() {
-                last = Some(update);
+            let mut disconnected = false;
+            loop {
+                match rx.try_recv() {
+                    Ok(update) => last = Some(update),
+                    Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        disconnected = true;
+                        break;
+                    }
+                }
             }
 
-            // If channel is closed, ensure receiver is cleared and return.
-            if let Err(std::sync::mpsc::TryRecvError::Disconnected) = rx.try_recv() {
+            // If the channel closed without a last word, clear the receiver
+            // and return. A final update sent just before the worker hung
+            // up is still handled below.
+            if disconnected && last.is_none() {
                 self.op_progress_rx = None;
+                self.op_job = None;
                 return;
             }
 
             if let Some(update) = last {
-                if let Some(conflict_path) = update.conflict {
+                if let Some(conflict) = update.conflict {
                     self.mode = Mode::Conflict {
-                        path: conflict_path,
+                        conflict: Box::new(conflict),
                         selected: 0,
                         apply_all: false,
                     };
                     return;
                 }
 
+                if let Some(item) = update.failed {
+                    self.mode = Mode::ItemError { item, selected: 0 };
+                    return;
+                }
+
                 if update.done {
                     self.op_progress_rx = None;
                     self.op_cancel_flag = None;
                     self.op_decision_tx = None;
-
-                    if let Some(err_msg) = update.error {
+                    let job = self.op_job.take();
+                    let state = job.and_then(|id| self.jobs.state(id));
+                    let verified = state.as_ref().and_then(|s| s.verified).map(|v| {
+                        format!("\n\nVerified {} files ({})", v.files, crate::ui::widgets::progress_bar::format_bytes(v.bytes))
+                    });
+                    // Special files such as sockets that copies left out.
+                    let skipped = state.as_ref().filter(|s| !s.skipped.is_empty()).map(|s| {
+                        let lines: Vec<String> = s.skipped.iter().map(|(path, kind)| format!("{} ({})", path.display(), kind)).collect();
+                        format!("\n\nNot copied:\n{}", lines.join("\n"))
+                    });
+                    // Limits of what the job could do, e.g. shredding on
+                    // copy-on-write storage.
+                    let warnings = state.as_ref().filter(|s| !s.warnings.is_empty()).map(|s| format!("\n\nWarning:\n{}", s.warnings.join("\n")));
+                    let notes = format!("{}{}{}", verified.unwrap_or_default(), skipped.unwrap_or_default(), warnings.unwrap_or_default());
+                    let failures = state.map(|s| s.failures).unwrap_or_default();
+
+                    if let (Some(id), false) = (job, failures.is_empty()) {
+                        // Items were skipped after failing: list them and
+                        // offer to run just those again.
+                        let summary = match &update.error {
+                            Some(err_msg) => err_msg.clone(),
+                            None => format!("{} items processed, {} failed", update.processed, failures.len()),
+                        };
+                        let lines: Vec<String> = failures.iter().map(|f| f.to_string()).collect();
+                        self.mode = Mode::Message {
+                            title: "Failed items".to_string(),
+                            content: format!("{}:\n\n{}{}", summary, lines.join("\n"), notes),
+                            buttons: vec!["Retry failed".to_string(), "OK".to_string()],
+                            selected: 0,
+                            actions: Some(vec![crate::app::Action::RetryFailed(id)]),
+                        };
+                    } else if let Some(err_msg) = update.error {
                         self.mode = Mode::Message {
                             title: "Error".to_string(),
                             content: err_msg,
@@ -132,7 +156,7 @@ impl App {
                             actions: None,
                         };
                     } else {
-                        let content = format!("{} items processed", update.processed);
+                        let content = format!("{} items processed{}", update.processed, notes);
                         self.mode = Mode::Message {
                             title: "Done".to_string(),
                             content,
@@ -147,12 +171,19 @@ impl App {
                     let _ = self.refresh();
                 } else {
                     let message = update.message.unwrap_or_default();
+                    // Keep the operation's title ("Copying", ...) set when it
+                    // started; the message carries the per-item detail.
+                    let title = match &self.mode {
+                        Mode::Progress { title, .. } if !matches!(title.as_str(), "" | "Resolving") => title.clone(),
+                        _ => "Progress".to_string(),
+                    };
                     self.mode = Mode::Progress {
-                        title: if message.is_empty() { "Progress".to_string() } else { message.clone() },
+                        title,
                         processed: update.processed,
                         total: update.total,
                         message,
                         cancelled: false,
+                        bytes: update.bytes,
                     };
                 }
             }
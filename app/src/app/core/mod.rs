@@ -65,6 +65,12 @@ pub struct App {
     pub drag_current: Option<(u16, u16)>,
     /// Which mouse button started the drag.
     pub drag_button: Option<crate::input::mouse::MouseButton>,
+    /// Background copy, move and delete jobs; the progress channels above
+    /// follow the job started most recently from the UI.
+    pub jobs: crate::runner::job_queue::JobQueue,
+    /// Queue id of the job the progress channels follow, if they follow
+    /// a queued job (packing runs outside the queue).
+    pub op_job: Option<crate::runner::job_queue::JobId>,
 }
 
 // submodules live in `app/src/app/core/`
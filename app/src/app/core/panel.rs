@@ -1,10 +1,11 @@
-use crate::app::types::Entry;
+use crate::app::types::{Entry, EntryKind};
+use crate::vfs::{LocalVfs, Vfs};
 use chrono::{DateTime, Local};
 use globset::{GlobBuilder, GlobMatcher};
 use std::collections::HashSet;
 use std::io;
-use std::path::PathBuf;
-use walkdir::WalkDir;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Panel holds the minimal, UI-independent state for one side of the
 /// dual-pane file manager. It intentionally keeps presentation details
@@ -50,11 +51,19 @@ pub struct Panel {
     /// Optional glob filter applied to entry names.
     pub filter_pattern: Option<String>,
     filter_matcher: Option<GlobMatcher>,
+    /// Storage backend this panel is browsing. `cwd` and entry paths are
+    /// interpreted in this backend's namespace.
+    pub vfs: Arc<dyn Vfs>,
 }
 
 impl Panel {
-    /// Create a new panel rooted at `cwd` with sensible defaults.
+    /// Create a new panel rooted at `cwd` on the local filesystem.
     pub fn new(cwd: PathBuf) -> Self {
+        Self::with_vfs(cwd, Arc::new(LocalVfs::new()))
+    }
+
+    /// Create a new panel rooted at `cwd` inside the given backend.
+    pub fn with_vfs(cwd: PathBuf, vfs: Arc<dyn Vfs>) -> Self {
         Panel {
             cwd,
             entries: Vec::new(),
@@ -66,6 +75,7 @@ impl Panel {
             filter_pattern: None,
             filter_matcher: None,
             mode: PanelMode::default(),
+            vfs,
         }
     }
 
@@ -160,11 +170,14 @@ impl Panel {
             if let Ok(threshold) = num_str.parse::<u64>() {
                 self.selections.clear();
                 for (i, e) in self.entries.iter().enumerate() {
-                    match op {
-                        '>' => if e.size > threshold { self.selections.insert(i); },
-                        '<' => if e.size < threshold { self.selections.insert(i); },
-                        '=' => if e.size == threshold { self.selections.insert(i); },
-                        _ => {}
+                    let hit = match op {
+                        '>' => e.size > threshold,
+                        '<' => e.size < threshold,
+                        '=' => e.size == threshold,
+                        _ => false,
+                    };
+                    if hit {
+                        self.selections.insert(i);
                     }
                 }
             }
@@ -179,10 +192,13 @@ impl Panel {
                 self.selections.clear();
                 for (i, e) in self.entries.iter().enumerate() {
                     if let Some(mod_t) = e.modified {
-                        match op {
-                            '>' => if mod_t > dt_local { self.selections.insert(i); },
-                            '<' => if mod_t < dt_local { self.selections.insert(i); },
-                            _ => {}
+                        let hit = match op {
+                            '>' => mod_t > dt_local,
+                            '<' => mod_t < dt_local,
+                            _ => false,
+                        };
+                        if hit {
+                            self.selections.insert(i);
                         }
                     }
                 }
@@ -232,6 +248,17 @@ impl Panel {
             .and_then(|idx| self.entries.get(idx))
     }
 
+    /// The entries a mutating action applies to: the marked ones in listing
+    /// order when there are any, otherwise the entry under the cursor.
+    pub fn marked_entries(&self) -> Vec<&Entry> {
+        if self.selections.is_empty() {
+            return self.selected_entry().into_iter().collect();
+        }
+        let mut marked: Vec<usize> = self.selections.iter().copied().collect();
+        marked.sort_unstable();
+        marked.into_iter().filter_map(|idx| self.entries.get(idx)).collect()
+    }
+
     /// Move selection down by one, clamping at the last UI row.
     pub fn select_next(&mut self) {
         let max_rows = super::utils::ui_row_count(self);
@@ -294,110 +321,49 @@ impl Panel {
         self.preview_offset = 0;
     }
 
-    /// Read directory entries and return a Vec<Entry>.
-    /// This centralises the filesystem access and metadata reading used by
-    /// `App::refresh_panel` and keeps the Panel's path-related concerns in one place.
-    /// Read the immediate children of the panel's `cwd` and return them as
-    /// a `Vec<Entry>`. This is intentionally a thin wrapper around
-    /// filesystem access so callers can handle errors appropriately.
+    /// Read the immediate children of the panel's `cwd` through the
+    /// panel's backend and return them as a `Vec<Entry>`. The local
+    /// backend fills in each entry's `kind` and symlink details itself;
+    /// for other backends they are worked out here (see `describe_kind`).
     pub(crate) fn read_entries(&self) -> io::Result<Vec<Entry>> {
-        let mut entries_vec = Vec::new();
-
-        for dir_entry_result in WalkDir::new(&self.cwd)
-            .min_depth(1)
-            .max_depth(1)
-            .follow_links(false)
-        {
-            let dir_entry = dir_entry_result
-                .map_err(io::Error::other)?;
-
-            let metadata = dir_entry.metadata()?;
-            let modified_time = metadata.modified().ok().map(DateTime::<Local>::from);
-            let name = dir_entry.file_name().to_string_lossy().into_owned();
-            let path_buf = dir_entry.path().to_path_buf();
-
-            let mut file_entry = if metadata.is_dir() {
-                Entry::directory(name, path_buf.clone(), modified_time)
-            } else {
-                Entry::file(name, path_buf.clone(), metadata.len(), modified_time)
-            };
-
-            // Best-effort: populate permission/ownership flags using the
-            // existing helpers. Failure to inspect is tolerated.
-            if let Ok(perms) = crate::fs_op::permissions::inspect_permissions(&path_buf, false)
-            {
-                file_entry.unix_mode = perms.unix_mode;
-                file_entry.can_read = Some(perms.can_read);
-                file_entry.can_write = Some(perms.can_write);
-                file_entry.can_execute = Some(perms.can_execute);
-            }
-
-            // Best-effort: uid/gid when available on unix platforms.
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::MetadataExt;
-                file_entry.uid = Some(metadata.uid());
-                file_entry.gid = Some(metadata.gid());
-
-                // Best-effort: resolve uid/gid to names for display
-                // Use the `users` crate which works cross-platform.
-                if let Some(u) = users::get_user_by_uid(metadata.uid()) {
-                    file_entry.owner = Some(u.name().to_string_lossy().into_owned());
-                }
-                if let Some(g) = users::get_group_by_gid(metadata.gid()) {
-                    file_entry.group = Some(g.name().to_string_lossy().into_owned());
-                }
+        let mut entries = self.vfs.list(&self.cwd)?;
+        if !self.vfs.is_local() {
+            for entry in &mut entries {
+                describe_kind(self.vfs.as_ref(), entry);
             }
-            #[cfg(not(unix))]
-            {
-                // populate the uid/gid fields where possible via metadata but
-                // avoid making platform assumptions about user/group resolution
-                file_entry.uid = None;
-                file_entry.gid = None;
-            }
-
-            entries_vec.push(file_entry);
         }
-
-        Ok(entries_vec)
+        Ok(entries)
     }
 
     /// Get a recursive tree of entries starting at this panel's cwd.
     /// Returns entries paired with their depth (0 => immediate child).
     /// Depth is limited by `max_depth` to avoid pathological recursion in tests.
+    /// Entries are returned in pre-order: each directory is followed by its
+    /// children. Symlinks to directories are not descended into.
     pub fn tree_entries(&self, max_depth: usize) -> io::Result<Vec<(Entry, usize)>> {
         let mut entries_vec = Vec::new();
+        if max_depth > 0 {
+            self.walk_tree(&self.cwd, 0, max_depth, &mut entries_vec)?;
+        }
+        Ok(entries_vec)
+    }
 
-        for dir_entry_result in WalkDir::new(&self.cwd)
-            .min_depth(1)
-            .max_depth(max_depth)
-            .follow_links(false)
-        {
-            let dir_entry = dir_entry_result.map_err(io::Error::other)?;
-
-            let metadata = dir_entry.metadata()?;
-            let depth = dir_entry.depth().saturating_sub(1) as usize;
-            let modified_time = metadata.modified().ok().map(DateTime::<Local>::from);
-            let name = dir_entry.file_name().to_string_lossy().into_owned();
-            let path_buf = dir_entry.path().to_path_buf();
-
-            let mut file_entry = if metadata.is_dir() {
-                Entry::directory(name, path_buf.clone(), modified_time)
-            } else {
-                Entry::file(name, path_buf.clone(), metadata.len(), modified_time)
-            };
-
-            if let Ok(perms) = crate::fs_op::permissions::inspect_permissions(&path_buf, false) {
-                file_entry.unix_mode = perms.unix_mode;
-                file_entry.can_read = Some(perms.can_read);
-                file_entry.can_write = Some(perms.can_write);
-                file_entry.can_execute = Some(perms.can_execute);
+    fn walk_tree(
+        &self,
+        dir: &Path,
+        depth: usize,
+        max_depth: usize,
+        out: &mut Vec<(Entry, usize)>,
+    ) -> io::Result<()> {
+        for entry in self.vfs.list(dir)? {
+            let descend = entry.is_dir && depth + 1 < max_depth;
+            let path = entry.path.clone();
+            out.push((entry, depth));
+            if descend {
+                self.walk_tree(&path, depth + 1, max_depth, out)?;
             }
-
-            entries_vec.push((file_entry, depth));
         }
-
-        Ok(entries_vec)
+        Ok(())
     }
 
     /// Flatten the directory tree under this panel's cwd up to `max_depth`.
@@ -411,6 +377,26 @@ impl Panel {
     }
 }
 
+/// Set `entry.kind` from its Unix mode, and for a symlink read its target
+/// and look it up: a missing target marks the link broken. The lookup does
+/// not follow the target further, so a link to a link to a directory does
+/// not count as pointing to one.
+fn describe_kind(vfs: &dyn Vfs, entry: &mut Entry) {
+    if let Some(kind) = entry.unix_mode.and_then(EntryKind::from_mode) {
+        entry.kind = kind;
+    }
+    if entry.kind != EntryKind::Symlink || entry.link_target.is_some() {
+        return;
+    }
+    let Ok(target) = vfs.read_link(&entry.path) else { return };
+    let resolved = entry.path.parent().map_or_else(|| target.clone(), |dir| dir.join(&target));
+    match vfs.stat(&resolved) {
+        Ok(found) => entry.points_to_dir = found.is_dir,
+        Err(_) => entry.broken_link = true,
+    }
+    entry.link_target = Some(target);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -544,4 +530,59 @@ mod tests {
         let flat = p.flat_entries(4).unwrap();
         assert!(flat.iter().any(|e| e.name == "file.txt"));
     }
+
+    /// Minimal backend serving a fixed two-level tree, used to check that
+    /// panel listing goes through the backend rather than the host fs.
+    #[derive(Debug)]
+    struct StubVfs;
+
+    impl Vfs for StubVfs {
+        fn scheme(&self) -> &'static str {
+            "stub"
+        }
+        fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+            match dir.to_str() {
+                Some("/root") => Ok(vec![
+                    Entry::directory("d", PathBuf::from("/root/d"), None),
+                    Entry::file("f", PathBuf::from("/root/f"), 1, None),
+                ]),
+                Some("/root/d") => Ok(vec![Entry::file("g", PathBuf::from("/root/d/g"), 2, None)]),
+                _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+        fn stat(&self, _: &Path) -> io::Result<Entry> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+        fn open_read(&self, _: &Path) -> io::Result<Box<dyn std::io::Read + Send>> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+        fn open_write(&self, _: &Path) -> io::Result<Box<dyn std::io::Write + Send>> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+        fn mkdir(&self, _: &Path) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+        fn rename(&self, _: &Path, _: &Path) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+        fn remove(&self, _: &Path) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+        fn symlink(&self, _: &Path, _: &Path) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+    }
+
+    #[test]
+    fn listing_is_routed_through_the_panel_backend() {
+        let p = Panel::with_vfs(PathBuf::from("/root"), Arc::new(StubVfs));
+        assert_eq!(p.vfs.scheme(), "stub");
+        assert_eq!(p.read_entries().unwrap().len(), 2);
+
+        let tree: Vec<(String, usize)> = p.tree_entries(2).unwrap().into_iter().map(|(e, d)| (e.name, d)).collect();
+        assert_eq!(tree, vec![("d".to_string(), 0), ("g".to_string(), 1), ("f".to_string(), 0)]);
+
+        // max_depth 1 only yields immediate children.
+        assert_eq!(p.flat_entries(1).unwrap().len(), 2);
+    }
 }
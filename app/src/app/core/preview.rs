@@ -4,7 +4,8 @@ use std::io::Read;
 use std::path::Path;
 
 use super::App;
-use crate::app::types::Side;
+use crate::app::types::{Entry, EntryKind, Side};
+use crate::vfs::Vfs;
 
 /// Maximum number of directory entries to include in a directory preview.
 /// Maximum number of directory entries to include in a directory preview.
@@ -104,8 +105,35 @@ pub fn build_directory_preview(path: &Path) -> String {
 /// `Err(PreviewError::Unreadable)` if the file could not be read.
 pub fn build_file_preview(path: &Path, max_bytes: usize) -> Result<String, PreviewError> {
     let file = File::open(path).map_err(|_| PreviewError::Unreadable)?;
+    let len = fs::metadata(path).ok().map(|md| md.len());
+    preview_from_reader(file, len, max_bytes)
+}
+
+/// Like `build_directory_preview` but lists `path` through a `Vfs` backend.
+pub fn build_vfs_directory_preview(vfs: &dyn Vfs, path: &Path) -> String {
+    let mut out = format!("Directory: {}\n", vfs.display_path(path));
+    let mut names: Vec<String> = vfs
+        .list(path)
+        .map(|entries| entries.into_iter().map(|e| e.name).collect())
+        .unwrap_or_default();
+    names.sort();
+    for name in names.into_iter().take(MAX_DIR_PREVIEW_ENTRIES) {
+        out.push_str(&name);
+        out.push('\n');
+    }
+    out
+}
 
-    let mut reader = file.take(max_bytes as u64);
+/// Like `build_file_preview` but reads `entry` through a `Vfs` backend.
+pub fn build_vfs_file_preview(vfs: &dyn Vfs, entry: &Entry, max_bytes: usize) -> Result<String, PreviewError> {
+    let reader = vfs.open_read(&entry.path).map_err(|_| PreviewError::Unreadable)?;
+    preview_from_reader(reader, Some(entry.size), max_bytes)
+}
+
+/// Sample up to `max_bytes` from `reader` and turn them into preview text.
+/// `total_len` is the full size of the source, used to flag truncation.
+fn preview_from_reader<R: Read>(reader: R, total_len: Option<u64>, max_bytes: usize) -> Result<String, PreviewError> {
+    let mut reader = reader.take(max_bytes as u64);
     let mut buf = Vec::with_capacity(max_bytes.min(8 * 1024));
     reader
         .read_to_end(&mut buf)
@@ -122,10 +150,7 @@ pub fn build_file_preview(path: &Path, max_bytes: usize) -> Result<String, Previ
     }
 
     // If the file is longer than the bytes we sampled, indicate truncation.
-    let truncated = match fs::metadata(path) {
-        Ok(md) => (md.len() as usize) > buf.len(),
-        Err(_) => false,
-    };
+    let truncated = total_len.map(|len| (len as usize) > buf.len()).unwrap_or(false);
 
     if truncated {
         Ok(format!("{}\n... (truncated)", preview))
@@ -147,12 +172,32 @@ impl App {
         // - `selected_entry` encapsulates bounds-safe access
         // - `set_preview` resets `preview_offset` to zero
         if let Some(e) = panel.selected_entry() {
-            if e.is_dir {
-                let s = build_directory_preview(&e.path);
+            let local = panel.vfs.is_local();
+            let special = match e.kind {
+                EntryKind::Fifo => Some("FIFO"),
+                EntryKind::Socket => Some("Socket"),
+                EntryKind::BlockDevice => Some("Block device"),
+                EntryKind::CharDevice => Some("Character device"),
+                _ => None,
+            };
+            if let Some(kind) = special {
+                // Opening a FIFO would block until something writes to it.
+                panel.set_preview(format!("{}: {} (preview not available)", kind, e.path.display()));
+            } else if e.is_dir || e.points_to_dir {
+                let s = if local {
+                    build_directory_preview(&e.path)
+                } else {
+                    build_vfs_directory_preview(panel.vfs.as_ref(), &e.path)
+                };
                 panel.set_preview(s);
             } else {
                 // Read up to the module-level `MAX_PREVIEW_BYTES` for previews.
-                match build_file_preview(&e.path, super::MAX_PREVIEW_BYTES) {
+                let result = if local {
+                    build_file_preview(&e.path, super::MAX_PREVIEW_BYTES)
+                } else {
+                    build_vfs_file_preview(panel.vfs.as_ref(), e, super::MAX_PREVIEW_BYTES)
+                };
+                match result {
                     Ok(s) => panel.set_preview(s),
                     Err(PreviewError::Binary) => panel.set_preview(format!(
                         "Binary file: {} (preview not available)",
@@ -1,5 +1,11 @@
+use crate::fs_op::fastcopy::CopyMethod;
+use crate::fs_op::shred::ShredOptions;
+use crate::runner::job_queue::ErrorPolicy;
+use crate::runner::progress::ConflictPolicy;
+use crate::vfs::pack::PackOptions;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::io::Write;
@@ -29,6 +35,64 @@ pub struct Settings {
     /// user's `EDITOR` command; integrated launcher is still used when
     /// the editor is `vim` or `vi`.
     pub prefer_integrated_vim: bool,
+    /// What copy and move jobs do when an item fails: `"abort"`, `"skip"`
+    /// (and list the failures at the end) or `"ask"`.
+    #[serde(default)]
+    pub error_policy: ErrorPolicy,
+    /// How copy and move jobs settle targets that already exist: `"ask"`
+    /// (default), or a policy applied without asking, such as `"skip"`,
+    /// `"rename-new"` or `"overwrite-if-newer"` (see `ConflictPolicy`).
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Hash every copied file and compare it with its source; moves delete
+    /// the source only after the copy matched. Off by default since it
+    /// reads everything twice.
+    #[serde(default)]
+    pub verify_copies: bool,
+    /// First way copies try to move file data: `"reflink"` (default),
+    /// `"copy-range"`, `"sparse"` or `"plain"`; each falls back to the
+    /// ones after it (see `fs_op::fastcopy`).
+    #[serde(default)]
+    pub copy_method: CopyMethod,
+    /// Credentials for `smb://` locations, keyed by server name as typed
+    /// in the URL (e.g. `[smb_hosts."nas.lan"]`). Hosts without an entry
+    /// are tried as guest.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub smb_hosts: BTreeMap<String, SmbCredentials>,
+    /// Compression level and metadata options for the Pack action.
+    #[serde(default)]
+    pub pack: PackOptions,
+    /// Overwrite passes for the Shred action.
+    #[serde(default)]
+    pub shred: ShredOptions,
+}
+
+/// Login details for one SMB server. `Debug` leaves the password out.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SmbCredentials {
+    pub user: String,
+    #[serde(default)]
+    pub password: String,
+    /// Optional NT domain / workgroup; sent as `DOMAIN\user`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+impl std::fmt::Debug for SmbCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmbCredentials").field("user", &self.user).field("password", &"<redacted>").field("domain", &self.domain).finish()
+    }
+}
+
+impl Settings {
+    /// Look up the SMB credentials configured for `host`. Server names are
+    /// matched case-insensitively, as SMB itself does.
+    pub fn smb_credentials(&self, host: &str) -> Option<&SmbCredentials> {
+        self.smb_hosts
+            .iter()
+            .find(|(h, _)| h.eq_ignore_ascii_case(host))
+            .map(|(_, c)| c)
+    }
 }
 
 impl Default for Settings {
@@ -52,6 +116,13 @@ impl Default for Settings {
             prefer_integrated_vim: false,
             // Default to CLI-style listing to match the expected TUI look
             show_cli_listing: true,
+            error_policy: ErrorPolicy::default(),
+            conflict_policy: ConflictPolicy::default(),
+            verify_copies: false,
+            copy_method: CopyMethod::default(),
+            smb_hosts: BTreeMap::new(),
+            pack: PackOptions::default(),
+            shred: ShredOptions::default(),
         }
     }
 }
@@ -1,4 +1,18 @@
 pub mod vim_support;
 
 // Add more editors here (e.g. nano_support) and re-export helpers as needed.
-pub use vim_support::spawn_vim;
+pub use vim_support::{spawn_editor, spawn_vim};
+
+/// The editor command to run in the terminal: `vim` when
+/// `prefer_integrated_vim` is set, otherwise `$VISUAL`, `$EDITOR`, then
+/// `vi`.
+pub fn editor_command(prefer_integrated_vim: bool) -> String {
+	if prefer_integrated_vim {
+		return "vim".to_string();
+	}
+	["VISUAL", "EDITOR"]
+		.iter()
+		.filter_map(|var| std::env::var(var).ok())
+		.find(|cmd| !cmd.trim().is_empty())
+		.unwrap_or_else(|| "vi".to_string())
+}
@@ -13,6 +13,27 @@ use crossterm::terminal::{disable_raw_mode, enable_raw_mode, LeaveAlternateScree
 /// This function is conservative: it attempts to restore the terminal state
 /// even if launching the editor fails.
 pub fn spawn_vim<P: AsRef<Path>>(path: P) -> io::Result<()> {
+	let mut command = Command::new("vim");
+	command.arg(path.as_ref());
+	run_suspended(command, "vim")
+}
+
+/// Like `spawn_vim`, but runs `command` (such as the user's `$EDITOR`,
+/// which may carry arguments) with `path` appended. The command is split
+/// into words the way a shell would, without running a shell.
+pub fn spawn_editor<P: AsRef<Path>>(command: &str, path: P) -> io::Result<()> {
+	let words = shell_words::split(command).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", command, e)))?;
+	let Some((program, args)) = words.split_first() else {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, "the editor command is empty"));
+	};
+	let mut cmd = Command::new(program);
+	cmd.args(args).arg(path.as_ref());
+	run_suspended(cmd, program)
+}
+
+/// Run `command` to completion with the TUI suspended. `name` labels a
+/// non-zero exit status in the error.
+fn run_suspended(mut command: Command, name: &str) -> io::Result<()> {
 	// Disable raw mode and leave the alternate screen so the spawned editor
 	// can take full control of the terminal with normal line buffering.
 	// Best-effort restore of terminal state; propagate errors directly.
@@ -22,9 +43,9 @@ pub fn spawn_vim<P: AsRef<Path>>(path: P) -> io::Result<()> {
 	// Leave alternate screen, disable mouse capture and show cursor
 	let _ = execute!(stdout, LeaveAlternateScreen, DisableMouseCapture, Show);
 
-	// Run the editor synchronously. If `vim` isn't available, the error will
+	// Run the editor synchronously. If it isn't available, the error will
 	// be returned to the caller after we try to restore terminal state.
-	let status = Command::new("vim").arg(path.as_ref()).status();
+	let status = command.status();
 
 	// After the editor exits (or fails to spawn), try to re-enter the TUI
 	// environment: hide cursor, enable mouse capture and enter alternate
@@ -38,7 +59,7 @@ pub fn spawn_vim<P: AsRef<Path>>(path: P) -> io::Result<()> {
 	// Propagate the editor process status (map to io::Error when appropriate).
 	match status {
 		Ok(s) if s.success() => Ok(()),
-		Ok(s) => Err(io::Error::other(format!("vim exited with status: {}", s))),
+		Ok(s) => Err(io::Error::other(format!("{} exited with status: {}", name, s))),
 		Err(e) => Err(e),
 	}
 }
@@ -39,6 +39,44 @@ pub struct Entry {
     pub owner: Option<String>,
     /// Optional human-readable group name (best-effort lookup from GID).
     pub group: Option<String>,
+    /// What the entry is, without following a symlink.
+    pub kind: EntryKind,
+    /// Where a symlink points, as stored in the link.
+    pub link_target: Option<PathBuf>,
+    /// Whether the entry is a symbolic link whose target does not exist.
+    pub broken_link: bool,
+    /// Whether the entry is a symbolic link leading to a directory.
+    pub points_to_dir: bool,
+}
+
+/// The type of filesystem object behind an `Entry`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EntryKind {
+    #[default]
+    File,
+    Dir,
+    Symlink,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+}
+
+impl EntryKind {
+    /// The kind given by the file-type (`S_IFMT`) bits of a Unix `mode`,
+    /// or `None` when they name no known type.
+    pub fn from_mode(mode: u32) -> Option<Self> {
+        Some(match mode & 0o170000 {
+            0o100000 => EntryKind::File,
+            0o040000 => EntryKind::Dir,
+            0o120000 => EntryKind::Symlink,
+            0o010000 => EntryKind::Fifo,
+            0o140000 => EntryKind::Socket,
+            0o060000 => EntryKind::BlockDevice,
+            0o020000 => EntryKind::CharDevice,
+            _ => return None,
+        })
+    }
 }
 
 impl Entry {
@@ -63,6 +101,10 @@ impl Entry {
             can_execute: None,
             owner: None,
             group: None,
+            kind: EntryKind::File,
+            link_target: None,
+            broken_link: false,
+            points_to_dir: false,
         }
     }
 
@@ -86,6 +128,10 @@ impl Entry {
             can_execute: None,
             owner: None,
             group: None,
+            kind: EntryKind::Dir,
+            link_target: None,
+            broken_link: false,
+            points_to_dir: false,
         }
     }
 
@@ -159,12 +205,30 @@ pub enum Mode {
         total: usize,
         message: String,
         cancelled: bool,
+        /// Byte counters, throughput and ETA when the operation reports them.
+        bytes: Option<crate::runner::progress::ByteProgress>,
     },
+    /// A job waits for a decision about an existing target; `selected`
+    /// indexes the policy buttons, then Cancel and Compare.
     Conflict {
-        path: std::path::PathBuf,
+        conflict: Box<crate::runner::progress::Conflict>,
         selected: usize,
         apply_all: bool,
     },
+    /// A read-only text view (such as the diff of a conflict) scrolled to
+    /// line `scroll`; closing it returns to `back`.
+    Diff {
+        title: String,
+        text: String,
+        scroll: usize,
+        back: Box<Mode>,
+    },
+    /// A job item failed under the "ask" error policy; `selected` indexes
+    /// the Retry / Skip / Skip all / Abort buttons.
+    ItemError {
+        item: crate::runner::progress::FailedItem,
+        selected: usize,
+    },
     /// Context menu shown for a selected entry. `options` are the action
     /// labels (e.g. View, Edit, Permissions). `path` is the target entry.
     ContextMenu {
@@ -178,6 +242,29 @@ pub enum Mode {
         buffer: String,
         kind: InputKind,
     },
+    /// List of background jobs; `selected` indexes `JobQueue::list`.
+    Jobs { selected: usize },
+    /// Items in the trash, most recently deleted first.
+    Trash { items: Vec<crate::fs_op::trash::TrashedItem>, selected: usize },
+    /// Batch rename of the marked entries: the mask, find and replace
+    /// fields (`focus` indexes the one being edited) and the preview they
+    /// give, or why they do not parse. `scroll` is the first preview row
+    /// shown.
+    BatchRename {
+        fields: [String; 3],
+        focus: usize,
+        preview: Result<Vec<crate::fs_op::batch_rename::PreviewRow>, String>,
+        scroll: usize,
+    },
+    /// Confirmation of the changes made to the marked entries' names in an
+    /// editor: `text` is the diff shown (scrolled to line `scroll`), and
+    /// `renames` and `deletes` are applied on Enter.
+    EditNames {
+        text: String,
+        scroll: usize,
+        renames: Vec<(PathBuf, PathBuf)>,
+        deletes: Vec<PathBuf>,
+    },
 }
 
 // Default for Mode is derived via `#[default]` on the `Normal` variant.
@@ -194,6 +281,12 @@ pub enum InputKind {
     ChangePath,
     /// Quick filter on the active panel (glob-style, empty to clear).
     Filter,
+    /// Octal permission bits for the selected entry (e.g. `644`).
+    Chmod,
+    /// Archive name for the Pack action; the suffix picks the format.
+    Pack,
+    /// New target for the selected symlink.
+    EditSymlink,
 }
 
 /// Actions represent high-level user requests executed by the runner.
@@ -202,23 +295,51 @@ pub enum InputKind {
 /// and executed by `runner::commands::perform_action`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Action {
+    /// Delete the selected entry permanently.
     DeleteSelected,
+    /// Move the selected entry to the trash.
+    TrashSelected,
+    /// Overwrite and delete the selected entry (see `fs_op::shred`).
+    ShredSelected,
+    /// Delete one item in the trash permanently.
+    PurgeTrashed(crate::fs_op::trash::TrashedItem),
+    /// Delete everything in the trash permanently.
+    EmptyTrash,
     CopyTo(PathBuf),
     MoveTo(PathBuf),
     RenameTo(String),
     NewFile(String),
     NewDir(String),
+    /// Resume the jobs a previous run left unfinished.
+    ResumeJobs,
+    /// Discard the jobs a previous run left unfinished.
+    DiscardJobs,
+    /// List the jobs a previous run left unfinished, item by item.
+    InspectJobs,
+    /// Run the items a finished job skipped after they failed again.
+    RetryFailed(crate::runner::job_queue::JobId),
+    /// Link the marked entries into the other panel's directory.
+    Link(crate::fs_op::symlink::LinkKind),
 }
 
 impl fmt::Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Action::DeleteSelected => write!(f, "DeleteSelected"),
+            Action::TrashSelected => write!(f, "TrashSelected"),
+            Action::ShredSelected => write!(f, "ShredSelected"),
+            Action::PurgeTrashed(item) => write!(f, "PurgeTrashed({})", item.original.display()),
+            Action::EmptyTrash => write!(f, "EmptyTrash"),
             Action::CopyTo(p) => write!(f, "CopyTo({})", p.display()),
             Action::MoveTo(p) => write!(f, "MoveTo({})", p.display()),
             Action::RenameTo(name) => write!(f, "RenameTo({})", name),
             Action::NewFile(name) => write!(f, "NewFile({})", name),
             Action::NewDir(name) => write!(f, "NewDir({})", name),
+            Action::ResumeJobs => write!(f, "ResumeJobs"),
+            Action::DiscardJobs => write!(f, "DiscardJobs"),
+            Action::InspectJobs => write!(f, "InspectJobs"),
+            Action::RetryFailed(_) => write!(f, "RetryFailed"),
+            Action::Link(kind) => write!(f, "Link({:?})", kind),
         }
     }
 }
@@ -9,8 +9,7 @@ use std::process::Command;
 /// Copy a directory recursively from `src` to `dst`.
 pub fn copy_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
     // Reuse the shared `fs_op::copy::copy_recursive` helper to avoid
-    // duplicating traversal logic and to benefit from `fs_extra` where
-    // that helper chooses to use it.
+    // duplicating traversal logic.
     crate::fs_op::copy::copy_recursive(src, dst)
 }
 
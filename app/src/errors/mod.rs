@@ -2,7 +2,6 @@ use handlebars::Handlebars;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::sync::OnceLock;
-use toml::Value;
 
 /// Parse a TOML string and extract the `[errors]` table into a String map.
 ///
@@ -13,7 +12,7 @@ use toml::Value;
 fn parse_templates_from_str(raw: &str) -> HashMap<String, String> {
     let mut map = HashMap::new();
 
-    match raw.parse::<Value>() {
+    match raw.parse::<toml::Table>() {
         Ok(val) => {
             if let Some(errors) = val.get("errors") {
                 if let Some(table) = errors.as_table() {
@@ -176,5 +175,10 @@ pub fn render_fsop_error(
             let dst_s = d.display().to_string();
             format_template(&tmpl, &[("src", &src_s), ("dst", &dst_s), ("err", msg)])
         }
+        FsOpError::Unsupported { path: p, msg } => {
+            let tmpl = template_or_default("unsupported", "Not supported: {{path}} ({{err}})");
+            let path_s = p.display().to_string();
+            format_template(&tmpl, &[("path", &path_s), ("err", msg)])
+        }
     }
 }
@@ -8,18 +8,28 @@
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use crate::fs_op::batch_rename::{self, PreviewRow, Template};
 use crate::fs_op::error::FsOpError;
+use crate::fs_op::symlink::{self, LinkKind};
 
 impl crate::app::core::App {
     /// Enter the selected directory (if any) by updating the active
-    /// panel's `cwd` and refreshing the panel listing.
+    /// panel's `cwd` and refreshing the panel listing. A symlink to a
+    /// directory is followed, keeping the link's path as the `cwd`.
+    /// Supported archives are entered too: the panel switches to an archive
+    /// backend rooted at the archive's path.
     pub fn enter(&mut self) -> Result<(), FsOpError> {
         if let Some(sel) = self.selected_index() {
             let panel = self.active_panel_mut();
             if let Some(entry) = panel.entries.get(sel) {
-                if entry.is_dir {
+                if entry.is_dir || entry.points_to_dir {
                     panel.cwd = entry.path.clone();
                     self.refresh_active()?;
+                } else if let Some(archive) = crate::vfs::open_archive(&panel.vfs, &entry.path) {
+                    panel.vfs = archive.map_err(|e| FsOpError::from_io_at(&entry.path, e))?;
+                    panel.cwd = entry.path.clone();
+                    panel.selections.clear();
+                    self.refresh_active()?;
                 }
             }
         }
@@ -27,30 +37,64 @@ impl crate::app::core::App {
     }
 
     /// Move the active panel up to its parent directory (if any) and
-    /// refresh the listing.
+    /// refresh the listing. Leaving the top of an archive returns the
+    /// panel to the backend that contains the archive.
     pub fn go_up(&mut self) -> Result<(), FsOpError> {
         let panel = self.active_panel_mut();
         if let Some(parent) = panel.cwd.parent() {
-            panel.cwd = parent.to_path_buf();
+            let parent = parent.to_path_buf();
+            while let Some((outer, root)) = panel.vfs.outer() {
+                if parent.starts_with(root) {
+                    break;
+                }
+                panel.vfs = outer.clone();
+                panel.selections.clear();
+            }
+            panel.cwd = parent;
             self.refresh_active()?;
         }
         Ok(())
     }
 
-    /// Delete the currently selected entry (file or directory).
+    /// Delete the marked entries, or the selected one when nothing is
+    /// marked (files or directories). Stops at the first failure; the marks
+    /// are cleared either way since the listing changes.
     pub fn delete_selected(&mut self) -> Result<(), FsOpError> {
-        if let Some(sel) = self.selected_index() {
-            let panel = self.active_panel_mut();
-            if let Some(entry) = panel.entries.get(sel) {
-                if entry.is_dir {
-                    fs::remove_dir_all(&entry.path)?;
-                } else {
-                    fs::remove_file(&entry.path)?;
-                }
-                self.refresh_active()?;
+        let panel = self.active_panel_mut();
+        let targets: Vec<(PathBuf, bool)> = panel.marked_entries().iter().map(|e| (e.path.clone(), e.is_dir)).collect();
+        if targets.is_empty() {
+            return Ok(());
+        }
+        let res = targets.iter().try_for_each(|(path, is_dir)| {
+            if !panel.vfs.is_local() {
+                crate::vfs::remove_tree(panel.vfs.as_ref(), path)
+            } else if *is_dir {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
             }
+        });
+        panel.selections.clear();
+        self.refresh_active()?;
+        Ok(res?)
+    }
+
+    /// Move the marked entries, or the selected one, to the trash (see
+    /// `fs_op::trash`). Only local panels have a trash; elsewhere use
+    /// `delete_selected`.
+    pub fn trash_selected(&mut self) -> Result<(), FsOpError> {
+        let panel = self.active_panel_mut();
+        let targets: Vec<PathBuf> = panel.marked_entries().iter().map(|e| e.path.clone()).collect();
+        if let (Some(first), false) = (targets.first(), panel.vfs.is_local()) {
+            return Err(FsOpError::Unsupported { path: first.clone(), msg: "only local files can be moved to the trash".to_string() });
         }
-        Ok(())
+        let trash = crate::fs_op::trash::Trash::user();
+        let res = targets.iter().try_for_each(|path| trash.trash(path).map(|_| ()).map_err(|e| FsOpError::from_io_at(path, e)));
+        if !targets.is_empty() {
+            panel.selections.clear();
+            self.refresh_active()?;
+        }
+        res
     }
 
     /// Copy the selected entry to `dst`.
@@ -63,6 +107,15 @@ impl crate::app::core::App {
             if let Some(src_entry) = panel.entries.get(sel) {
                 let src_path: &Path = &src_entry.path;
                 let src_name: &str = &src_entry.name;
+                if !panel.vfs.is_local() {
+                    // Destinations typed while browsing a remote backend are
+                    // interpreted inside that same backend.
+                    let vfs = panel.vfs.as_ref();
+                    let target = resolve_vfs_target(vfs, &dst, src_name);
+                    crate::vfs::copy_tree(vfs, src_path, vfs, &target)?;
+                    self.refresh_active()?;
+                    return Ok(());
+                }
                 let target = crate::fs_op::helpers::resolve_target(&dst, src_name);
                 if src_entry.is_dir {
                     // Call into the shared copy implementation directly so we
@@ -89,6 +142,13 @@ impl crate::app::core::App {
             if let Some(src_entry) = panel.entries.get(sel) {
                 let src_path: &Path = &src_entry.path;
                 let src_name: &str = &src_entry.name;
+                if !panel.vfs.is_local() {
+                    let vfs = panel.vfs.as_ref();
+                    let target = resolve_vfs_target(vfs, &dst, src_name);
+                    crate::vfs::move_tree(vfs, src_path, vfs, &target, true)?;
+                    self.refresh_active()?;
+                    return Ok(());
+                }
                 let target = crate::fs_op::helpers::resolve_target(&dst, src_name);
                 crate::fs_op::helpers::ensure_parent_exists(&target)?;
                 crate::fs_op::helpers::atomic_rename_or_copy(src_path, &target)?;
@@ -99,26 +159,102 @@ impl crate::app::core::App {
     }
 
     /// Rename the selected entry to `name` within the same directory.
+    ///
+    /// With several entries marked, `name` is a `batch_rename::Template`
+    /// mask in which `*` stands for each entry's current name (`*.bak`,
+    /// `old-*`) and is applied through `batch_rename`.
     pub fn rename_selected_to(&mut self, name: String) -> Result<(), FsOpError> {
-        if let Some(sel) = self.selected_index() {
-            let panel = self.active_panel_mut();
-            if let Some(src_entry) = panel.entries.get(sel) {
-                let src_path: &Path = &src_entry.path;
-                let target = panel.cwd.join(name);
-                crate::fs_op::helpers::atomic_rename_or_copy(src_path, &target)?;
-                self.refresh_active()?;
+        let panel = self.active_panel_mut();
+        let target = match panel.marked_entries().as_slice() {
+            [] => return Ok(()),
+            [entry] => (entry.path.clone(), panel.cwd.join(&name)),
+            _ => {
+                let template = Template::parse(&name, "", "").map_err(FsOpError::Message)?;
+                return self.batch_rename(&template).map(|_| ());
             }
+        };
+        let (src, target) = target;
+        let res = if src == target {
+            Ok(())
+        } else if panel.vfs.is_local() {
+            crate::fs_op::helpers::atomic_rename_or_copy(&src, &target)
+        } else {
+            panel.vfs.rename(&src, &target)
+        };
+        panel.selections.clear();
+        self.refresh_active()?;
+        Ok(res?)
+    }
+
+    /// The new names `template` gives the marked entries of the active
+    /// panel, or the selected one, with any collision flagged.
+    pub fn batch_rename_preview(&self, template: &Template) -> Vec<PreviewRow> {
+        let panel = self.active_panel();
+        batch_rename::preview(template, &panel.marked_entries(), &|name| name_taken(panel, &panel.cwd.join(name)))
+    }
+
+    /// Rename the marked entries of the active panel, or the selected one,
+    /// as `template` says, in an order that is safe against chains and
+    /// cycles (`batch_rename::plan`). Nothing is renamed while the preview
+    /// flags a problem. Returns how many entries got a new name.
+    pub fn batch_rename(&mut self, template: &Template) -> Result<usize, FsOpError> {
+        let rows = self.batch_rename_preview(template);
+        if let Some(row) = rows.iter().find(|r| r.problem.is_some()) {
+            let problem = row.problem.as_deref().unwrap_or_default();
+            return Err(FsOpError::path_context(row.path.clone(), PathBuf::from(&row.new), format!("{}; nothing was renamed", problem)));
         }
-        Ok(())
+        let panel = self.active_panel_mut();
+        let renames: Vec<(PathBuf, PathBuf)> = rows.iter().map(|r| (r.path.clone(), panel.cwd.join(&r.new))).collect();
+        let renamed = renames.iter().filter(|(src, target)| src != target).count();
+        let res = run_renames(panel, &renames);
+        panel.selections.clear();
+        self.refresh_active()?;
+        res?;
+        Ok(renamed)
+    }
+
+    /// Apply the changes from an edited list of names (see
+    /// `fs_op::edit_names`) in the active panel: delete `deletes`, then
+    /// rename as `renames` says in an order that is safe against chains and
+    /// cycles. Deletes go to the trash like `d`, except on backends other
+    /// than the local filesystem, where directories are removed with their
+    /// contents.
+    pub fn apply_name_edits(&mut self, renames: &[(PathBuf, PathBuf)], deletes: &[PathBuf]) -> Result<(), FsOpError> {
+        let panel = self.active_panel_mut();
+        let trash = crate::fs_op::trash::Trash::user();
+        let res = deletes
+            .iter()
+            .try_for_each(|path| {
+                if panel.vfs.is_local() {
+                    trash.trash(path).map(|_| ())
+                } else {
+                    crate::vfs::remove_tree(panel.vfs.as_ref(), path)
+                }
+            })
+            .and_then(|_| run_renames(panel, renames));
+        panel.selections.clear();
+        self.refresh_active()?;
+        Ok(res?)
     }
 
     /// Create a new empty file with `name` in the active panel.
     ///
     /// The file is written atomically to avoid races with concurrent
-    /// readers; parent directories are created as needed.
+    /// readers; parent directories are created as needed. Other backends
+    /// refuse a name that is already taken, since writing would truncate
+    /// it.
     pub fn new_file(&mut self, name: String) -> Result<(), FsOpError> {
         let panel = self.active_panel_mut();
         let path = panel.cwd.join(name);
+        if !panel.vfs.is_local() {
+            if panel.vfs.stat(&path).is_ok() {
+                let err = std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already exists", panel.vfs.display_path(&path)));
+                return Err(FsOpError::from_io_at(path, err));
+            }
+            panel.vfs.open_write(&path)?;
+            self.refresh_active()?;
+            return Ok(());
+        }
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -131,7 +267,104 @@ impl crate::app::core::App {
     pub fn new_dir(&mut self, name: String) -> Result<(), FsOpError> {
         let panel = self.active_panel_mut();
         let path = panel.cwd.join(name);
-        fs::create_dir_all(path)?;
+        if panel.vfs.is_local() {
+            fs::create_dir_all(path)?;
+        } else {
+            panel.vfs.mkdir(&path)?;
+        }
+        self.refresh_active()?;
+        Ok(())
+    }
+
+    /// Change the permission bits of the marked entries, or the selected
+    /// one, to `mode` through the active panel's backend. The marks stay so
+    /// further actions can use them.
+    pub fn chmod_selected(&mut self, mode: u32) -> Result<(), FsOpError> {
+        let panel = self.active_panel_mut();
+        let targets: Vec<PathBuf> = panel.marked_entries().iter().map(|e| e.path.clone()).collect();
+        let res = targets.iter().try_for_each(|path| panel.vfs.set_mode(path, mode));
+        if !targets.is_empty() {
+            self.refresh_active()?;
+        }
+        Ok(res?)
+    }
+
+    /// Link the marked entries, or the selected one, into the other
+    /// panel's directory under their own names, as `kind` says. Both panels
+    /// must be local. Nothing is created when one of the names is taken
+    /// there or a directory would be hard-linked. Returns how many links
+    /// were made.
+    pub fn link_selected(&mut self, kind: LinkKind) -> Result<usize, FsOpError> {
+        let other = match self.active {
+            crate::app::Side::Left => &self.right,
+            crate::app::Side::Right => &self.left,
+        };
+        let panel = self.active_panel();
+        let sources: Vec<(PathBuf, bool)> = panel.marked_entries().iter().map(|e| (e.path.clone(), e.is_dir)).collect();
+        let Some((first, _)) = sources.first() else { return Ok(0) };
+        if !panel.vfs.is_local() || !other.vfs.is_local() {
+            return Err(FsOpError::Unsupported { path: first.clone(), msg: "links can only be made between local directories".to_string() });
+        }
+        let dir = other.cwd.clone();
+        // `..` in a relative link is resolved from the directory the link
+        // really is in, so work from canonical paths.
+        let canonical = |path: &Path| fs::canonicalize(path).map_err(|e| FsOpError::from_io_at(path, e));
+        let real_dir = canonical(&dir)?;
+        let mut links = Vec::with_capacity(sources.len());
+        for (src, is_dir) in &sources {
+            let (Some(parent), Some(name)) = (src.parent(), src.file_name()) else { continue };
+            let link = dir.join(name);
+            if link.symlink_metadata().is_ok() {
+                return Err(FsOpError::path_context(src.clone(), link, "the name is already taken there; nothing was linked"));
+            }
+            if kind == LinkKind::Hard && *is_dir {
+                return Err(FsOpError::path_context(src.clone(), link, "directories cannot be hard-linked; nothing was linked"));
+            }
+            let target = match kind {
+                LinkKind::Relative => symlink::relative_path(&real_dir, &canonical(parent)?.join(name)),
+                LinkKind::Absolute | LinkKind::Hard => src.clone(),
+            };
+            links.push((target, link));
+        }
+        let res = links.iter().try_for_each(|(target, link)| {
+            let made = match kind {
+                LinkKind::Hard => fs::hard_link(target, link),
+                LinkKind::Absolute | LinkKind::Relative => symlink::create_symlink(target, link),
+            };
+            made.map_err(|e| FsOpError::from_io_at(link, e))
+        });
+        self.refresh()?;
+        res?;
+        Ok(links.len())
+    }
+
+    /// Point the selected symlink at `target` instead. On local panels the
+    /// new link is made under a temporary name and renamed over the old
+    /// one, so the link never goes missing; other backends remove and
+    /// recreate it.
+    pub fn retarget_symlink(&mut self, target: PathBuf) -> Result<(), FsOpError> {
+        let panel = self.active_panel();
+        let Some(entry) = panel.selected_entry() else { return Ok(()) };
+        let link = entry.path.clone();
+        let is_link = if panel.vfs.is_local() {
+            symlink::is_symlink(&link).map_err(|e| FsOpError::from_io_at(&link, e))?
+        } else {
+            crate::vfs::transfer::is_symlink_mode(entry.unix_mode)
+        };
+        if !is_link {
+            return Err(FsOpError::Unsupported { path: link, msg: "not a symbolic link".to_string() });
+        }
+        if panel.vfs.is_local() {
+            let name = link.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let temp = link.with_file_name(format!(".{}.relink-{}", name, std::process::id()));
+            symlink::create_symlink(&target, &temp).map_err(|e| FsOpError::from_io_at(&temp, e))?;
+            if let Err(e) = fs::rename(&temp, &link) {
+                let _ = fs::remove_file(&temp);
+                return Err(FsOpError::from_io_at(&link, e));
+            }
+        } else {
+            panel.vfs.remove(&link).and_then(|_| panel.vfs.symlink(&target, &link)).map_err(|e| FsOpError::from_io_at(&link, e))?;
+        }
         self.refresh_active()?;
         Ok(())
     }
@@ -141,6 +374,38 @@ impl crate::app::core::App {
     // conflicts when a mutable borrow to a panel is active.
 }
 
+/// Rename within `panel`'s backend in the order `batch_rename::plan`
+/// gives.
+fn run_renames(panel: &crate::app::Panel, renames: &[(PathBuf, PathBuf)]) -> std::io::Result<()> {
+    batch_rename::plan(renames, &|path| name_taken(panel, path)).iter().try_for_each(|(src, target)| {
+        if panel.vfs.is_local() {
+            fs::rename(src, target)
+        } else {
+            panel.vfs.rename(src, target)
+        }
+    })
+}
+
+/// Whether `path` exists in `panel`'s backend, symlinks included.
+pub(crate) fn name_taken(panel: &crate::app::Panel, path: &Path) -> bool {
+    if panel.vfs.is_local() {
+        path.symlink_metadata().is_ok()
+    } else {
+        panel.vfs.stat(path).is_ok()
+    }
+}
+
+/// Backend-aware counterpart of `fs_op::helpers::resolve_target`: join
+/// `src_name` when `dst` names an existing directory in `vfs` or ends with `/`.
+fn resolve_vfs_target(vfs: &dyn crate::vfs::Vfs, dst: &Path, src_name: &str) -> PathBuf {
+    let is_dir = vfs.stat(dst).map(|e| e.is_dir).unwrap_or(false);
+    if is_dir || dst.to_string_lossy().ends_with('/') {
+        dst.join(src_name)
+    } else {
+        dst.to_path_buf()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs as stdfs;
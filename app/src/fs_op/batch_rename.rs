@@ -0,0 +1,379 @@
+//! Renaming many entries at once.
+//!
+//! A `Template` turns each entry's name into a new one: first a mask of
+//! text and `[..]` tokens, then an optional regex find/replace over the
+//! result. `preview` pairs old and new names and flags every collision
+//! before anything is touched, and `plan` orders the renames so none lands
+//! on a name that another rename still has to move away (for a→b, b→c the
+//! b→c rename goes first); cycles such as a↔b go through a temporary name.
+//!
+//! Mask tokens:
+//!
+//! - `[N]` the name without its extension, `[E]` the extension without the
+//!   dot, `*` the whole name;
+//! - `[C]` a counter from 1; `[C:3]` pads it to three digits, `[C10+5]`
+//!   starts at 10 and steps by 5 (both combine: `[C10+5:3]`);
+//! - `[Y]` `[M]` `[D]` `[h]` `[m]` `[s]` the modification date and time,
+//!   zero-padded;
+//! - `[U]` `[L]` `[F]` `[n]` put what follows in upper case, lower case,
+//!   with each word capitalised, or as it is;
+//! - `[[` a literal `[`.
+
+use crate::app::types::Entry;
+use chrono::{Datelike, Timelike};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A parsed mask plus find/replace, ready to apply to entries.
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+    find: Option<Regex>,
+    replace: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Name,
+    Ext,
+    Whole,
+    Counter { start: u64, step: u64, width: usize },
+    Date(char),
+    Case(Case),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Keep,
+    Upper,
+    Lower,
+    Words,
+}
+
+impl Template {
+    /// Parse `mask` (see the module docs) and the regex `find`; an empty
+    /// `find` leaves the masked name alone. `replace` may refer to
+    /// captures as `$1` or `${name}`.
+    pub fn parse(mask: &str, find: &str, replace: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut rest = mask;
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix("[[") {
+                text.push('[');
+                rest = after;
+                continue;
+            }
+            if c == '*' {
+                flush(&mut text, &mut parts);
+                parts.push(Part::Whole);
+                rest = &rest[1..];
+                continue;
+            }
+            if c != '[' {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            let end = rest.find(']').ok_or_else(|| format!("unclosed token: {}", rest))?;
+            flush(&mut text, &mut parts);
+            parts.push(parse_token(&rest[1..end])?);
+            rest = &rest[end + 1..];
+        }
+        flush(&mut text, &mut parts);
+        let find = if find.is_empty() { None } else { Some(Regex::new(find).map_err(|e| format!("invalid find pattern: {}", e))?) };
+        Ok(Template { parts, find, replace: replace.to_string() })
+    }
+
+    /// The new name of `entry`, the `index`-th (from 0) of the entries
+    /// being renamed. Fails when the mask needs a modification time the
+    /// entry does not have, or a counter goes past `u64::MAX`.
+    pub fn apply(&self, index: usize, entry: &Entry) -> Result<String, String> {
+        let (stem, ext) = split_name(&entry.name);
+        let mut case = Case::Keep;
+        let mut out = String::new();
+        for part in &self.parts {
+            let piece = match part {
+                Part::Case(c) => {
+                    case = *c;
+                    continue;
+                }
+                Part::Text(t) => t.clone(),
+                Part::Name => stem.to_string(),
+                Part::Ext => ext.to_string(),
+                Part::Whole => entry.name.clone(),
+                Part::Counter { start, step, width } => {
+                    let value = step.checked_mul(index as u64).and_then(|n| n.checked_add(*start)).ok_or("counter out of range")?;
+                    format!("{:0width$}", value, width = *width)
+                }
+                Part::Date(field) => {
+                    let modified = entry.modified.ok_or("no modification time")?;
+                    match field {
+                        'Y' => format!("{:04}", modified.year()),
+                        'M' => format!("{:02}", modified.month()),
+                        'D' => format!("{:02}", modified.day()),
+                        'h' => format!("{:02}", modified.hour()),
+                        'm' => format!("{:02}", modified.minute()),
+                        _ => format!("{:02}", modified.second()),
+                    }
+                }
+            };
+            out.push_str(&convert_case(&piece, case));
+        }
+        Ok(match &self.find {
+            Some(find) => find.replace_all(&out, self.replace.as_str()).into_owned(),
+            None => out,
+        })
+    }
+}
+
+fn flush(text: &mut String, parts: &mut Vec<Part>) {
+    if !text.is_empty() {
+        parts.push(Part::Text(std::mem::take(text)));
+    }
+}
+
+fn parse_token(token: &str) -> Result<Part, String> {
+    let part = match token {
+        "N" => Part::Name,
+        "E" => Part::Ext,
+        "Y" | "M" | "D" | "h" | "m" | "s" => Part::Date(token.chars().next().unwrap_or('s')),
+        "U" => Part::Case(Case::Upper),
+        "L" => Part::Case(Case::Lower),
+        "F" => Part::Case(Case::Words),
+        "n" => Part::Case(Case::Keep),
+        _ => match token.strip_prefix('C') {
+            Some(counter) => parse_counter(counter).ok_or_else(|| format!("invalid counter: [{}]", token))?,
+            None => return Err(format!("unknown token: [{}]", token)),
+        },
+    };
+    Ok(part)
+}
+
+/// `[C]`, `[C<start>]`, `[C<start>+<step>]`, each optionally `:<width>`
+/// (at most 255, the longest name most filesystems allow).
+fn parse_counter(spec: &str) -> Option<Part> {
+    let (spec, width) = match spec.split_once(':') {
+        Some((spec, width)) => (spec, width.parse().ok().filter(|w| *w <= 255)?),
+        None => (spec, 1),
+    };
+    let (start, step) = match spec.split_once('+') {
+        Some((start, step)) => (start, step.parse().ok()?),
+        None => (spec, 1),
+    };
+    let start = if start.is_empty() { 1 } else { start.parse().ok()? };
+    Some(Part::Counter { start, step, width })
+}
+
+/// `name` split at its last dot into stem and extension. Dot files such as
+/// `.bashrc` and names without a dot have no extension.
+fn split_name(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => (&name[..dot], &name[dot + 1..]),
+        _ => (name, ""),
+    }
+}
+
+fn convert_case(s: &str, case: Case) -> String {
+    match case {
+        Case::Keep => s.to_string(),
+        Case::Upper => s.to_uppercase(),
+        Case::Lower => s.to_lowercase(),
+        Case::Words => {
+            let mut out = String::with_capacity(s.len());
+            let mut start = true;
+            for c in s.chars() {
+                if start {
+                    out.extend(c.to_uppercase());
+                } else {
+                    out.extend(c.to_lowercase());
+                }
+                start = !c.is_alphanumeric();
+            }
+            out
+        }
+    }
+}
+
+/// One line of the preview: the current name, the new one, and what is
+/// wrong with it, if anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewRow {
+    pub path: PathBuf,
+    pub old: String,
+    pub new: String,
+    pub problem: Option<String>,
+}
+
+/// Apply `template` to `entries` and check the results: a new name must be
+/// a valid file name, may not be given to two entries, and may not belong
+/// to anything but one of the entries being renamed (`exists` says whether
+/// a name is taken in the directory).
+pub fn preview(template: &Template, entries: &[&Entry], exists: &dyn Fn(&str) -> bool) -> Vec<PreviewRow> {
+    let mut rows: Vec<PreviewRow> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (new, problem) = match template.apply(i, entry) {
+                Ok(new) => {
+                    let problem = invalid_name(&new).map(str::to_string);
+                    (new, problem)
+                }
+                Err(e) => (String::new(), Some(e)),
+            };
+            let problem = problem.or_else(|| entry.path.file_name().is_some_and(|n| n.to_str().is_none()).then(|| "name is not valid UTF-8".to_string()));
+            PreviewRow { path: entry.path.clone(), old: entry.name.clone(), new, problem }
+        })
+        .collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for row in &rows {
+        *counts.entry(row.new.clone()).or_default() += 1;
+    }
+    let renamed: HashSet<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    let problems: Vec<Option<String>> = rows
+        .iter()
+        .map(|row| {
+            if row.problem.is_some() || row.new == row.old {
+                row.problem.clone()
+            } else if counts[&row.new] > 1 {
+                Some("another item gets the same name".to_string())
+            } else if !renamed.contains(row.new.as_str()) && exists(&row.new) {
+                Some("name already exists".to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+    for (row, problem) in rows.iter_mut().zip(problems) {
+        row.problem = problem;
+    }
+    rows
+}
+
+/// Why `name` cannot be a file name, if it cannot.
+pub(crate) fn invalid_name(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        Some("empty name")
+    } else if name == "." || name == ".." {
+        Some("reserved name")
+    } else if name.contains('/') || name.contains('\0') {
+        Some("name contains / or NUL")
+    } else {
+        None
+    }
+}
+
+/// Order `renames` so that each one's target is free when it runs: a
+/// rename waits while its target is still the source of a pending rename.
+/// Renames left waiting on each other form cycles; one of each is moved
+/// to a temporary name first (one `exists` does not report) and finished
+/// last. Renames to the same path are dropped.
+pub fn plan(renames: &[(PathBuf, PathBuf)], exists: &dyn Fn(&Path) -> bool) -> Vec<(PathBuf, PathBuf)> {
+    let mut pending: Vec<(PathBuf, PathBuf)> = renames.iter().filter(|(from, to)| from != to).cloned().collect();
+    let mut steps = Vec::with_capacity(pending.len());
+    let mut temps = 0;
+    while !pending.is_empty() {
+        let free = pending.iter().position(|(_, to)| !pending.iter().any(|(from, _)| from == to));
+        match free {
+            Some(i) => steps.push(pending.remove(i)),
+            None => {
+                let (from, to) = pending.remove(0);
+                let temp = loop {
+                    temps += 1;
+                    let name = format!(".{}.rename-{}", from.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(), temps);
+                    let temp = from.with_file_name(name);
+                    if !exists(&temp) && !renames.iter().any(|(a, b)| *a == temp || *b == temp) {
+                        break temp;
+                    }
+                };
+                steps.push((from, temp.clone()));
+                pending.push((temp, to));
+            }
+        }
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn entry(name: &str) -> Entry {
+        let mut e = Entry::file(name, PathBuf::from("/d").join(name), 0, None);
+        e.modified = Some(Local.with_ymd_and_hms(2024, 3, 9, 7, 5, 1).unwrap());
+        e
+    }
+
+    fn names(mask: &str, find: &str, replace: &str, of: &[&str]) -> Vec<String> {
+        let template = Template::parse(mask, find, replace).unwrap();
+        of.iter().enumerate().map(|(i, n)| template.apply(i, &entry(n)).unwrap()).collect()
+    }
+
+    #[test]
+    fn masks_expand_tokens_counters_dates_and_case() {
+        assert_eq!(names("[N]-[C:3].[E]", "", "", &["a.txt", "b.txt"]), ["a-001.txt", "b-002.txt"]);
+        assert_eq!(names("[C10+5] *", "", "", &["x", ".rc"]), ["10 x", "15 .rc"]);
+        assert_eq!(names("[Y]-[M]-[D]_[h][m][s]_[N]", "", "", &["p.jpg"]), ["2024-03-09_070501_p"]);
+        assert_eq!(names("[U][N][n].[L][E]", "", "", &["Photo.JPG"]), ["PHOTO.jpg"]);
+        assert_eq!(names("[F][N]", "", "", &["hello big-world"]), ["Hello Big-World"]);
+        assert_eq!(names("[[[N]]", "", "", &["a"]), ["[a]"]);
+        assert_eq!(names("*", r"^IMG_(\d+)", "photo-$1", &["IMG_0042.jpg", "other.jpg"]), ["photo-0042.jpg", "other.jpg"]);
+        for bad in ["[X]", "[C+]", "[N", "[C:x]", "[C:1000]"] {
+            assert!(Template::parse(bad, "", "").is_err(), "{}", bad);
+        }
+        assert!(Template::parse("*", "(", "").is_err());
+    }
+
+    #[test]
+    fn preview_flags_collisions_and_bad_names() {
+        let entries = [entry("a.txt"), entry("b.txt"), entry("c.md")];
+        let refs: Vec<&Entry> = entries.iter().collect();
+        let taken = |name: &str| name == "c.txt" || name == "a.txt";
+
+        let rows = preview(&Template::parse("[N].txt", "", "").unwrap(), &refs, &taken);
+        let problems: Vec<_> = rows.iter().map(|r| (r.new.as_str(), r.problem.as_deref())).collect();
+        assert_eq!(problems, [("a.txt", None), ("b.txt", None), ("c.txt", Some("name already exists"))]);
+
+        let big = format!("[C{}+{}]", u64::MAX - 1, u64::MAX / 2);
+        let rows = preview(&Template::parse(&big, "", "").unwrap(), &refs, &taken);
+        let problems: Vec<_> = rows.iter().map(|r| r.problem.as_deref()).collect();
+        assert_eq!(problems, [None, Some("counter out of range"), Some("counter out of range")]);
+
+        let rows = preview(&Template::parse("same", "", "").unwrap(), &refs, &taken);
+        assert!(rows.iter().all(|r| r.problem.as_deref() == Some("another item gets the same name")));
+
+        // Names of entries being renamed away are free to take.
+        let rows = preview(&Template::parse("[N].txt", "^b", "a").unwrap(), &refs[1..2], &taken);
+        assert_eq!(rows[0].problem.as_deref(), Some("name already exists"));
+        let rows = preview(&Template::parse("*", "^a", "x").unwrap(), &refs[..1], &taken);
+        assert_eq!((rows[0].new.as_str(), rows[0].problem.as_deref()), ("x.txt", None));
+
+        let rows = preview(&Template::parse("[N]/x", "", "").unwrap(), &refs[..1], &taken);
+        assert_eq!(rows[0].problem.as_deref(), Some("name contains / or NUL"));
+    }
+
+    #[test]
+    fn plans_run_chains_in_order_and_break_cycles() {
+        let p = |s: &str| PathBuf::from("/d").join(s);
+        let nothing = |_: &Path| false;
+
+        let steps = plan(&[(p("a"), p("b")), (p("b"), p("c"))], &nothing);
+        assert_eq!(steps, [(p("b"), p("c")), (p("a"), p("b"))]);
+
+        let steps = plan(&[(p("a"), p("b")), (p("b"), p("a")), (p("x"), p("x"))], &nothing);
+        assert_eq!(steps, [(p("a"), p(".a.rename-1")), (p("b"), p("a")), (p(".a.rename-1"), p("b"))]);
+
+        // Replay the plan on a set of names to check nothing is clobbered.
+        let rotate = [(p("1"), p("2")), (p("2"), p("3")), (p("3"), p("1")), (p("0"), p("4"))];
+        let mut present: HashSet<PathBuf> = [p("0"), p("1"), p("2"), p("3")].into_iter().collect();
+        let taken = |path: &Path| path == p(".1.rename-1");
+        for (from, to) in plan(&rotate, &taken) {
+            assert!(!taken(&to));
+            assert!(present.remove(&from) && present.insert(to.clone()), "{:?} -> {:?}", from, to);
+        }
+        assert_eq!(present, [p("1"), p("2"), p("3"), p("4")].into_iter().collect());
+    }
+}
@@ -0,0 +1,52 @@
+//! Streaming content checksums, used to verify copies.
+//!
+//! Digests are 64-bit xxh3: fast enough to re-read a large copy without
+//! doubling its run time, and plenty to catch corrupted or truncated
+//! targets (it is not meant to resist tampering).
+
+use std::hash::Hasher;
+use std::io::{self, Read};
+use twox_hash::XxHash3_64;
+
+/// Buffer size used while hashing.
+const HASH_CHUNK: usize = 64 * 1024;
+
+/// xxh3 digest of everything `reader` yields. `checkpoint` is called with
+/// the bytes hashed so far after every chunk; an error from it stops
+/// hashing and is returned unchanged.
+pub fn xxh3_reader(reader: &mut dyn Read, checkpoint: &mut dyn FnMut(u64) -> io::Result<()>) -> io::Result<u64> {
+    let mut hasher = XxHash3_64::new();
+    let mut buf = vec![0u8; HASH_CHUNK];
+    let mut done = 0u64;
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.write(&buf[..read]);
+        done += read as u64;
+        checkpoint(done)?;
+    }
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_match_the_one_shot_hash_across_chunks() {
+        let data: Vec<u8> = (0..HASH_CHUNK * 2 + 17).map(|i| (i % 251) as u8).collect();
+        let mut seen = Vec::new();
+        let digest = xxh3_reader(&mut &data[..], &mut |n| {
+            seen.push(n);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(digest, XxHash3_64::oneshot(&data));
+        assert_eq!(seen, vec![HASH_CHUNK as u64, 2 * HASH_CHUNK as u64, data.len() as u64]);
+        assert_ne!(xxh3_reader(&mut &data[1..], &mut |_| Ok(())).unwrap(), digest);
+    }
+}
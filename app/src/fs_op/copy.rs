@@ -1,8 +1,8 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::path::Path;
-use fs_extra::file::copy as file_copy;
-use fs_extra::dir::{copy as dir_copy, CopyOptions};
+use std::path::{Path, PathBuf};
+use crate::fs_op::helpers::{CopyCheckpoint, FailureHook, SkipHook};
 #[cfg(unix)]
 use std::os::unix::fs::{PermissionsExt, symlink as unix_symlink};
 #[cfg(unix)]
@@ -15,7 +15,7 @@ use std::os::windows::fs::{symlink_dir as windows_symlink_dir, symlink_file as w
 /// This helper will:
 /// - create `dst` (and parents) if necessary;
 /// - copy the contents of `src` into `dst` (i.e. the children of `src`, not
-///   the `src` directory itself) using `fs_extra`'s directory copy;
+///   the `src` directory itself), merging into existing subdirectories;
 /// - attempt to preserve metadata (permissions/timestamps) by delegating to
 ///   `crate::fs_op::metadata::preserve_all_metadata` after a successful copy.
 ///
@@ -26,91 +26,176 @@ use std::os::windows::fs::{symlink_dir as windows_symlink_dir, symlink_file as w
 ///   preserved when possible. On Unix this helper will recreate symlinks and
 ///   named pipes (FIFOs) using best-effort system calls; device node creation
 ///   may require privileges and will be attempted but may fail with a
-///   permission error.
+///   permission error. Sockets and unknown file types are left out.
+/// - Files hardlinked to each other inside `src` are hardlinked to each
+///   other in `dst` too, rather than copied once per name.
+/// - Names are copied byte for byte, whether or not they are valid UTF-8.
 ///
 /// # Errors
 /// Returns an `io::Error` for any underlying filesystem or copy errors.
-/// Errors coming from `fs_extra` are mapped into `io::ErrorKind::Other`.
 pub(crate) fn copy_recursive(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
-    let src = src.as_ref();
-    let dst = dst.as_ref();
-
-    // Ensure the destination directory exists before starting.
-    fs::create_dir_all(dst)?;
-
-
-    // Iterate top-level entries under `src` and copy them into `dst` using
-    // `fs_extra` so we get predictable behaviour (each child of `src` is
-    // copied into `dst` rather than possibly nesting the source directory).
-    for entry in fs::read_dir(src).map_err(io::Error::other)? {
-        let entry = entry.map_err(io::Error::other)?;
-        let path = entry.path();
-        let file_name = match entry.file_name().into_string() {
-            Ok(s) => s,
-            Err(_) => continue, // skip non-UTF8 names in tests/complex scenarios
+    copy_recursive_with(src.as_ref(), dst.as_ref(), &mut |_, _, _| Ok(()), &mut |_, e| Err(e), &mut |_, _| {})
+}
+
+/// Like `copy_recursive`, copying files in chunks and calling `checkpoint`
+/// around each chunk (see `helpers::CopyCheckpoint`). An error from
+/// `checkpoint` stops the copy between or inside files. Entries that fail
+/// are passed to `on_error`, which decides whether to retry, skip or stop
+/// (see `helpers::FailureHook`); entries left out on purpose go to
+/// `on_skip`.
+pub(crate) fn copy_recursive_with(src: &Path, dst: &Path, checkpoint: &mut CopyCheckpoint<'_>, on_error: &mut FailureHook<'_>, on_skip: &mut SkipHook<'_>) -> io::Result<()> {
+    TreeCopy { resume: None, checkpoint, on_error, on_skip, links: HashMap::new() }.copy_dir_contents(src, dst)?;
+
+    // Attempt to preserve metadata for the whole tree (best-effort).
+    crate::fs_op::metadata::preserve_all_metadata(src, dst)?;
+
+    Ok(())
+}
+
+/// Like `copy_recursive_with`, copying each file with
+/// `helpers::resumable_copy_file_with` so a copy interrupted mid-file
+/// leaves a partial file behind. `resume_at` names the file the job
+/// journal recorded as partly copied and its offset; that partial file is
+/// continued if it still checks out. Files already in `dst` are skipped as
+/// usual, so rerunning an interrupted tree copy only does what is left.
+pub(crate) fn copy_recursive_resumable(src: &Path, dst: &Path, resume_at: Option<(&Path, u64)>, checkpoint: &mut CopyCheckpoint<'_>, on_error: &mut FailureHook<'_>, on_skip: &mut SkipHook<'_>) -> io::Result<()> {
+    TreeCopy { resume: Some(resume_at.map(|(file, offset)| (file.to_path_buf(), offset))), checkpoint, on_error, on_skip, links: HashMap::new() }.copy_dir_contents(src, dst)?;
+
+    // Attempt to preserve metadata for the whole tree (best-effort).
+    crate::fs_op::metadata::preserve_all_metadata(src, dst)?;
+
+    Ok(())
+}
+
+/// State of one tree copy, shared by every entry in it.
+struct TreeCopy<'a, 'b> {
+    /// `None` for plain atomic copies, otherwise the journaled file and
+    /// offset to continue, if any (see `copy_recursive_resumable`).
+    resume: Option<Option<(PathBuf, u64)>>,
+    checkpoint: &'a mut CopyCheckpoint<'b>,
+    on_error: &'a mut FailureHook<'b>,
+    on_skip: &'a mut SkipHook<'b>,
+    /// Where each multiply-linked source file, keyed by device and inode,
+    /// was first copied to; later names for it become hardlinks to that.
+    links: HashMap<(u64, u64), PathBuf>,
+}
+
+impl TreeCopy<'_, '_> {
+    /// Entries that fail go to `on_error` (see `helpers::FailureHook`);
+    /// directories recurse outside of it so a failure deep in the tree is
+    /// reported once, for the entry that failed.
+    fn copy_dir_contents(&mut self, src: &Path, dst: &Path) -> io::Result<()> {
+        // Ensure the destination directory exists and the source is readable
+        // before starting.
+        let entries = loop {
+            match fs::create_dir_all(dst).and_then(|_| fs::read_dir(src)) {
+                Ok(entries) => break entries,
+                Err(e) => {
+                    if !(self.on_error)(src, e)? {
+                        return Ok(());
+                    }
+                }
+            }
         };
 
-        // Use symlink_metadata so we can detect symlinks and special file types
-        // without following the link.
-        let meta = fs::symlink_metadata(&path).map_err(io::Error::other)?;
-
-        if meta.file_type().is_dir() {
-            // If the destination directory already exists, copy the contents
-            // of `path` into it (preserving existing files). Otherwise copy
-            // the directory itself into `dst`.
-            let dest_dir = dst.join(&file_name);
-            let mut dir_opts = CopyOptions::new();
-            dir_opts.overwrite = false;
-            dir_opts.buffer_size = 64 * 1024;
-
-            if dest_dir.exists() {
-                // copy contents into existing dest_dir
-                dir_opts.copy_inside = true;
-                dir_copy(&path, &dest_dir, &dir_opts).map_err(|e| io::Error::other(e.to_string()))?;
-            } else {
-                // copy directory as a child of dst
-                dir_opts.copy_inside = false;
-                dir_copy(&path, dst, &dir_opts).map_err(|e| io::Error::other(e.to_string()))?;
+        // Copy each child of `src` into `dst`, recursing into directories so
+        // existing destination directories are merged into.
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    (self.on_error)(src, e)?;
+                    continue;
+                }
+            };
+            let path = entry.path();
+            let file_name = entry.file_name();
+
+            // Use symlink_metadata so we can detect symlinks and special file types
+            // without following the link.
+            let meta = match fs::symlink_metadata(&path) {
+                Ok(meta) => meta,
+                Err(e) => {
+                    (self.on_error)(&path, e)?;
+                    continue;
+                }
+            };
+
+            if meta.file_type().is_dir() {
+                self.copy_dir_contents(&path, &dst.join(&file_name))?;
+                continue;
+            }
+
+            loop {
+                match self.copy_entry(&path, &meta, &dst.join(&file_name)) {
+                    Ok(()) => break,
+                    Err(e) => {
+                        if !(self.on_error)(&path, e)? {
+                            break;
+                        }
+                    }
+                }
             }
-            continue;
         }
 
-        if meta.file_type().is_file() {
-            // Copy the file into `dst/<file_name>` using fs_extra file copy.
-            let dest_file = dst.join(&file_name);
-            if dest_file.exists() {
-                // Respect non-overwrite semantics: skip existing files.
-                continue;
+        Ok(())
+    }
+
+    /// Copy a regular file, or hardlink it to an earlier copy of the same
+    /// source inode. Existing destinations are left alone.
+    fn copy_file(&mut self, path: &Path, meta: &fs::Metadata, dest: &Path) -> io::Result<()> {
+        if dest.exists() {
+            // Respect non-overwrite semantics: skip existing files.
+            return Ok(());
+        }
+        let key = link_key(meta);
+        if let Some(first) = key.and_then(|key| self.links.get(&key)) {
+            if fs::hard_link(first, dest).is_ok() {
+                return Ok(());
             }
-            let mut file_opts = fs_extra::file::CopyOptions::new();
-            file_opts.overwrite = false;
-            file_opts.buffer_size = 64 * 1024;
-            file_copy(&path, &dest_file, &file_opts).map_err(|e| io::Error::other(e.to_string()))?;
-            continue;
+        }
+        match &self.resume {
+            Some(resume) => {
+                let at = resume.as_ref().filter(|(src, _)| src == path).map(|(_, offset)| *offset);
+                crate::fs_op::helpers::resumable_copy_file_with(path, dest, at, self.checkpoint)?
+            }
+            None => crate::fs_op::helpers::atomic_copy_file_with(path, dest, self.checkpoint)?,
+        };
+        if let Some(key) = key {
+            self.links.entry(key).or_insert_with(|| dest.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Copy one non-directory entry `path` (described by `meta`) to `dest`.
+    /// Existing destinations are left alone.
+    fn copy_entry(&mut self, path: &Path, meta: &fs::Metadata, dest: &Path) -> io::Result<()> {
+        if meta.file_type().is_file() {
+            return self.copy_file(path, meta, dest);
         }
 
         // Handle symlinks and some special file types.
         if meta.file_type().is_symlink() {
             // Recreate the symlink at the destination with the same target.
-            let target = fs::read_link(&path).map_err(io::Error::other)?;
-            let dest_link = dst.join(&file_name);
+            let target = fs::read_link(path).map_err(io::Error::other)?;
+            let dest_link = dest;
             // If destination exists, do not overwrite.
             if dest_link.exists() {
-                continue;
+                return Ok(());
             }
             #[cfg(unix)]
             {
-                unix_symlink(&target, &dest_link).map_err(io::Error::other)?;
+                unix_symlink(&target, dest_link).map_err(io::Error::other)?;
             }
             #[cfg(windows)]
             {
                 if meta.file_type().is_dir() {
-                    windows_symlink_dir(&target, &dest_link).map_err(io::Error::other)?;
+                    windows_symlink_dir(&target, dest_link).map_err(io::Error::other)?;
                 } else {
-                    windows_symlink_file(&target, &dest_link).map_err(io::Error::other)?;
+                    windows_symlink_file(&target, dest_link).map_err(io::Error::other)?;
                 }
             }
-            continue;
+            return Ok(());
         }
 
         // Unix-only: try to preserve FIFOs (named pipes) and device nodes where possible.
@@ -119,7 +204,7 @@ pub(crate) fn copy_recursive(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io
             use std::os::unix::fs::FileTypeExt;
             use std::ffi::CString;
 
-            let dest_path = dst.join(&file_name);
+            let dest_path = dest;
 
             if meta.file_type().is_fifo() {
                 // Create a FIFO at dest with the same mode bits as source (best-effort).
@@ -129,7 +214,7 @@ pub(crate) fn copy_recursive(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io
                 if res != 0 {
                     return Err(io::Error::last_os_error());
                 }
-                continue;
+                return Ok(());
             }
 
             if meta.file_type().is_char_device() || meta.file_type().is_block_device() {
@@ -146,19 +231,33 @@ pub(crate) fn copy_recursive(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io
                 if res != 0 {
                     return Err(io::Error::last_os_error());
                 }
-                continue;
+                return Ok(());
             }
         }
 
-        // Other special types (sockets, unknown) are currently ignored.
+        // Sockets only exist while a process listens on them, so there is
+        // nothing to copy; report them (and anything unknown) instead.
+        #[cfg(unix)]
+        let kind = if std::os::unix::fs::FileTypeExt::is_socket(&meta.file_type()) { "socket" } else { "unknown file type" };
+        #[cfg(not(unix))]
+        let kind = "unknown file type";
+        (self.on_skip)(path, kind);
+        Ok(())
     }
+}
 
-    // Attempt to preserve metadata for the whole tree (best-effort).
-    crate::fs_op::metadata::preserve_all_metadata(src, dst)?;
-
-    Ok(())
+/// Device and inode of a file with more than one name, or `None` when it
+/// has a single one (or the platform has no inodes).
+#[cfg(unix)]
+fn link_key(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (meta.nlink() > 1).then(|| (meta.dev(), meta.ino()))
 }
 
+#[cfg(not(unix))]
+fn link_key(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
 
 #[cfg(test)]
 mod tests {
@@ -304,4 +403,46 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn recreates_hardlinks_and_keeps_non_utf8_names() -> io::Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::fs::MetadataExt;
+
+        let src = tempfile::tempdir()?;
+        let dst = tempfile::tempdir()?;
+        write_file(&src.path().join("a.bin"), "shared")?;
+        fs::create_dir_all(src.path().join("sub"))?;
+        fs::hard_link(src.path().join("a.bin"), src.path().join("sub/b.bin"))?;
+        let odd = OsStr::from_bytes(b"caf\xe9.txt");
+        write_file(&src.path().join(odd), "latin-1")?;
+
+        copy_recursive(src.path(), dst.path())?;
+
+        let a = fs::metadata(dst.path().join("a.bin"))?;
+        let b = fs::metadata(dst.path().join("sub/b.bin"))?;
+        assert_eq!((a.ino(), a.nlink()), (b.ino(), 2));
+        assert_ne!(a.ino(), fs::metadata(src.path().join("a.bin"))?.ino());
+        assert_eq!(fs::read(dst.path().join(odd))?, b"latin-1");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_sockets_instead_of_copying_them() -> io::Result<()> {
+        let src = tempfile::tempdir()?;
+        let dst = tempfile::tempdir()?;
+        let sock = src.path().join("app.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&sock)?;
+        write_file(&src.path().join("data.txt"), "data")?;
+
+        let mut skipped = Vec::new();
+        copy_recursive_with(src.path(), dst.path(), &mut |_, _, _| Ok(()), &mut |_, e| Err(e), &mut |path, kind| skipped.push((path.to_path_buf(), kind)))?;
+
+        assert_eq!(skipped, vec![(sock, "socket")]);
+        assert!(!dst.path().join("app.sock").exists());
+        assert!(dst.path().join("data.txt").exists());
+        Ok(())
+    }
 }
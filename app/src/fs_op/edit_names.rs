@@ -0,0 +1,141 @@
+//! Renaming by editing a list of names in a text editor, like `vidir`.
+//!
+//! `listing` writes one line per entry: an id, a tab, then the name. The
+//! user edits the names; `parse` reads the file back and turns changed
+//! lines into renames and removed lines into deletes. Ids tie each line to
+//! its entry, so lines may also be reordered. Everything is checked before
+//! anything is applied: ids must be known and used once, names valid, and
+//! no two entries may end up with the same name or take one that belongs
+//! to something else in the directory.
+
+use crate::fs_op::batch_rename::invalid_name;
+use std::collections::{HashMap, HashSet};
+
+/// What an edited listing asks for, by index into the listed names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NameEdits {
+    /// Entries to rename and their new names.
+    pub renames: Vec<(usize, String)>,
+    /// Entries whose lines were removed.
+    pub deletes: Vec<usize>,
+}
+
+impl NameEdits {
+    /// Whether the listing came back without changes.
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty() && self.deletes.is_empty()
+    }
+}
+
+/// The text to edit: `names` one per line, each after an id (its index
+/// plus one, zero-padded to the same width) and a tab.
+pub fn listing(names: &[String]) -> String {
+    let width = names.len().to_string().len();
+    names.iter().enumerate().map(|(i, name)| format!("{:0width$}\t{}\n", i + 1, name, width = width)).collect()
+}
+
+/// Read `edited` back against the listed `names`. Blank lines are
+/// ignored. `exists` says whether a name is taken in the directory; names
+/// of listed entries are always free to take. Errors name the offending
+/// line.
+pub fn parse(names: &[String], edited: &str, exists: &dyn Fn(&str) -> bool) -> Result<NameEdits, String> {
+    let mut new_names: Vec<Option<(usize, &str)>> = vec![None; names.len()];
+    for (n, line) in edited.lines().enumerate().map(|(n, line)| (n + 1, line)) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (id, name) = line.trim_start().split_once('\t').ok_or_else(|| format!("line {}: expected an id, a tab and a name", n))?;
+        let index = id.parse::<usize>().ok().filter(|id| (1..=names.len()).contains(id)).ok_or_else(|| format!("line {}: unknown id {}", n, id))? - 1;
+        if new_names[index].is_some() {
+            return Err(format!("line {}: id {} is used twice", n, id));
+        }
+        if let Some(problem) = invalid_name(name) {
+            return Err(format!("line {}: {}", n, problem));
+        }
+        new_names[index] = Some((n, name));
+    }
+
+    let listed: HashSet<&str> = names.iter().map(String::as_str).collect();
+    let mut taken: HashMap<&str, usize> = HashMap::new();
+    let mut edits = NameEdits::default();
+    for (index, new) in new_names.iter().enumerate() {
+        let Some((n, name)) = *new else {
+            edits.deletes.push(index);
+            continue;
+        };
+        if let Some(other) = taken.insert(name, n) {
+            return Err(format!("line {}: {} is also given on line {}", n, name, other));
+        }
+        if name != names[index] {
+            if !listed.contains(name) && exists(name) {
+                return Err(format!("line {}: {} already exists", n, name));
+            }
+            edits.renames.push((index, name.to_string()));
+        }
+    }
+    Ok(edits)
+}
+
+/// The changes as a diff for the confirmation view: renames as `-old` /
+/// `+new` pairs, then deletes as `-old`, each group under an `@@` header.
+/// The deletes' header says whether they go to the trash or for good.
+pub fn summary(names: &[String], edits: &NameEdits, trash: bool) -> String {
+    let mut text = String::new();
+    if !edits.renames.is_empty() {
+        text.push_str(&format!("@@ {} renamed\n", edits.renames.len()));
+        for (index, new) in &edits.renames {
+            text.push_str(&format!("-{}\n+{}\n", names[*index], new));
+        }
+    }
+    if !edits.deletes.is_empty() {
+        let how = if trash { "moved to the trash" } else { "deleted permanently" };
+        text.push_str(&format!("@@ {} {}\n", edits.deletes.len(), how));
+        for index in &edits.deletes {
+            text.push_str(&format!("-{}\n", names[*index]));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn changed_lines_rename_and_removed_lines_delete() {
+        let names = names(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        let text = listing(&names);
+        assert!(text.starts_with("01\ta\n02\tb\n"));
+        assert!(text.ends_with("10\tj\n"));
+
+        // Swap a and b, drop c, reorder the rest, leave a blank line.
+        let edited = text.replace("01\ta", "01\tb").replace("02\tb", "02\ta").replace("03\tc\n", "\n").replace("10\tj\n", "");
+        let edits = parse(&names, &format!("10\tJ two\n{}", edited), &|_| false).unwrap();
+        assert_eq!(edits.renames, [(0, "b".to_string()), (1, "a".to_string()), (9, "J two".to_string())].to_vec());
+        assert_eq!(edits.deletes, [2]);
+        assert_eq!(summary(&names, &edits, true), "@@ 3 renamed\n-a\n+b\n-b\n+a\n-j\n+J two\n@@ 1 moved to the trash\n-c\n");
+        assert!(summary(&names, &edits, false).ends_with("@@ 1 deleted permanently\n-c\n"));
+
+        assert!(parse(&names, &text, &|_| false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_lines_and_collisions_are_refused() {
+        let names = names(&["a", "b", "c"]);
+        let taken = |name: &str| name == "x";
+        let check = |edited: &str| parse(&names, edited, &taken).unwrap_err();
+        assert_eq!(check("1\ta\nb\n"), "line 2: expected an id, a tab and a name");
+        assert_eq!(check("1\ta\n7\tb\n"), "line 2: unknown id 7");
+        assert_eq!(check("1\ta\n1\tb\n"), "line 2: id 1 is used twice");
+        assert_eq!(check("1\ta/b\n"), "line 1: name contains / or NUL");
+        assert_eq!(check("1\tc\n2\tb\n3\tc\n"), "line 3: c is also given on line 1");
+        assert_eq!(check("1\tx\n"), "line 1: x already exists");
+        // Taking the name of a deleted entry is fine.
+        let edits = parse(&names, "1\tc\n2\tb\n", &taken).unwrap();
+        assert_eq!((edits.renames, edits.deletes), (vec![(0, "c".to_string())], vec![2]));
+    }
+}
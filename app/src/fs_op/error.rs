@@ -28,6 +28,14 @@ pub enum FsOpError {
         dst: PathBuf,
         msg: String,
     },
+
+    /// The path uses a feature this build cannot handle, such as an
+    /// encrypted archive or an unknown compression method.
+    #[error("Unsupported: `{path}`: {msg}")]
+    Unsupported {
+        path: PathBuf,
+        msg: String,
+    },
 }
 
 impl From<String> for FsOpError {
@@ -51,4 +59,14 @@ impl FsOpError {
             msg: msg.into(),
         }
     }
+
+    /// Wrap an I/O error for `path`, keeping `ErrorKind::Unsupported`
+    /// distinct so the UI can say what is missing rather than "I/O error".
+    pub fn from_io_at<P: Into<PathBuf>>(path: P, err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::Unsupported {
+            FsOpError::Unsupported { path: path.into(), msg: err.to_string() }
+        } else {
+            FsOpError::Io(err)
+        }
+    }
 }
@@ -0,0 +1,301 @@
+//! How file data gets from one open file to another.
+//!
+//! Copies try the cheapest way first and fall back step by step:
+//!
+//! 1. a reflink (`FICLONE`), which shares the source's extents on btrfs,
+//!    XFS and similar filesystems and finishes instantly;
+//! 2. `copy_file_range`, which keeps the data in the kernel;
+//! 3. a buffered read/write loop.
+//!
+//! Steps 2 and 3 walk the source's data segments with `SEEK_DATA` /
+//! `SEEK_HOLE` and seek over holes in the target, so sparse files such as
+//! VM images stay sparse. Steps that the platform or filesystem does not
+//! support fail with an error that just moves on to the next one.
+//!
+//! The `copy_method` setting picks the first step (`CopyMethod`); it is
+//! process-wide, like the theme, and read by every copy.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Buffer size of the read/write loop.
+const COPY_CHUNK: usize = 64 * 1024;
+
+/// Bytes handed to one `copy_file_range` call, so progress (and pausing or
+/// cancelling) still happens between calls.
+const RANGE_CHUNK: u64 = 4 * 1024 * 1024;
+
+/// The first step a copy tries; later steps are still used as fallbacks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CopyMethod {
+    /// Reflink, then `copy_file_range`, then the read/write loop.
+    #[default]
+    Reflink,
+    /// `copy_file_range`, then the read/write loop, skipping holes.
+    CopyRange,
+    /// Only the read/write loop, skipping holes.
+    Sparse,
+    /// The read/write loop over every byte; holes in the source are
+    /// written out as zeros.
+    Plain,
+}
+
+impl CopyMethod {
+    const ALL: [CopyMethod; 4] = [CopyMethod::Reflink, CopyMethod::CopyRange, CopyMethod::Sparse, CopyMethod::Plain];
+
+    /// The value `METHOD` stores for this method.
+    fn index(self) -> u8 {
+        Self::ALL.iter().position(|m| *m == self).unwrap_or(0) as u8
+    }
+
+    /// The method stored as `index`; `Reflink` for anything unknown.
+    fn from_index(index: u8) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+}
+
+static METHOD: AtomicU8 = AtomicU8::new(0);
+
+/// Make `method` the first step of every later copy.
+pub fn set_method(method: CopyMethod) {
+    METHOD.store(method.index(), Ordering::Relaxed);
+}
+
+/// The method set by `set_method`; `CopyMethod::Reflink` until then.
+pub fn method() -> CopyMethod {
+    CopyMethod::from_index(METHOD.load(Ordering::Relaxed))
+}
+
+/// Copy `reader`'s data from byte `offset` to its end into `writer`, which
+/// must already hold the first `offset` bytes. `len` is the source size;
+/// `progress` gets the bytes reached so far after every step, and an error
+/// from it stops the copy. Returns the size of the target.
+pub fn copy_data(reader: &File, writer: &File, offset: u64, len: u64, method: CopyMethod, progress: &mut dyn FnMut(u64) -> io::Result<()>) -> io::Result<u64> {
+    if method == CopyMethod::Reflink && offset == 0 && len > 0 && reflink(reader, writer).is_ok() {
+        progress(len)?;
+        return Ok(len);
+    }
+    if method == CopyMethod::Plain {
+        return copy_buffered(reader, writer, offset, None, progress);
+    }
+    let mut use_range = method != CopyMethod::Sparse;
+    let mut pos = offset;
+    while pos < len {
+        let Some((start, end)) = next_data(reader, pos, len) else {
+            // Only a hole is left.
+            pos = len;
+            progress(pos)?;
+            break;
+        };
+        if start > pos {
+            progress(start)?;
+        }
+        let mut at = start;
+        if use_range {
+            let (done, supported) = copy_range(reader, writer, start, end, progress)?;
+            at = done;
+            use_range = supported;
+        }
+        if at < end {
+            at = copy_buffered(reader, writer, at, Some(end), progress)?;
+        }
+        if at < end {
+            // The source shrank while it was being copied.
+            pos = at;
+            break;
+        }
+        pos = end;
+    }
+    // Holes are seeked over rather than written, so one at the end still
+    // needs the target's size set.
+    if writer.metadata()?.len() < pos {
+        writer.set_len(pos)?;
+    }
+    Ok(pos)
+}
+
+/// Clone all of `reader`'s extents into `writer`.
+#[cfg(target_os = "linux")]
+fn reflink(reader: &File, writer: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let res = unsafe { libc::ioctl(writer.as_raw_fd(), libc::FICLONE, reader.as_raw_fd()) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_reader: &File, _writer: &File) -> io::Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// The data segment at or after `pos` as `(start, end)`, or `None` when
+/// only a hole is left. Filesystems without hole reporting yield one
+/// segment up to `len`.
+#[cfg(target_os = "linux")]
+fn next_data(reader: &File, pos: u64, len: u64) -> Option<(u64, u64)> {
+    use std::os::unix::io::AsRawFd;
+    let fd = reader.as_raw_fd();
+    let start = unsafe { libc::lseek(fd, pos as libc::off_t, libc::SEEK_DATA) };
+    if start < 0 {
+        return match io::Error::last_os_error().raw_os_error() {
+            Some(libc::ENXIO) => None,
+            _ => Some((pos, len)),
+        };
+    }
+    let end = unsafe { libc::lseek(fd, start, libc::SEEK_HOLE) };
+    let end = if end < 0 { len } else { (end as u64).min(len) };
+    Some((start as u64, end)).filter(|(s, e)| s < e)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn next_data(_reader: &File, pos: u64, len: u64) -> Option<(u64, u64)> {
+    Some((pos, len))
+}
+
+/// Copy bytes `start..end` with `copy_file_range`. Returns the position
+/// reached and `false` if a call failed, in which case the caller carries
+/// on from there another way (a genuine I/O error shows up again there).
+/// Only errors from `progress` are returned.
+#[cfg(target_os = "linux")]
+fn copy_range(reader: &File, writer: &File, start: u64, end: u64, progress: &mut dyn FnMut(u64) -> io::Result<()>) -> io::Result<(u64, bool)> {
+    use std::os::unix::io::AsRawFd;
+    let mut off_in = start as libc::loff_t;
+    let mut off_out = start as libc::loff_t;
+    while (off_in as u64) < end {
+        let want = (end - off_in as u64).min(RANGE_CHUNK) as usize;
+        let n = unsafe { libc::copy_file_range(reader.as_raw_fd(), &mut off_in, writer.as_raw_fd(), &mut off_out, want, 0) };
+        if n < 0 {
+            if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Ok((off_in as u64, false));
+        }
+        if n == 0 {
+            break;
+        }
+        progress(off_in as u64)?;
+    }
+    Ok((off_in as u64, true))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn copy_range(_reader: &File, _writer: &File, start: u64, _end: u64, _progress: &mut dyn FnMut(u64) -> io::Result<()>) -> io::Result<(u64, bool)> {
+    Ok((start, false))
+}
+
+/// Copy from `start` up to `end` (or the end of the source) through a
+/// buffer. Returns the position reached.
+fn copy_buffered(mut reader: &File, mut writer: &File, start: u64, end: Option<u64>, progress: &mut dyn FnMut(u64) -> io::Result<()>) -> io::Result<u64> {
+    reader.seek(SeekFrom::Start(start))?;
+    writer.seek(SeekFrom::Start(start))?;
+    let mut buf = vec![0u8; COPY_CHUNK];
+    let mut pos = start;
+    loop {
+        let want = end.map_or(COPY_CHUNK as u64, |end| (end - pos).min(COPY_CHUNK as u64)) as usize;
+        if want == 0 {
+            break;
+        }
+        let n = match reader.read(&mut buf[..want]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        pos += n as u64;
+        progress(pos)?;
+    }
+    Ok(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    #[cfg(unix)]
+    use std::os::unix::fs::MetadataExt;
+
+    /// An 8 MiB file with a little data at the start and at 4 MiB, holes
+    /// elsewhere.
+    fn sparse_source(dir: &std::path::Path) -> std::path::PathBuf {
+        let path = dir.join("image.raw");
+        let mut f = File::create(&path).unwrap();
+        f.write_all(b"head").unwrap();
+        f.seek(SeekFrom::Start(4 * 1024 * 1024)).unwrap();
+        f.write_all(b"middle").unwrap();
+        f.set_len(8 * 1024 * 1024).unwrap();
+        path
+    }
+
+    fn copy_with(src: &std::path::Path, dst: &std::path::Path, method: CopyMethod) -> Vec<u64> {
+        let reader = File::open(src).unwrap();
+        let writer = File::create(dst).unwrap();
+        let len = reader.metadata().unwrap().len();
+        let mut seen = Vec::new();
+        let n = copy_data(&reader, &writer, 0, len, method, &mut |done| {
+            seen.push(done);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(n, len);
+        seen
+    }
+
+    #[test]
+    fn every_method_copies_the_same_bytes_and_holes_stay_holes() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = sparse_source(dir.path());
+        let expected = fs::read(&src).unwrap();
+        for method in CopyMethod::ALL {
+            let dst = dir.path().join(format!("{:?}", method));
+            let seen = copy_with(&src, &dst, method);
+            assert_eq!(fs::read(&dst).unwrap(), expected, "{:?}", method);
+            assert_eq!(seen.last(), Some(&(8 * 1024 * 1024)), "{:?}", method);
+            // Allocated blocks are only known on Unix.
+            #[cfg(unix)]
+            {
+                let allocated = fs::metadata(&dst).unwrap().blocks() * 512;
+                if method == CopyMethod::Plain {
+                    assert!(allocated >= 8 * 1024 * 1024, "plain copies write zeros: {}", allocated);
+                } else if fs::metadata(&src).unwrap().blocks() * 512 < 1024 * 1024 {
+                    assert!(allocated < 1024 * 1024, "{:?} allocated {}", method, allocated);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn copies_continue_from_an_offset_and_stop_on_progress_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let data: Vec<u8> = (0..3 * COPY_CHUNK).map(|i| (i % 253) as u8).collect();
+        fs::write(&src, &data).unwrap();
+        for method in CopyMethod::ALL {
+            let dst = dir.path().join("dst");
+            fs::write(&dst, &data[..100]).unwrap();
+            let writer = fs::OpenOptions::new().write(true).open(&dst).unwrap();
+            copy_data(&File::open(&src).unwrap(), &writer, 100, data.len() as u64, method, &mut |_| Ok(())).unwrap();
+            assert_eq!(fs::read(&dst).unwrap(), data, "{:?}", method);
+
+            let writer = File::create(&dst).unwrap();
+            let err = copy_data(&File::open(&src).unwrap(), &writer, 0, data.len() as u64, method, &mut |_| Err(io::Error::other("stop"))).unwrap_err();
+            assert_eq!(err.to_string(), "stop", "{:?}", method);
+        }
+    }
+
+    /// Checks the encoding `set_method` and `method` use rather than the
+    /// global itself, which other tests read while copying in parallel.
+    #[test]
+    fn every_method_survives_the_stored_encoding() {
+        assert_eq!(method(), CopyMethod::Reflink);
+        for m in CopyMethod::ALL {
+            assert_eq!(CopyMethod::from_index(m.index()), m);
+        }
+        assert_eq!(CopyMethod::from_index(u8::MAX), CopyMethod::Reflink);
+    }
+}
@@ -1,13 +1,12 @@
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::io;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use fs_extra::file::{copy as fs_extra_copy, CopyOptions};
 use super::test_helpers as tests;
 
 /// Resolve destination path for an operation: if `dst` looks like a directory
@@ -84,11 +83,30 @@ pub fn atomic_write(target: &Path, data: &[u8]) -> io::Result<()> {
 /// destination directory and renaming into place. Returns number of bytes
 /// copied on success.
 pub fn atomic_copy_file(src: &Path, dst: &Path) -> io::Result<u64> {
-    // Prepare copy options used in both branches.
-    let mut options = CopyOptions::new();
-    options.overwrite = false;
-    options.buffer_size = 64 * 1024;
+    atomic_copy_file_with(src, dst, &mut |_, _, _| Ok(()))
+}
+
+/// Hook called while file data is copied, with the source file, the bytes
+/// of it copied so far and its size. It runs once before the first chunk
+/// and again after every chunk; an error returned from it aborts the copy.
+pub type CopyCheckpoint<'a> = dyn FnMut(&Path, u64, u64) -> io::Result<()> + 'a;
+
+/// Hook called when one entry of a tree copy fails, with the entry's
+/// source path and the error. `Ok(true)` tries the entry again, `Ok(false)`
+/// skips it and carries on with the next one; an error stops the whole
+/// copy and is returned to the caller.
+pub type FailureHook<'a> = dyn FnMut(&Path, io::Error) -> io::Result<bool> + 'a;
 
+/// Hook called for an entry a tree copy leaves out because it cannot be
+/// copied, such as a socket, with the entry's source path and what kind of
+/// file it is.
+pub type SkipHook<'a> = dyn FnMut(&Path, &'static str) + 'a;
+
+/// Like `atomic_copy_file`, calling `checkpoint` as each chunk is written.
+/// Background jobs use the hook to report byte progress, to pause between
+/// chunks and to stop mid-file: an error returned by `checkpoint` aborts
+/// the copy, removes the temp file and is passed to the caller.
+pub fn atomic_copy_file_with(src: &Path, dst: &Path, checkpoint: &mut CopyCheckpoint<'_>) -> io::Result<u64> {
     if let Some(dir) = dst.parent() {
         fs::create_dir_all(dir)?;
         let mut tmp = dir.join(".tmp_atomic_copy");
@@ -110,7 +128,9 @@ pub fn atomic_copy_file(src: &Path, dst: &Path) -> io::Result<u64> {
         let suffix = raw.chars().rev().take(12).collect::<String>().chars().rev().collect::<String>();
         tmp.set_file_name(format!(".tmp_atomic_copy.{}", suffix));
 
-        let n = fs_extra_copy(src, &tmp, &options).map_err(io::Error::other)?;
+        let n = copy_chunked(src, &tmp, checkpoint).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })?;
 
         // test hook may force a failure to exercise cleanup
         if tests::should_force_rename_fail_in_copy() {
@@ -125,12 +145,104 @@ pub fn atomic_copy_file(src: &Path, dst: &Path) -> io::Result<u64> {
         let _ = crate::fs_op::metadata::preserve_all_metadata(src, dst);
         Ok(n)
     } else {
-        let res = fs_extra_copy(src, dst, &options).map_err(io::Error::other)?;
+        let res = copy_chunked(src, dst, checkpoint)?;
         let _ = crate::fs_op::metadata::preserve_all_metadata(src, dst);
         Ok(res)
     }
 }
 
+/// Copy `src` into the new file `dst` (see `fastcopy` for how the data is
+/// moved). The copy never overwrites: an existing `dst` is an error.
+fn copy_chunked(src: &Path, dst: &Path, checkpoint: &mut CopyCheckpoint<'_>) -> io::Result<u64> {
+    let writer = fs::OpenOptions::new().write(true).create_new(true).open(dst)?;
+    copy_chunked_from(src, writer, 0, checkpoint)
+}
+
+/// Copy `src` from byte `offset` onwards into `writer`, which must already
+/// hold the first `offset` bytes, with the process-wide
+/// `fastcopy::method`. Returns the file size reached.
+fn copy_chunked_from(src: &Path, writer: fs::File, offset: u64, checkpoint: &mut CopyCheckpoint<'_>) -> io::Result<u64> {
+    let reader = fs::File::open(src)?;
+    let len = reader.metadata()?.len();
+    checkpoint(src, offset, len)?;
+    crate::fs_op::fastcopy::copy_data(&reader, &writer, offset, len, crate::fs_op::fastcopy::method(), &mut |done| checkpoint(src, done, len))
+}
+
+/// File an interrupted `resumable_copy_file_with` leaves next to `dst`:
+/// `.name.fzpart` in the same directory.
+pub fn partial_path(dst: &Path) -> PathBuf {
+    let name = dst.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    dst.with_file_name(format!(".{}.fzpart", name))
+}
+
+/// Copy `src` to `dst` through `partial_path(dst)`, renaming it into place
+/// once complete. Unlike `atomic_copy_file_with` the partial file is kept
+/// when the copy fails, so a job interrupted mid-file can pick it up again.
+///
+/// `resume_at` is the offset the job journal recorded for this file, if
+/// any. An existing partial file is continued only when it is exactly that
+/// long and all of its data still matches the source; otherwise (or
+/// without `resume_at`) the copy starts over from the first byte.
+pub fn resumable_copy_file_with(src: &Path, dst: &Path, resume_at: Option<u64>, checkpoint: &mut CopyCheckpoint<'_>) -> io::Result<u64> {
+    ensure_parent_exists(dst)?;
+    let part = partial_path(dst);
+    let offset = match resume_at {
+        Some(at) => verified_offset(src, &part, Some(at)).unwrap_or(0),
+        None => 0,
+    };
+    let writer = fs::OpenOptions::new().write(true).create(true).truncate(offset == 0).open(&part)?;
+    if offset > 0 {
+        writer.set_len(offset)?;
+    }
+    let n = copy_chunked_from(src, writer, offset, checkpoint)?;
+    fs::rename(&part, dst)?;
+    let _ = crate::fs_op::metadata::preserve_all_metadata(src, dst);
+    Ok(n)
+}
+
+/// Length of the prefix of `src` that `part` already holds, checked by
+/// comparing xxh3 digests of the whole prefix on both sides. A length
+/// other than `expected` (when given) or any difference yields 0.
+fn verified_offset(src: &Path, part: &Path, expected: Option<u64>) -> io::Result<u64> {
+    let have = fs::metadata(part)?.len();
+    if have == 0 || have > fs::metadata(src)?.len() || expected.is_some_and(|at| at != have) {
+        return Ok(0);
+    }
+    let digest = |path: &Path| crate::fs_op::checksum::xxh3_reader(&mut fs::File::open(path)?.take(have), &mut |_| Ok(()));
+    Ok(if digest(src)? == digest(part)? { have } else { 0 })
+}
+
+/// Continue a copy that left `dst` truncated: append the rest of `src`,
+/// provided the data `dst` already holds still lines up with the source
+/// (see `verified_offset`); otherwise `dst` is rewritten from the start.
+pub fn append_copy_file_with(src: &Path, dst: &Path, checkpoint: &mut CopyCheckpoint<'_>) -> io::Result<u64> {
+    let offset = verified_offset(src, dst, None).unwrap_or(0);
+    let writer = fs::OpenOptions::new().write(true).truncate(offset == 0).open(dst)?;
+    if offset > 0 {
+        writer.set_len(offset)?;
+    }
+    let n = copy_chunked_from(src, writer, offset, checkpoint)?;
+    let _ = crate::fs_op::metadata::preserve_all_metadata(src, dst);
+    Ok(n)
+}
+
+/// `path` with ` (n)` added to its name, before the extension of a file:
+/// `notes (1).txt`, `photos (1)`. Dot files count as having no extension.
+pub fn numbered_path(path: &Path, n: usize, is_dir: bool) -> PathBuf {
+    let name = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+    let split = if is_dir { None } else { name.rfind('.').filter(|&i| i > 0) };
+    let numbered = match split {
+        Some(i) => format!("{} ({}){}", &name[..i], n, &name[i..]),
+        None => format!("{} ({})", name, n),
+    };
+    path.with_file_name(numbered)
+}
+
+/// The first `numbered_path` of `path` for which `exists` is false.
+pub fn free_numbered_path(path: &Path, is_dir: bool, exists: impl Fn(&Path) -> bool) -> PathBuf {
+    (1..).map(|n| numbered_path(path, n, is_dir)).find(|p| !exists(p)).expect("numbers run out")
+}
+
 /// Try to rename `src` to `dst`. If `rename` fails due to cross-filesystem
 /// issues, fall back to an atomic copy+remove approach.
 /// Rename `src` to `dst`, falling back to copy+remove on failure (for
@@ -240,6 +352,21 @@ mod parallel_tests {
         }
     }
 }
+#[cfg(test)]
+mod numbered_tests {
+    use super::*;
+
+    #[test]
+    fn numbers_go_before_the_extension_of_files_only() {
+        assert_eq!(numbered_path(Path::new("/d/notes.txt"), 1, false), Path::new("/d/notes (1).txt"));
+        assert_eq!(numbered_path(Path::new("/d/a.tar.gz"), 2, false), Path::new("/d/a.tar (2).gz"));
+        assert_eq!(numbered_path(Path::new("/d/.bashrc"), 1, false), Path::new("/d/.bashrc (1)"));
+        assert_eq!(numbered_path(Path::new("/d/v1.2"), 1, true), Path::new("/d/v1.2 (1)"));
+        let taken = [PathBuf::from("/d/x (1).txt"), PathBuf::from("/d/x (2).txt")];
+        assert_eq!(free_numbered_path(Path::new("/d/x.txt"), false, |p| taken.iter().any(|t| t == p)), Path::new("/d/x (3).txt"));
+    }
+}
+
 // test hooks have been moved to `app/src/fs_op/test_helpers.rs` and are
 // imported above as the `tests` alias so the existing call sites remain
 // unchanged (e.g. `tests::should_force_rename_fail_in_copy()`).
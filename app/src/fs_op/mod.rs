@@ -1,6 +1,10 @@
 pub mod app_ops;
+pub mod batch_rename;
+pub mod checksum;
 pub mod copy;
 pub mod create;
+pub mod edit_names;
+pub mod fastcopy;
 pub mod files;
 pub mod helpers;
 pub mod test_helpers;
@@ -12,7 +16,9 @@ pub mod path;
 pub mod permissions;
 pub mod remove;
 pub mod stat;
+pub mod shred;
 pub mod symlink;
+pub mod trash;
 #[cfg(feature = "fs-watch")]
 pub mod watcher;
 
@@ -100,7 +100,7 @@ pub fn inspect_permissions<P: AsRef<Path>>(
             .next()
             .is_some_and(|r| r.is_ok())
     } else {
-        OpenOptions::new().read(true).open(&path).is_ok()
+        probe_open(&path, false)
     };
 
     // Best-effort write check. If test_write is false, prefer metadata only
@@ -127,12 +127,25 @@ pub fn inspect_permissions<P: AsRef<Path>>(
             Err(_) => false,
         }
     } else {
-        OpenOptions::new().write(true).open(&path).is_ok()
+        probe_open(&path, true)
     };
 
     Ok(info)
 }
 
+/// Whether `path` can be opened for reading (or writing). The open does not
+/// block, so a FIFO without a writer (or reader) does not hang the caller.
+fn probe_open(path: &Path, write: bool) -> bool {
+    let mut options = OpenOptions::new();
+    options.read(!write).write(write);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NONBLOCK);
+    }
+    options.open(path).is_ok()
+}
+
 /// Attempt to change permissions (Unix only).
 ///
 /// On non-Unix platforms this returns an error indicating unsupported.
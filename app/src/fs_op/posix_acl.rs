@@ -1,6 +1,10 @@
 use std::io;
 use std::path::Path;
 
+#[cfg(unix)]
+use crate::vfs::LocalVfs;
+use crate::vfs::Vfs;
+
 /// Minimal POSIX ACL helper implemented in Rust.
 ///
 /// This module provides a pure-Rust helper to read and write the common
@@ -14,79 +18,78 @@ pub struct PosixAcl {
     pub default: Option<Vec<u8>>,
 }
 
+const ACCESS: &str = "system.posix_acl_access";
+const DEFAULT: &str = "system.posix_acl_default";
+
 impl PosixAcl {
     /// Read POSIX ACL xattrs from `path`. Returns Ok(None) if no ACLs
     /// were present.
     pub fn read_from_path(path: &Path) -> io::Result<Option<Self>> {
         #[cfg(unix)]
         {
-            let mut found = false;
-            let mut access = None;
-            let mut default = None;
-            if let Ok(Some(v)) = xattr::get(path, "system.posix_acl_access") {
-                access = Some(v);
-                found = true;
-            }
-            if let Ok(Some(v)) = xattr::get(path, "system.posix_acl_default") {
-                default = Some(v);
-                found = true;
-            }
-            if found {
-                return Ok(Some(PosixAcl { access, default }));
-            }
-            Ok(None)
+            Self::read_from(&LocalVfs::new(), path)
         }
         #[cfg(not(unix))]
         {
             // No-op on non-Unix platforms.
+            let _ = path;
             Ok(None)
         }
     }
 
+    /// Read POSIX ACL xattrs of `path` through `vfs`. Returns Ok(None) if
+    /// no ACLs were present or the backend has no extended attributes.
+    pub fn read_from(vfs: &dyn Vfs, path: &Path) -> io::Result<Option<Self>> {
+        let access = vfs.get_xattr(path, ACCESS).ok().flatten();
+        let default = vfs.get_xattr(path, DEFAULT).ok().flatten();
+        if access.is_none() && default.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(PosixAcl { access, default }))
+    }
+
     /// Write ACL blobs to `path`. Best-effort: ignore set failures.
     pub fn write_to_path(&self, path: &Path) -> io::Result<()> {
         #[cfg(unix)]
         {
-            if let Some(ref v) = self.access {
-                let _ = xattr::set(path, "system.posix_acl_access", v);
-            }
-            if let Some(ref v) = self.default {
-                let _ = xattr::set(path, "system.posix_acl_default", v);
-            }
-            Ok(())
+            self.write_to(&LocalVfs::new(), path)
         }
         #[cfg(not(unix))]
         {
+            let _ = path;
             Ok(())
         }
     }
+
+    /// Write ACL blobs to `path` through `vfs`. Best-effort: ignore set
+    /// failures.
+    pub fn write_to(&self, vfs: &dyn Vfs, path: &Path) -> io::Result<()> {
+        if let Some(ref v) = self.access {
+            let _ = vfs.set_xattr(path, ACCESS, v);
+        }
+        if let Some(ref v) = self.default {
+            let _ = vfs.set_xattr(path, DEFAULT, v);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-    use tempfile::tempdir;
+    use crate::vfs::vfs_mem::MemVfs;
 
     #[test]
     fn roundtrip_acl_xattrs() {
-        let dir = tempdir().unwrap();
-        let p = dir.path().join("f.txt");
-        File::create(&p).unwrap();
+        let vfs = MemVfs::new();
+        vfs.write_file("/f.txt", "").unwrap();
+        let p = Path::new("/f.txt");
+        assert_eq!(PosixAcl::read_from(&vfs, p).unwrap(), None);
         let acl = PosixAcl {
             access: Some(vec![1, 2, 3, 4]),
             default: Some(vec![5, 6, 7]),
         };
-        acl.write_to_path(&p).unwrap();
-        // Some CI filesystems / platforms may not support xattrs. In that
-        // case `read_from_path` will return `Ok(None)` and the best option
-        // is to treat the test as a no-op rather than a hard failure.
-        let got_opt = PosixAcl::read_from_path(&p).unwrap();
-        if got_opt.is_none() {
-            eprintln!("posix ACL xattrs not supported, skipping roundtrip test");
-            return;
-        }
-        let got = got_opt.unwrap();
-        assert_eq!(got, acl);
+        acl.write_to(&vfs, p).unwrap();
+        assert_eq!(PosixAcl::read_from(&vfs, p).unwrap(), Some(acl));
     }
 }
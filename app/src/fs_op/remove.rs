@@ -41,6 +41,28 @@ pub fn remove_path(path: impl AsRef<Path>) -> Result<(), RemoveError> {
     Ok(())
 }
 
+/// Remove `path` like `remove_path`, walking directories one entry at a
+/// time and calling `checkpoint` before each removal so a caller can pause
+/// or abort a large delete part-way. Symlinks are removed, not followed.
+pub fn remove_path_with(path: &Path, checkpoint: &mut dyn FnMut() -> io::Result<()>) -> Result<(), RemoveError> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            remove_path_with(&entry?.path(), checkpoint)?;
+        }
+        checkpoint()?;
+        fs::remove_dir(path)?;
+    } else {
+        checkpoint()?;
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,313 @@
+//! Secure deletion ("shred").
+//!
+//! A shredded file is overwritten in place with one or more passes, each
+//! flushed to the disk with `fsync`, then renamed to random names of
+//! decreasing length so its directory entry no longer tells what it was,
+//! and finally unlinked. Directories are shredded entry by entry and then
+//! removed the same way; symlinks and special files hold no data of their
+//! own and are only renamed and unlinked.
+//!
+//! Overwriting in place only reaches the old data when the filesystem
+//! writes to the same blocks. Copy-on-write and log-structured filesystems
+//! put new data elsewhere, and SSDs remap blocks for wear levelling;
+//! `weak_spot` names these cases so callers can say so rather than imply
+//! the data is gone.
+
+use crate::fs_op::helpers::CopyCheckpoint;
+use rand::distr::{Alphanumeric, SampleString};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes written per `write` call of a pass.
+const CHUNK: usize = 256 * 1024;
+
+/// Filesystems that never overwrite data in place, by `statfs` magic.
+const COW_FILESYSTEMS: [(i64, &str); 5] = [(0x9123_683e, "btrfs"), (0x2fc1_2fc1, "ZFS"), (0xca45_1a4e, "bcachefs"), (0xf2f5_2010, "F2FS"), (0x3434, "NILFS")];
+
+/// What the overwrite passes write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShredMethod {
+    /// Passes of zeros.
+    Zeros,
+    /// Passes of random data.
+    #[default]
+    Random,
+    /// The three passes of DoD 5220.22-M: zeros, ones, then random data.
+    Dod,
+}
+
+impl ShredMethod {
+    /// The passes to run. `count` repeats zeros or random data (at least
+    /// once); `Dod` always runs its three.
+    pub fn passes(self, count: u32) -> Vec<Pass> {
+        let count = count.max(1) as usize;
+        match self {
+            ShredMethod::Zeros => vec![Pass::Byte(0); count],
+            ShredMethod::Random => vec![Pass::Random; count],
+            ShredMethod::Dod => vec![Pass::Byte(0), Pass::Byte(0xff), Pass::Random],
+        }
+    }
+}
+
+/// One overwrite of a file's contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Every byte set to this value.
+    Byte(u8),
+    /// Fresh random bytes.
+    Random,
+}
+
+/// Tunables for the Shred action, persisted as the `[shred]` table in
+/// `settings.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShredOptions {
+    /// `"zeros"`, `"random"` (default) or `"dod"`.
+    pub method: ShredMethod,
+    /// How many passes of zeros or random data to write; `"dod"` ignores
+    /// it.
+    pub passes: u32,
+}
+
+impl Default for ShredOptions {
+    fn default() -> Self {
+        ShredOptions { method: ShredMethod::Random, passes: 3 }
+    }
+}
+
+/// Shred `path`, recursively for a directory. `checkpoint` gets every
+/// regular file with the bytes written to it so far over all passes and
+/// that total (its size times the number of passes); an error from it
+/// stops the shred. A file with other hard links is refused before
+/// anything is written, since overwriting it would destroy the data seen
+/// through those links too.
+pub fn shred_path(path: &Path, passes: &[Pass], checkpoint: &mut CopyCheckpoint) -> io::Result<()> {
+    refuse_shared(path)?;
+    shred_tree(path, passes, checkpoint)
+}
+
+fn shred_tree(path: &Path, passes: &[Pass], checkpoint: &mut CopyCheckpoint) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        for child in fs::read_dir(path)? {
+            shred_tree(&child?.path(), passes, checkpoint)?;
+        }
+        return fs::remove_dir(obscure(path)?);
+    }
+    if meta.is_file() {
+        overwrite(path, meta.len(), passes, checkpoint)?;
+    }
+    fs::remove_file(obscure(path)?)
+}
+
+/// Fail if `path`, or a regular file below it, has more than one link.
+#[cfg(unix)]
+fn refuse_shared(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        for child in fs::read_dir(path)? {
+            refuse_shared(&child?.path())?;
+        }
+    } else if meta.is_file() && meta.nlink() > 1 {
+        return Err(io::Error::other(format!("{} has other hard links; shredding it would destroy their data too", path.display())));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn refuse_shared(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Write every pass over the `len` bytes of `path`, syncing after each.
+fn overwrite(path: &Path, len: u64, passes: &[Pass], checkpoint: &mut CopyCheckpoint) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let total = len * passes.len() as u64;
+    let mut buf = vec![0u8; (len as usize).clamp(1, CHUNK)];
+    let mut rng = rand::rng();
+    checkpoint(path, 0, total)?;
+    for (n, pass) in passes.iter().enumerate() {
+        file.seek(SeekFrom::Start(0))?;
+        if let Pass::Byte(b) = pass {
+            buf.fill(*b);
+        }
+        let mut done = 0;
+        while done < len {
+            let want = (len - done).min(buf.len() as u64) as usize;
+            if *pass == Pass::Random {
+                rng.fill_bytes(&mut buf[..want]);
+            }
+            file.write_all(&buf[..want])?;
+            done += want as u64;
+            checkpoint(path, n as u64 * len + done, total)?;
+        }
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Rename `path` to random names, halving the length each time down to a
+/// single character, and sync the directory after each rename. Returns
+/// the final path.
+fn obscure(path: &Path) -> io::Result<PathBuf> {
+    let Some(dir) = path.parent() else { return Ok(path.to_path_buf()) };
+    let mut current = path.to_path_buf();
+    let mut len = path.file_name().map_or(1, |n| n.len()).clamp(1, 255);
+    let mut rng = rand::rng();
+    loop {
+        // A few tries per length in case a name is taken.
+        if let Some(next) = (0..8).map(|_| dir.join(Alphanumeric.sample_string(&mut rng, len))).find(|p| p.symlink_metadata().is_err()) {
+            fs::rename(&current, &next)?;
+            let _ = File::open(dir).and_then(|d| d.sync_all());
+            current = next;
+        }
+        if len == 1 {
+            return Ok(current);
+        }
+        len /= 2;
+    }
+}
+
+/// Why overwriting `path` in place may leave its old data readable, or
+/// `None` when nothing is known to speak against it.
+pub fn weak_spot(path: &Path) -> Option<String> {
+    describe(fs_type(path), rotational(path))
+}
+
+fn describe(fs_type: Option<i64>, rotational: Option<bool>) -> Option<String> {
+    if let Some((_, name)) = fs_type.and_then(|t| COW_FILESYSTEMS.iter().find(|(magic, _)| *magic == t)) {
+        return Some(format!("{} writes changes to new blocks, so the old data may survive the overwrite", name));
+    }
+    if rotational == Some(false) {
+        return Some("solid-state drives remap blocks for wear levelling, so the old data may survive the overwrite".to_string());
+    }
+    None
+}
+
+/// The `statfs` magic number of the filesystem holding `path`.
+#[cfg(target_os = "linux")]
+fn fs_type(path: &Path) -> Option<i64> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut buf: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut buf) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(buf.f_type as i64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn fs_type(_path: &Path) -> Option<i64> {
+    None
+}
+
+/// Whether the block device holding `path` spins, from
+/// `/sys/dev/block/MAJOR:MINOR/queue/rotational` (or the one of the disk a
+/// partition belongs to). `None` for filesystems without a block device.
+#[cfg(target_os = "linux")]
+fn rotational(path: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let dev = fs::symlink_metadata(path).ok()?.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    let node = fs::canonicalize(format!("/sys/dev/block/{}:{}", major, minor)).ok()?;
+    let read = |dir: &Path| fs::read_to_string(dir.join("queue/rotational")).ok();
+    let flag = read(&node).or_else(|| node.parent().and_then(read))?;
+    Some(flag.trim() != "0")
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rotational(_path: &Path) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_and_trees_are_overwritten_then_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("secret.txt");
+        fs::write(&file, vec![b'x'; 3000]).unwrap();
+        let mut seen = Vec::new();
+        let mut data = Vec::new();
+        shred_path(&file, &ShredMethod::Dod.passes(7), &mut |path, done, total| {
+            seen.push((path.to_path_buf(), done, total));
+            // Read the overwritten contents back before the file goes.
+            if done == total {
+                data = fs::read(path)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert!(!file.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0, "no renamed leftovers");
+        assert_eq!(data.len(), 3000);
+        assert!(data.iter().any(|b| *b != b'x' && *b != 0 && *b != 0xff), "last pass is random");
+        assert_eq!(seen.first(), Some(&(file.clone(), 0, 9000)));
+        assert_eq!(seen.last(), Some(&(file.clone(), 9000, 9000)));
+
+        let tree = dir.path().join("tree");
+        fs::create_dir_all(tree.join("sub")).unwrap();
+        fs::write(tree.join("sub/a"), b"aaaa").unwrap();
+        shred_path(&tree, &ShredMethod::Zeros.passes(1), &mut |_, _, _| Ok(())).unwrap();
+        assert!(!tree.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_are_removed_without_touching_what_they_share() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept");
+        fs::write(&kept, b"keep").unwrap();
+
+        // A symlink goes, not what it points at.
+        let tree = dir.path().join("tree");
+        fs::create_dir(&tree).unwrap();
+        std::os::unix::fs::symlink(&kept, tree.join("link")).unwrap();
+        shred_path(&tree, &[Pass::Random], &mut |_, _, _| Ok(())).unwrap();
+        assert!(!tree.exists());
+        assert_eq!(fs::read(&kept).unwrap(), b"keep");
+
+        // A file with another hard link is refused before any pass, also
+        // when it sits inside a tree.
+        let tree = dir.path().join("tree2");
+        fs::create_dir(&tree).unwrap();
+        fs::write(tree.join("first"), b"first").unwrap();
+        fs::hard_link(&kept, tree.join("shared")).unwrap();
+        let err = shred_path(&tree, &[Pass::Random], &mut |_, _, _| Ok(())).unwrap_err();
+        assert!(err.to_string().contains("other hard links"), "{}", err);
+        assert_eq!(fs::read(&kept).unwrap(), b"keep");
+        assert_eq!(fs::read(tree.join("first")).unwrap(), b"first");
+        let err = shred_path(&kept, &[Pass::Random], &mut |_, _, _| Ok(())).unwrap_err();
+        assert!(err.to_string().contains("other hard links"), "{}", err);
+        assert_eq!(fs::read(tree.join("shared")).unwrap(), b"keep");
+    }
+
+    #[test]
+    fn checkpoint_errors_stop_the_shred() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a");
+        fs::write(&file, b"abc").unwrap();
+        let err = shred_path(&file, &[Pass::Random], &mut |_, _, _| Err(io::Error::other("stop"))).unwrap_err();
+        assert_eq!(err.to_string(), "stop");
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn copy_on_write_and_solid_state_storage_are_flagged() {
+        assert!(describe(Some(0x9123_683e), Some(true)).unwrap().starts_with("btrfs "));
+        assert!(describe(Some(0xef53), Some(false)).unwrap().starts_with("solid-state drives"));
+        assert_eq!(describe(Some(0xef53), Some(true)), None);
+        assert_eq!(describe(None, None), None);
+        assert_eq!(ShredMethod::Random.passes(0), vec![Pass::Random]);
+    }
+}
@@ -7,7 +7,19 @@
 
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+/// What the Link action creates for each marked entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A symlink holding the entry's absolute path.
+    Absolute,
+    /// A symlink holding the path from the link's directory to the entry
+    /// (see `relative_path`).
+    Relative,
+    /// A hard link; directories cannot have one.
+    Hard,
+}
 
 /// Create a symbolic link at `dst` that points to `src`.
 ///
@@ -18,7 +30,6 @@ use std::path::{Path, PathBuf};
 ///
 /// This function does not attempt to elevate privileges on Windows; callers
 /// must handle permission errors if needed.
-#[allow(dead_code)]
 pub(crate) fn create_symlink(src: &Path, dst: &Path) -> io::Result<()> {
     #[cfg(unix)]
     {
@@ -45,11 +56,28 @@ pub(crate) fn create_symlink(src: &Path, dst: &Path) -> io::Result<()> {
 /// Returns `true` when `path` itself is a symbolic link.
 ///
 /// This checks the *link* metadata (does not follow the link).
-#[allow(dead_code)]
 pub(crate) fn is_symlink(path: &Path) -> io::Result<bool> {
     Ok(path.symlink_metadata()?.file_type().is_symlink())
 }
 
+/// The path leading from directory `from` to `to`, for a relative symlink
+/// in `from`: the components both share are dropped, and each remaining
+/// one of `from` becomes `..`. Both paths should be absolute and free of
+/// symlinks in their directories (canonicalize them first), since `..`
+/// is resolved from where a link lives, not from the path used to get
+/// there.
+pub(crate) fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut out: PathBuf = from[common..].iter().map(|_| Component::ParentDir).collect();
+    out.extend(&to[common..]);
+    if out.as_os_str().is_empty() {
+        out.push(".");
+    }
+    out
+}
+
 /// Read the target of a symbolic link.
 ///
 /// Returns the path that the symbolic link points to. This is a thin wrapper
@@ -111,6 +139,16 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn relative_paths_climb_to_the_common_directory() {
+        let rel = |from: &str, to: &str| relative_path(Path::new(from), Path::new(to));
+        assert_eq!(rel("/a/b", "/a/b/c.txt"), PathBuf::from("c.txt"));
+        assert_eq!(rel("/a/b", "/a/c/d.txt"), PathBuf::from("../c/d.txt"));
+        assert_eq!(rel("/x/y/z", "/a"), PathBuf::from("../../../a"));
+        assert_eq!(rel("/a/b", "/a"), PathBuf::from(".."));
+        assert_eq!(rel("/a", "/a"), PathBuf::from("."));
+    }
+
     #[cfg(unix)]
     #[test]
     fn create_and_remove_dir_symlink() -> io::Result<()> {
@@ -0,0 +1,411 @@
+//! The freedesktop.org trash can.
+//!
+//! Trashed items are renamed into a trash directory's `files/` folder, and
+//! a `.trashinfo` file of the same name in `info/` records where each came
+//! from and when it was deleted, as the Trash specification describes.
+//! Desktop file managers read the same directories, so items trashed here
+//! show up in theirs and the other way round.
+//!
+//! Items on the same filesystem as the home trash (`$XDG_DATA_HOME/Trash`)
+//! go there. Items on other mounts go to the mount's own trash, so nothing
+//! is copied across devices: `$topdir/.Trash/$uid` when the administrator
+//! set up a sticky `.Trash`, otherwise `$topdir/.Trash-$uid`. Those record
+//! the original path relative to `$topdir`.
+
+use chrono::{Local, NaiveDateTime};
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes of a path left as they are in a `.trashinfo` `Path=` line.
+const PATH_CHARS: &AsciiSet = &NON_ALPHANUMERIC.remove(b'/').remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Format of `DeletionDate=`.
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// An item in a trash directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedItem {
+    /// The trash directory holding the item.
+    pub trash_dir: PathBuf,
+    /// Name of the item under `files/` (and of its `.trashinfo`).
+    pub name: OsString,
+    /// Where the item was before it was trashed.
+    pub original: PathBuf,
+    /// When it was trashed, if the info file says.
+    pub deleted: Option<NaiveDateTime>,
+}
+
+impl TrashedItem {
+    /// The trashed file or directory itself.
+    pub fn file_path(&self) -> PathBuf {
+        self.trash_dir.join("files").join(&self.name)
+    }
+
+    /// Its `.trashinfo` file.
+    pub fn info_path(&self) -> PathBuf {
+        let mut name = self.name.clone();
+        name.push(".trashinfo");
+        self.trash_dir.join("info").join(name)
+    }
+}
+
+/// The user's trash: the home trash directory plus the per-mount ones.
+#[derive(Debug, Clone)]
+pub struct Trash {
+    home: PathBuf,
+    /// Whether listing looks for trash directories on every mount.
+    scan_mounts: bool,
+}
+
+impl Trash {
+    /// A trash whose home directory is `home`. Items on other mounts are
+    /// still trashed on those mounts, but only `home` is listed.
+    pub fn new(home: impl Into<PathBuf>) -> Self {
+        Trash { home: home.into(), scan_mounts: false }
+    }
+
+    /// The current user's trash: `$XDG_DATA_HOME/Trash`, falling back to
+    /// `~/.local/share/Trash`, and the trash directories of every mount.
+    pub fn user() -> Self {
+        let data = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).filter(|p| p.is_absolute()).unwrap_or_else(|| {
+            let mut p = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+            p.push(".local");
+            p.push("share");
+            p
+        });
+        Trash { home: data.join("Trash"), scan_mounts: true }
+    }
+
+    /// The home trash directory.
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// Move `path` into the trash and return the entry describing it.
+    pub fn trash(&self, path: &Path) -> io::Result<TrashedItem> {
+        let path = absolute(path)?;
+        let name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "cannot trash a filesystem root"))?.to_os_string();
+        let is_dir = fs::symlink_metadata(&path)?.is_dir();
+        let (trash_dir, topdir) = self.trash_dir_for(&path)?;
+        fs::create_dir_all(trash_dir.join("files"))?;
+        fs::create_dir_all(trash_dir.join("info"))?;
+        let recorded = match &topdir {
+            Some(top) => path.strip_prefix(top).map(Path::to_path_buf).unwrap_or_else(|_| path.clone()),
+            None => path.clone(),
+        };
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            percent_encode(recorded.as_os_str().as_encoded_bytes(), PATH_CHARS),
+            Local::now().format(DATE_FORMAT)
+        );
+
+        // Claim a free name by creating its info file, then move the item
+        // in under that name.
+        for n in 0.. {
+            let candidate = if n == 0 { name.clone() } else { numbered(&name, n, is_dir) };
+            let item = TrashedItem { trash_dir: trash_dir.clone(), name: candidate, original: path.clone(), deleted: None };
+            let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(item.info_path()) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            };
+            if fs::symlink_metadata(item.file_path()).is_ok() {
+                // Left behind without its info file; keep clear of it.
+                drop(file);
+                let _ = fs::remove_file(item.info_path());
+                continue;
+            }
+            let written = file.write_all(info.as_bytes()).and_then(|_| file.sync_all());
+            if let Err(e) = written.and_then(|_| fs::rename(&path, item.file_path())) {
+                let _ = fs::remove_file(item.info_path());
+                return Err(e);
+            }
+            let deleted = read_info(&item.info_path()).ok().and_then(|(_, date)| date);
+            return Ok(TrashedItem { deleted, ..item });
+        }
+        unreachable!("names run out")
+    }
+
+    /// Trash directories that exist: the home one and those on mounts.
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        let mounts = if self.scan_mounts { mount_points() } else { Vec::new() };
+        let mut dirs = Vec::new();
+        for dir in std::iter::once(self.home.clone()).chain(mounts.iter().flat_map(|top| mount_trash_candidates(top))) {
+            if dir.join("info").is_dir() && !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        dirs
+    }
+
+    /// Every trashed item, most recently deleted first. Info files that
+    /// cannot be read are left out.
+    pub fn list(&self) -> Vec<TrashedItem> {
+        let mut items = Vec::new();
+        for dir in self.dirs() {
+            items.extend(list_dir(&dir));
+        }
+        items.sort_by(|a, b| b.deleted.cmp(&a.deleted).then_with(|| a.original.cmp(&b.original)));
+        items
+    }
+
+    /// Move `item` back to where it was. Fails when something else is
+    /// there now.
+    pub fn restore(&self, item: &TrashedItem) -> io::Result<()> {
+        if fs::symlink_metadata(&item.original).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", item.original.display())));
+        }
+        if let Some(parent) = item.original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(item.file_path(), &item.original)?;
+        fs::remove_file(item.info_path())
+    }
+
+    /// Delete `item` for good.
+    pub fn purge(&self, item: &TrashedItem) -> io::Result<()> {
+        let file = item.file_path();
+        match fs::symlink_metadata(&file) {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(&file)?,
+            Ok(_) => fs::remove_file(&file)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        match fs::remove_file(item.info_path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Delete every trashed item for good; returns how many there were.
+    pub fn empty(&self) -> io::Result<usize> {
+        let items = self.list();
+        for item in &items {
+            self.purge(item)?;
+        }
+        Ok(items.len())
+    }
+
+    /// The trash directory for `path`, and the mount's top directory when
+    /// it is not the home trash.
+    #[cfg(unix)]
+    fn trash_dir_for(&self, path: &Path) -> io::Result<(PathBuf, Option<PathBuf>)> {
+        use std::os::unix::fs::MetadataExt;
+        let dev = fs::symlink_metadata(path)?.dev();
+        let home_dev = self.home.ancestors().find_map(|p| fs::metadata(p).ok()).map(|m| m.dev());
+        if home_dev == Some(dev) {
+            return Ok((self.home.clone(), None));
+        }
+        let mut top = path.parent().unwrap_or(path).to_path_buf();
+        while let Some(parent) = top.parent() {
+            if fs::metadata(parent)?.dev() != dev {
+                break;
+            }
+            top = parent.to_path_buf();
+        }
+        let [admin, own] = mount_trash_candidates(&top);
+        let sticky = fs::symlink_metadata(top.join(".Trash")).is_ok_and(|m| m.is_dir() && m.mode() & 0o1000 != 0);
+        if sticky && create_private_dir(&admin).is_ok() {
+            return Ok((admin, Some(top)));
+        }
+        create_private_dir(&own)?;
+        let meta = fs::symlink_metadata(&own)?;
+        if !meta.is_dir() || meta.uid() != nix::unistd::Uid::current().as_raw() {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is not a usable trash directory", own.display())));
+        }
+        Ok((own, Some(top)))
+    }
+
+    #[cfg(not(unix))]
+    fn trash_dir_for(&self, _path: &Path) -> io::Result<(PathBuf, Option<PathBuf>)> {
+        Ok((self.home.clone(), None))
+    }
+}
+
+/// `path` made absolute against the current directory; trash info files
+/// need the full original location.
+fn absolute(path: &Path) -> io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// `name` with ` (n)` added, like `helpers::numbered_path`.
+fn numbered(name: &OsStr, n: usize, is_dir: bool) -> OsString {
+    crate::fs_op::helpers::numbered_path(Path::new(name), n, is_dir).into_os_string()
+}
+
+/// `$topdir/.Trash/$uid` and `$topdir/.Trash-$uid`, in that order.
+fn mount_trash_candidates(top: &Path) -> [PathBuf; 2] {
+    #[cfg(unix)]
+    let uid = nix::unistd::Uid::current().as_raw();
+    #[cfg(not(unix))]
+    let uid = 0;
+    [top.join(".Trash").join(uid.to_string()), top.join(format!(".Trash-{}", uid))]
+}
+
+/// Create `dir` readable by its owner only, if it is not there yet.
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    match builder.create(dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Mount points listed in `/proc/self/mounts`; empty where there is none.
+fn mount_points() -> Vec<PathBuf> {
+    let Ok(mounts) = fs::read_to_string("/proc/self/mounts") else { return Vec::new() };
+    mounts.lines().filter_map(|line| line.split_whitespace().nth(1)).map(|field| PathBuf::from(unescape_mount(field))).collect()
+}
+
+/// Undo the octal escapes (`\040` for a space) of a mounts field.
+fn unescape_mount(field: &str) -> String {
+    let mut out = String::new();
+    let mut rest = field;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        match rest.get(i + 1..i + 4).and_then(|oct| u8::from_str_radix(oct, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The items of one trash directory.
+fn list_dir(trash_dir: &Path) -> Vec<TrashedItem> {
+    let Ok(entries) = fs::read_dir(trash_dir.join("info")) else { return Vec::new() };
+    let topdir = topdir_of(trash_dir);
+    let mut items = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(name) = file_name.as_encoded_bytes().strip_suffix(b".trashinfo") else { continue };
+        let name = os_string(name.to_vec());
+        let Ok((path, deleted)) = read_info(&entry.path()) else { continue };
+        let original = match &topdir {
+            Some(top) if path.is_relative() => top.join(path),
+            _ => path,
+        };
+        items.push(TrashedItem { trash_dir: trash_dir.to_path_buf(), name, original, deleted });
+    }
+    items
+}
+
+/// The mount top a per-mount trash directory belongs to, `None` for a
+/// home trash.
+fn topdir_of(trash_dir: &Path) -> Option<PathBuf> {
+    let name = trash_dir.file_name()?.to_string_lossy();
+    if name.starts_with(".Trash-") {
+        return trash_dir.parent().map(Path::to_path_buf);
+    }
+    let parent = trash_dir.parent()?;
+    (parent.file_name()? == ".Trash").then(|| parent.parent().map(Path::to_path_buf)).flatten()
+}
+
+/// `Path=` and `DeletionDate=` of a `.trashinfo` file.
+fn read_info(info: &Path) -> io::Result<(PathBuf, Option<NaiveDateTime>)> {
+    parse_info(&fs::read_to_string(info)?).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a trash info file", info.display())))
+}
+
+/// Parse the contents of a `.trashinfo` file.
+fn parse_info(text: &str) -> Option<(PathBuf, Option<NaiveDateTime>)> {
+    let mut lines = text.lines().map(str::trim).skip_while(|l| l.is_empty());
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+    let mut path = None;
+    let mut deleted = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Path=") {
+            path = Some(decode_path(value));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deleted = NaiveDateTime::parse_from_str(value, DATE_FORMAT).ok();
+        } else if line.starts_with('[') {
+            break;
+        }
+    }
+    Some((path?, deleted))
+}
+
+/// Percent-decode a `Path=` value into a path, byte for byte.
+fn decode_path(value: &str) -> PathBuf {
+    PathBuf::from(os_string(percent_decode_str(value).collect()))
+}
+
+/// Raw name bytes as an `OsString`; lossy where names are not bytes.
+fn os_string(bytes: Vec<u8>) -> OsString {
+    #[cfg(unix)]
+    return std::os::unix::ffi::OsStringExt::from_vec(bytes);
+    #[cfg(not(unix))]
+    return OsString::from(String::from_utf8_lossy(&bytes).into_owned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trashed_items_are_listed_restored_and_purged() {
+        let td = tempfile::tempdir().unwrap();
+        let trash = Trash::new(td.path().join("data/Trash"));
+        let docs = td.path().join("docs");
+        fs::create_dir_all(docs.join("dir")).unwrap();
+        fs::write(docs.join("a b%.txt"), "one").unwrap();
+        fs::write(docs.join("dir/inner"), "two").unwrap();
+
+        let first = trash.trash(&docs.join("a b%.txt")).unwrap();
+        fs::write(docs.join("a b%.txt"), "again").unwrap();
+        let second = trash.trash(&docs.join("a b%.txt")).unwrap();
+        let dir = trash.trash(&docs.join("dir")).unwrap();
+        assert_eq!(first.name, "a b%.txt");
+        assert_eq!(second.name, "a b% (1).txt");
+        assert!(!docs.join("a b%.txt").exists() && !docs.join("dir").exists());
+
+        let info = fs::read_to_string(first.info_path()).unwrap();
+        assert!(info.starts_with(&format!("[Trash Info]\nPath={}/a%20b%25.txt\nDeletionDate=", docs.display())), "{}", info);
+        assert!(first.deleted.is_some());
+
+        let listed = trash.list();
+        assert_eq!(listed.len(), 3);
+        assert!(listed.contains(&first) && listed.contains(&dir));
+
+        // Something new took the old place: restoring has to wait.
+        fs::write(docs.join("a b%.txt"), "newer").unwrap();
+        assert_eq!(trash.restore(&first).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        trash.restore(&dir).unwrap();
+        assert_eq!(fs::read(docs.join("dir/inner")).unwrap(), b"two");
+
+        trash.purge(&second).unwrap();
+        assert_eq!(trash.list(), vec![first.clone()]);
+        assert_eq!(trash.empty().unwrap(), 1);
+        assert!(trash.list().is_empty());
+        assert!(!first.file_path().exists());
+    }
+
+    #[test]
+    fn info_files_parse_relative_and_encoded_paths() {
+        let (path, date) = parse_info("[Trash Info]\nPath=media/caf%C3%A9%20menu\nDeletionDate=2024-03-01T09:15:00\n").unwrap();
+        assert_eq!(path, Path::new("media/café menu"));
+        assert_eq!(date.unwrap().to_string(), "2024-03-01 09:15:00");
+        assert!(parse_info("Path=/x").is_none());
+        assert_eq!(topdir_of(Path::new("/mnt/usb/.Trash-1000")), Some(PathBuf::from("/mnt/usb")));
+        assert_eq!(topdir_of(Path::new("/mnt/usb/.Trash/1000")), Some(PathBuf::from("/mnt/usb")));
+        assert_eq!(topdir_of(Path::new("/home/me/.local/share/Trash")), None);
+        assert_eq!(unescape_mount("/mnt/my\\040disk"), "/mnt/my disk");
+    }
+}
@@ -11,6 +11,9 @@ pub mod runner;
 #[path = "UI/mod.rs"]
 pub mod ui;
 pub mod panic_hook;
+// Storage backends that panels browse through (`vfs::Vfs`).
+#[path = "vfs/vfs.rs"]
+pub mod vfs;
 
 // Building helpers (refactored from the make_fakefs binary)
 #[path = "building/mod.rs"]
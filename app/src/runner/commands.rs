@@ -72,12 +72,21 @@ pub fn known_commands() -> impl Iterator<Item = &'static str> {
 /// from the underlying filesystem helpers (`FsOpError`).
 pub fn perform_action(app: &mut App, action: Action) -> Result<(), FsOpError> {
     match action {
-        Action::DeleteSelected => app.delete_selected(),
+        Action::DeleteSelected => crate::runner::handlers::normal::start_delete(app),
+        Action::TrashSelected => app.trash_selected(),
+        Action::ShredSelected => crate::runner::handlers::normal::start_shred(app),
+        Action::PurgeTrashed(item) => crate::runner::handlers::trash::purge(app, &item),
+        Action::EmptyTrash => crate::runner::handlers::trash::empty(app),
         Action::CopyTo(p) => app.copy_selected_to(p),
         Action::MoveTo(p) => app.move_selected_to(p),
         Action::RenameTo(name) => app.rename_selected_to(name),
         Action::NewFile(name) => app.new_file(name),
         Action::NewDir(name) => app.new_dir(name),
+        Action::Link(kind) => app.link_selected(kind).map(|_| ()),
+        Action::ResumeJobs => crate::runner::handlers::unfinished::resume_unfinished(app),
+        Action::DiscardJobs => crate::runner::handlers::unfinished::discard_unfinished(app),
+        Action::InspectJobs => crate::runner::handlers::unfinished::inspect_unfinished(app),
+        Action::RetryFailed(id) => crate::runner::handlers::jobs::retry_failed(app, id),
     }
 }
 
@@ -50,6 +50,7 @@ pub fn run_app(
         // Apply any persisted UI-only flags into live app state so settings
         // correctly reflect the desired layout (for example file-stats).
         app.file_stats_visible = app.settings.file_stats_visible;
+        crate::fs_op::fastcopy::set_method(app.settings.copy_method);
     }
 
     // Re-apply CLI-provided startup overrides (CLI should win over persisted settings).
@@ -64,6 +65,11 @@ pub fn run_app(
         crate::ui::colors::set_theme(theme.as_str());
     }
 
+    // Journal jobs so they survive a crash or quit, and offer whatever a
+    // previous run left unfinished.
+    app.jobs = crate::runner::job_queue::JobQueue::with_journal(crate::runner::job_journal::JobJournal::in_cache_dir());
+    crate::runner::handlers::unfinished::offer_unfinished(&mut app);
+
     // Track current mouse capture state so we can toggle it at runtime when
     // user changes the `mouse_enabled` setting in the UI. Use a small enum
     // for clearer intent instead of a raw boolean.
@@ -169,6 +175,9 @@ pub fn run_app(
             break;
         }
 
+        // Pick up progress from the followed job before drawing it.
+        app.poll_progress();
+
         // Draw once at the top of the loop. Resize events will also trigger
         // an immediate redraw below when detected in the aggregated events.
         terminal.draw(|f| ui::ui(f, &app))?;
@@ -3,23 +3,33 @@
 //! This module keeps the top-level dispatch small and delegates mode-specific
 //! handling into individual submodules (see the public submodules below).
 
+pub mod batch_rename;
 pub mod confirm;
 pub mod conflict;
 pub mod context_menu;
+pub mod edit_names;
 pub mod input_mode;
+pub mod jobs;
 pub mod mouse;
 pub mod normal;
+pub mod pack;
 pub mod progress_mode;
 pub mod settings;
+pub mod trash;
+pub mod unfinished;
 
+pub use batch_rename::handle_batch_rename;
 pub use confirm::handle_confirm;
-pub use conflict::handle_conflict;
+pub use conflict::{handle_conflict, handle_diff, handle_item_error};
 pub use context_menu::handle_context_menu;
+pub use edit_names::handle_edit_names;
 pub use input_mode::handle_input;
+pub use jobs::handle_jobs;
 pub use mouse::handle_mouse;
 pub use normal::handle_normal;
 pub use progress_mode::handle_progress;
 pub use settings::handle_settings;
+pub use trash::handle_trash;
 
 use crate::app::{App, Mode};
 use crate::app::settings::keybinds;
@@ -41,6 +51,8 @@ pub fn handle_key(app: &mut App, code: KeyCode, page_size: usize) -> anyhow::Res
         Mode::Normal => handle_normal(app, code, page_size),
         Mode::Progress { .. } => handle_progress(app, code),
         Mode::Conflict { .. } => handle_conflict(app, code),
+        Mode::Diff { .. } => handle_diff(app, code),
+        Mode::ItemError { .. } => handle_item_error(app, code),
         Mode::ContextMenu { .. } => handle_context_menu(app, code),
         Mode::Message {
             title: _,
@@ -60,9 +72,12 @@ pub fn handle_key(app: &mut App, code: KeyCode, page_size: usize) -> anyhow::Res
             } else if keybinds::is_enter(&code) {
                 // If an action mapping exists, execute the mapped action for
                 // the selected button. Otherwise simply dismiss the dialog.
+                // The dialog closes before the action runs, so an action may
+                // open a dialog of its own.
                 if let Some(act) = crate::ui::dialogs::selection_to_action(*selected, actions.as_deref()) {
+                    app.mode = Mode::Normal;
                     match crate::runner::commands::perform_action(app, act) {
-                        Ok(_) => app.mode = Mode::Normal,
+                        Ok(_) => {}
                         Err(e) => {
                             app.mode = Mode::Message {
                                 title: "Error".to_string(),
@@ -84,6 +99,10 @@ pub fn handle_key(app: &mut App, code: KeyCode, page_size: usize) -> anyhow::Res
         Mode::Confirm { .. } => handle_confirm(app, code),
         Mode::Input { .. } => handle_input(app, code),
         Mode::Settings { .. } => handle_settings(app, code),
+        Mode::Jobs { .. } => handle_jobs(app, code),
+        Mode::Trash { .. } => handle_trash(app, code),
+        Mode::BatchRename { .. } => handle_batch_rename(app, code),
+        Mode::EditNames { .. } => handle_edit_names(app, code),
     }
 
     
@@ -0,0 +1,68 @@
+//! Batch rename dialog.
+//!
+//! `R` with several entries marked opens it. The mask, find and replace
+//! fields are edited in place (Tab moves between them) and every change
+//! recomputes the preview of old and new names; Enter renames once no row
+//! is flagged (see `fs_op::batch_rename`).
+
+use crate::app::{App, Mode};
+use crate::errors;
+use crate::fs_op::batch_rename::Template;
+use crate::input::KeyCode;
+
+/// Open the dialog for the marked entries with a mask that keeps every
+/// name as it is.
+pub fn open_batch_rename(app: &mut App) {
+    app.mode = Mode::BatchRename { fields: ["*".to_string(), String::new(), String::new()], focus: 0, preview: Ok(Vec::new()), scroll: 0 };
+    update_preview(app);
+}
+
+/// Handle input while the batch rename dialog is shown.
+pub fn handle_batch_rename(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    let Mode::BatchRename { fields, focus, preview, scroll } = &mut app.mode else { return Ok(false) };
+    match code {
+        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Tab => *focus = (*focus + 1) % fields.len(),
+        KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        KeyCode::Down => *scroll = (*scroll + 1).min(preview.as_ref().map_or(0, |rows| rows.len().saturating_sub(1))),
+        KeyCode::Backspace => {
+            fields[*focus].pop();
+            update_preview(app);
+        }
+        KeyCode::Char(c) => {
+            fields[*focus].push(c);
+            update_preview(app);
+        }
+        KeyCode::Enter => {
+            // The dialog already shows what blocks the rename.
+            let ready = matches!(preview, Ok(rows) if rows.iter().all(|r| r.problem.is_none()));
+            let Ok(template) = Template::parse(&fields[0], &fields[1], &fields[2]) else { return Ok(false) };
+            if !ready {
+                return Ok(false);
+            }
+            app.mode = Mode::Normal;
+            if let Err(e) = app.batch_rename(&template) {
+                app.mode = Mode::Message {
+                    title: "Error".to_string(),
+                    content: errors::render_fsop_error(&e, None, None, None),
+                    buttons: vec!["OK".to_string()],
+                    selected: 0,
+                    actions: None,
+                };
+            }
+            return Ok(true);
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// Recompute the preview from the dialog's fields.
+fn update_preview(app: &mut App) {
+    let Mode::BatchRename { fields, .. } = &app.mode else { return };
+    let preview = Template::parse(&fields[0], &fields[1], &fields[2]).map(|template| app.batch_rename_preview(&template));
+    if let Mode::BatchRename { preview: p, scroll, .. } = &mut app.mode {
+        *scroll = (*scroll).min(preview.as_ref().map_or(0, |rows| rows.len().saturating_sub(1)));
+        *p = preview;
+    }
+}
@@ -52,11 +52,6 @@ fn set_error_message(app: &mut App, err: &crate::fs_op::error::FsOpError) {
 /// filesystem errors as a message mode.
 fn execute_action(app: &mut App, action: Action) {
     match action {
-        Action::DeleteSelected => {
-            if let Err(err) = app.delete_selected() {
-                set_error_message(app, &err);
-            }
-        }
         Action::CopyTo(p) => {
             if let Err(err) = app.copy_selected_to(p) {
                 set_error_message(app, &err);
@@ -82,6 +77,20 @@ fn execute_action(app: &mut App, action: Action) {
                 set_error_message(app, &err);
             }
         }
+        other @ (Action::DeleteSelected
+        | Action::TrashSelected
+        | Action::ShredSelected
+        | Action::PurgeTrashed(_)
+        | Action::EmptyTrash
+        | Action::ResumeJobs
+        | Action::DiscardJobs
+        | Action::InspectJobs
+        | Action::RetryFailed(_)
+        | Action::Link(_)) => {
+            if let Err(err) = crate::runner::commands::perform_action(app, other) {
+                set_error_message(app, &err);
+            }
+        }
     }
 }
 
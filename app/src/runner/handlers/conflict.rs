@@ -1,32 +1,36 @@
 use crate::app::{App, Mode};
 use crate::app::settings::keybinds;
 use crate::input::KeyCode;
-use crate::runner::progress::OperationDecision;
+use crate::runner::progress::{ConflictPolicy, OperationDecision};
+use crate::vfs::Vfs;
+use std::io::Read;
+use std::path::Path;
 
 const RESOLVING_TITLE: &str = "Resolving";
 const APPLYING_MSG: &str = "Applying decision";
 const CANCELLING_MSG: &str = "Cancelling";
 
 /// Map the user's current selection and the `apply_all` toggle to an
-/// `OperationDecision` value.
+/// `OperationDecision` value. The buttons are `ConflictPolicy::CHOICES`
+/// followed by Cancel.
 fn map_selection_to_decision(selected: usize, apply_all: bool) -> OperationDecision {
-    match selected {
-        0 => {
-            if apply_all {
-                OperationDecision::OverwriteAll
-            } else {
-                OperationDecision::Overwrite
-            }
-        }
-        1 => {
-            if apply_all {
-                OperationDecision::SkipAll
-            } else {
-                OperationDecision::Skip
-            }
-        }
-        _ => OperationDecision::Cancel,
-    }
+    ConflictPolicy::CHOICES.get(selected).and_then(|policy| policy.decision(apply_all)).unwrap_or(OperationDecision::Cancel)
+}
+
+/// Shortcut keys of the conflict dialog: `o`verwrite, `s`kip, `k`eep both,
+/// r`e`name existing, if `n`ewer, if size `d`iffers and `r`esume.
+fn policy_for_key(code: &KeyCode) -> Option<ConflictPolicy> {
+    let KeyCode::Char(c) = code else { return None };
+    Some(match c.to_ascii_lowercase() {
+        'o' => ConflictPolicy::Overwrite,
+        's' => ConflictPolicy::Skip,
+        'k' => ConflictPolicy::RenameNew,
+        'e' => ConflictPolicy::RenameExisting,
+        'n' => ConflictPolicy::OverwriteIfNewer,
+        'd' => ConflictPolicy::OverwriteIfSizeDiffers,
+        'r' => ConflictPolicy::Resume,
+        _ => return None,
+    })
 }
 
 /// Helper to send a decision to the worker (if present) and transition the
@@ -41,6 +45,7 @@ fn send_decision_and_enter_progress(app: &mut App, decision: OperationDecision,
         total: 0,
         message: message.to_string(),
         cancelled,
+        bytes: None,
     };
 }
 
@@ -48,38 +53,137 @@ fn send_decision_and_enter_progress(app: &mut App, decision: OperationDecision,
 ///
 /// Returns `Ok(false)` currently (keeps existing behaviour). The function
 /// mutates `app.mode` and may send an `OperationDecision` to a background
-/// worker via `app.op_decision_tx`.
+/// worker via `app.op_decision_tx`. `v` (or the Compare button) shows a
+/// diff of both files when they are text.
 pub fn handle_conflict(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
-    if let Mode::Conflict { path: _, selected, apply_all } = &mut app.mode {
-            if keybinds::is_left(&code) {
-                *selected = (*selected).saturating_sub(1);
-            } else if keybinds::is_right(&code) {
-                *selected = (*selected + 1).min(2);
-            } else if keybinds::is_toggle_selection(&code) || keybinds::is_char(&code, 'a') || keybinds::is_char(&code, 'A') {
-                *apply_all = !*apply_all;
-            } else if keybinds::is_enter(&code)
-                || keybinds::is_char(&code, 'o') || keybinds::is_char(&code, 'O')
-                || keybinds::is_char(&code, 's') || keybinds::is_char(&code, 'S')
-            {
-                // Determine decision based on the selection and toggle.
-                let decision = if keybinds::is_enter(&code) {
-                    map_selection_to_decision(*selected, *apply_all)
-                } else if keybinds::is_char(&code, 'o') || keybinds::is_char(&code, 'O') {
-                    if *apply_all { OperationDecision::OverwriteAll } else { OperationDecision::Overwrite }
-                } else {
-                    // 's' / 'S'
-                    if *apply_all { OperationDecision::SkipAll } else { OperationDecision::Skip }
-                };
-
-                send_decision_and_enter_progress(app, decision, APPLYING_MSG, false);
-            } else if keybinds::is_esc(&code) || keybinds::is_char(&code, 'c') || keybinds::is_char(&code, 'C') {
-                send_decision_and_enter_progress(app, OperationDecision::Cancel, CANCELLING_MSG, true);
-            }
+    let Mode::Conflict { conflict, selected, apply_all } = &mut app.mode else { return Ok(false) };
+    let compare = ConflictPolicy::CHOICES.len() + 1;
+    if keybinds::is_left(&code) {
+        *selected = (*selected).saturating_sub(1);
+    } else if keybinds::is_right(&code) {
+        *selected = (*selected + 1).min(crate::ui::widgets::conflict::buttons(conflict).len() - 1);
+    } else if keybinds::is_toggle_selection(&code) || keybinds::is_char(&code, 'a') || keybinds::is_char(&code, 'A') {
+        *apply_all = !*apply_all;
+    } else if (keybinds::is_enter(&code) && *selected == compare) || keybinds::is_char(&code, 'v') || keybinds::is_char(&code, 'V') {
+        open_compare(app);
+    } else if keybinds::is_enter(&code) {
+        let decision = map_selection_to_decision(*selected, *apply_all);
+        if decision == OperationDecision::Cancel {
+            send_decision_and_enter_progress(app, decision, CANCELLING_MSG, true);
+        } else {
+            send_decision_and_enter_progress(app, decision, APPLYING_MSG, false);
+        }
+    } else if let Some(decision) = policy_for_key(&code).and_then(|policy| policy.decision(*apply_all)) {
+        send_decision_and_enter_progress(app, decision, APPLYING_MSG, false);
+    } else if keybinds::is_esc(&code) || keybinds::is_char(&code, 'c') || keybinds::is_char(&code, 'C') {
+        send_decision_and_enter_progress(app, OperationDecision::Cancel, CANCELLING_MSG, true);
+    }
+
+    Ok(false)
+}
+
+/// Files larger than this are not diffed.
+const DIFF_LIMIT: u64 = 1024 * 1024;
+
+/// Show the diff of the conflicting files over the conflict dialog, which
+/// comes back when the diff is closed. Needs both files and the backends
+/// of the followed job.
+fn open_compare(app: &mut App) {
+    let Mode::Conflict { conflict, .. } = &app.mode else { return };
+    let Some(src) = conflict.src.clone().filter(|_| conflict.both_files()) else { return };
+    let Some((src_vfs, dst_vfs)) = app.op_job.and_then(|id| app.jobs.backends(id)) else { return };
+    let target = conflict.target.clone();
+    let text = text_diff(src_vfs.as_ref(), &src, dst_vfs.as_ref(), &target).unwrap_or_else(|reason| reason);
+    let title = format!("Compare {}", target.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+    let back = Box::new(std::mem::take(&mut app.mode));
+    app.mode = Mode::Diff { title, text, scroll: 0, back };
+}
+
+/// Unified diff from the existing `target` to `src`, or why there is none
+/// to show (binary or oversized files, identical contents, read errors).
+pub fn text_diff(src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path) -> Result<String, String> {
+    let read = |vfs: &dyn Vfs, path: &Path| -> Result<String, String> {
+        let mut data = Vec::new();
+        vfs.open_read(path)
+            .and_then(|r| r.take(DIFF_LIMIT + 1).read_to_end(&mut data))
+            .map_err(|e| format!("Cannot read {}: {}", vfs.display_path(path), e))?;
+        if data.len() as u64 > DIFF_LIMIT {
+            return Err("Files over 1 MiB are not compared".to_string());
+        }
+        match String::from_utf8(data) {
+            Ok(text) if !text.contains('\0') => Ok(text),
+            _ => Err(format!("{} is not a text file", vfs.display_path(path))),
+        }
+    };
+    let old = read(dst_vfs, target)?;
+    let new = read(src_vfs, src)?;
+    if old == new {
+        return Err("The files are identical".to_string());
     }
+    let diff = similar::TextDiff::from_lines(&old, &new);
+    let existing = format!("{} (existing)", dst_vfs.display_path(target));
+    Ok(diff.unified_diff().context_radius(3).header(&existing, &src_vfs.display_path(src)).to_string())
+}
 
+/// Handle keys in the read-only diff view: scroll, or close it and go back
+/// to the dialog it was opened from.
+pub fn handle_diff(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    let Mode::Diff { text, scroll, .. } = &mut app.mode else { return Ok(false) };
+    let last = text.lines().count().saturating_sub(1);
+    if keybinds::is_up(&code) {
+        *scroll = scroll.saturating_sub(1);
+    } else if keybinds::is_down(&code) {
+        *scroll = (*scroll + 1).min(last);
+    } else if keybinds::is_page_up(&code) {
+        *scroll = scroll.saturating_sub(DIFF_PAGE);
+    } else if keybinds::is_page_down(&code) {
+        *scroll = (*scroll + DIFF_PAGE).min(last);
+    } else if keybinds::is_esc(&code) || keybinds::is_enter(&code) || keybinds::is_char(&code, 'q') || keybinds::is_char(&code, 'v') {
+        if let Mode::Diff { back, .. } = std::mem::take(&mut app.mode) {
+            app.mode = *back;
+        }
+    }
     Ok(false)
 }
 
+/// Lines moved by PageUp / PageDown in the diff view.
+const DIFF_PAGE: usize = 10;
+
+/// Decisions behind the buttons of the failed-item dialog, in order.
+const ITEM_ERROR_DECISIONS: [OperationDecision; 4] =
+    [OperationDecision::Retry, OperationDecision::Skip, OperationDecision::SkipAll, OperationDecision::Cancel];
+
+/// Handle key events while a job waits for a decision about a failed item
+/// (`Mode::ItemError`): retry it, skip it, skip every later failure too,
+/// or abort the job.
+pub fn handle_item_error(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    let Mode::ItemError { selected, .. } = &mut app.mode else { return Ok(false) };
+    let decision = if keybinds::is_left(&code) {
+        *selected = (*selected).saturating_sub(1);
+        None
+    } else if keybinds::is_right(&code) {
+        *selected = (*selected + 1).min(ITEM_ERROR_DECISIONS.len() - 1);
+        None
+    } else if keybinds::is_enter(&code) {
+        Some(ITEM_ERROR_DECISIONS[*selected])
+    } else if keybinds::is_char(&code, 'r') || keybinds::is_char(&code, 'R') {
+        Some(OperationDecision::Retry)
+    } else if keybinds::is_char(&code, 's') || keybinds::is_char(&code, 'S') {
+        Some(OperationDecision::Skip)
+    } else if keybinds::is_char(&code, 'a') || keybinds::is_char(&code, 'A') {
+        Some(OperationDecision::SkipAll)
+    } else if keybinds::is_esc(&code) || keybinds::is_char(&code, 'c') || keybinds::is_char(&code, 'C') {
+        Some(OperationDecision::Cancel)
+    } else {
+        None
+    };
+    match decision {
+        Some(OperationDecision::Cancel) => send_decision_and_enter_progress(app, OperationDecision::Cancel, CANCELLING_MSG, true),
+        Some(decision) => send_decision_and_enter_progress(app, decision, APPLYING_MSG, false),
+        None => {}
+    }
+    Ok(false)
+}
 
 #[cfg(test)]
 mod tests {
@@ -97,9 +201,23 @@ mod tests {
         assert!(matches!(map_selection_to_decision(1, true), OperationDecision::SkipAll));
     }
 
+    #[test]
+    fn map_selection_extended_policies() {
+        assert_eq!(map_selection_to_decision(2, false), OperationDecision::RenameNew);
+        assert_eq!(map_selection_to_decision(3, true), OperationDecision::RenameExistingAll);
+        assert_eq!(map_selection_to_decision(6, false), OperationDecision::Resume);
+    }
+
     #[test]
     fn map_selection_cancel() {
-        assert!(matches!(map_selection_to_decision(2, false), OperationDecision::Cancel));
+        assert!(matches!(map_selection_to_decision(ConflictPolicy::CHOICES.len(), false), OperationDecision::Cancel));
         assert!(matches!(map_selection_to_decision(99, true), OperationDecision::Cancel));
     }
+
+    #[test]
+    fn shortcut_keys_pick_policies() {
+        assert_eq!(policy_for_key(&KeyCode::Char('K')), Some(ConflictPolicy::RenameNew));
+        assert_eq!(policy_for_key(&KeyCode::Char('d')), Some(ConflictPolicy::OverwriteIfSizeDiffers));
+        assert_eq!(policy_for_key(&KeyCode::Char('c')), None);
+    }
 }
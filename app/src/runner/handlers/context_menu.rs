@@ -1,4 +1,4 @@
-use crate::app::{App, Mode};
+use crate::app::{App, InputKind, Mode};
 use crate::input::KeyCode;
 use crate::app::settings::keybinds;
 
@@ -11,6 +11,8 @@ enum ContextAction {
     View,
     Edit,
     Permissions,
+    /// Point the selected symlink somewhere else.
+    EditSymlink,
     /// Any action label we don't specifically recognise.
     Other(String),
 }
@@ -22,6 +24,7 @@ impl ContextAction {
             "View" | "Open" => ContextAction::View,
             "Edit" => ContextAction::Edit,
             "Permissions" | "Inspect Permissions" => ContextAction::Permissions,
+            "Edit symlink" => ContextAction::EditSymlink,
             other => ContextAction::Other(other.to_string()),
         }
     }
@@ -82,6 +85,9 @@ pub fn handle_context_menu(app: &mut App, code: KeyCode) -> anyhow::Result<bool>
                             app.preview_visible = true;
                             app.update_preview_for(app.active);
                         }
+                        ContextAction::Edit if !app.active_panel().vfs.is_local() => {
+                            pending_mode = Some(build_message("Edit", "Editing is only available for local files".to_string()));
+                        }
                         ContextAction::Edit => {
                             if let Some(e) = app.active_panel().selected_entry() {
                                 let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
@@ -106,26 +112,52 @@ pub fn handle_context_menu(app: &mut App, code: KeyCode) -> anyhow::Result<bool>
                             }
                         }
                         ContextAction::Permissions => {
-                            if let Some(e) = app.active_panel().selected_entry() {
-                                match std::fs::metadata(&e.path) {
-                                    Ok(md) => {
-                                        #[cfg(unix)]
-                                        {
-                                            use std::os::unix::fs::PermissionsExt;
-                                            let mode = md.permissions().mode();
-                                            pending_mode = Some(build_message("Permissions", format!("{}: {:o}", e.name, mode)));
-                                        }
-                                        #[cfg(not(unix))]
-                                        {
-                                            pending_mode = Some(build_message("Permissions", format!("{}: (platform-specific metadata)", e.name)));
-                                        }
+                            // Show the current mode and let the user edit it;
+                            // the mode comes from the listing so this works for
+                            // remote backends as well. With several entries
+                            // marked the new mode applies to all of them.
+                            let marked = app.active_panel().marked_entries();
+                            match marked.as_slice() {
+                                [] => pending_mode = Some(build_message("Permissions", "No entry selected".to_string())),
+                                [e] => match e.unix_mode {
+                                    Some(mode) => {
+                                        pending_mode = Some(Mode::Input {
+                                            prompt: format!("Permissions for {} (octal, current {:o}):", e.name, mode & 0o7777),
+                                            buffer: format!("{:o}", mode & 0o7777),
+                                            kind: InputKind::Chmod,
+                                        });
                                     }
-                                    Err(_) => pending_mode = Some(build_message("Permissions", "Cannot read metadata".to_string())),
+                                    None => pending_mode = Some(build_message("Permissions", format!("{}: (platform-specific metadata)", e.name))),
+                                },
+                                several => {
+                                    // Prefill the mode only when they all share it.
+                                    let modes: std::collections::HashSet<Option<u32>> = several.iter().map(|e| e.unix_mode.map(|m| m & 0o7777)).collect();
+                                    let buffer = match modes.into_iter().collect::<Vec<_>>().as_slice() {
+                                        [Some(mode)] => format!("{:o}", mode),
+                                        _ => String::new(),
+                                    };
+                                    pending_mode = Some(Mode::Input {
+                                        prompt: format!("Permissions for {} items (octal):", several.len()),
+                                        buffer,
+                                        kind: InputKind::Chmod,
+                                    });
                                 }
-                            } else {
-                                pending_mode = Some(build_message("Permissions", "No entry selected".to_string()));
                             }
                         }
+                        ContextAction::EditSymlink => {
+                            let panel = app.active_panel();
+                            pending_mode = Some(match panel.selected_entry() {
+                                None => build_message("Edit symlink", "No entry selected".to_string()),
+                                Some(e) => match panel.vfs.read_link(&e.path) {
+                                    Ok(target) => Mode::Input {
+                                        prompt: format!("Symlink {} points to:", e.name),
+                                        buffer: target.to_string_lossy().into_owned(),
+                                        kind: InputKind::EditSymlink,
+                                    },
+                                    Err(err) => build_message("Edit symlink", format!("{} is not a readable symlink: {}", e.name, err)),
+                                },
+                            });
+                        }
                         ContextAction::Other(label) => pending_mode = Some(build_message("Action", format!("Action '{}' not implemented", label))),
                     }
                 }
@@ -0,0 +1,112 @@
+//! Renaming in a text editor (see `fs_op::edit_names`).
+//!
+//! `E` writes the marked entries' names to a temporary file and opens it
+//! in the user's editor, suspending the TUI. Once the editor exits the file
+//! is read back and the changes are shown as a diff; Enter applies them and
+//! Esc drops them.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::app::settings::keybinds;
+use crate::app::text_editors;
+use crate::app::{App, Mode};
+use crate::errors;
+use crate::fs_op::app_ops::name_taken;
+use crate::fs_op::edit_names;
+use crate::input::KeyCode;
+
+/// Lines moved by PageUp / PageDown in the confirmation view.
+const PAGE: usize = 10;
+
+/// Edit the marked entries' names in the configured editor.
+pub fn open_edit_names(app: &mut App) {
+    let command = text_editors::editor_command(app.settings.prefer_integrated_vim);
+    edit_names_with(app, |path| text_editors::spawn_editor(&command, path));
+}
+
+/// `open_edit_names` with `edit` in place of the editor: it gets the path
+/// of the listing and changes the file in place.
+pub fn edit_names_with(app: &mut App, edit: impl FnOnce(&Path) -> io::Result<()>) {
+    let panel = app.active_panel();
+    let entries = panel.marked_entries();
+    if entries.is_empty() {
+        return;
+    }
+    if let Some(e) = entries.iter().find(|e| e.name.contains('\n') || crate::vfs::transfer::child_name(e).to_str().is_none()) {
+        return show_message(app, "Edit names", format!("{} cannot be edited as a line of text", e.name));
+    }
+    let names: Vec<String> = entries.iter().map(|e| e.name.clone()).collect();
+    let paths: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+
+    let edited = match edit_listing(&edit_names::listing(&names), edit) {
+        Ok(edited) => edited,
+        Err(e) => return show_message(app, "Error", format!("Could not edit the names: {}", e)),
+    };
+    let panel = app.active_panel();
+    let edits = match edit_names::parse(&names, &edited, &|name| name_taken(panel, &panel.cwd.join(name))) {
+        Ok(edits) => edits,
+        Err(e) => return show_message(app, "Error", format!("Nothing was changed: {}", e)),
+    };
+    if edits.is_empty() {
+        app.mode = Mode::Normal;
+        return;
+    }
+    app.mode = Mode::EditNames {
+        text: edit_names::summary(&names, &edits, panel.vfs.is_local()),
+        scroll: 0,
+        renames: edits.renames.iter().map(|(i, name)| (paths[*i].clone(), panel.cwd.join(name))).collect(),
+        deletes: edits.deletes.iter().map(|i| paths[*i].clone()).collect(),
+    };
+}
+
+/// Write `listing` to a new temporary file, let `edit` change it, and
+/// return what it holds afterwards. The file is removed either way.
+fn edit_listing(listing: &str, edit: impl FnOnce(&Path) -> io::Result<()>) -> io::Result<String> {
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("filezoom-names-{}-{}.txt", std::process::id(), nanos));
+    let res = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut f| io::Write::write_all(&mut f, listing.as_bytes()))
+        .and_then(|_| edit(&path))
+        .and_then(|_| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    res
+}
+
+/// Handle input while the changes are shown for confirmation.
+pub fn handle_edit_names(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    let Mode::EditNames { text, scroll, .. } = &mut app.mode else { return Ok(false) };
+    let last = text.lines().count().saturating_sub(1);
+    if keybinds::is_up(&code) {
+        *scroll = scroll.saturating_sub(1);
+    } else if keybinds::is_down(&code) {
+        *scroll = (*scroll + 1).min(last);
+    } else if keybinds::is_page_up(&code) {
+        *scroll = scroll.saturating_sub(PAGE);
+    } else if keybinds::is_page_down(&code) {
+        *scroll = (*scroll + PAGE).min(last);
+    } else if keybinds::is_enter(&code) || keybinds::is_char(&code, 'y') {
+        let Mode::EditNames { renames, deletes, .. } = std::mem::take(&mut app.mode) else { return Ok(false) };
+        if let Err(e) = app.apply_name_edits(&renames, &deletes) {
+            show_message(app, "Error", errors::render_fsop_error(&e, None, None, None));
+        }
+        return Ok(true);
+    } else if keybinds::is_esc(&code) || keybinds::is_char(&code, 'n') || keybinds::is_char(&code, 'q') {
+        app.mode = Mode::Normal;
+    }
+    Ok(false)
+}
+
+fn show_message(app: &mut App, title: &str, content: String) {
+    app.mode = Mode::Message {
+        title: title.to_string(),
+        content,
+        buttons: vec!["OK".to_string()],
+        selected: 0,
+        actions: None,
+    };
+}
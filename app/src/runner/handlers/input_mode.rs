@@ -57,11 +57,35 @@ pub fn handle_input(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
                     }
                 }
                 InputKind::ChangePath => {
-                    let p = PathBuf::from(&input);
-                    let panel = app.active_panel_mut();
-                    panel.cwd = p;
-                    if let Err(e) = app.refresh() {
-                        set_error_message(app, errors::render_io_error(&e, None, None, None));
+                    // `sftp://` and `smb://` locations switch the panel to a
+                    // remote backend; plain paths browse the local filesystem.
+                    match crate::vfs::open_location(&input, &app.settings) {
+                        Ok((vfs, p)) => {
+                            let panel = app.active_panel_mut();
+                            panel.vfs = vfs;
+                            panel.cwd = p;
+                            panel.selections.clear();
+                            if let Err(e) = app.refresh() {
+                                set_error_message(app, errors::render_io_error(&e, None, None, None));
+                            }
+                        }
+                        Err(e) => set_error_message(app, errors::render_io_error(&e, Some(input.as_str()), None, None)),
+                    }
+                }
+                InputKind::Chmod => {
+                    match u32::from_str_radix(input.trim(), 8) {
+                        Ok(mode) if mode <= 0o7777 => {
+                            if let Err(e) = app.chmod_selected(mode) {
+                                set_error_message(app, errors::render_fsop_error(&e, None, None, None));
+                            }
+                        }
+                        _ => set_error_message(app, format!("Invalid mode '{}': expected octal such as 644", input.trim())),
+                    }
+                }
+                InputKind::Pack => crate::runner::handlers::pack::start_pack(app, &input),
+                InputKind::EditSymlink => {
+                    if let Err(e) = app.retarget_symlink(PathBuf::from(&input)) {
+                        set_error_message(app, errors::render_fsop_error(&e, None, None, None));
                     }
                 }
                 InputKind::Filter => {
@@ -135,6 +159,17 @@ mod tests {
         assert!(matches!(app.mode, Mode::Normal));
     }
 
+    #[test]
+    fn chmod_rejects_non_octal_input() {
+        let mut app = CoreApp::new().unwrap();
+        app.mode = Mode::Input { prompt: "".into(), buffer: "9z".into(), kind: InputKind::Chmod };
+        let _ = handle_input(&mut app, KeyCode::Enter).unwrap();
+        match &app.mode {
+            Mode::Message { content, .. } => assert!(content.contains("Invalid mode")),
+            other => panic!("expected error message, got {:?}", other),
+        }
+    }
+
     #[test]
     fn enter_with_copy_kind_runs_noop_when_nothing_selected() {
         let mut app = CoreApp::new().unwrap();
@@ -0,0 +1,123 @@
+//! Jobs view and following background jobs.
+//!
+//! `J` opens a list of every job on `App::jobs`. From there a job can be
+//! paused, resumed, cancelled, retried or moved in the queue, and `Enter`
+//! brings it back into the progress dialog. `b` in the progress dialog
+//! sends the followed job to the background, so browsing continues while
+//! it runs; the footer shows a summary of unfinished jobs. `f` runs just
+//! the items a finished job skipped after they failed.
+
+use crate::app::{App, Mode};
+use crate::fs_op::error::FsOpError;
+use crate::input::KeyCode;
+use crate::runner::job_queue::{JobId, JobStatus, JobTicket};
+
+/// Open the jobs view with the first job selected.
+pub fn open_jobs(app: &mut App) {
+    app.mode = Mode::Jobs { selected: 0 };
+}
+
+/// Handle input while the jobs view is shown.
+pub fn handle_jobs(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    let Mode::Jobs { selected } = app.mode else { return Ok(false) };
+    let jobs = app.jobs.list();
+    let current = jobs.get(selected.min(jobs.len().saturating_sub(1)));
+    let mut selected = selected;
+    match code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('J') => {
+            app.mode = Mode::Normal;
+            return Ok(false);
+        }
+        KeyCode::Up => selected = selected.saturating_sub(1),
+        KeyCode::Down => selected = (selected + 1).min(jobs.len().saturating_sub(1)),
+        KeyCode::Char('p') | KeyCode::Char(' ') => {
+            if let Some((id, _, state)) = current {
+                if state.status == JobStatus::Paused {
+                    app.jobs.resume(*id);
+                } else {
+                    app.jobs.pause(*id);
+                }
+            }
+        }
+        KeyCode::Char('c') | KeyCode::Delete => {
+            if let Some((id, _, _)) = current {
+                app.jobs.cancel(*id);
+            }
+        }
+        KeyCode::Char('r') => {
+            if let Some(ticket) = current.and_then(|(id, _, _)| app.jobs.retry(*id)) {
+                app.jobs.detach(ticket);
+                selected = jobs.len();
+            }
+        }
+        KeyCode::Char('f') => {
+            if let Some(ticket) = current.and_then(|(id, _, _)| app.jobs.retry_failed(*id)) {
+                app.jobs.detach(ticket);
+                selected = jobs.len();
+            }
+        }
+        KeyCode::Char('+') => {
+            if let Some((id, _, _)) = current {
+                app.jobs.move_up(*id);
+                selected = selected.saturating_sub(1);
+            }
+        }
+        KeyCode::Char('-') => {
+            if let Some((id, _, _)) = current {
+                app.jobs.move_down(*id);
+                selected = (selected + 1).min(jobs.len().saturating_sub(1));
+            }
+        }
+        KeyCode::Enter => {
+            if let Some(ticket) = current.filter(|(_, _, s)| !s.status.is_finished()).and_then(|(id, _, _)| app.jobs.attach(*id)) {
+                follow(app, ticket);
+                return Ok(false);
+            }
+        }
+        _ => {}
+    }
+    app.mode = Mode::Jobs { selected };
+    Ok(false)
+}
+
+/// Run the failed items of job `id` again and follow the new job. Does
+/// nothing when the job has no failures.
+pub fn retry_failed(app: &mut App, id: JobId) -> Result<(), FsOpError> {
+    if let Some(ticket) = app.jobs.retry_failed(id) {
+        follow(app, ticket);
+    }
+    Ok(())
+}
+
+/// Point the progress dialog at `ticket`'s job, sending any job followed
+/// so far to the background.
+pub fn follow(app: &mut App, ticket: JobTicket) {
+    background(app);
+    let (title, processed, total, message, bytes) = match app.jobs.list().into_iter().find(|(id, _, _)| *id == ticket.id) {
+        Some((_, kind, state)) => (kind.title().to_string(), state.processed, state.total, state.message, state.bytes),
+        None => ("Progress".to_string(), 0, 0, String::new(), None),
+    };
+    app.mode = Mode::Progress { title, processed, total, message, cancelled: false, bytes };
+    app.op_job = Some(ticket.id);
+    app.op_progress_rx = Some(ticket.progress);
+    app.op_decision_tx = Some(ticket.decisions);
+    app.op_cancel_flag = Some(ticket.cancel);
+}
+
+/// Stop following the current job and hand its channels back to the
+/// queue, where the jobs view can pick them up again. Returns whether a
+/// queued job was followed; packing, which runs outside the queue, stays
+/// in the foreground.
+pub fn background(app: &mut App) -> bool {
+    let Some(id) = app.op_job.take() else { return false };
+    let progress = app.op_progress_rx.take();
+    let decisions = app.op_decision_tx.take();
+    let cancel = app.op_cancel_flag.take();
+    if let (Some(progress), Some(decisions), Some(cancel)) = (progress, decisions, cancel) {
+        app.jobs.detach(JobTicket { id, progress, decisions, cancel });
+    }
+    if matches!(app.mode, Mode::Progress { .. }) {
+        app.mode = Mode::Normal;
+    }
+    true
+}
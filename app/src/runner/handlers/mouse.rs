@@ -34,7 +34,7 @@ pub fn handle_mouse(app: &mut App, me: MouseEvent, term_rect: Rect) -> Result<bo
     if app.menu_state.open && me.row >= chunks[1].y && me.row < chunks[1].y + chunks[1].height {
         let labels = menu::menu_labels();
         if !labels.is_empty() && me.column < term_rect.width {
-            let idx = crate::ui::menu_model::MenuState::index_for_x(me.column, term_rect.width as u16, &labels);
+            let idx = crate::ui::menu_model::MenuState::index_for_x(me.column, term_rect.width, &labels);
             if idx == app.menu_index {
                 // Map the click within the header area to a submenu row index
                 // (row offset inside the header area). If the submenu vector
@@ -59,7 +59,7 @@ pub fn handle_mouse(app: &mut App, me: MouseEvent, term_rect: Rect) -> Result<bo
 
     // Menu bar row
     if me.row >= chunks[0].y && me.row < chunks[0].y + chunks[0].height {
-        let width = term_rect.width as u16;
+        let width = term_rect.width;
         let labels = menu::menu_labels();
         if !labels.is_empty() {
             // Map the click X coordinate to a label index using the model helper.
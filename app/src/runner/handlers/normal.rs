@@ -1,12 +1,9 @@
 use crate::app::{Action, App, InputKind, Mode, Side};
 use crate::errors;
+use crate::fs_op::symlink::LinkKind;
 use crate::input::KeyCode;
-use crate::runner::progress::{OperationDecision, ProgressUpdate};
+use crate::runner::job_queue::{Job, JobKind};
 use std::path::PathBuf;
-use fs_extra::copy_items;
-use fs_extra::dir::CopyOptions as FsCopyOptions;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc};
 
 /// Handle keys when the application is in the normal (default) mode.
 ///
@@ -29,7 +26,9 @@ pub fn handle_normal(app: &mut App, code: KeyCode, page_size: usize) -> anyhow::
         KeyCode::Enter if !app.menu_focused => handle_enter(app)?,
         KeyCode::Backspace => handle_go_up(app)?,
         KeyCode::Char('r') => handle_refresh(app)?,
-        KeyCode::Char('d') => handle_delete_prompt(app),
+        KeyCode::Char('d') | KeyCode::Delete => handle_delete_prompt(app, false),
+        KeyCode::Char('D') => handle_delete_prompt(app, true),
+        KeyCode::Char('X') => handle_shred_prompt(app),
         KeyCode::Char('c') => handle_copy_prompt(app),
         KeyCode::Char('m') => handle_move_prompt(app),
         KeyCode::Char('n') => {
@@ -39,6 +38,8 @@ pub fn handle_normal(app: &mut App, code: KeyCode, page_size: usize) -> anyhow::
             app.mode = Mode::Input { prompt: "New dir name:".to_string(), buffer: String::new(), kind: InputKind::NewDir };
         }
         KeyCode::Char('R') => handle_rename_prompt(app),
+        KeyCode::Char('E') => super::edit_names::open_edit_names(app),
+        KeyCode::Char('L') => handle_link_prompt(app),
         KeyCode::Char('s') => { app.sort = app.sort.next(); app.refresh()?; }
         KeyCode::Char('S') => { use crate::app::types::SortOrder::*; app.sort_order = match app.sort_order { Ascending => Descending, Descending => Ascending }; app.refresh()?; }
         KeyCode::Char(' ') => app.active_panel_mut().toggle_selection(),
@@ -101,8 +102,11 @@ pub fn handle_normal(app: &mut App, code: KeyCode, page_size: usize) -> anyhow::
         // Alternate keybinding to cycle panel mode
         KeyCode::Char('v') => { app.active_panel_mut().cycle_mode(); let _ = app.refresh(); },
         KeyCode::Char('t') => crate::ui::colors::toggle(),
+        KeyCode::Char('P') => crate::runner::handlers::pack::handle_pack_prompt(app),
+        KeyCode::Char('J') => crate::runner::handlers::jobs::open_jobs(app),
+        KeyCode::Char('T') => crate::runner::handlers::trash::open_trash(app),
         KeyCode::Char('?') => {
-            let content = "Keys:\n\nq: quit\nF1: toggle menu focus\nLeft/Right: menu navigation when focused\nEnter: open/activate\nBackspace: up\nd: delete\nc: copy\nm: move\nn/N: new file/dir\nR: rename\n/: quick filter (glob, empty to clear)\n:: inline command (e.g. toggle-preview; Tab completes, Up/Down history)\ns/S: sort (toggle desc)\nTab: switch panels\n?: show this help\n".to_string();
+            let content = "Keys:\n\nq: quit\nF1: toggle menu focus\nLeft/Right: menu navigation when focused\nEnter: open/activate\nBackspace: up\nd: move to trash\nD: delete permanently\nX: shred (overwrite, then delete)\nT: trash (restore, purge, empty)\nc: copy\nm: move\nn/N: new file/dir\nR: rename (batch rename when several are marked)\nE: edit the marked names in $EDITOR\nL: link into the other panel (symlink, relative symlink, hardlink)\nP: pack selection into an archive\nJ: jobs (pause, resume, cancel, retry, reorder)\n/: quick filter (glob, empty to clear)\n:: inline command (e.g. toggle-preview; Tab completes, Up/Down history)\ns/S: sort (toggle desc)\nTab: switch panels\n?: show this help\n".to_string();
             app.mode = Mode::Message { title: "Help".to_string(), content, buttons: vec!["OK".to_string()], selected: 0, actions: None };
         }
         KeyCode::Char('>') => app.active_panel_mut().preview_offset = app.active_panel_mut().preview_offset.saturating_add(5),
@@ -141,7 +145,7 @@ fn make_message_mode(title: &str, content: String) -> Mode {
 fn handle_enter(app: &mut App) -> anyhow::Result<()> {
     let panel = app.active_panel_mut();
     if panel.selected == 0 {
-        let prompt = format!("Change path (current: {}):", panel.cwd.display());
+        let prompt = format!("Change path (current: {}):", panel.vfs.display_path(&panel.cwd));
         app.mode = Mode::Input { prompt, buffer: String::new(), kind: InputKind::ChangePath };
         return Ok(());
     }
@@ -183,15 +187,97 @@ fn handle_refresh(app: &mut App) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Prompt the user to confirm deletion of the currently selected entry.
+/// Prompt the user to confirm deleting the marked entries (or the selected
+/// one): into the trash, or for good when `permanent` is set or the panel
+/// is not on the local filesystem (which has no trash). The prompt names
+/// the entry or counts the items, and sums them up (see `size_summary`).
 ///
 /// If there is no selected entry this is a no-op.
-fn handle_delete_prompt(app: &mut App) {
-    let panel = app.active_panel_mut();
-    if let Some(e) = panel.selected_entry() {
-        let msg = format!("Delete {}? (y/n)", e.name);
-        app.mode = Mode::Confirm { msg, on_yes: Action::DeleteSelected, selected: 0 };
+fn handle_delete_prompt(app: &mut App, permanent: bool) {
+    let panel = app.active_panel();
+    let local = panel.vfs.is_local();
+    let marked = panel.marked_entries();
+    let what = match marked.as_slice() {
+        [] => return,
+        [e] => e.name.clone(),
+        several => format!("{} items", several.len()),
+    };
+    let size = size_summary(&marked);
+    let (msg, on_yes) = if permanent || !local {
+        (format!("Delete {} ({}) permanently? (y/n)", what, size), Action::DeleteSelected)
+    } else {
+        (format!("Move {} ({}) to the trash? (y/n)", what, size), Action::TrashSelected)
+    };
+    app.mode = Mode::Confirm { msg, on_yes, selected: 0 };
+}
+
+/// Prompt the user to confirm shredding the marked entries (or the
+/// selected one), naming them, their size and the passes that will run.
+/// When the storage may keep the old data anyway the prompt says so.
+fn handle_shred_prompt(app: &mut App) {
+    let panel = app.active_panel();
+    let marked = panel.marked_entries();
+    let what = match marked.as_slice() {
+        [] => return,
+        [e] => e.name.clone(),
+        several => format!("{} items", several.len()),
+    };
+    if !panel.vfs.is_local() {
+        app.mode = make_message_mode("Shred", "Shredding is only available for local files".to_string());
+        return;
     }
+    let passes = app.settings.shred.method.passes(app.settings.shred.passes).len();
+    let mut msg = format!(
+        "Shred {} ({})? The data is overwritten {} time{} and cannot be recovered.",
+        what,
+        size_summary(&marked),
+        passes,
+        if passes == 1 { "" } else { "s" }
+    );
+    if let Some(reason) = marked.first().and_then(|e| crate::fs_op::shred::weak_spot(&e.path)) {
+        msg.push_str(&format!(" Warning: {}.", reason));
+    }
+    msg.push_str(" (y/n)");
+    app.mode = Mode::Confirm { msg, on_yes: Action::ShredSelected, selected: 0 };
+}
+
+/// The size of the files among `entries` as listed, plus how many
+/// directories there are: `3.0 KiB`, `1 directory`, `12 B + 2
+/// directories`. Directories are not walked, so the prompt shows at once
+/// even for big or remote trees; the job's progress covers what is inside.
+fn size_summary(entries: &[&crate::app::Entry]) -> String {
+    let size: u64 = entries.iter().filter(|e| !e.is_dir && e.kind != crate::app::EntryKind::Symlink).map(|e| e.size).sum();
+    let size = crate::ui::widgets::progress_bar::format_bytes(size);
+    match entries.iter().filter(|e| e.is_dir).count() {
+        0 => size,
+        dirs => {
+            let dirs = format!("{} director{}", dirs, if dirs == 1 { "y" } else { "ies" });
+            if entries.iter().all(|e| e.is_dir) { dirs } else { format!("{} + {}", size, dirs) }
+        }
+    }
+}
+
+/// Ask what kind of link to make for the marked entries (or the selected
+/// one) in the other panel's directory. Links need both panels local.
+fn handle_link_prompt(app: &mut App) {
+    let other = match app.active { Side::Left => &app.right, Side::Right => &app.left };
+    let panel = app.active_panel();
+    let what = match panel.marked_entries().as_slice() {
+        [] => return,
+        [e] => e.name.clone(),
+        several => format!("{} items", several.len()),
+    };
+    if !panel.vfs.is_local() || !other.vfs.is_local() {
+        app.mode = make_message_mode("Link", "Links can only be made between local directories".to_string());
+        return;
+    }
+    app.mode = Mode::Message {
+        title: "Link".to_string(),
+        content: format!("Link {} into {} as:", what, other.cwd.display()),
+        buttons: ["Symlink", "Relative symlink", "Hardlink", "Cancel"].iter().map(|b| b.to_string()).collect(),
+        selected: 0,
+        actions: Some(vec![Action::Link(LinkKind::Absolute), Action::Link(LinkKind::Relative), Action::Link(LinkKind::Hard)]),
+    };
 }
 
 /// Prompt the user for a destination path to copy the currently selected entry.
@@ -212,13 +298,15 @@ fn handle_move_prompt(app: &mut App) {
     }
 }
 
-/// Prompt the user to rename the currently selected entry.
+/// Prompt the user to rename the currently selected entry, or open the
+/// batch rename dialog when several entries are marked.
 fn handle_rename_prompt(app: &mut App) {
-    let panel = app.active_panel_mut();
-    if let Some(e) = panel.entries.get(panel.selected) {
-        let prompt = format!("Rename {} to:", e.name);
-        app.mode = Mode::Input { prompt, buffer: String::new(), kind: InputKind::Rename };
-    }
+    let prompt = match app.active_panel().marked_entries().as_slice() {
+        [] => return,
+        [e] => format!("Rename {} to:", e.name),
+        _ => return super::batch_rename::open_batch_rename(app),
+    };
+    app.mode = Mode::Input { prompt, buffer: String::new(), kind: InputKind::Rename };
 }
 
 /// Move selection to the last entry in the active panel (End key behaviour).
@@ -239,11 +327,16 @@ fn handle_end_key(app: &mut App) {
 fn handle_context_actions(app: &mut App) {
     let panel = app.active_panel();
     if let Some(e) = panel.selected_entry() {
-        let options = if app.settings.context_actions.is_empty() {
+        let mut options = if app.settings.context_actions.is_empty() {
             vec!["View".to_string(), "Edit".to_string(), "Permissions".to_string(), "Cancel".to_string()]
         } else {
             app.settings.context_actions.clone()
         };
+        // Symlinks can also be pointed elsewhere; offer it before Cancel.
+        if crate::vfs::transfer::is_symlink_mode(e.unix_mode) && !options.iter().any(|o| o == "Edit symlink") {
+            let at = options.iter().position(|o| o == "Cancel").unwrap_or(options.len());
+            options.insert(at, "Edit symlink".to_string());
+        }
         app.mode = Mode::ContextMenu { title: format!("Actions: {}", e.name), options, selected: 0, path: e.path.clone() };
     } else {
         app.mode = make_message_mode("Actions", "No entry selected".to_string());
@@ -256,15 +349,8 @@ fn handle_context_actions(app: &mut App) {
 /// 1. If the panel has multi-selections, return all selected entries.
 /// 2. Otherwise return the single selected entry (if any).
 /// 3. Otherwise return an empty vector.
-fn collect_src_paths(app: &App) -> Vec<PathBuf> {
-    let panel = app.active_panel();
-    if !panel.selections.is_empty() {
-        panel.selections.iter().filter_map(|&idx| panel.entries.get(idx).map(|e| e.path.clone())).collect()
-    } else if let Some(si) = app.selected_index() {
-        panel.entries.get(si).map(|e| vec![e.path.clone()]).unwrap_or_default()
-    } else {
-        Vec::new()
-    }
+pub(crate) fn collect_src_paths(app: &App) -> Vec<PathBuf> {
+    app.active_panel().marked_entries().iter().map(|e| e.path.clone()).collect()
 }
 
 /// Start a background file operation (copy or move).
@@ -272,10 +358,10 @@ fn collect_src_paths(app: &App) -> Vec<PathBuf> {
 /// This function:
 /// - collects source paths using `collect_src_paths`;
 /// - determines the destination directory (the opposite panel's cwd);
-/// - sets up channels for progress (`op_progress_rx`) and conflict/decision events (`op_decision_tx`);
-/// - sets `op_cancel_flag` so the UI can cancel the running operation;
-/// - updates `app.mode` to `Mode::Progress` so the UI shows progress;
-/// - spawns the appropriate background worker thread (`spawn_copy_worker` or `spawn_move_worker`).
+/// - submits a copy or move job to `app.jobs` (see `runner::job_queue`);
+/// - follows the new job (`jobs::follow`): `op_progress_rx`, `op_decision_tx`
+///   and `op_cancel_flag` point at it and `app.mode` becomes
+///   `Mode::Progress`, until `b` sends it to the background.
 ///
 /// Progress protocol (the `ProgressUpdate` messages):
 ///
@@ -284,21 +370,21 @@ fn collect_src_paths(app: &App) -> Vec<PathBuf> {
 /// - `message: Option<String>` — a short human-readable status message suitable for displaying in the progress UI.
 /// - `done: bool` — whether the operation has finished (successfully, with error, or cancelled).
 /// - `error: Option<String>` — when present and `done == true` indicates the operation ended with an error.
-/// - `conflict: Option<PathBuf>` — when present this indicates the worker encountered an existing target and awaits a user decision.
+/// - `conflict: Option<Conflict>` — when present this indicates the worker encountered an existing target and awaits a user decision; it carries both paths and their metadata.
 ///
 /// Typical sequence (example):
 ///
 /// ```text
 /// { processed: 0, total: 3, message: "Starting", done: false }
 /// { processed: 1, total: 3, message: "Copied /src/a.txt", done: false }
-/// { processed: 1, total: 3, message: "Conflict", conflict: Some(/dst/b.txt), done: false }
+/// { processed: 1, total: 3, message: "Conflict", conflict: Some(/src/b.txt -> /dst/b.txt), done: false }
 /// <-- UI sends an OperationDecision (e.g. Overwrite) via `op_decision_tx` -->
 /// { processed: 2, total: 3, message: "Copied /src/b.txt", done: false }
 /// { processed: 3, total: 3, message: "Completed", done: true }
 /// ```
 ///
 /// The UI should display `message` for quick feedback, render a conflict dialog
-/// when `conflict` is Some(path) and send an `OperationDecision` down the
+/// when `conflict` is Some(..) and send an `OperationDecision` down the
 /// decision channel. When `done == true` the UI should stop tracking progress
 /// and show `error` if present.
 fn handle_operation_start(app: &mut App, op: Operation) -> anyhow::Result<()> {
@@ -306,153 +392,46 @@ fn handle_operation_start(app: &mut App, op: Operation) -> anyhow::Result<()> {
     if src_paths.is_empty() { return Ok(()); }
 
     let dst_dir = match app.active { Side::Left => app.right.cwd.clone(), Side::Right => app.left.cwd.clone() };
+    let src_vfs = app.active_panel().vfs.clone();
+    let dst_vfs = match app.active { Side::Left => app.right.vfs.clone(), Side::Right => app.left.vfs.clone() };
 
-    let (tx, rx) = mpsc::channel();
-    let (dec_tx, dec_rx) = mpsc::channel::<OperationDecision>();
-    app.op_decision_tx = Some(dec_tx.clone());
-    app.op_progress_rx = Some(rx);
-    let total = src_paths.len();
-    app.mode = Mode::Progress { title: match op { Operation::Copy => "Copying".to_string(), Operation::Move => "Moving".to_string() }, processed: 0, total, message: "Starting".to_string(), cancelled: false };
-
-    let cancel_flag = Arc::new(AtomicBool::new(false));
-    app.op_cancel_flag = Some(cancel_flag.clone());
-
-    match op {
-        Operation::Copy => spawn_copy_worker(src_paths, dst_dir, tx, dec_rx, cancel_flag),
-        Operation::Move => spawn_move_worker(src_paths, dst_dir, tx, dec_rx, cancel_flag),
-    }
+    let kind = match op {
+        Operation::Copy => JobKind::Copy { sources: src_paths, dst_dir },
+        Operation::Move => JobKind::Move { sources: src_paths, dst_dir },
+    };
+    let ticket = app.jobs.submit(Job::on(kind, src_vfs, dst_vfs).with_error_policy(app.settings.error_policy).with_conflict_policy(app.settings.conflict_policy).with_verify(app.settings.verify_copies));
+    crate::runner::handlers::jobs::follow(app, ticket);
 
     Ok(())
 }
 
-/// Spawn a background thread that performs copy operations.
-///
-/// The worker sends `ProgressUpdate` messages over `tx` to report per-item
-/// progress and final completion. Conflict resolution decisions are read
-/// synchronously from `dec_rx` (the UI thread sends `OperationDecision`
-/// values when the user chooses). A shared `cancel_flag` can be set by the
-/// UI to request cancellation; the worker will observe it and abort.
-///
-/// Implementation notes:
-/// - Attempts a fast-path batch copy with `fs_extra::copy_items` when no
-///   destination names already exist, falling back to per-item handling if
-///   conflicts are possible.
-/// - Preserves metadata after a successful batch copy via
-///   `crate::fs_op::metadata::preserve_all_metadata`.
-fn spawn_copy_worker(src_paths: Vec<PathBuf>, dst_dir: PathBuf, tx: mpsc::Sender<ProgressUpdate>, dec_rx: mpsc::Receiver<OperationDecision>, cancel_flag: Arc<AtomicBool>) {
-    std::thread::spawn(move || {
-        let total = src_paths.len();
-        // Fast-path: if none of the targets already exist, use batch copy.
-        let any_conflict = src_paths.iter().any(|src| src.file_name().map(|fname| dst_dir.join(fname).exists()).unwrap_or(false));
-
-        if !any_conflict {
-            let mut options = FsCopyOptions::new();
-            options.copy_inside = false;
-            options.overwrite = false;
-            options.buffer_size = 64 * 1024;
-            match copy_items(&src_paths, &dst_dir, &options) {
-                Ok(_) => {
-                    for src in &src_paths {
-                        if let Some(fname) = src.file_name() {
-                            let target = dst_dir.join(fname);
-                            let _ = crate::fs_op::metadata::preserve_all_metadata(src, &target);
-                        }
-                    }
-                    for (i, src) in src_paths.iter().enumerate() {
-                        let _ = tx.send(ProgressUpdate { processed: i + 1, total, message: Some(format!("Copied {}", src.display())), done: false, error: None, conflict: None });
-                    }
-                    let _ = tx.send(ProgressUpdate { processed: total, total, message: Some("Completed".to_string()), done: true, error: None, conflict: None });
-                    return;
-                }
-                Err(e) => {
-                    let _ = tx.send(ProgressUpdate { processed: 0, total, message: Some(format!("Error: {}", e)), done: true, error: Some(format!("{}", e)), conflict: None });
-                    return;
-                }
-            }
-        }
-
-        // Per-item handling when conflicts may occur.
-        let mut overwrite_all = false;
-        let mut skip_all = false;
-        for (i, src) in src_paths.into_iter().enumerate() {
-            if cancel_flag.load(Ordering::SeqCst) {
-                let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Cancelled".to_string()), done: true, error: Some("Cancelled".to_string()), conflict: None });
-                return;
-            }
-            let target = src.file_name().map(|f| dst_dir.join(f)).unwrap_or_else(|| dst_dir.clone());
-
-            if target.exists() {
-                if skip_all {
-                    let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {}", src.display())), done: false, error: None, conflict: None });
-                    continue;
-                }
-                if !overwrite_all {
-                    let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Conflict".to_string()), done: false, error: None, conflict: Some(target.clone()) });
-                    match dec_rx.recv() {
-                        Ok(OperationDecision::Cancel) => { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Cancelled by user".to_string()), done: true, error: Some("Cancelled by user".to_string()), conflict: None }); return; }
-                        Ok(OperationDecision::Skip) => { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {}", src.display())), done: false, error: None, conflict: None }); continue; }
-                        Ok(OperationDecision::SkipAll) => { skip_all = true; let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {} (all)", src.display())), done: false, error: None, conflict: None }); continue; }
-                        Ok(OperationDecision::OverwriteAll) => { overwrite_all = true; }
-                        Ok(OperationDecision::Overwrite) => {}
-                        Err(_) => { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Decision channel closed".to_string()), done: true, error: Some("Decision channel closed".to_string()), conflict: None }); return; }
-                    }
-                }
-                let _ = if target.is_dir() { std::fs::remove_dir_all(&target) } else { std::fs::remove_file(&target) };
-            }
-
-            let res = if src.is_dir() {
-                crate::fs_op::copy::copy_recursive(&src, &target)
-            } else if let Err(e) = crate::fs_op::helpers::ensure_parent_exists(&target) {
-                Err(e)
-            } else {
-                crate::fs_op::helpers::atomic_copy_file(&src, &target).map(|_| ())
-            };
-            if let Err(e) = res { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Error: {}", e)), done: true, error: Some(format!("{}", e)), conflict: None }); return; }
-            let _ = tx.send(ProgressUpdate { processed: i + 1, total, message: Some(format!("Copied {}", src.display())), done: false, error: None, conflict: None });
+/// Delete the marked entries (or the selected one). A single file goes at
+/// once; anything bigger — several items or a directory — runs as a delete
+/// job followed in the progress dialog, like copies and moves.
+pub(crate) fn start_delete(app: &mut App) -> Result<(), crate::fs_op::error::FsOpError> {
+    let marked = app.active_panel().marked_entries();
+    if !matches!(marked.as_slice(), [e] if !e.is_dir) {
+        let targets: Vec<PathBuf> = marked.iter().map(|e| e.path.clone()).collect();
+        if !targets.is_empty() {
+            let vfs = app.active_panel().vfs.clone();
+            let ticket = app.jobs.submit(Job::on(JobKind::Delete { targets }, vfs.clone(), vfs).with_error_policy(app.settings.error_policy));
+            crate::runner::handlers::jobs::follow(app, ticket);
         }
-        let _ = tx.send(ProgressUpdate { processed: total, total, message: Some("Completed".to_string()), done: true, error: None, conflict: None });
-    });
+        return Ok(());
+    }
+    app.delete_selected()
 }
 
-/// Spawn a background thread that performs move (rename) operations.
-///
-/// The worker semantics mirror `spawn_copy_worker` but use
-/// `atomic_rename_or_copy` to attempt a rename and fall back to copying
-/// when necessary. Progress, conflict decisions, and cancellation behave
-/// the same as for the copy worker.
-fn spawn_move_worker(src_paths: Vec<PathBuf>, dst_dir: PathBuf, tx: mpsc::Sender<ProgressUpdate>, dec_rx: mpsc::Receiver<OperationDecision>, cancel_flag: Arc<AtomicBool>) {
-    std::thread::spawn(move || {
-        let mut overwrite_all = false;
-        let mut skip_all = false;
-        let total = src_paths.len();
-        for (i, src) in src_paths.into_iter().enumerate() {
-            if cancel_flag.load(Ordering::SeqCst) { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Cancelled".to_string()), done: true, error: Some("Cancelled".to_string()), conflict: None }); return; }
-            let target = src.file_name().map(|f| dst_dir.join(f)).unwrap_or_else(|| dst_dir.clone());
-
-            if target.exists() {
-                if skip_all { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {}", src.display())), done: false, error: None, conflict: None }); continue; }
-                if !overwrite_all {
-                    let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Conflict".to_string()), done: false, error: None, conflict: Some(target.clone()) });
-                    match dec_rx.recv() {
-                        Ok(OperationDecision::Cancel) => { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Cancelled by user".to_string()), done: true, error: Some("Cancelled by user".to_string()), conflict: None }); return; }
-                        Ok(OperationDecision::Skip) => { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {}", src.display())), done: false, error: None, conflict: None }); continue; }
-                        Ok(OperationDecision::SkipAll) => { skip_all = true; let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {} (all)", src.display())), done: false, error: None, conflict: None }); continue; }
-                        Ok(OperationDecision::OverwriteAll) => { overwrite_all = true; }
-                        Ok(OperationDecision::Overwrite) => {}
-                        Err(_) => { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some("Decision channel closed".to_string()), done: true, error: Some("Decision channel closed".to_string()), conflict: None }); return; }
-                    }
-                }
-                let _ = if target.is_dir() { std::fs::remove_dir_all(&target) } else { std::fs::remove_file(&target) };
-            }
-
-            let res = if let Err(e) = crate::fs_op::helpers::ensure_parent_exists(&target) {
-                Err(e)
-            } else {
-                crate::fs_op::helpers::atomic_rename_or_copy(&src, &target).map(|_| ())
-            };
-            if let Err(e) = res { let _ = tx.send(ProgressUpdate { processed: i, total, message: Some(format!("Error: {}", e)), done: true, error: Some(format!("{}", e)), conflict: None }); return; }
-            let _ = tx.send(ProgressUpdate { processed: i + 1, total, message: Some(format!("Moved {}", src.display())), done: false, error: None, conflict: None });
-        }
-        let _ = tx.send(ProgressUpdate { processed: total, total, message: Some("Completed".to_string()), done: true, error: None, conflict: None });
-    });
+/// Shred the marked entries (or the selected one) in a job followed in the
+/// progress dialog, with the passes from the `shred` settings.
+pub(crate) fn start_shred(app: &mut App) -> Result<(), crate::fs_op::error::FsOpError> {
+    let targets: Vec<PathBuf> = app.active_panel().marked_entries().iter().map(|e| e.path.clone()).collect();
+    if targets.is_empty() {
+        return Ok(());
+    }
+    let vfs = app.active_panel().vfs.clone();
+    let kind = JobKind::Shred { targets, method: app.settings.shred.method, passes: app.settings.shred.passes };
+    let ticket = app.jobs.submit(Job::on(kind, vfs.clone(), vfs).with_error_policy(app.settings.error_policy));
+    crate::runner::handlers::jobs::follow(app, ticket);
+    Ok(())
 }
@@ -0,0 +1,143 @@
+//! Pack action: create an archive from the selection.
+//!
+//! `P` prompts for an archive name in the other panel's directory; the
+//! suffix picks the format (see `vfs::pack::PackFormat`). Packing runs on a
+//! background thread that reports byte progress through `op_progress_rx`,
+//! asks before replacing an existing archive via `op_decision_tx` and stops
+//! when `op_cancel_flag` is set.
+
+use crate::app::{App, InputKind, Mode, Side};
+use crate::errors;
+use crate::fs_op::error::FsOpError;
+use crate::runner::handlers::normal::collect_src_paths;
+use crate::fs_op::helpers::free_numbered_path;
+use crate::runner::progress::{ByteProgress, Conflict, ConflictPolicy, OperationDecision, ProgressUpdate, Throughput};
+use crate::vfs::pack::{pack, PackFormat, PackOptions};
+use crate::vfs::Vfs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+/// Prompt for the name of the archive to create from the selection (or the
+/// entry under the cursor). The buffer is prefilled with a `.zip` name.
+pub fn handle_pack_prompt(app: &mut App) {
+    let src_paths = collect_src_paths(app);
+    let Some(first) = src_paths.first() else { return };
+    let (what, stem) = if src_paths.len() == 1 {
+        let name = first.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let stem = first.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        (name, stem)
+    } else {
+        let cwd = &app.active_panel().cwd;
+        let stem = cwd.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| "archive".to_string());
+        (format!("{} items", src_paths.len()), stem)
+    };
+    let prompt = format!("Pack {} as ({}):", what, PackFormat::EXTENSIONS.join(" "));
+    app.mode = Mode::Input { prompt, buffer: format!("{}.zip", stem), kind: InputKind::Pack };
+}
+
+/// Start packing the selection into `name`, resolved against the other
+/// panel's directory and backend. Unknown suffixes are reported without
+/// starting a job.
+pub fn start_pack(app: &mut App, name: &str) {
+    let name = name.trim();
+    let src_paths = collect_src_paths(app);
+    if src_paths.is_empty() || name.is_empty() {
+        return;
+    }
+    if PackFormat::from_name(name).is_none() {
+        let content = format!("Unknown archive type '{}': use one of {}", name, PackFormat::EXTENSIONS.join(", "));
+        app.mode = Mode::Message { title: "Error".to_string(), content, buttons: vec!["OK".to_string()], selected: 0, actions: None };
+        return;
+    }
+
+    let other = match app.active { Side::Left => &app.right, Side::Right => &app.left };
+    let target = other.cwd.join(name);
+    let dst_vfs = other.vfs.clone();
+    let src_vfs = app.active_panel().vfs.clone();
+    let options = app.settings.pack;
+
+    let (tx, rx) = mpsc::channel();
+    let (dec_tx, dec_rx) = mpsc::channel::<OperationDecision>();
+    app.op_decision_tx = Some(dec_tx);
+    app.op_progress_rx = Some(rx);
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    app.op_cancel_flag = Some(cancel_flag.clone());
+    app.mode = Mode::Progress { title: "Packing".to_string(), processed: 0, total: 0, message: "Starting".to_string(), cancelled: false, bytes: None };
+
+    spawn_pack_worker(src_vfs, dst_vfs, src_paths, target, options, tx, dec_rx, cancel_flag);
+}
+
+/// Spawn the background thread that writes the archive.
+///
+/// Follows the copy worker's protocol, except that progress while packing
+/// is reported in bytes only; the final update carries the number of
+/// members written. An existing archive at `target` is reported as a
+/// conflict before anything is written.
+#[allow(clippy::too_many_arguments)]
+fn spawn_pack_worker(
+    src_vfs: Arc<dyn Vfs>,
+    dst_vfs: Arc<dyn Vfs>,
+    src_paths: Vec<PathBuf>,
+    target: PathBuf,
+    options: PackOptions,
+    tx: mpsc::Sender<ProgressUpdate>,
+    dec_rx: mpsc::Receiver<OperationDecision>,
+    cancel_flag: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let mut target = target;
+        if dst_vfs.stat(&target).is_ok() {
+            let conflict = Conflict::target_only(target.clone(), dst_vfs.stat(&target).ok());
+            let _ = tx.send(ProgressUpdate::conflict(conflict, 0, 0, Some("Conflict".to_string())));
+            let policy = match dec_rx.recv() {
+                Ok(OperationDecision::Retry) => Some(ConflictPolicy::Overwrite),
+                Ok(decision) => decision.conflict_policy().map(|(policy, _)| policy),
+                Err(_) => None,
+            };
+            let exists = |p: &Path| dst_vfs.stat(p).is_ok();
+            match policy {
+                None => {
+                    let _ = tx.send(ProgressUpdate::done_with_error(0, 0, Some("Cancelled by user".to_string())));
+                    return;
+                }
+                Some(ConflictPolicy::Skip) => {
+                    let message = format!("Skipped {}", dst_vfs.display_path(&target));
+                    let _ = tx.send(ProgressUpdate { processed: 0, total: 0, message: Some(message), done: true, error: None, conflict: None, bytes: None, failed: None });
+                    return;
+                }
+                Some(ConflictPolicy::RenameNew) => target = free_numbered_path(&target, false, exists),
+                Some(ConflictPolicy::RenameExisting) => {
+                    if let Err(e) = dst_vfs.rename(&target, &free_numbered_path(&target, false, exists)) {
+                        let msg = errors::render_fsop_error(&FsOpError::from_io_at(Path::new(&target), e), Some(&dst_vfs.display_path(&target)), None, None);
+                        let _ = tx.send(ProgressUpdate::done_with_error(0, 0, Some(msg)));
+                        return;
+                    }
+                }
+                // The archive is rebuilt from scratch: the remaining
+                // policies all replace it.
+                Some(_) => {}
+            }
+        }
+        let display = dst_vfs.display_path(&target);
+
+        let mut throughput = Throughput::new();
+        let mut on_progress = |done: u64, total: u64, member: &str| {
+            let rate = throughput.sample(done);
+            let bytes = ByteProgress { done, total, rate, eta_secs: Throughput::eta(rate, total.saturating_sub(done)), ..Default::default() };
+            let _ = tx.send(ProgressUpdate { processed: 0, total: 0, message: Some(format!("Packing {}", member)), done: false, error: None, conflict: None, bytes: Some(bytes), failed: None });
+        };
+        match pack(src_vfs.as_ref(), &src_paths, dst_vfs.as_ref(), &target, &options, &cancel_flag, &mut on_progress) {
+            Ok(members) => {
+                let _ = tx.send(ProgressUpdate { processed: members, total: members, message: Some(format!("Packed {}", display)), done: true, error: None, conflict: None, bytes: None, failed: None });
+            }
+            Err(_) if cancel_flag.load(Ordering::SeqCst) => {
+                let _ = tx.send(ProgressUpdate::done_with_error(0, 0, Some("Cancelled".to_string())));
+            }
+            Err(e) => {
+                let msg = errors::render_fsop_error(&FsOpError::from_io_at(Path::new(&target), e), Some(&display), None, None);
+                let _ = tx.send(ProgressUpdate::done_with_error(0, 0, Some(msg)));
+            }
+        }
+    });
+}
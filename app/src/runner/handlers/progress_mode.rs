@@ -14,6 +14,11 @@ use std::sync::atomic::Ordering;
 /// Returns `Ok(false)` to indicate no immediate screen redraw request is
 /// required by the caller.
 pub fn handle_progress(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    if let KeyCode::Char('b') = code {
+        // Keep the job running but return to browsing.
+        crate::runner::handlers::jobs::background(app);
+        return Ok(false);
+    }
     if let KeyCode::Esc = code {
         if let Some(flag) = app.op_cancel_flag.take() {
             flag.store(true, Ordering::SeqCst);
@@ -64,6 +69,8 @@ mod tests {
             drag_start: None,
             drag_current: None,
             drag_button: None,
+            jobs: Default::default(),
+            op_job: None,
         };
 
         // Prepare a cancel flag shared with the handler.
@@ -77,6 +84,7 @@ mod tests {
             total: 10,
             message: "Working".into(),
             cancelled: false,
+            bytes: None,
         };
 
         // Invoke handler with Escape.
@@ -123,6 +131,8 @@ mod tests {
             drag_start: None,
             drag_current: None,
             drag_button: None,
+            jobs: Default::default(),
+            op_job: None,
         };
 
         // Prepare a cancel flag and set it, but keep it attached to app.
@@ -136,6 +146,7 @@ mod tests {
             total: 20,
             message: "Working".into(),
             cancelled: false,
+            bytes: None,
         };
 
         // Invoke handler with a non-Esc key (Char)
@@ -183,6 +194,8 @@ mod tests {
             drag_start: None,
             drag_current: None,
             drag_button: None,
+            jobs: Default::default(),
+            op_job: None,
         };
 
         // Put the app into Progress mode with initial values and no flag.
@@ -192,6 +205,7 @@ mod tests {
             total: 30,
             message: "Working".into(),
             cancelled: false,
+            bytes: None,
         };
 
         // Invoke handler with a non-Esc key (Enter)
@@ -0,0 +1,95 @@
+//! Trash view.
+//!
+//! `d` moves the selected entry of a local panel to the trash (see
+//! `fs_op::trash`) and `D` deletes it for good. `T` lists what is in the
+//! trash, newest first; from there an item can be restored to where it
+//! came from, deleted permanently, or the whole trash emptied.
+
+use crate::app::types::Action;
+use crate::app::{App, Mode};
+use crate::fs_op::error::FsOpError;
+use crate::fs_op::trash::{Trash, TrashedItem};
+use crate::input::KeyCode;
+
+/// Open the trash view with the most recently deleted item selected.
+pub fn open_trash(app: &mut App) {
+    app.mode = Mode::Trash { items: Trash::user().list(), selected: 0 };
+}
+
+/// Handle input while the trash view is shown.
+pub fn handle_trash(app: &mut App, code: KeyCode) -> anyhow::Result<bool> {
+    let Mode::Trash { items, selected } = &app.mode else { return Ok(false) };
+    let current = items.get(*selected).cloned();
+    let mut selected = *selected;
+    let last = items.len().saturating_sub(1);
+    let empty = items.is_empty();
+    match code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => {
+            app.mode = Mode::Normal;
+            return Ok(false);
+        }
+        KeyCode::Up => selected = selected.saturating_sub(1),
+        KeyCode::Down => selected = (selected + 1).min(last),
+        KeyCode::Enter | KeyCode::Char('r') => {
+            if let Some(item) = current {
+                if let Err(e) = Trash::user().restore(&item) {
+                    show_error(app, &format!("Could not restore {}: {}", item.original.display(), e));
+                    return Ok(false);
+                }
+                app.refresh()?;
+                reopen(app, selected);
+                return Ok(true);
+            }
+        }
+        KeyCode::Char('x') | KeyCode::Delete => {
+            if let Some(item) = current {
+                let msg = format!("Delete {} permanently? (y/n)", item.original.display());
+                app.mode = Mode::Confirm { msg, on_yes: Action::PurgeTrashed(item), selected: 0 };
+                return Ok(false);
+            }
+        }
+        KeyCode::Char('E') if !empty => {
+            let msg = "Empty the trash? Everything in it is deleted permanently. (y/n)".to_string();
+            app.mode = Mode::Confirm { msg, on_yes: Action::EmptyTrash, selected: 0 };
+            return Ok(false);
+        }
+        _ => {}
+    }
+    if let Mode::Trash { selected: s, .. } = &mut app.mode {
+        *s = selected;
+    }
+    Ok(false)
+}
+
+/// Delete `item` from the trash for good and go back to the trash view.
+pub fn purge(app: &mut App, item: &TrashedItem) -> Result<(), FsOpError> {
+    Trash::user().purge(item).map_err(|e| FsOpError::from_io_at(item.file_path(), e))?;
+    reopen(app, 0);
+    Ok(())
+}
+
+/// Delete everything in the trash and go back to the (now empty) trash
+/// view.
+pub fn empty(app: &mut App) -> Result<(), FsOpError> {
+    Trash::user().empty()?;
+    reopen(app, 0);
+    Ok(())
+}
+
+/// Show the trash view again with a fresh listing, keeping `selected`
+/// within it.
+fn reopen(app: &mut App, selected: usize) {
+    let items = Trash::user().list();
+    let selected = selected.min(items.len().saturating_sub(1));
+    app.mode = Mode::Trash { items, selected };
+}
+
+fn show_error(app: &mut App, content: &str) {
+    app.mode = Mode::Message {
+        title: "Error".to_string(),
+        content: content.to_string(),
+        buttons: vec!["OK".to_string()],
+        selected: 0,
+        actions: None,
+    };
+}
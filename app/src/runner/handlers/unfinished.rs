@@ -0,0 +1,123 @@
+//! Jobs left unfinished by a previous run.
+//!
+//! On start `offer_unfinished` lists what the job journal (see
+//! `runner::job_journal`) still holds and offers to resume, discard or
+//! inspect it. Resumed jobs go back on the queue with only their remaining
+//! items; the progress dialog follows the first of them while the others
+//! are detached on the queue.
+
+use crate::app::{Action, App, Mode};
+use crate::fs_op::error::FsOpError;
+use crate::runner::job_journal::JournalEntry;
+use crate::runner::job_queue::JobKind;
+use crate::ui::widgets::progress_bar::format_bytes;
+
+/// Show the "Unfinished jobs" prompt when the journal holds anything.
+pub fn offer_unfinished(app: &mut App) {
+    let entries = app.jobs.unfinished();
+    if entries.is_empty() {
+        return;
+    }
+    let jobs = if entries.len() == 1 { "1 job".to_string() } else { format!("{} jobs", entries.len()) };
+    let mut content = format!("{} did not finish last time:\n", jobs);
+    for entry in &entries {
+        content.push_str(&format!("\n  {}", summary(entry)));
+    }
+    app.mode = Mode::Message {
+        title: "Unfinished jobs".to_string(),
+        content,
+        buttons: vec!["Resume".to_string(), "Discard".to_string(), "Inspect".to_string(), "Later".to_string()],
+        selected: 0,
+        actions: Some(vec![Action::ResumeJobs, Action::DiscardJobs, Action::InspectJobs]),
+    };
+}
+
+/// List every item of every unfinished job with its state.
+pub fn inspect_unfinished(app: &mut App) -> Result<(), FsOpError> {
+    let entries = app.jobs.unfinished();
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let content = entries.iter().map(details).collect::<Vec<_>>().join("\n\n");
+    app.mode = Mode::Message {
+        title: "Unfinished jobs".to_string(),
+        content,
+        buttons: vec!["Resume".to_string(), "Discard".to_string(), "Later".to_string()],
+        selected: 0,
+        actions: Some(vec![Action::ResumeJobs, Action::DiscardJobs]),
+    };
+    Ok(())
+}
+
+/// Put every unfinished job back on the queue and follow the first one.
+pub fn resume_unfinished(app: &mut App) -> Result<(), FsOpError> {
+    let mut first = true;
+    for entry in app.jobs.unfinished() {
+        let ticket = app.jobs.resume_unfinished(&entry);
+        if !first {
+            app.jobs.detach(ticket);
+            continue;
+        }
+        first = false;
+        crate::runner::handlers::jobs::follow(app, ticket);
+    }
+    Ok(())
+}
+
+/// Forget every unfinished job. Partly copied files are removed; items
+/// already finished stay where they are.
+pub fn discard_unfinished(app: &mut App) -> Result<(), FsOpError> {
+    for entry in app.jobs.unfinished() {
+        app.jobs.discard_unfinished(&entry)?;
+    }
+    Ok(())
+}
+
+/// One line per job, e.g. `Copy 3 items to /backup (1/3 done)`.
+pub fn summary(entry: &JournalEntry) -> String {
+    format!("{} ({}/{} done)", entry.kind.summary(), entry.finished(), entry.kind.len())
+}
+
+/// The summary followed by every item: `[x]` finished, `[>]` in progress
+/// (with how much of its current file was copied), `[ ]` not started.
+pub fn details(entry: &JournalEntry) -> String {
+    let items = match &entry.kind {
+        JobKind::Copy { sources, .. } | JobKind::Move { sources, .. } => sources.clone(),
+        JobKind::Delete { targets } | JobKind::Shred { targets, .. } => targets.clone(),
+        JobKind::Transfer { pairs, .. } => pairs.iter().map(|(src, _)| src.clone()).collect(),
+    };
+    let mut out = summary(entry);
+    for (i, item) in items.iter().enumerate() {
+        let mark = if entry.done.get(i).copied().unwrap_or(false) {
+            "x"
+        } else if entry.current == Some(i) {
+            ">"
+        } else {
+            " "
+        };
+        out.push_str(&format!("\n  [{}] {}", mark, item.display()));
+        if let (">", Some(partial)) = (mark, &entry.partial) {
+            out.push_str(&format!("\n      {} of {} copied", format_bytes(partial.offset), partial.src.display()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::job_journal::PartialFile;
+
+    #[test]
+    fn details_mark_finished_current_and_pending_items() {
+        let kind = JobKind::Copy { sources: vec!["/a".into(), "/b".into(), "/c".into()], dst_dir: "/dst".into() };
+        let mut entry = JournalEntry::new(1, kind);
+        entry.done[0] = true;
+        entry.current = Some(1);
+        entry.partial = Some(PartialFile { src: "/b/big.iso".into(), dst: "/dst/b/big.iso".into(), offset: 3 * 1024 * 1024 });
+        assert_eq!(
+            details(&entry),
+            "Copy 3 items to /dst (1/3 done)\n  [x] /a\n  [>] /b\n      3.0 MiB of /b/big.iso copied\n  [ ] /c"
+        );
+    }
+}
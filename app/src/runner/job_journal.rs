@@ -0,0 +1,204 @@
+//! On-disk journal of queued jobs, so work interrupted by a crash, a kill
+//! or quitting the app can be offered again on the next start.
+//!
+//! Every local job gets one JSON file in the journal directory (by default
+//! `jobs/` under the user cache dir). The file records the job kind with
+//! its item list, which items are finished, the item in progress and how
+//! far into its current file the copy got. `JobQueue` rewrites the file as
+//! the job advances and deletes it once the job completes, fails or is
+//! cancelled; files left behind belong to jobs that never got that far.
+
+use crate::runner::job_queue::{ErrorPolicy, JobKind};
+use crate::runner::progress::ConflictPolicy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A file the job was copying when its journal entry was last written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialFile {
+    /// Source file being copied.
+    pub src: PathBuf,
+    /// Final destination; data is written to `helpers::partial_path(dst)`.
+    pub dst: PathBuf,
+    /// Bytes of the file copied at the time of the write.
+    pub offset: u64,
+}
+
+/// Journaled state of one job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Process that ran the job.
+    pub pid: u32,
+    /// Job id within that process.
+    pub job: u64,
+    /// Unix time the job was submitted.
+    pub started: u64,
+    pub kind: JobKind,
+    /// Per item of `kind`: finished (copied, moved, deleted or skipped).
+    pub done: Vec<bool>,
+    /// Item the job was working on.
+    pub current: Option<usize>,
+    pub partial: Option<PartialFile>,
+    /// What the job does when an item fails.
+    #[serde(default)]
+    pub error_policy: ErrorPolicy,
+    /// How the job settles existing targets.
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Whether the job verifies its copies.
+    #[serde(default)]
+    pub verify: bool,
+}
+
+impl JournalEntry {
+    /// Fresh entry for a job that has not started yet.
+    pub fn new(job: u64, kind: JobKind) -> Self {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let done = vec![false; kind.len()];
+        JournalEntry { pid: std::process::id(), job, started, kind, done, current: None, partial: None, error_policy: ErrorPolicy::default(), conflict_policy: ConflictPolicy::default(), verify: false }
+    }
+
+    fn file_name(&self) -> String {
+        format!("{}-{}.json", self.pid, self.job)
+    }
+
+    /// Number of finished items.
+    pub fn finished(&self) -> usize {
+        self.done.iter().filter(|d| **d).count()
+    }
+
+    /// The job reduced to the items still to do, and whether the first of
+    /// them was already in progress (and should continue rather than start
+    /// over).
+    pub fn remaining(&self) -> (JobKind, bool) {
+        let keep = |items: &[PathBuf]| items.iter().zip(&self.done).filter(|(_, d)| !**d).map(|(p, _)| p.clone()).collect::<Vec<_>>();
+        let kind = match &self.kind {
+            JobKind::Copy { sources, dst_dir } => JobKind::Copy { sources: keep(sources), dst_dir: dst_dir.clone() },
+            JobKind::Move { sources, dst_dir } => JobKind::Move { sources: keep(sources), dst_dir: dst_dir.clone() },
+            JobKind::Delete { targets } => JobKind::Delete { targets: keep(targets) },
+            JobKind::Shred { targets, method, passes } => JobKind::Shred { targets: keep(targets), method: *method, passes: *passes },
+            JobKind::Transfer { moving, pairs } => {
+                JobKind::Transfer { moving: *moving, pairs: pairs.iter().zip(&self.done).filter(|(_, d)| !**d).map(|(p, _)| p.clone()).collect() }
+            }
+        };
+        let in_progress = self.current.is_some_and(|c| self.done.iter().take(c).all(|d| *d) && !self.done.get(c).copied().unwrap_or(true));
+        (kind, in_progress)
+    }
+}
+
+/// Directory of journal files.
+#[derive(Debug, Clone)]
+pub struct JobJournal {
+    dir: PathBuf,
+}
+
+impl JobJournal {
+    /// Journal kept in `dir`, created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        JobJournal { dir: dir.into() }
+    }
+
+    /// Journal in the `jobs` directory of the user cache dir.
+    pub fn in_cache_dir() -> Self {
+        Self::new(crate::app::settings::user_cache_dir().join("jobs"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write `entry`, replacing its previous state atomically.
+    pub fn save(&self, entry: &JournalEntry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let data = serde_json::to_vec_pretty(entry).map_err(io::Error::other)?;
+        crate::fs_op::helpers::atomic_write(&self.dir.join(entry.file_name()), &data)
+    }
+
+    /// Forget `entry`. A missing file is not an error.
+    pub fn remove(&self, entry: &JournalEntry) -> io::Result<()> {
+        match fs::remove_file(self.dir.join(entry.file_name())) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Entries left by jobs that did not finish, oldest first. Entries of
+    /// this process and of other instances still running are left alone,
+    /// as are files that do not parse.
+    pub fn unfinished(&self) -> Vec<JournalEntry> {
+        let Ok(dir) = fs::read_dir(&self.dir) else { return Vec::new() };
+        let mut entries: Vec<JournalEntry> = dir
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+            .filter_map(|e| serde_json::from_slice(&fs::read(e.path()).ok()?).ok())
+            .filter(|e: &JournalEntry| e.pid != std::process::id() && !process_alive(e.pid))
+            .collect();
+        entries.sort_by_key(|e| (e.started, e.pid, e.job));
+        entries
+    }
+
+    /// Forget `entry` and delete the partial file it was writing. Items
+    /// already copied stay where they are.
+    pub fn discard(&self, entry: &JournalEntry) -> io::Result<()> {
+        if let Some(partial) = &entry.partial {
+            let _ = fs::remove_file(crate::fs_op::helpers::partial_path(&partial.dst));
+        }
+        self.remove(entry)
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists; EPERM means it does
+    // but belongs to someone else.
+    let Ok(pid) = libc::pid_t::try_from(pid) else { return false };
+    if pid <= 0 {
+        return false;
+    }
+    unsafe { libc::kill(pid, 0) == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM) }
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn entries_round_trip_and_reduce_to_remaining_items() {
+        let td = tempdir().unwrap();
+        let journal = JobJournal::new(td.path().join("jobs"));
+        let kind = JobKind::Copy { sources: vec!["/a".into(), "/b".into(), "/c".into()], dst_dir: "/dst".into() };
+        let mut entry = JournalEntry::new(7, kind);
+        // Pretend the job belongs to a process that is gone.
+        entry.pid = i32::MAX as u32;
+        entry.done[0] = true;
+        entry.current = Some(1);
+        entry.partial = Some(PartialFile { src: "/b".into(), dst: "/dst/b".into(), offset: 4096 });
+        journal.save(&entry).unwrap();
+
+        assert_eq!(journal.unfinished(), vec![entry.clone()]);
+        let (kind, in_progress) = entry.remaining();
+        assert_eq!(kind, JobKind::Copy { sources: vec!["/b".into(), "/c".into()], dst_dir: "/dst".into() });
+        assert!(in_progress);
+
+        journal.remove(&entry).unwrap();
+        assert!(journal.unfinished().is_empty());
+    }
+
+    #[test]
+    fn own_entries_are_not_offered() {
+        let td = tempdir().unwrap();
+        let journal = JobJournal::new(td.path());
+        journal.save(&JournalEntry::new(1, JobKind::Delete { targets: vec!["/x".into()] })).unwrap();
+        fs::write(td.path().join("garbage.json"), b"{").unwrap();
+        assert!(journal.unfinished().is_empty());
+    }
+}
@@ -1,19 +1,192 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc, Mutex};
+//! Background engine for copy, move, delete and shred jobs.
+//!
+//! Jobs are submitted to a `JobQueue` and run on a small pool of worker
+//! threads, so several operations can make progress at the same time. Each
+//! job reports through the same `ProgressUpdate`/`OperationDecision`
+//! protocol the progress and conflict dialogs already understand (see
+//! `handlers::normal::handle_operation_start`), and the queue keeps a
+//! `JobState` snapshot of every job for callers that only want to poll.
+//!
+//! Pause and cancel are checked between items and after every chunk of
+//! file data written, so a large file stops promptly rather than at the
+//! next file boundary.
+//!
+//! A queue created with `JobQueue::with_journal` also records local jobs in
+//! a `JobJournal`, so jobs cut short by a crash or by quitting can be
+//! resumed on the next start (see `JobQueue::unfinished`).
+//!
+//! Targets that already exist are settled by the job's `ConflictPolicy`:
+//! asking is the default, any other policy (overwrite, skip, keep both,
+//! overwrite if newer, ...) runs unattended. An "all" decision from the
+//! conflict dialog becomes the policy for the rest of the job.
+//!
+//! What happens when an item fails is set per job by its `ErrorPolicy`:
+//! stop the job, skip the item, or ask. Skipped items are collected in
+//! `JobState::failures`, and `JobQueue::retry_failed` submits a job that
+//! does just those items again.
+//!
+//! Jobs built `Job::with_verify` hash every copied file on both sides
+//! (`fs_op::checksum`) and treat a mismatch like any other failed item; a
+//! move removes its source only after the copy verified.
+
+use crate::fs_op::helpers::{CopyCheckpoint, FailureHook, SkipHook};
+use crate::fs_op::shred::{Pass, ShredMethod};
+use crate::runner::job_journal::{JobJournal, JournalEntry, PartialFile};
+use crate::runner::progress::{ByteProgress, Conflict, ConflictPolicy, FailedItem, OperationDecision, ProgressUpdate, Throughput};
+use crate::vfs::transfer::child_name;
+use crate::vfs::{LocalVfs, Vfs};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Maximum number of jobs running at the same time.
+const MAX_WORKERS: usize = 4;
+
+/// How often a paused job or a pending conflict re-checks its flags.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Minimum time between two byte-progress updates sent for one job.
+const BYTES_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Minimum time between two journal writes made while copying a file.
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Unique identifier for a queued job.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct JobId(u64);
 
-/// Kind of work the job queue can represent.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Kind of work the job queue can run.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum JobKind {
-    Copy { src: PathBuf, dst: PathBuf },
-    Move { src: PathBuf, dst: PathBuf },
-    Delete { target: PathBuf },
+    /// Copy each of `sources` into `dst_dir`, keeping their names.
+    Copy { sources: Vec<PathBuf>, dst_dir: PathBuf },
+    /// Move each of `sources` into `dst_dir`, keeping their names.
+    Move { sources: Vec<PathBuf>, dst_dir: PathBuf },
+    /// Remove each of `targets` (directories recursively).
+    Delete { targets: Vec<PathBuf> },
+    /// Overwrite and then remove each of `targets` (see `fs_op::shred`),
+    /// with `passes` passes of `method`. Local files only.
+    Shred { targets: Vec<PathBuf>, method: ShredMethod, passes: u32 },
+    /// Copy (or, with `moving`, move) each source to the target it is
+    /// paired with. Used to retry the failed items of another job.
+    Transfer { moving: bool, pairs: Vec<(PathBuf, PathBuf)> },
+}
+
+impl JobKind {
+    /// Title shown in the progress dialog while the job runs.
+    pub fn title(&self) -> &'static str {
+        match self {
+            JobKind::Copy { .. } | JobKind::Transfer { moving: false, .. } => "Copying",
+            JobKind::Move { .. } | JobKind::Transfer { moving: true, .. } => "Moving",
+            JobKind::Delete { .. } => "Deleting",
+            JobKind::Shred { .. } => "Shredding",
+        }
+    }
+
+    /// Name of the operation for failure reports: `"copy"`, `"move"`,
+    /// `"delete"` or `"shred"`.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            JobKind::Copy { .. } | JobKind::Transfer { moving: false, .. } => "copy",
+            JobKind::Move { .. } | JobKind::Transfer { moving: true, .. } => "move",
+            JobKind::Delete { .. } => "delete",
+            JobKind::Shred { .. } => "shred",
+        }
+    }
+
+    /// Number of top-level items the job acts on.
+    pub fn len(&self) -> usize {
+        match self {
+            JobKind::Copy { sources, .. } | JobKind::Move { sources, .. } => sources.len(),
+            JobKind::Delete { targets } | JobKind::Shred { targets, .. } => targets.len(),
+            JobKind::Transfer { pairs, .. } => pairs.len(),
+        }
+    }
+
+    /// Whether the job has nothing to do.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// One-line description, e.g. `Copy 3 items to /backup`.
+    pub fn summary(&self) -> String {
+        let items = |n: usize| format!("{} item{}", n, if n == 1 { "" } else { "s" });
+        match self {
+            JobKind::Copy { sources, dst_dir } => format!("Copy {} to {}", items(sources.len()), dst_dir.display()),
+            JobKind::Move { sources, dst_dir } => format!("Move {} to {}", items(sources.len()), dst_dir.display()),
+            JobKind::Delete { targets } => format!("Delete {}", items(targets.len())),
+            JobKind::Shred { targets, .. } => format!("Shred {}", items(targets.len())),
+            JobKind::Transfer { moving, pairs } => format!("{} {}", if *moving { "Move" } else { "Copy" }, items(pairs.len())),
+        }
+    }
+}
+
+/// What a job does when an item fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorPolicy {
+    /// Stop the job at the first failure.
+    #[default]
+    Abort,
+    /// Record the failure and carry on with the next item.
+    Skip,
+    /// Report the failure (`ProgressUpdate::failed`) and wait for a retry,
+    /// skip or cancel decision.
+    Ask,
+}
+
+/// A job together with the backends it reads from and writes to. For
+/// `Delete` and `Shred` only `src_vfs` is used.
+pub struct Job {
+    pub kind: JobKind,
+    pub src_vfs: Arc<dyn Vfs>,
+    pub dst_vfs: Arc<dyn Vfs>,
+    pub error_policy: ErrorPolicy,
+    /// How existing targets are settled; anything but `Ask` never blocks.
+    pub conflict_policy: ConflictPolicy,
+    /// Compare checksums of every copied file with its source.
+    pub verify: bool,
+    /// The first item was in progress when an earlier run was interrupted:
+    /// continue it in place instead of treating its target as a conflict.
+    resume: bool,
+    /// The file of that item the journal recorded as partly copied.
+    resume_from: Option<PartialFile>,
+}
+
+impl Job {
+    /// A job on the local filesystem.
+    pub fn local(kind: JobKind) -> Self {
+        let vfs: Arc<dyn Vfs> = Arc::new(LocalVfs::new());
+        Job { kind, src_vfs: vfs.clone(), dst_vfs: vfs, error_policy: ErrorPolicy::default(), conflict_policy: ConflictPolicy::default(), verify: false, resume: false, resume_from: None }
+    }
+
+    /// A job reading from `src_vfs` and writing to `dst_vfs`.
+    pub fn on(kind: JobKind, src_vfs: Arc<dyn Vfs>, dst_vfs: Arc<dyn Vfs>) -> Self {
+        Job { kind, src_vfs, dst_vfs, error_policy: ErrorPolicy::default(), conflict_policy: ConflictPolicy::default(), verify: false, resume: false, resume_from: None }
+    }
+
+    /// Set what the job does when an item fails.
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Settle existing targets with `conflict_policy` instead of asking.
+    pub fn with_conflict_policy(mut self, conflict_policy: ConflictPolicy) -> Self {
+        self.conflict_policy = conflict_policy;
+        self
+    }
+
+    /// Verify copied files against their sources (see `JobState::verified`).
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
 }
 
 /// Status for a job as observed by callers.
@@ -27,64 +200,365 @@ pub enum JobStatus {
     Failed(String),
 }
 
+impl JobStatus {
+    /// Short label for lists.
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "Queued",
+            JobStatus::Running => "Running",
+            JobStatus::Paused => "Paused",
+            JobStatus::Completed => "Done",
+            JobStatus::Cancelled => "Cancelled",
+            JobStatus::Failed(_) => "Failed",
+        }
+    }
+
+    /// Whether the job has finished and will not change again.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Cancelled | JobStatus::Failed(_))
+    }
+}
+
 /// Observable state for a job.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JobState {
     pub status: JobStatus,
     pub progress: u8, // 0..=100
+    /// Items finished so far.
+    pub processed: usize,
+    /// Items in the job.
+    pub total: usize,
+    /// Last progress message reported by the job.
+    pub message: String,
+    /// Byte counters, for jobs that copy data.
+    pub bytes: Option<ByteProgress>,
+    /// Items skipped after failing, in the order they failed.
+    pub failures: Vec<FailedItem>,
+    /// Files and bytes checked so far by a job that verifies its copies.
+    pub verified: Option<Verified>,
+    /// Entries left out of copied trees because they cannot be copied
+    /// (sockets and the like), with what kind of file each is.
+    pub skipped: Vec<(PathBuf, &'static str)>,
+    /// Reasons the job may not have done all it set out to, each listed
+    /// once; a shred on a copy-on-write filesystem, for example.
+    pub warnings: Vec<String>,
+}
+
+/// Running totals of a verifying job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verified {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Handles returned by `JobQueue::submit` for following a single job:
+/// its progress updates, the channel for conflict decisions and the flag
+/// that cancels it.
+pub struct JobTicket {
+    pub id: JobId,
+    pub progress: mpsc::Receiver<ProgressUpdate>,
+    pub decisions: mpsc::Sender<OperationDecision>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// Pause and cancel flags shared between the queue and a running job.
+struct Control {
+    cancel: Arc<AtomicBool>,
+    paused: AtomicBool,
 }
 
-enum WorkerMsg {
-    Enqueue(JobId, JobKind),
-    Shutdown,
+impl Control {
+    /// Block while the job is paused; fail once it has been cancelled.
+    fn checkpoint(&self) -> io::Result<()> {
+        loop {
+            if self.cancel.load(Ordering::SeqCst) {
+                return Err(io::Error::other("cancelled"));
+            }
+            if !self.paused.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+}
+
+/// A submitted job waiting for a worker.
+struct Queued {
+    id: JobId,
+    job: Job,
+    tx: mpsc::Sender<ProgressUpdate>,
+    decisions: mpsc::Receiver<OperationDecision>,
+    control: Arc<Control>,
+    entry: Option<JournalEntry>,
+}
+
+struct Record {
+    kind: JobKind,
+    /// Backends and options of the job, kept so it can be retried.
+    src_vfs: Arc<dyn Vfs>,
+    dst_vfs: Arc<dyn Vfs>,
+    error_policy: ErrorPolicy,
+    conflict_policy: ConflictPolicy,
+    verify: bool,
+    state: JobState,
+    control: Arc<Control>,
+    started: bool,
+}
+
+#[derive(Default)]
+struct Inner {
+    order: Vec<JobId>,
+    records: HashMap<JobId, Record>,
+    pending: VecDeque<Queued>,
+    /// Tickets handed to `JobQueue::detach` until someone attaches again.
+    detached: HashMap<JobId, JobTicket>,
+    workers: usize,
+    idle: usize,
+    shutdown: bool,
+}
+
+struct Shared {
+    inner: Mutex<Inner>,
+    wake: Condvar,
+    journal: Option<JobJournal>,
 }
 
-/// Minimal job queue with a dummy worker. Intended as scaffolding for future IO-bound work.
+/// Queue of file operations run by a pool of background workers.
+///
+/// Workers are started on demand, up to `MAX_WORKERS`; further jobs wait
+/// in submission order. Dropping the queue cancels everything still
+/// pending or running.
 pub struct JobQueue {
-    tx: mpsc::Sender<WorkerMsg>,
-    states: Arc<Mutex<HashMap<JobId, JobState>>>,
-    _handle: thread::JoinHandle<()>,
+    shared: Arc<Shared>,
 }
 
 impl JobQueue {
-    /// Create a new queue and spawn the background worker thread.
+    /// Create an empty queue. No threads are started until a job is submitted.
     pub fn new() -> Self {
-        let (tx, rx) = mpsc::channel::<WorkerMsg>();
-        let states: Arc<Mutex<HashMap<JobId, JobState>>> = Arc::new(Mutex::new(HashMap::new()));
-        let states_clone = Arc::clone(&states);
-        let handle = thread::spawn(move || worker_loop(rx, states_clone));
-        JobQueue { tx, states, _handle: handle }
+        Self::build(None)
     }
 
-    /// Enqueue a new job and return its id.
-    pub fn enqueue(&self, kind: JobKind) -> JobId {
+    /// Create an empty queue that records local jobs in `journal`.
+    pub fn with_journal(journal: JobJournal) -> Self {
+        Self::build(Some(journal))
+    }
+
+    fn build(journal: Option<JobJournal>) -> Self {
+        JobQueue { shared: Arc::new(Shared { inner: Mutex::new(Inner::default()), wake: Condvar::new(), journal }) }
+    }
+
+    /// Submit `job` and return the handles for following it.
+    pub fn submit(&self, job: Job) -> JobTicket {
         let id = JobId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
-        {
-            let mut st = self.states.lock().unwrap();
-            st.insert(id, JobState { status: JobStatus::Pending, progress: 0 });
+        let (tx, progress) = mpsc::channel();
+        let (decisions, dec_rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let control = Arc::new(Control { cancel: cancel.clone(), paused: AtomicBool::new(false) });
+        let state = JobState { status: JobStatus::Pending, progress: 0, processed: 0, total: job.kind.len(), message: "Queued".to_string(), bytes: None, failures: Vec::new(), verified: job.verify.then_some(Verified::default()), skipped: Vec::new(), warnings: Vec::new() };
+        let entry = match &self.shared.journal {
+            Some(journal) if job.src_vfs.is_local() && job.dst_vfs.is_local() => {
+                let mut entry = JournalEntry::new(id.0, job.kind.clone());
+                entry.error_policy = job.error_policy;
+                entry.conflict_policy = job.conflict_policy;
+                entry.verify = job.verify;
+                let _ = journal.save(&entry);
+                Some(entry)
+            }
+            _ => None,
+        };
+
+        let mut inner = self.shared.inner.lock().unwrap();
+        inner.order.push(id);
+        let record = Record {
+            kind: job.kind.clone(),
+            src_vfs: job.src_vfs.clone(),
+            dst_vfs: job.dst_vfs.clone(),
+            error_policy: job.error_policy,
+            conflict_policy: job.conflict_policy,
+            verify: job.verify,
+            state,
+            control: control.clone(),
+            started: false,
+        };
+        inner.records.insert(id, record);
+        inner.pending.push_back(Queued { id, job, tx, decisions: dec_rx, control, entry });
+        if inner.idle == 0 && inner.workers < MAX_WORKERS {
+            inner.workers += 1;
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || worker_loop(shared));
+        }
+        drop(inner);
+        self.shared.wake.notify_one();
+
+        JobTicket { id, progress, decisions, cancel }
+    }
+
+    /// Enqueue a local job nobody needs to follow and return its id.
+    /// Conflicts in such a job are resolved by skipping the existing item.
+    pub fn enqueue(&self, kind: JobKind) -> JobId {
+        self.submit(Job::local(kind).with_conflict_policy(ConflictPolicy::Skip)).id
+    }
+
+    /// Keep `ticket` with the queue while nobody follows its job, so the
+    /// job can still be asked about conflicts once someone `attach`es.
+    pub fn detach(&self, ticket: JobTicket) {
+        self.shared.inner.lock().unwrap().detached.insert(ticket.id, ticket);
+    }
+
+    /// The backends job `id` reads from and writes to.
+    pub fn backends(&self, id: JobId) -> Option<(Arc<dyn Vfs>, Arc<dyn Vfs>)> {
+        self.shared.inner.lock().unwrap().records.get(&id).map(|rec| (rec.src_vfs.clone(), rec.dst_vfs.clone()))
+    }
+
+    /// Take back a ticket handed to `detach`.
+    pub fn attach(&self, id: JobId) -> Option<JobTicket> {
+        self.shared.inner.lock().unwrap().detached.remove(&id)
+    }
+
+    /// Jobs a previous run left unfinished, oldest first. Empty for a
+    /// queue without a journal.
+    pub fn unfinished(&self) -> Vec<JournalEntry> {
+        self.shared.journal.as_ref().map(|j| j.unfinished()).unwrap_or_default()
+    }
+
+    /// Submit what is left of an unfinished job: items not yet done, with
+    /// a partly copied file continued from its last verified offset.
+    pub fn resume_unfinished(&self, entry: &JournalEntry) -> JobTicket {
+        let (kind, in_progress) = entry.remaining();
+        if let Some(journal) = &self.shared.journal {
+            let _ = journal.remove(entry);
+        }
+        let mut job = Job::local(kind).with_error_policy(entry.error_policy).with_conflict_policy(entry.conflict_policy).with_verify(entry.verify);
+        job.resume = in_progress;
+        job.resume_from = entry.partial.clone().filter(|_| in_progress);
+        self.submit(job)
+    }
+
+    /// Drop an unfinished job, removing its partly copied file.
+    pub fn discard_unfinished(&self, entry: &JournalEntry) -> io::Result<()> {
+        match &self.shared.journal {
+            Some(journal) => journal.discard(entry),
+            None => Ok(()),
         }
-        let _ = self.tx.send(WorkerMsg::Enqueue(id, kind));
-        id
     }
 
-    /// Pause a running job (no-op if already paused or finished).
+    /// Pause a pending or running job (no-op if already paused or finished).
+    /// A running job stops at its next checkpoint; a pending one is not
+    /// started until resumed.
     pub fn pause(&self, id: JobId) {
-        update_status(&self.states, id, JobStatus::Paused);
+        let mut inner = self.shared.inner.lock().unwrap();
+        if let Some(rec) = inner.records.get_mut(&id) {
+            if matches!(rec.state.status, JobStatus::Pending | JobStatus::Running) {
+                rec.control.paused.store(true, Ordering::SeqCst);
+                rec.state.status = JobStatus::Paused;
+            }
+        }
     }
 
     /// Resume a paused job.
     pub fn resume(&self, id: JobId) {
-        update_status_running(&self.states, id);
+        let mut inner = self.shared.inner.lock().unwrap();
+        if let Some(rec) = inner.records.get_mut(&id) {
+            if rec.state.status == JobStatus::Paused {
+                rec.control.paused.store(false, Ordering::SeqCst);
+                rec.state.status = if rec.started { JobStatus::Running } else { JobStatus::Pending };
+            }
+        }
+        drop(inner);
+        self.shared.wake.notify_all();
     }
 
-    /// Cancel a job.
+    /// Cancel a job. A job that has not started yet finishes immediately;
+    /// a running one stops at its next checkpoint.
     pub fn cancel(&self, id: JobId) {
-        update_status(&self.states, id, JobStatus::Cancelled);
+        let mut inner = self.shared.inner.lock().unwrap();
+        match inner.records.get(&id) {
+            Some(rec) if !rec.state.status.is_finished() => rec.control.cancel.store(true, Ordering::SeqCst),
+            _ => return,
+        }
+        if let Some(pos) = inner.pending.iter().position(|q| q.id == id) {
+            let queued = inner.pending.remove(pos).expect("position is in range");
+            let total = queued.job.kind.len();
+            if let Some(rec) = inner.records.get_mut(&id) {
+                rec.state.status = JobStatus::Cancelled;
+                rec.state.message = "Cancelled".to_string();
+            }
+            let _ = queued.tx.send(ProgressUpdate::done_with_error(0, total, Some("Cancelled".to_string())));
+        }
+    }
+
+    /// Move a job one place earlier in the queue, so it starts before the
+    /// job it passes if both are still waiting.
+    pub fn move_up(&self, id: JobId) {
+        self.shift(id, true);
+    }
+
+    /// Move a job one place later in the queue.
+    pub fn move_down(&self, id: JobId) {
+        self.shift(id, false);
+    }
+
+    fn shift(&self, id: JobId, up: bool) {
+        let mut inner = self.shared.inner.lock().unwrap();
+        let Inner { order, pending, .. } = &mut *inner;
+        let Some(pos) = order.iter().position(|j| *j == id) else { return };
+        let other = if up { pos.checked_sub(1) } else { Some(pos + 1).filter(|p| *p < order.len()) };
+        if let Some(other) = other {
+            order.swap(pos, other);
+            pending.make_contiguous().sort_by_key(|q| order.iter().position(|j| *j == q.id));
+        }
+    }
+
+    /// Submit a failed or cancelled job again, on the same backends, as a
+    /// new job at the end of the queue.
+    pub fn retry(&self, id: JobId) -> Option<JobTicket> {
+        let job = {
+            let inner = self.shared.inner.lock().unwrap();
+            let rec = inner.records.get(&id)?;
+            if !matches!(rec.state.status, JobStatus::Cancelled | JobStatus::Failed(_)) {
+                return None;
+            }
+            Job::on(rec.kind.clone(), rec.src_vfs.clone(), rec.dst_vfs.clone()).with_error_policy(rec.error_policy).with_conflict_policy(rec.conflict_policy).with_verify(rec.verify)
+        };
+        Some(self.submit(job))
+    }
+
+    /// Submit a new job for just the items a finished job skipped after
+    /// they failed (see `JobState::failures`). `None` when there are none.
+    pub fn retry_failed(&self, id: JobId) -> Option<JobTicket> {
+        let job = {
+            let inner = self.shared.inner.lock().unwrap();
+            let rec = inner.records.get(&id)?;
+            if !rec.state.status.is_finished() || rec.state.failures.is_empty() {
+                return None;
+            }
+            let failures = &rec.state.failures;
+            let kind = match &rec.kind {
+                JobKind::Delete { .. } => JobKind::Delete { targets: failures.iter().map(|f| f.path.clone()).collect() },
+                JobKind::Shred { method, passes, .. } => JobKind::Shred { targets: failures.iter().map(|f| f.path.clone()).collect(), method: *method, passes: *passes },
+                kind => JobKind::Transfer {
+                    moving: kind.operation() == "move",
+                    pairs: failures.iter().filter_map(|f| Some((f.path.clone(), f.target.clone()?))).collect(),
+                },
+            };
+            Job::on(kind, rec.src_vfs.clone(), rec.dst_vfs.clone()).with_error_policy(rec.error_policy).with_conflict_policy(rec.conflict_policy).with_verify(rec.verify)
+        };
+        Some(self.submit(job))
     }
 
     /// Snapshot the current state of a job.
     pub fn state(&self, id: JobId) -> Option<JobState> {
-        self.states.lock().unwrap().get(&id).cloned()
+        self.shared.inner.lock().unwrap().records.get(&id).map(|r| r.state.clone())
+    }
+
+    /// Snapshot every job in submission order.
+    pub fn list(&self) -> Vec<(JobId, JobKind, JobState)> {
+        let inner = self.shared.inner.lock().unwrap();
+        inner.order.iter().filter_map(|id| inner.records.get(id).map(|r| (*id, r.kind.clone(), r.state.clone()))).collect()
     }
 }
 
@@ -96,77 +570,813 @@ impl Default for JobQueue {
 
 impl Drop for JobQueue {
     fn drop(&mut self) {
-        let _ = self.tx.send(WorkerMsg::Shutdown);
+        let mut inner = self.shared.inner.lock().unwrap();
+        inner.shutdown = true;
+        for rec in inner.records.values() {
+            rec.control.cancel.store(true, Ordering::SeqCst);
+        }
+        inner.pending.clear();
+        drop(inner);
+        self.shared.wake.notify_all();
     }
 }
 
 static NEXT_ID: AtomicU64 = AtomicU64::new(1);
 
-fn worker_loop(rx: mpsc::Receiver<WorkerMsg>, states: Arc<Mutex<HashMap<JobId, JobState>>>) {
-    while let Ok(msg) = rx.recv() {
-        match msg {
-            WorkerMsg::Enqueue(id, kind) => {
-                let states = Arc::clone(&states);
-                thread::spawn(move || run_job(id, kind, &states));
+fn worker_loop(shared: Arc<Shared>) {
+    loop {
+        let queued = {
+            let mut inner = shared.inner.lock().unwrap();
+            loop {
+                if inner.shutdown {
+                    inner.workers -= 1;
+                    return;
+                }
+                // Paused jobs keep their place but let later ones through.
+                if let Some(pos) = inner.pending.iter().position(|q| !q.control.paused.load(Ordering::SeqCst)) {
+                    let queued = inner.pending.remove(pos).expect("position is in range");
+                    if let Some(rec) = inner.records.get_mut(&queued.id) {
+                        rec.started = true;
+                        rec.state.status = JobStatus::Running;
+                    }
+                    break queued;
+                }
+                inner.idle += 1;
+                inner = shared.wake.wait(inner).unwrap();
+                inner.idle -= 1;
             }
-            WorkerMsg::Shutdown => break,
+        };
+        let (runner, job) = Runner::new(&shared, queued);
+        runner.run(job);
+    }
+}
+
+/// Outcome of the conflict check for a single item.
+enum Resolution {
+    /// Replace the existing target.
+    Overwrite,
+    /// Write the item to this path, which is free.
+    Write(PathBuf),
+    /// Keep the target and append the rest of the source to it.
+    Append,
+    Skip,
+    Stop,
+}
+
+/// Byte accounting for the job a `Runner` is working on. Delete jobs move
+/// no data and leave `bytes` unset; shred jobs count every pass.
+struct Meter {
+    bytes: Option<ByteProgress>,
+    throughput: Throughput,
+    /// Job bytes done before the file currently being copied.
+    file_base: u64,
+    last_sent: Instant,
+}
+
+impl Meter {
+    /// Whether enough time has passed since the last byte update was sent.
+    fn due(&mut self) -> bool {
+        let now = Instant::now();
+        if now.duration_since(self.last_sent) < BYTES_INTERVAL {
+            return false;
         }
+        self.last_sent = now;
+        true
     }
 }
 
-fn run_job(id: JobId, _kind: JobKind, states: &Arc<Mutex<HashMap<JobId, JobState>>>) {
-    update_status_running(states, id);
-    // Dummy progress: 10 steps with pause/cancel checks.
-    for step in 1..=10 {
-        thread::sleep(Duration::from_millis(30));
-        // Check for pause/cancel signals delivered via shared state.
-        loop {
-            let status = states.lock().unwrap().get(&id).map(|s| s.status.clone());
-            match status {
-                Some(JobStatus::Paused) => {
-                    thread::sleep(Duration::from_millis(5));
+/// Runs one job on a worker thread, mirroring every update it sends into
+/// the queue's `JobState` for that job.
+struct Runner<'a> {
+    shared: &'a Shared,
+    id: JobId,
+    tx: mpsc::Sender<ProgressUpdate>,
+    decisions: mpsc::Receiver<OperationDecision>,
+    control: Arc<Control>,
+    meter: RefCell<Meter>,
+    /// Journal entry of a local job on a queue with a journal.
+    entry: RefCell<Option<JournalEntry>>,
+    /// Last time `entry` was written.
+    saved: Cell<Instant>,
+    /// Source and target of the item being worked on.
+    item: RefCell<Option<(PathBuf, PathBuf)>>,
+    /// The job's error policy; "skip all" turns `Ask` into `Skip`.
+    error_policy: Cell<ErrorPolicy>,
+    /// Operation named in failure reports (see `JobKind::operation`).
+    operation: Cell<&'static str>,
+    /// Failures recorded so far.
+    failed: Cell<usize>,
+    /// Entries left out as uncopyable so far (see `JobState::skipped`).
+    skipped: Cell<usize>,
+    /// Whether copies are verified after they are written.
+    verify: bool,
+    /// The job's conflict policy; an "all" decision replaces `Ask`.
+    conflict_policy: Cell<ConflictPolicy>,
+    /// Set when the current item continues its target (see
+    /// `Resolution::Append`); taken by the next transfer.
+    append: Cell<bool>,
+}
+
+impl<'a> Runner<'a> {
+    fn new(shared: &'a Shared, queued: Queued) -> (Self, Job) {
+        let meter = Meter { bytes: None, throughput: Throughput::new(), file_base: 0, last_sent: Instant::now() };
+        let runner = Runner {
+            shared,
+            id: queued.id,
+            tx: queued.tx,
+            decisions: queued.decisions,
+            control: queued.control,
+            meter: RefCell::new(meter),
+            entry: RefCell::new(queued.entry),
+            saved: Cell::new(Instant::now()),
+            item: RefCell::new(None),
+            error_policy: Cell::new(queued.job.error_policy),
+            operation: Cell::new(queued.job.kind.operation()),
+            failed: Cell::new(0),
+            skipped: Cell::new(0),
+            verify: queued.job.verify,
+            conflict_policy: Cell::new(queued.job.conflict_policy),
+            append: Cell::new(false),
+        };
+        (runner, queued.job)
+    }
+
+    fn run(&self, job: Job) {
+        self.run_items(job);
+        self.close_journal();
+    }
+
+    fn run_items(&self, job: Job) {
+        let same_backend = std::ptr::addr_eq(Arc::as_ptr(&job.src_vfs), Arc::as_ptr(&job.dst_vfs));
+        let src_vfs = job.src_vfs.as_ref();
+        let dst_vfs = job.dst_vfs.as_ref();
+        let local = src_vfs.is_local() && dst_vfs.is_local();
+        let (items, targets): (Vec<PathBuf>, Vec<Option<PathBuf>>) = match &job.kind {
+            JobKind::Copy { sources, dst_dir } | JobKind::Move { sources, dst_dir } => {
+                let target = |src: &PathBuf| src.file_name().map(|f| dst_dir.join(f)).unwrap_or_else(|| dst_dir.clone());
+                (sources.clone(), sources.iter().map(|src| Some(target(src))).collect())
+            }
+            JobKind::Delete { targets } | JobKind::Shred { targets, .. } => (targets.clone(), vec![None; targets.len()]),
+            JobKind::Transfer { pairs, .. } => pairs.iter().map(|(src, target)| (src.clone(), Some(target.clone()))).unzip(),
+        };
+        let deleting = matches!(job.kind, JobKind::Delete { .. });
+        let shred = match &job.kind {
+            JobKind::Shred { method, passes, .. } => Some(method.passes(*passes)),
+            _ => None,
+        };
+        let moving = job.kind.operation() == "move";
+        let total = items.len();
+        let sizes = if !deleting {
+            match self.scan(src_vfs, &items, src_vfs.is_local(), total) {
+                Ok(sizes) => sizes,
+                Err(_) => {
+                    self.send(ProgressUpdate::done_with_error(0, total, Some("Cancelled".to_string())));
+                    return;
+                }
+            }
+        } else {
+            vec![0; total]
+        };
+        // Every pass writes the whole file again.
+        let sizes = match &shred {
+            Some(passes) => {
+                if let Some(bytes) = self.meter.borrow_mut().bytes.as_mut() {
+                    bytes.total *= passes.len() as u64;
+                }
+                sizes.iter().map(|size| size * passes.len() as u64).collect()
+            }
+            None => sizes,
+        };
+
+        for (i, (src, target)) in items.iter().zip(&targets).enumerate() {
+            if self.control.checkpoint().is_err() {
+                self.send(ProgressUpdate::done_with_error(i, total, Some("Cancelled".to_string())));
+                return;
+            }
+            let item_base = self.bytes_done();
+            let resume = job.resume && i == 0;
+            let resume_at = job.resume_from.as_ref().filter(|_| resume).map(|p| (p.src.as_path(), p.offset));
+            let partial = self.entry.borrow().is_some().then_some(resume_at);
+            self.journal(|entry| entry.current = Some(i));
+            self.append.set(false);
+            let mut target = target.clone();
+            if let Some(existing) = target.clone() {
+                let exists = if dst_vfs.is_local() { existing.exists() } else { dst_vfs.stat(&existing).is_ok() };
+                if exists && !resume {
+                    match self.resolve(i, total, src_vfs, src, dst_vfs, &existing, sizes[i], local) {
+                        Resolution::Overwrite => {
+                            if dst_vfs.is_local() {
+                                let _ = if existing.is_dir() { std::fs::remove_dir_all(&existing) } else { std::fs::remove_file(&existing) };
+                            } else {
+                                let _ = crate::vfs::remove_tree(dst_vfs, &existing);
+                            }
+                        }
+                        Resolution::Write(path) => target = Some(path),
+                        Resolution::Append => self.append.set(true),
+                        Resolution::Skip => {
+                            self.journal_item_done(i);
+                            continue;
+                        }
+                        Resolution::Stop => return,
+                    }
+                }
+            }
+            let target = &target;
+            *self.item.borrow_mut() = target.clone().map(|target| (src.clone(), target));
+            // Retry the item for as long as the error policy says so.
+            let res = loop {
+                let res = match target {
+                    None => match &shred {
+                        Some(passes) => self.shred(i, total, src_vfs, src, passes, local),
+                        None => self.delete(src_vfs, src, local),
+                    },
+                    Some(target) if moving => self.move_item(i, total, src_vfs, src, dst_vfs, target, local, same_backend, partial),
+                    Some(target) => self.copy(i, total, src_vfs, src, dst_vfs, target, local, partial),
+                };
+                match res {
+                    Ok(()) => break Ok(true),
+                    Err(e) => match self.on_failure(i, total, src_vfs, src, e) {
+                        Ok(true) => continue,
+                        Ok(false) => break Ok(false),
+                        Err(e) => break Err(e),
+                    },
+                }
+            };
+            match res {
+                Err(e) => {
+                    let msg = if self.control.cancelled() { "Cancelled".to_string() } else { e.to_string() };
+                    let message = if self.control.cancelled() { msg.clone() } else { format!("Error: {}", msg) };
+                    self.send(ProgressUpdate { processed: i, total, message: Some(message), done: true, error: Some(msg), conflict: None, bytes: None, failed: None });
+                    return;
+                }
+                Ok(false) => {
+                    self.skip_item(sizes[i]);
+                    self.journal_item_done(i);
                     continue;
                 }
-                Some(JobStatus::Cancelled) => return,
-                _ => break,
+                Ok(true) => {}
+            }
+            // Renames and natively exported trees report no chunks; settle
+            // the item at its scanned size either way.
+            self.finish_item(item_base + sizes[i]);
+            self.journal_item_done(i);
+            let verb = match job.kind.operation() { "copy" => "Copied", "move" => "Moved", "shred" => "Shredded", _ => "Deleted" };
+            self.send(ProgressUpdate { processed: i + 1, total, message: Some(format!("{} {}", verb, src_vfs.display_path(src))), done: false, error: None, conflict: None, bytes: None, failed: None });
+        }
+        let message = match self.failed.get() {
+            0 => "Completed".to_string(),
+            n => format!("Completed, {} failed", n),
+        };
+        self.send(ProgressUpdate { processed: total, total, message: Some(message), done: true, error: None, conflict: None, bytes: None, failed: None });
+    }
+
+    /// Failure hook for an item or an entry inside it: apply the error
+    /// policy to `err`. `Ok(true)` retries, `Ok(false)` skips (recording
+    /// the failure) and an error stops the job.
+    fn on_failure(&self, i: usize, total: usize, vfs: &dyn Vfs, path: &Path, err: io::Error) -> io::Result<bool> {
+        if self.control.cancelled() || self.error_policy.get() == ErrorPolicy::Abort {
+            return Err(err);
+        }
+        let target = self.item.borrow().as_ref().map(|(src, target)| match path.strip_prefix(src) {
+            Ok(rel) if !rel.as_os_str().is_empty() => target.join(rel),
+            _ => target.clone(),
+        });
+        let display = vfs.display_path(path);
+        let error = crate::errors::render_io_error(&err, Some(&display), None, None);
+        let item = FailedItem { path: path.to_path_buf(), target, operation: self.operation.get(), error };
+        if self.error_policy.get() == ErrorPolicy::Ask {
+            self.send(ProgressUpdate { processed: i, total, message: Some("Error".to_string()), done: false, error: None, conflict: None, bytes: None, failed: Some(item.clone()) });
+            match self.wait_decision() {
+                Ok(OperationDecision::Retry | OperationDecision::Overwrite | OperationDecision::OverwriteAll) => return Ok(true),
+                Ok(OperationDecision::SkipAll) => self.error_policy.set(ErrorPolicy::Skip),
+                Ok(OperationDecision::Cancel) => {
+                    self.control.cancel.store(true, Ordering::SeqCst);
+                    return Err(err);
+                }
+                // `Skip`, and conflict-only decisions, skip the item.
+                Ok(_) => {}
+                Err(()) => return Err(err),
             }
         }
+        self.drop_partial(path);
+        self.failed.set(self.failed.get() + 1);
+        if let Some(rec) = self.shared.inner.lock().unwrap().records.get_mut(&self.id) {
+            rec.state.failures.push(item);
+        }
+        self.send(ProgressUpdate { processed: i, total, message: Some(format!("Failed {}", display)), done: false, error: None, conflict: None, bytes: None, failed: None });
+        Ok(false)
+    }
 
-        let progress = (step * 10).min(100) as u8;
-        let mut guard = states.lock().unwrap();
-        if let Some(st) = guard.get_mut(&id) {
-            if matches!(st.status, JobStatus::Cancelled) {
-                return;
+    /// Total the size of every source before copying, so byte progress has
+    /// a denominator. Unreadable entries count as empty; only a cancel
+    /// stops the scan.
+    fn scan(&self, vfs: &dyn Vfs, items: &[PathBuf], local: bool, total: usize) -> io::Result<Vec<u64>> {
+        self.meter.borrow_mut().bytes = Some(ByteProgress { scanning: true, ..Default::default() });
+        let mut sizes = Vec::with_capacity(items.len());
+        for src in items {
+            let mut size = 0;
+            if local {
+                for entry in walkdir::WalkDir::new(src).into_iter().flatten() {
+                    self.control.checkpoint()?;
+                    if entry.file_type().is_file() {
+                        let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                        size += len;
+                        self.scanned(total, len, || entry.path().display().to_string());
+                    }
+                }
+            } else {
+                self.scan_vfs(vfs, src, total, &mut size)?;
+            }
+            sizes.push(size);
+        }
+        if let Some(bytes) = self.meter.borrow_mut().bytes.as_mut() {
+            bytes.scanning = false;
+        }
+        self.meter.borrow_mut().throughput = Throughput::new();
+        Ok(sizes)
+    }
+
+    fn scan_vfs(&self, vfs: &dyn Vfs, path: &Path, total: usize, size: &mut u64) -> io::Result<()> {
+        self.control.checkpoint()?;
+        let Ok(entry) = vfs.stat(path) else { return Ok(()) };
+        if entry.is_dir {
+            for child in vfs.list(path).unwrap_or_default() {
+                self.scan_vfs(vfs, &child.path, total, size)?;
+            }
+        } else if !crate::vfs::transfer::is_symlink_mode(entry.unix_mode) {
+            *size += entry.size;
+            self.scanned(total, entry.size, || vfs.display_path(path));
+        }
+        Ok(())
+    }
+
+    /// Add a file found by the pre-scan to the job total.
+    fn scanned(&self, total: usize, len: u64, display: impl FnOnce() -> String) {
+        let mut meter = self.meter.borrow_mut();
+        if let Some(bytes) = meter.bytes.as_mut() {
+            bytes.total += len;
+        }
+        if meter.due() {
+            drop(meter);
+            self.send(ProgressUpdate { processed: 0, total, message: Some(format!("Scanning {}", display())), done: false, error: None, conflict: None, bytes: None, failed: None });
+        }
+    }
+
+    /// Chunk hook for the copy helpers: account the bytes, send a throttled
+    /// update, then honour pause and cancel.
+    fn on_chunk(&self, i: usize, total: usize, vfs: &dyn Vfs, file: &Path, copied: u64, len: u64) -> io::Result<()> {
+        let due = {
+            let mut meter = self.meter.borrow_mut();
+            let meter = &mut *meter;
+            match meter.bytes.as_mut() {
+                Some(bytes) => {
+                    if copied == 0 {
+                        meter.file_base = bytes.done;
+                    }
+                    bytes.done = meter.file_base + copied;
+                    bytes.file_done = copied;
+                    bytes.file_total = len;
+                    meter.due()
+                }
+                None => false,
             }
-            st.progress = progress;
-            st.status = JobStatus::Running;
+        };
+        if due {
+            let verb = if self.operation.get() == "shred" { "Shredding" } else { "Copying" };
+            self.send(ProgressUpdate { processed: i, total, message: Some(format!("{} {}", verb, vfs.display_path(file))), done: false, error: None, conflict: None, bytes: None, failed: None });
+        }
+        self.journal_chunk(file, copied);
+        self.control.checkpoint()
+    }
+
+    /// Update the journal entry, if the job has one, and write it out.
+    fn journal(&self, update: impl FnOnce(&mut JournalEntry)) {
+        let mut entry = self.entry.borrow_mut();
+        if let (Some(entry), Some(journal)) = (entry.as_mut(), &self.shared.journal) {
+            update(entry);
+            let _ = journal.save(entry);
+            self.saved.set(Instant::now());
+        }
+    }
+
+    /// Remove the partial file left by a failed copy of `src` that is being
+    /// skipped: nothing will continue it, and the journal only remembers
+    /// the file in progress.
+    fn drop_partial(&self, src: &Path) {
+        let mut entry = self.entry.borrow_mut();
+        let Some(entry) = entry.as_mut() else { return };
+        if let Some(partial) = entry.partial.take_if(|p| p.src == src) {
+            let _ = std::fs::remove_file(crate::fs_op::helpers::partial_path(&partial.dst));
+        }
+    }
+
+    fn journal_item_done(&self, i: usize) {
+        self.journal(|entry| {
+            entry.done[i] = true;
+            entry.current = None;
+            entry.partial = None;
+        });
+    }
+
+    /// Record how far into `file` the copy got, writing the journal at
+    /// most every `JOURNAL_INTERVAL`.
+    fn journal_chunk(&self, file: &Path, copied: u64) {
+        let Some((src, target)) = self.item.borrow().clone() else { return };
+        let dst = match file.strip_prefix(&src) {
+            Ok(rel) if !rel.as_os_str().is_empty() => target.join(rel),
+            _ => target,
+        };
+        let partial = PartialFile { src: file.to_path_buf(), dst, offset: copied };
+        if self.saved.get().elapsed() >= JOURNAL_INTERVAL {
+            self.journal(|entry| entry.partial = Some(partial));
+        } else if let Some(entry) = self.entry.borrow_mut().as_mut() {
+            entry.partial = Some(partial);
+        }
+    }
+
+    /// Settle the journal entry once the job stops. A job stopped because
+    /// the queue is shutting down keeps its entry (and partial file) so it
+    /// can be resumed; any other ending removes both.
+    fn close_journal(&self) {
+        let Some(entry) = self.entry.borrow_mut().take() else { return };
+        let Some(journal) = &self.shared.journal else { return };
+        if self.shared.inner.lock().unwrap().shutdown {
+            let _ = journal.save(&entry);
+        } else {
+            let _ = journal.discard(&entry);
+        }
+    }
+
+    fn bytes_done(&self) -> u64 {
+        self.meter.borrow().bytes.map(|b| b.done).unwrap_or(0)
+    }
+
+    /// Mark the current item finished with `done` job bytes behind it.
+    fn finish_item(&self, done: u64) {
+        if let Some(bytes) = self.meter.borrow_mut().bytes.as_mut() {
+            bytes.done = bytes.done.max(done);
+            bytes.file_done = bytes.file_total;
         }
     }
-    update_status(states, id, JobStatus::Completed);
+
+    /// Drop a skipped item's bytes from the job total.
+    fn skip_item(&self, size: u64) {
+        if let Some(bytes) = self.meter.borrow_mut().bytes.as_mut() {
+            bytes.total = bytes.total.saturating_sub(size);
+        }
+    }
+
+    /// Settle an existing `target` with the job's conflict policy, asking
+    /// for a decision while it is `Ask`.
+    #[allow(clippy::too_many_arguments)]
+    fn resolve(&self, i: usize, total: usize, src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path, size: u64, local: bool) -> Resolution {
+        let skipped = |suffix: &str| {
+            self.skip_item(size);
+            self.send(ProgressUpdate { processed: i, total, message: Some(format!("Skipped {}{}", src_vfs.display_path(src), suffix)), done: false, error: None, conflict: None, bytes: None, failed: None });
+            Resolution::Skip
+        };
+        let mut policy = self.conflict_policy.get();
+        if policy == ConflictPolicy::Ask {
+            self.send(ProgressUpdate { processed: i, total, message: Some("Conflict".to_string()), done: false, error: None, conflict: Some(Conflict::inspect(src_vfs, src, dst_vfs, target)), bytes: None, failed: None });
+            let decision = match self.wait_decision() {
+                Ok(decision) => decision,
+                Err(()) => {
+                    self.send(ProgressUpdate::done_with_error(i, total, Some("Decision channel closed".to_string())));
+                    return Resolution::Stop;
+                }
+            };
+            match decision.conflict_policy() {
+                Some((chosen, all)) => {
+                    if all {
+                        self.conflict_policy.set(chosen);
+                        if chosen == ConflictPolicy::Skip {
+                            return skipped(" (all)");
+                        }
+                    }
+                    policy = chosen;
+                }
+                None if decision == OperationDecision::Retry => policy = ConflictPolicy::Overwrite,
+                None => {
+                    self.send(ProgressUpdate::done_with_error(i, total, Some("Cancelled by user".to_string())));
+                    return Resolution::Stop;
+                }
+            }
+        }
+        let src_entry = src_vfs.stat(src).ok();
+        let dst_entry = dst_vfs.stat(target).ok();
+        let is_dir = |entry: &Option<crate::app::types::Entry>| entry.as_ref().is_some_and(|e| e.is_dir);
+        match policy {
+            ConflictPolicy::Ask | ConflictPolicy::Overwrite => Resolution::Overwrite,
+            ConflictPolicy::Skip => skipped(""),
+            ConflictPolicy::RenameNew => {
+                let free = crate::fs_op::helpers::free_numbered_path(target, is_dir(&src_entry), |p| dst_vfs.stat(p).is_ok());
+                Resolution::Write(free)
+            }
+            ConflictPolicy::RenameExisting => {
+                let aside = crate::fs_op::helpers::free_numbered_path(target, is_dir(&dst_entry), |p| dst_vfs.stat(p).is_ok());
+                match dst_vfs.rename(target, &aside) {
+                    Ok(()) => Resolution::Write(target.to_path_buf()),
+                    Err(e) => {
+                        let msg = format!("Cannot rename {}: {}", dst_vfs.display_path(target), e);
+                        self.send(ProgressUpdate::done_with_error(i, total, Some(msg)));
+                        Resolution::Stop
+                    }
+                }
+            }
+            ConflictPolicy::OverwriteIfNewer => {
+                let modified = |entry: &Option<crate::app::types::Entry>| entry.as_ref().and_then(|e| e.modified);
+                match (modified(&src_entry), modified(&dst_entry)) {
+                    (Some(new), Some(old)) if new > old => Resolution::Overwrite,
+                    _ => skipped(" (not newer)"),
+                }
+            }
+            ConflictPolicy::OverwriteIfSizeDiffers => {
+                let existing = if is_dir(&dst_entry) { tree_bytes(dst_vfs, target).ok() } else { dst_entry.as_ref().map(|e| e.size) };
+                if existing == Some(size) {
+                    skipped(" (same size)")
+                } else {
+                    Resolution::Overwrite
+                }
+            }
+            ConflictPolicy::Resume => {
+                let regular = |entry: &Option<crate::app::types::Entry>| entry.as_ref().is_some_and(|e| !e.is_dir && e.unix_mode.is_none_or(|m| m & 0o170000 == 0o100000));
+                if local && regular(&src_entry) && regular(&dst_entry) {
+                    Resolution::Append
+                } else {
+                    Resolution::Overwrite
+                }
+            }
+        }
+    }
+
+    /// Block until the UI sends a decision. A cancelled job counts as
+    /// `Cancel`; `Err` means nobody is left to answer.
+    fn wait_decision(&self) -> Result<OperationDecision, ()> {
+        loop {
+            match self.decisions.recv_timeout(POLL_INTERVAL) {
+                Ok(d) => return Ok(d),
+                Err(mpsc::RecvTimeoutError::Timeout) if self.control.cancelled() => return Ok(OperationDecision::Cancel),
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(()),
+            }
+        }
+    }
+
+    /// Copy `src` to `target`, then verify the copy when the job asks for
+    /// it.
+    #[allow(clippy::too_many_arguments)]
+    fn copy(&self, i: usize, total: usize, src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path, local: bool, partial: Partial<'_>) -> io::Result<()> {
+        self.transfer(i, total, src_vfs, src, dst_vfs, target, local, partial)?;
+        if self.verify {
+            self.verify_tree(i, total, src_vfs, src, dst_vfs, target, local)?;
+        }
+        Ok(())
+    }
+
+    /// `partial` is `None` for a plain copy, otherwise the journaled point
+    /// to continue an interrupted run from (see `copy_local`).
+    #[allow(clippy::too_many_arguments)]
+    fn transfer(&self, i: usize, total: usize, src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path, local: bool, partial: Partial<'_>) -> io::Result<()> {
+        let mut checkpoint = |file: &Path, copied: u64, len: u64| self.on_chunk(i, total, src_vfs, file, copied, len);
+        let mut on_error = |path: &Path, e: io::Error| self.on_nested_failure(i, total, src_vfs, src, path, e);
+        if local && self.append.take() {
+            return crate::fs_op::helpers::append_copy_file_with(src, target, &mut checkpoint).map(|_| ());
+        }
+        if local {
+            let mut on_skip = |path: &Path, kind: &'static str| self.on_skip(path, kind);
+            return copy_local(src, target, partial, &mut checkpoint, &mut on_error, &mut on_skip);
+        }
+        // Report every member written so extracting a large directory out
+        // of an archive shows steady progress.
+        let mut on_member = |p: &Path| self.send(ProgressUpdate { processed: i, total, message: Some(format!("Copied {}", src_vfs.display_path(p))), done: false, error: None, conflict: None, bytes: None, failed: None });
+        crate::vfs::copy_tree_with(src_vfs, src, dst_vfs, target, &mut on_member, &mut checkpoint, &mut on_error).map(|_| ())
+    }
+
+    /// Record an entry a tree copy left out (see `JobState::skipped`).
+    fn on_skip(&self, path: &Path, kind: &'static str) {
+        self.skipped.set(self.skipped.get() + 1);
+        if let Some(rec) = self.shared.inner.lock().unwrap().records.get_mut(&self.id) {
+            rec.state.skipped.push((path.to_path_buf(), kind));
+        }
+    }
+
+    /// Rename when possible, otherwise copy (verifying the copy when the
+    /// job asks for it) and then remove the source. A source that was only
+    /// partly copied (some entries failed or could not be copied) is kept
+    /// whole.
+    #[allow(clippy::too_many_arguments)]
+    fn move_item(&self, i: usize, total: usize, src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path, local: bool, same_backend: bool, partial: Partial<'_>) -> io::Result<()> {
+        let left_out = || self.failed.get() + self.skipped.get();
+        let before = left_out();
+        if local {
+            crate::fs_op::helpers::ensure_parent_exists(target)?;
+            if std::fs::rename(src, target).is_ok() {
+                return Ok(());
+            }
+        } else if same_backend && src_vfs.rename(src, target).is_ok() {
+            return Ok(());
+        }
+        self.copy(i, total, src_vfs, src, dst_vfs, target, local, partial)?;
+        if left_out() > before {
+            return Ok(());
+        }
+        self.delete(src_vfs, src, local)
+    }
+
+    /// Compare every regular file under `src` with its copy under `target`.
+    /// A mismatch (or a missing copy) goes through the error policy like a
+    /// failed copy: retrying copies the file again, skipping removes the
+    /// bad copy. Entries that already
+    /// failed to copy, links and special files are not checked.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_tree(&self, i: usize, total: usize, src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path, local: bool) -> io::Result<()> {
+        let entry = src_vfs.stat(src)?;
+        if entry.is_dir {
+            for child in src_vfs.list(src)? {
+                self.verify_tree(i, total, src_vfs, &child.path, dst_vfs, &target.join(child_name(&child)), local)?;
+            }
+            return Ok(());
+        }
+        let regular = entry.unix_mode.is_none_or(|m| m & 0o170000 == 0o100000);
+        let skipped = || self.shared.inner.lock().unwrap().records.get(&self.id).is_some_and(|r| r.state.failures.iter().any(|f| f.path == src));
+        if !regular || skipped() {
+            return Ok(());
+        }
+        loop {
+            match self.compare(i, total, src_vfs, src, dst_vfs, target) {
+                Ok(len) => {
+                    if let Some(verified) = self.shared.inner.lock().unwrap().records.get_mut(&self.id).and_then(|r| r.state.verified.as_mut()) {
+                        verified.files += 1;
+                        verified.bytes += len;
+                    }
+                    return Ok(());
+                }
+                Err(e) => {
+                    if !self.on_failure(i, total, src_vfs, src, e)? {
+                        // Don't leave a bad copy behind; a retry then finds
+                        // no conflict.
+                        let _ = self.delete(dst_vfs, target, local);
+                        return Ok(());
+                    }
+                    self.transfer(i, total, src_vfs, src, dst_vfs, target, local, None)?;
+                }
+            }
+        }
+    }
+
+    /// Hash `src` and its copy `target`; returns the file size when they
+    /// match.
+    fn compare(&self, i: usize, total: usize, src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path) -> io::Result<u64> {
+        let mut len = 0;
+        let mut checkpoint = |done: u64| {
+            len = done;
+            if self.meter.borrow_mut().due() {
+                self.send(ProgressUpdate { processed: i, total, message: Some(format!("Verifying {}", src_vfs.display_path(src))), done: false, error: None, conflict: None, bytes: None, failed: None });
+            }
+            self.control.checkpoint()
+        };
+        let expected = crate::fs_op::checksum::xxh3_reader(&mut src_vfs.open_read(src)?, &mut checkpoint)?;
+        let actual = crate::fs_op::checksum::xxh3_reader(&mut dst_vfs.open_read(target)?, &mut |_| self.control.checkpoint())?;
+        if expected != actual {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "checksum mismatch between source and copy"));
+        }
+        Ok(len)
+    }
+
+    /// Failure hook for entries inside item `src`. A failure of `src`
+    /// itself is left to the item loop, so it is not reported twice.
+    #[allow(clippy::too_many_arguments)]
+    fn on_nested_failure(&self, i: usize, total: usize, vfs: &dyn Vfs, src: &Path, path: &Path, err: io::Error) -> io::Result<bool> {
+        if path == src {
+            return Err(err);
+        }
+        self.on_failure(i, total, vfs, path, err)
+    }
+
+    fn delete(&self, vfs: &dyn Vfs, path: &Path, local: bool) -> io::Result<()> {
+        if local {
+            crate::fs_op::remove::remove_path_with(path, &mut || self.control.checkpoint()).map_err(|e| e.0)
+        } else {
+            crate::vfs::remove_tree_with(vfs, path, &mut || self.control.checkpoint())
+        }
+    }
+
+    /// Overwrite, rename and remove `path` (see `fs_op::shred`), counting
+    /// the bytes written like a copy. Storage where overwriting may not
+    /// reach the old data is noted in `JobState::warnings`.
+    fn shred(&self, i: usize, total: usize, vfs: &dyn Vfs, path: &Path, passes: &[Pass], local: bool) -> io::Result<()> {
+        if !local {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "only local files can be shredded"));
+        }
+        if let Some(reason) = crate::fs_op::shred::weak_spot(path) {
+            self.warn(reason);
+        }
+        let mut checkpoint = |file: &Path, done: u64, len: u64| self.on_chunk(i, total, vfs, file, done, len);
+        crate::fs_op::shred::shred_path(path, passes, &mut checkpoint)
+    }
+
+    /// Add `warning` to `JobState::warnings` unless it is already there.
+    fn warn(&self, warning: String) {
+        if let Some(rec) = self.shared.inner.lock().unwrap().records.get_mut(&self.id) {
+            if !rec.state.warnings.contains(&warning) {
+                rec.state.warnings.push(warning);
+            }
+        }
+    }
+
+    /// Attach the current byte counters (with a fresh rate and ETA) to
+    /// `upd`, record it in the job's state and forward it to the ticket
+    /// holder.
+    fn send(&self, mut upd: ProgressUpdate) {
+        if upd.bytes.is_none() {
+            let mut meter = self.meter.borrow_mut();
+            let meter = &mut *meter;
+            if let Some(bytes) = meter.bytes.as_mut() {
+                if !bytes.scanning {
+                    bytes.rate = meter.throughput.sample(bytes.done);
+                    bytes.eta_secs = Throughput::eta(bytes.rate, bytes.total.saturating_sub(bytes.done));
+                }
+                upd.bytes = Some(*bytes);
+            }
+        }
+        {
+            let mut inner = self.shared.inner.lock().unwrap();
+            if let Some(rec) = inner.records.get_mut(&self.id) {
+                let st = &mut rec.state;
+                st.processed = upd.processed;
+                st.total = upd.total;
+                st.bytes = upd.bytes;
+                let pct = match upd.bytes.and_then(|b| b.percent()) {
+                    Some(p) => Some(p as usize),
+                    None => (upd.processed * 100).checked_div(upd.total),
+                };
+                if let Some(pct) = pct {
+                    st.progress = pct.min(100) as u8;
+                }
+                if let Some(msg) = &upd.message {
+                    st.message = msg.clone();
+                }
+                if upd.done {
+                    st.status = match &upd.error {
+                        None => {
+                            st.progress = 100;
+                            JobStatus::Completed
+                        }
+                        Some(e) if e.starts_with("Cancelled") => JobStatus::Cancelled,
+                        Some(e) => JobStatus::Failed(e.clone()),
+                    };
+                } else if st.status == JobStatus::Pending {
+                    st.status = JobStatus::Running;
+                }
+            }
+        }
+        let _ = self.tx.send(upd);
+    }
 }
 
-fn update_status(states: &Arc<Mutex<HashMap<JobId, JobState>>>, id: JobId, status: JobStatus) {
-    let mut guard = states.lock().unwrap();
-    if let Some(st) = guard.get_mut(&id) {
-        st.status = status;
+/// Copy a local file or tree. Journaled jobs (`partial` set) copy through
+/// partial files that survive an interruption, continuing the one the
+/// journal recorded if it still checks out; other jobs use plain atomic
+/// copies.
+fn copy_local(src: &Path, target: &Path, partial: Partial<'_>, checkpoint: &mut CopyCheckpoint<'_>, on_error: &mut FailureHook<'_>, on_skip: &mut SkipHook<'_>) -> io::Result<()> {
+    match (src.is_dir(), partial) {
+        (true, None) => crate::fs_op::copy::copy_recursive_with(src, target, checkpoint, on_error, on_skip),
+        (true, Some(resume)) => crate::fs_op::copy::copy_recursive_resumable(src, target, resume, checkpoint, on_error, on_skip),
+        (false, None) => {
+            crate::fs_op::helpers::ensure_parent_exists(target)?;
+            crate::fs_op::helpers::atomic_copy_file_with(src, target, checkpoint).map(|_| ())
+        }
+        (false, Some(resume)) => {
+            let at = resume.filter(|(file, _)| *file == src).map(|(_, offset)| offset);
+            crate::fs_op::helpers::resumable_copy_file_with(src, target, at, checkpoint).map(|_| ())
+        }
     }
 }
 
-fn update_status_running(states: &Arc<Mutex<HashMap<JobId, JobState>>>, id: JobId) {
-    let mut guard = states.lock().unwrap();
-    if let Some(st) = guard.get_mut(&id) {
-        st.status = JobStatus::Running;
-    } else {
-        guard.insert(id, JobState { status: JobStatus::Running, progress: 0 });
+/// How a journaled job copies a file or tree: `None` for a plain copy,
+/// otherwise through partial files, continuing the one the journal
+/// recorded (its source file and offset), if any.
+type Partial<'a> = Option<Option<(&'a Path, u64)>>;
+
+/// Total size of the files under `path`, links excluded, like the pre-scan
+/// counts a source.
+fn tree_bytes(vfs: &dyn Vfs, path: &Path) -> io::Result<u64> {
+    let entry = vfs.stat(path)?;
+    if !entry.is_dir {
+        return Ok(if crate::vfs::transfer::is_symlink_mode(entry.unix_mode) { 0 } else { entry.size });
     }
+    vfs.list(path)?.iter().map(|child| tree_bytes(vfs, &child.path)).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vfs::vfs_mem::MemVfs;
+    use std::fs;
+    use tempfile::tempdir;
 
     fn wait_for_status(q: &JobQueue, id: JobId, want: JobStatus) {
-        let deadline = std::time::Instant::now() + Duration::from_millis(500);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
         loop {
             if let Some(st) = q.state(id) {
                 if st.status == want {
@@ -174,34 +1384,538 @@ mod tests {
                 }
             }
             if std::time::Instant::now() > deadline {
-                panic!("timed out waiting for status {:?}", want);
+                panic!("timed out waiting for status {:?}, have {:?}", want, q.state(id));
             }
             thread::sleep(Duration::from_millis(5));
         }
     }
 
     #[test]
-    fn enqueue_and_complete_job() {
+    fn copy_move_and_delete_run_on_disk() {
+        let td = tempdir().unwrap();
+        let src = td.path().join("src");
+        let dst = td.path().join("dst");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::create_dir_all(&dst).unwrap();
+        fs::write(src.join("a.txt"), b"a").unwrap();
+        fs::write(src.join("nested/b.txt"), b"b").unwrap();
         let q = JobQueue::new();
-        let id = q.enqueue(JobKind::Delete { target: PathBuf::from("/tmp/x") });
+
+        let id = q.enqueue(JobKind::Copy { sources: vec![src.join("a.txt"), src.join("nested")], dst_dir: dst.clone() });
+        wait_for_status(&q, id, JobStatus::Completed);
+        assert_eq!(fs::read(dst.join("nested/b.txt")).unwrap(), b"b");
+        assert!(src.join("a.txt").exists());
+
+        let id = q.enqueue(JobKind::Move { sources: vec![src.join("a.txt")], dst_dir: dst.join("nested") });
+        wait_for_status(&q, id, JobStatus::Completed);
+        assert!(!src.join("a.txt").exists());
+        assert_eq!(fs::read(dst.join("nested/a.txt")).unwrap(), b"a");
+
+        let id = q.enqueue(JobKind::Delete { targets: vec![dst.join("nested")] });
         wait_for_status(&q, id, JobStatus::Completed);
+        assert!(!dst.join("nested").exists());
+        assert_eq!(q.state(id).unwrap().progress, 100);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn pause_and_resume_job() {
+    fn copied_trees_list_the_special_files_left_out() {
+        let td = tempdir().unwrap();
+        let src = td.path().join("src");
+        let dst = td.path().join("dst");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&dst).unwrap();
+        fs::write(src.join("a.txt"), b"a").unwrap();
+        let _listener = std::os::unix::net::UnixListener::bind(src.join("app.sock")).unwrap();
         let q = JobQueue::new();
-        let id = q.enqueue(JobKind::Copy { src: PathBuf::from("/a"), dst: PathBuf::from("/b") });
-        q.pause(id);
-        wait_for_status(&q, id, JobStatus::Paused);
-        q.resume(id);
+
+        let id = q.enqueue(JobKind::Copy { sources: vec![src.clone()], dst_dir: dst.clone() });
         wait_for_status(&q, id, JobStatus::Completed);
+        assert_eq!(q.state(id).unwrap().skipped, vec![(src.join("app.sock"), "socket")]);
+        assert_eq!(fs::read(dst.join("src/a.txt")).unwrap(), b"a");
+    }
+
+    /// Start copying a 1 MiB file over an existing one and pause the job
+    /// while it waits on the conflict. Once allowed to overwrite, it opens
+    /// the target and holds at the checkpoint before the first chunk.
+    fn held_copy(vfs: &MemVfs) -> (JobQueue, JobTicket) {
+        vfs.write_file("/src/big.bin", vec![1u8; 1024 * 1024]).unwrap();
+        vfs.write_file("/dst/big.bin", "old").unwrap();
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let q = JobQueue::new();
+        let ticket = q.submit(Job::on(JobKind::Copy { sources: vec![PathBuf::from("/src/big.bin")], dst_dir: PathBuf::from("/dst") }, shared.clone(), shared));
+        assert!(ticket.progress.recv_timeout(Duration::from_secs(5)).unwrap().conflict.is_some());
+        q.pause(ticket.id);
+        ticket.decisions.send(OperationDecision::Overwrite).unwrap();
+        while vfs.read_file("/dst/big.bin").map(|d| d.len()).unwrap_or(3) != 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(vfs.read_file("/dst/big.bin").unwrap().len(), 0);
+        (q, ticket)
+    }
+
+    #[test]
+    fn pause_and_resume_job() {
+        let vfs = MemVfs::new();
+        let (q, ticket) = held_copy(&vfs);
+        assert_eq!(q.state(ticket.id).unwrap().status, JobStatus::Paused);
+        q.resume(ticket.id);
+        wait_for_status(&q, ticket.id, JobStatus::Completed);
+        assert_eq!(vfs.read_file("/dst/big.bin").unwrap().len(), 1024 * 1024);
+    }
+
+    #[test]
+    fn copies_report_scanned_totals_and_byte_counters() {
+        let td = tempdir().unwrap();
+        let src = td.path().join("src");
+        fs::create_dir_all(src.join("tree")).unwrap();
+        fs::create_dir_all(td.path().join("dst")).unwrap();
+        fs::write(src.join("tree/a.bin"), vec![0u8; 200 * 1024]).unwrap();
+        fs::write(src.join("tree/b.bin"), vec![0u8; 56 * 1024]).unwrap();
+        fs::write(src.join("c.bin"), vec![0u8; 1024]).unwrap();
+        let q = JobQueue::new();
+        let ticket = q.submit(Job::local(JobKind::Copy { sources: vec![src.join("tree"), src.join("c.bin")], dst_dir: td.path().join("dst") }));
+
+        let updates: Vec<ProgressUpdate> = ticket.progress.iter().collect();
+        let first_item = updates.iter().find(|u| u.processed == 1).unwrap().bytes.unwrap();
+        assert!(!first_item.scanning);
+        assert_eq!((first_item.done, first_item.total), (256 * 1024, 257 * 1024));
+        let last = updates.last().unwrap();
+        assert!(last.done && last.error.is_none());
+        assert_eq!(last.bytes.map(|b| (b.done, b.total)), Some((257 * 1024, 257 * 1024)));
+        assert_eq!(q.state(ticket.id).unwrap().bytes, last.bytes);
+
+        // Deletes move no data and report items only.
+        let ticket = q.submit(Job::local(JobKind::Delete { targets: vec![td.path().join("dst")] }));
+        assert!(ticket.progress.iter().all(|u| u.bytes.is_none()));
+    }
+
+    #[test]
+    fn shred_jobs_count_every_pass_and_refuse_remote_files() {
+        let td = tempdir().unwrap();
+        fs::create_dir_all(td.path().join("tree")).unwrap();
+        fs::write(td.path().join("tree/a.bin"), vec![1u8; 3000]).unwrap();
+        fs::write(td.path().join("b.bin"), vec![2u8; 1000]).unwrap();
+        let q = JobQueue::new();
+        let kind = JobKind::Shred { targets: vec![td.path().join("tree"), td.path().join("b.bin")], method: ShredMethod::Zeros, passes: 2 };
+        assert_eq!((kind.title(), kind.summary()), ("Shredding", "Shred 2 items".to_string()));
+        let ticket = q.submit(Job::local(kind));
+        let updates: Vec<ProgressUpdate> = ticket.progress.iter().collect();
+        let last = updates.last().unwrap();
+        assert!(last.done && last.error.is_none(), "{:?}", last);
+        assert_eq!(last.bytes.map(|b| (b.done, b.total)), Some((8000, 8000)));
+        assert!(updates.iter().any(|u| u.message.as_deref() == Some(&format!("Shredded {}", td.path().join("b.bin").display()))));
+        assert_eq!(fs::read_dir(td.path()).unwrap().count(), 0);
+
+        let vfs = MemVfs::new();
+        vfs.write_file("/a.txt", "a").unwrap();
+        let vfs: Arc<dyn Vfs> = Arc::new(vfs);
+        let ticket = q.submit(Job::on(JobKind::Shred { targets: vec!["/a.txt".into()], method: ShredMethod::Random, passes: 1 }, vfs.clone(), vfs));
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert_eq!(last.error.as_deref(), Some("only local files can be shredded"));
+    }
+
+    #[test]
+    fn cancel_stops_a_running_copy_inside_a_file() {
+        let vfs = MemVfs::new();
+        let (q, ticket) = held_copy(&vfs);
+        q.cancel(ticket.id);
+        wait_for_status(&q, ticket.id, JobStatus::Cancelled);
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert_eq!(last.error.as_deref(), Some("Cancelled"));
+        assert!(vfs.read_file("/dst/big.bin").unwrap().is_empty());
+    }
+
+    #[test]
+    fn jobs_run_concurrently_and_are_listed_in_order() {
+        let td = tempdir().unwrap();
+        let q = JobQueue::new();
+        let vfs = MemVfs::new();
+        vfs.write_file("/src/held.bin", vec![0u8; 256 * 1024]).unwrap();
+        vfs.create_dir_all("/dst").unwrap();
+        let shared: Arc<dyn Vfs> = Arc::new(vfs);
+
+        // The first job waits on a conflict decision; the second still runs.
+        let held = q.submit(Job::on(JobKind::Copy { sources: vec![PathBuf::from("/src/held.bin")], dst_dir: PathBuf::from("/src") }, shared.clone(), shared));
+        let first = held.progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(first.conflict.is_some());
+        fs::write(td.path().join("gone.txt"), b"x").unwrap();
+        let quick = q.enqueue(JobKind::Delete { targets: vec![td.path().join("gone.txt")] });
+        wait_for_status(&q, quick, JobStatus::Completed);
+        assert_eq!(q.state(held.id).unwrap().status, JobStatus::Running);
+
+        let ids: Vec<JobId> = q.list().into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(ids, vec![held.id, quick]);
+        held.decisions.send(OperationDecision::Cancel).unwrap();
+        wait_for_status(&q, held.id, JobStatus::Cancelled);
+    }
+
+    /// A journal holding one interrupted copy of `src/big.bin` and
+    /// `src/small.txt` into `dst`, 128 KiB into the first file. Unless
+    /// `intact`, the partial file's first byte no longer matches the
+    /// source, as if the source changed after the interruption.
+    fn interrupted_copy(root: &Path, intact: bool) -> (JobJournal, JournalEntry) {
+        let (src, dst) = (root.join("src"), root.join("dst"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&dst).unwrap();
+        let data: Vec<u8> = (0..256 * 1024u32).map(|i| (i % 251) as u8).collect();
+        fs::write(src.join("big.bin"), &data).unwrap();
+        fs::write(src.join("small.txt"), b"small").unwrap();
+        let mut partial = data[..128 * 1024].to_vec();
+        if !intact {
+            partial[0] ^= 0xff;
+        }
+        fs::write(crate::fs_op::helpers::partial_path(&dst.join("big.bin")), partial).unwrap();
+
+        let journal = JobJournal::new(root.join("jobs"));
+        let mut entry = JournalEntry::new(1, JobKind::Copy { sources: vec![src.join("big.bin"), src.join("small.txt")], dst_dir: dst.clone() });
+        entry.pid = i32::MAX as u32;
+        entry.current = Some(0);
+        entry.partial = Some(PartialFile { src: src.join("big.bin"), dst: dst.join("big.bin"), offset: 128 * 1024 });
+        journal.save(&entry).unwrap();
+        (journal, entry)
+    }
+
+    #[test]
+    fn resumes_an_interrupted_copy_from_its_partial_file() {
+        for intact in [true, false] {
+            let td = tempdir().unwrap();
+            let (journal, _) = interrupted_copy(td.path(), intact);
+            let q = JobQueue::with_journal(journal.clone());
+            let entries = q.unfinished();
+            assert_eq!(entries.len(), 1);
+
+            let ticket = q.resume_unfinished(&entries[0]);
+            let last = ticket.progress.iter().find(|u| u.done).unwrap();
+            assert!(last.error.is_none(), "{:?}", last.error);
+            // A partial file that no longer matches is copied afresh.
+            assert_eq!(fs::read(td.path().join("dst/big.bin")).unwrap(), fs::read(td.path().join("src/big.bin")).unwrap());
+            assert_eq!(fs::read(td.path().join("dst/small.txt")).unwrap(), b"small");
+            assert!(!crate::fs_op::helpers::partial_path(&td.path().join("dst/big.bin")).exists());
+            // The entry goes once the worker settles the job.
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while fs::read_dir(journal.dir()).unwrap().next().is_some() {
+                assert!(std::time::Instant::now() < deadline, "journal entry left behind");
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    #[test]
+    fn only_a_partial_file_matching_its_journal_entry_is_continued() {
+        // (intact, journaled offset) and whether the copy continues.
+        for (intact, at, continues) in [(true, Some(128 * 1024), true), (false, Some(128 * 1024), false), (true, Some(64 * 1024), false), (true, None, false)] {
+            let td = tempdir().unwrap();
+            interrupted_copy(td.path(), intact);
+            let (src, dst) = (td.path().join("src/big.bin"), td.path().join("dst/big.bin"));
+            let mut first = None;
+            crate::fs_op::helpers::resumable_copy_file_with(&src, &dst, at, &mut |_, done, _| {
+                first.get_or_insert(done);
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(first, Some(if continues { 128 * 1024 } else { 0 }), "intact {} at {:?}", intact, at);
+            assert_eq!(fs::read(&dst).unwrap(), fs::read(&src).unwrap());
+        }
+    }
+
+    #[test]
+    fn skipping_a_failed_copy_removes_its_partial_file() {
+        let td = tempdir().unwrap();
+        let (journal, mut entry) = interrupted_copy(td.path(), true);
+        entry.error_policy = ErrorPolicy::Skip;
+        journal.save(&entry).unwrap();
+        // The partial file cannot be renamed over a non-empty directory.
+        fs::create_dir_all(td.path().join("dst/big.bin/inner")).unwrap();
+        let q = JobQueue::with_journal(journal);
+
+        let ticket = q.resume_unfinished(&q.unfinished()[0]);
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert_eq!(last.message.as_deref(), Some("Completed, 1 failed"));
+        assert!(!crate::fs_op::helpers::partial_path(&td.path().join("dst/big.bin")).exists());
+        assert_eq!(fs::read(td.path().join("dst/small.txt")).unwrap(), b"small");
+    }
+
+    #[test]
+    fn discarding_an_unfinished_job_removes_its_partial_file() {
+        let td = tempdir().unwrap();
+        let (journal, entry) = interrupted_copy(td.path(), true);
+        let q = JobQueue::with_journal(journal);
+        q.discard_unfinished(&entry).unwrap();
+        assert!(q.unfinished().is_empty());
+        assert!(!crate::fs_op::helpers::partial_path(&td.path().join("dst/big.bin")).exists());
+        assert!(!td.path().join("dst/big.bin").exists());
+    }
+
+    #[test]
+    fn reordered_jobs_start_in_their_new_order_and_failed_ones_retry() {
+        let td = tempdir().unwrap();
+        let vfs = MemVfs::new();
+        vfs.write_file("/src/held.bin", "held").unwrap();
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let q = JobQueue::new();
+        // Occupy every worker with a job waiting on a conflict.
+        let held: Vec<JobTicket> = (0..MAX_WORKERS)
+            .map(|_| q.submit(Job::on(JobKind::Copy { sources: vec![PathBuf::from("/src/held.bin")], dst_dir: PathBuf::from("/src") }, shared.clone(), shared.clone())))
+            .collect();
+        for t in &held {
+            assert!(t.progress.recv_timeout(Duration::from_secs(5)).unwrap().conflict.is_some());
+        }
+
+        let log = td.path().join("log");
+        fs::create_dir_all(&log).unwrap();
+        fs::write(td.path().join("a"), b"a").unwrap();
+        let first = q.enqueue(JobKind::Copy { sources: vec![td.path().join("a")], dst_dir: log.clone() });
+        let second = q.enqueue(JobKind::Delete { targets: vec![td.path().join("a")] });
+        q.move_up(second);
+        let ids: Vec<JobId> = q.list().into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(&ids[MAX_WORKERS..], &[second, first]);
+
+        // Free one worker: the delete now runs first, so the copy fails.
+        held[0].decisions.send(OperationDecision::Cancel).unwrap();
+        wait_for_status(&q, second, JobStatus::Completed);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !matches!(q.state(first).unwrap().status, JobStatus::Failed(_)) {
+            assert!(std::time::Instant::now() < deadline, "copy did not fail");
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(!log.join("a").exists());
+
+        fs::write(td.path().join("a"), b"again").unwrap();
+        let retried = q.retry(first).expect("failed jobs can be retried");
+        wait_for_status(&q, retried.id, JobStatus::Completed);
+        assert_eq!(fs::read(log.join("a")).unwrap(), b"again");
+        assert!(q.retry(retried.id).is_none());
+        for t in &held[1..] {
+            t.decisions.send(OperationDecision::Cancel).unwrap();
+        }
+    }
+
+    /// A tree `/src/tree` with one unreadable file, plus `/src/solo.txt`.
+    fn tree_with_bad_file() -> MemVfs {
+        let vfs = MemVfs::new();
+        for name in ["a.txt", "bad.txt", "c.txt"] {
+            vfs.write_file(Path::new("/src/tree").join(name), name).unwrap();
+        }
+        vfs.write_file("/src/solo.txt", "solo").unwrap();
+        vfs.create_dir_all("/dst").unwrap();
+        vfs.fail(crate::vfs::vfs_mem::MemOp::Read, "/src/tree/bad.txt", io::ErrorKind::PermissionDenied);
+        vfs
+    }
+
+    #[test]
+    fn skipped_failures_are_listed_and_retried_on_their_own() {
+        let vfs = tree_with_bad_file();
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let q = JobQueue::new();
+        let kind = JobKind::Copy { sources: vec![PathBuf::from("/src/tree"), PathBuf::from("/src/solo.txt")], dst_dir: PathBuf::from("/dst") };
+        let ticket = q.submit(Job::on(kind, shared.clone(), shared.clone()).with_error_policy(ErrorPolicy::Skip));
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert_eq!((last.error.as_deref(), last.message.as_deref()), (None, Some("Completed, 1 failed")));
+
+        let state = q.state(ticket.id).unwrap();
+        assert_eq!(state.status, JobStatus::Completed);
+        let failure = &state.failures[..];
+        assert_eq!(failure.len(), 1);
+        assert_eq!((failure[0].path.as_path(), failure[0].target.as_deref(), failure[0].operation), (Path::new("/src/tree/bad.txt"), Some(Path::new("/dst/tree/bad.txt")), "copy"));
+        assert!(failure[0].error.contains("Permission denied"), "{}", failure[0].error);
+        assert!(vfs.exists("/dst/tree/a.txt") && vfs.exists("/dst/tree/c.txt") && vfs.exists("/dst/solo.txt"));
+        assert!(!vfs.exists("/dst/tree/bad.txt"));
+
+        vfs.heal("/src/tree/bad.txt");
+        let retried = q.retry_failed(ticket.id).expect("the job has failures");
+        wait_for_status(&q, retried.id, JobStatus::Completed);
+        let (_, kind, state) = q.list().into_iter().find(|(id, _, _)| *id == retried.id).unwrap();
+        assert_eq!(kind, JobKind::Transfer { moving: false, pairs: vec![("/src/tree/bad.txt".into(), "/dst/tree/bad.txt".into())] });
+        assert!(state.failures.is_empty());
+        assert_eq!(vfs.read_file("/dst/tree/bad.txt").unwrap(), b"bad.txt");
+        assert!(q.retry_failed(retried.id).is_none());
+    }
+
+    #[test]
+    fn asked_failures_wait_for_retry_or_skip() {
+        let vfs = tree_with_bad_file();
+        // Force the move to copy the tree rather than rename it.
+        vfs.fail(crate::vfs::vfs_mem::MemOp::Rename, "/src/tree", io::ErrorKind::Unsupported);
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let q = JobQueue::new();
+        let kind = JobKind::Move { sources: vec![PathBuf::from("/src/tree")], dst_dir: PathBuf::from("/dst") };
+        let ticket = q.submit(Job::on(kind, shared.clone(), shared).with_error_policy(ErrorPolicy::Ask));
+        let asked = |ticket: &JobTicket| ticket.progress.iter().find_map(|u| u.failed).unwrap();
+
+        // A retry that fails again asks again; skipping carries on.
+        assert_eq!(asked(&ticket).path, Path::new("/src/tree/bad.txt"));
+        ticket.decisions.send(OperationDecision::Retry).unwrap();
+        assert_eq!(asked(&ticket).operation, "move");
+        ticket.decisions.send(OperationDecision::Skip).unwrap();
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert!(last.error.is_none(), "{:?}", last.error);
+        assert_eq!(q.state(ticket.id).unwrap().failures.len(), 1);
+        // The partly moved source is kept.
+        assert!(vfs.exists("/src/tree/a.txt") && vfs.exists("/dst/tree/a.txt"));
+
+        // Retrying after the cause is fixed only moves the failed file.
+        vfs.heal("/src/tree/bad.txt");
+        let retried = q.retry_failed(ticket.id).unwrap();
+        wait_for_status(&q, retried.id, JobStatus::Completed);
+        assert!(!vfs.exists("/src/tree/bad.txt"));
+        assert_eq!(vfs.read_file("/dst/tree/bad.txt").unwrap(), b"bad.txt");
+    }
+
+    #[test]
+    fn verified_moves_keep_sources_whose_copy_does_not_match() {
+        let vfs = MemVfs::new();
+        for name in ["a.txt", "bad.txt"] {
+            vfs.write_file(Path::new("/src/tree").join(name), name).unwrap();
+        }
+        vfs.write_file("/src/solo.txt", "solo").unwrap();
+        vfs.create_dir_all("/dst").unwrap();
+        // Force every move to copy rather than rename.
+        for path in ["/src/tree", "/src/tree/bad.txt", "/src/solo.txt"] {
+            vfs.fail(crate::vfs::vfs_mem::MemOp::Rename, path, io::ErrorKind::Unsupported);
+        }
+        vfs.corrupt_writes("/dst/tree/bad.txt");
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let q = JobQueue::new();
+        let kind = JobKind::Move { sources: vec![PathBuf::from("/src/tree"), PathBuf::from("/src/solo.txt")], dst_dir: PathBuf::from("/dst") };
+        let ticket = q.submit(Job::on(kind, shared.clone(), shared).with_error_policy(ErrorPolicy::Skip).with_verify(true));
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert_eq!(last.message.as_deref(), Some("Completed, 1 failed"));
+
+        let state = q.state(ticket.id).unwrap();
+        assert_eq!(state.verified, Some(Verified { files: 2, bytes: 9 }));
+        assert_eq!(state.failures.len(), 1);
+        assert_eq!(state.failures[0].path, Path::new("/src/tree/bad.txt"));
+        assert!(state.failures[0].error.contains("checksum mismatch"), "{}", state.failures[0].error);
+        // The verified file is moved; the tree with the bad copy is kept and
+        // the bad copy removed.
+        assert!(!vfs.exists("/src/solo.txt") && vfs.exists("/dst/solo.txt"));
+        assert!(vfs.exists("/src/tree/bad.txt") && vfs.exists("/src/tree/a.txt"));
+        assert!(vfs.exists("/dst/tree/a.txt") && !vfs.exists("/dst/tree/bad.txt"));
+
+        // Once the target behaves, retrying rewrites and verifies the file.
+        vfs.heal("/dst/tree/bad.txt");
+        let retried = q.retry_failed(ticket.id).unwrap();
+        wait_for_status(&q, retried.id, JobStatus::Completed);
+        let state = q.state(retried.id).unwrap();
+        assert_eq!((state.verified, state.failures.len()), (Some(Verified { files: 1, bytes: 7 }), 0));
+        assert_eq!(vfs.read_file("/dst/tree/bad.txt").unwrap(), b"bad.txt");
+        assert!(!vfs.exists("/src/tree/bad.txt"));
+    }
+
+    /// `/dst/a.txt` and `/dst/b.txt` already exist. `/src/a.txt` is newer
+    /// with the same size, `/src/b.txt` older with a different size.
+    fn conflicting_tree() -> MemVfs {
+        use chrono::{Local, TimeZone, Utc};
+        let noon = |h| Utc.with_ymd_and_hms(2024, 1, 1, h, 0, 0).unwrap().with_timezone(&Local);
+        let vfs = MemVfs::new();
+        vfs.set_clock(noon(12));
+        vfs.write_file("/dst/a.txt", "old a").unwrap();
+        vfs.write_file("/dst/b.txt", "old b!").unwrap();
+        vfs.set_clock(noon(13));
+        vfs.write_file("/src/a.txt", "new a").unwrap();
+        vfs.set_clock(noon(11));
+        vfs.write_file("/src/b.txt", "new b").unwrap();
+        vfs
+    }
+
+    /// Copy `/src/a.txt` and `/src/b.txt` to `/dst` with `policy` and
+    /// return every update the job sent.
+    fn copy_conflicting(vfs: &MemVfs, policy: ConflictPolicy) -> Vec<ProgressUpdate> {
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let kind = JobKind::Copy { sources: vec![PathBuf::from("/src/a.txt"), PathBuf::from("/src/b.txt")], dst_dir: PathBuf::from("/dst") };
+        let q = JobQueue::new();
+        let ticket = q.submit(Job::on(kind, shared.clone(), shared).with_conflict_policy(policy));
+        let mut updates = Vec::new();
+        for update in ticket.progress.iter() {
+            let done = update.done;
+            updates.push(update);
+            if done {
+                break;
+            }
+        }
+        updates
+    }
+
+    #[test]
+    fn conflict_policies_settle_existing_targets_without_asking() {
+        let read = |vfs: &MemVfs, path: &str| String::from_utf8(vfs.read_file(path).unwrap()).unwrap();
+        let cases: [(ConflictPolicy, &[(&str, &str)]); 5] = [
+            (ConflictPolicy::OverwriteIfNewer, &[("/dst/a.txt", "new a"), ("/dst/b.txt", "old b!")]),
+            (ConflictPolicy::OverwriteIfSizeDiffers, &[("/dst/a.txt", "old a"), ("/dst/b.txt", "new b")]),
+            (ConflictPolicy::RenameNew, &[("/dst/a.txt", "old a"), ("/dst/a (1).txt", "new a"), ("/dst/b (1).txt", "new b")]),
+            (ConflictPolicy::RenameExisting, &[("/dst/a.txt", "new a"), ("/dst/a (1).txt", "old a"), ("/dst/b (1).txt", "old b!")]),
+            (ConflictPolicy::Skip, &[("/dst/a.txt", "old a"), ("/dst/b.txt", "old b!")]),
+        ];
+        for (policy, expected) in cases {
+            let vfs = conflicting_tree();
+            let updates = copy_conflicting(&vfs, policy);
+            assert!(updates.iter().all(|u| u.conflict.is_none()), "{:?} asked", policy);
+            assert_eq!(updates.last().unwrap().message.as_deref(), Some("Completed"), "{:?}", policy);
+            for (path, contents) in expected {
+                assert_eq!(read(&vfs, path), *contents, "{:?}: {}", policy, path);
+            }
+        }
+        let skipped: Vec<_> = copy_conflicting(&conflicting_tree(), ConflictPolicy::OverwriteIfNewer).into_iter().filter_map(|u| u.message).filter(|m| m.starts_with("Skipped")).collect();
+        assert_eq!(skipped, vec!["Skipped mem:///src/b.txt (not newer)"]);
+    }
+
+    #[test]
+    fn an_all_decision_becomes_the_policy_for_later_conflicts() {
+        let vfs = conflicting_tree();
+        let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+        let kind = JobKind::Copy { sources: vec![PathBuf::from("/src/a.txt"), PathBuf::from("/src/b.txt")], dst_dir: PathBuf::from("/dst") };
+        let q = JobQueue::new();
+        let ticket = q.submit(Job::on(kind, shared.clone(), shared));
+        assert_eq!(ticket.progress.iter().find_map(|u| u.conflict).unwrap().target, Path::new("/dst/a.txt"));
+        ticket.decisions.send(OperationDecision::RenameNewAll).unwrap();
+        let rest: Vec<ProgressUpdate> = ticket.progress.iter().take_while(|u| !u.done).collect();
+        assert!(rest.iter().all(|u| u.conflict.is_none()));
+        assert!(vfs.exists("/dst/a (1).txt") && vfs.exists("/dst/b (1).txt"));
+    }
+
+    #[test]
+    fn resume_appends_to_a_truncated_target() {
+        let td = tempdir().unwrap();
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 253) as u8).collect();
+        fs::create_dir(td.path().join("dst")).unwrap();
+        fs::write(td.path().join("big.bin"), &data).unwrap();
+        fs::write(td.path().join("other.bin"), &data).unwrap();
+        // One target holds a prefix of its source, the other stray bytes.
+        fs::write(td.path().join("dst/big.bin"), &data[..150_000]).unwrap();
+        fs::write(td.path().join("dst/other.bin"), b"not a prefix").unwrap();
+        #[cfg(unix)]
+        let inode = |name: &str| std::os::unix::fs::MetadataExt::ino(&fs::metadata(td.path().join("dst").join(name)).unwrap());
+        #[cfg(unix)]
+        let before = inode("big.bin");
+
+        let kind = JobKind::Copy { sources: vec![td.path().join("big.bin"), td.path().join("other.bin")], dst_dir: td.path().join("dst") };
+        let q = JobQueue::new();
+        let ticket = q.submit(Job::local(kind).with_conflict_policy(ConflictPolicy::Resume));
+        let last = ticket.progress.iter().find(|u| u.done).unwrap();
+        assert_eq!(last.message.as_deref(), Some("Completed"));
+        assert_eq!(fs::read(td.path().join("dst/big.bin")).unwrap(), data);
+        assert_eq!(fs::read(td.path().join("dst/other.bin")).unwrap(), data);
+        // Continued in place rather than replaced by a fresh copy.
+        #[cfg(unix)]
+        assert_eq!(inode("big.bin"), before);
     }
 
     #[test]
     fn cancel_job() {
         let q = JobQueue::new();
-        let id = q.enqueue(JobKind::Move { src: PathBuf::from("/a"), dst: PathBuf::from("/b") });
+        let id = q.enqueue(JobKind::Move { sources: vec![PathBuf::from("/a")], dst_dir: PathBuf::from("/b") });
+        q.pause(id);
         q.cancel(id);
-        wait_for_status(&q, id, JobStatus::Cancelled);
+        // Depending on whether a worker picked the job up first it is either
+        // cancelled or fails on the missing source; it never completes.
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !q.state(id).unwrap().status.is_finished() {
+            assert!(std::time::Instant::now() < deadline, "job did not finish");
+            thread::sleep(Duration::from_millis(5));
+        }
+        let st = q.state(id).unwrap();
+        assert!(matches!(st.status, JobStatus::Cancelled | JobStatus::Failed(_)), "{:?}", st);
     }
 }
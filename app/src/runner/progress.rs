@@ -1,5 +1,8 @@
+use crate::app::types::Entry;
+use crate::vfs::Vfs;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// User decision sent from the UI to a background worker when a
 /// conflicting target is reported during a file operation.
@@ -14,6 +17,13 @@ use std::path::PathBuf;
 /// - `OverwriteAll`: overwrite this and all subsequent conflicts.
 /// - `SkipAll`: skip this and all subsequent conflicts.
 /// - `Cancel`: abort the whole operation.
+/// - `RenameNew`, `RenameExisting`, `OverwriteIfNewer`,
+///   `OverwriteIfSizeDiffers` and `Resume` (and their `...All` forms): the
+///   other `ConflictPolicy` choices, for this or every later conflict.
+///
+/// The same channel answers a failed item (`ProgressUpdate::failed`):
+/// `Retry` tries it again, `Skip` and `SkipAll` move on and `Cancel`
+/// aborts.
 // (see `OperationDecision` above)
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum OperationDecision {
@@ -31,6 +41,61 @@ pub enum OperationDecision {
 
     /// Cancel the whole operation immediately.
     Cancel,
+
+    /// Try a failed item again.
+    Retry,
+
+    /// Keep both: write this item next to the target as `name (1).ext`.
+    RenameNew,
+
+    /// Keep both: move the target aside to `name (1).ext`, then write this
+    /// item.
+    RenameExisting,
+
+    /// Overwrite this target only if the source is newer.
+    OverwriteIfNewer,
+
+    /// Overwrite this target only if its size differs from the source.
+    OverwriteIfSizeDiffers,
+
+    /// Continue this truncated target instead of starting over.
+    Resume,
+
+    /// `RenameNew` for this and all subsequent conflicts.
+    RenameNewAll,
+
+    /// `RenameExisting` for this and all subsequent conflicts.
+    RenameExistingAll,
+
+    /// `OverwriteIfNewer` for this and all subsequent conflicts.
+    OverwriteIfNewerAll,
+
+    /// `OverwriteIfSizeDiffers` for this and all subsequent conflicts.
+    OverwriteIfSizeDiffersAll,
+
+    /// `Resume` for this and all subsequent conflicts.
+    ResumeAll,
+}
+
+impl OperationDecision {
+    /// The conflict policy this decision applies and whether it applies to
+    /// every later conflict as well. `None` for `Cancel` and `Retry`.
+    #[must_use]
+    pub fn conflict_policy(self) -> Option<(ConflictPolicy, bool)> {
+        use OperationDecision::*;
+        let policy = match self {
+            Overwrite | OverwriteAll => ConflictPolicy::Overwrite,
+            Skip | SkipAll => ConflictPolicy::Skip,
+            RenameNew | RenameNewAll => ConflictPolicy::RenameNew,
+            RenameExisting | RenameExistingAll => ConflictPolicy::RenameExisting,
+            OverwriteIfNewer | OverwriteIfNewerAll => ConflictPolicy::OverwriteIfNewer,
+            OverwriteIfSizeDiffers | OverwriteIfSizeDiffersAll => ConflictPolicy::OverwriteIfSizeDiffers,
+            Resume | ResumeAll => ConflictPolicy::Resume,
+            Cancel | Retry => return None,
+        };
+        let all = matches!(self, OverwriteAll | SkipAll | RenameNewAll | RenameExistingAll | OverwriteIfNewerAll | OverwriteIfSizeDiffersAll | ResumeAll);
+        Some((policy, all))
+    }
 }
 
 impl fmt::Display for OperationDecision {
@@ -42,11 +107,100 @@ impl fmt::Display for OperationDecision {
             OverwriteAll => "OverwriteAll",
             SkipAll => "SkipAll",
             Cancel => "Cancel",
+            Retry => "Retry",
+            RenameNew => "RenameNew",
+            RenameExisting => "RenameExisting",
+            OverwriteIfNewer => "OverwriteIfNewer",
+            OverwriteIfSizeDiffers => "OverwriteIfSizeDiffers",
+            Resume => "Resume",
+            RenameNewAll => "RenameNewAll",
+            RenameExistingAll => "RenameExistingAll",
+            OverwriteIfNewerAll => "OverwriteIfNewerAll",
+            OverwriteIfSizeDiffersAll => "OverwriteIfSizeDiffersAll",
+            ResumeAll => "ResumeAll",
         };
         write!(f, "{}", s)
     }
 }
 
+/// How a job settles a target that already exists, either decided up front
+/// (the `conflict_policy` setting) or per conflict from the dialog.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Report the conflict and wait for an `OperationDecision`.
+    #[default]
+    Ask,
+    /// Replace the target.
+    Overwrite,
+    /// Leave the target alone and skip the item.
+    Skip,
+    /// Write the item as `name (1).ext` (the first free number).
+    RenameNew,
+    /// Move the target to `name (1).ext`, then write the item.
+    RenameExisting,
+    /// Replace the target if the source was modified later, otherwise skip.
+    OverwriteIfNewer,
+    /// Replace the target if its size differs from the source, otherwise
+    /// skip. Directories compare the total size of their files.
+    OverwriteIfSizeDiffers,
+    /// Append the missing end of a shorter target file, provided the data
+    /// it holds still matches the source; anything else is overwritten.
+    Resume,
+}
+
+impl ConflictPolicy {
+    /// Every policy a conflict can be settled with, in dialog order.
+    pub const CHOICES: [ConflictPolicy; 7] = [
+        ConflictPolicy::Overwrite,
+        ConflictPolicy::Skip,
+        ConflictPolicy::RenameNew,
+        ConflictPolicy::RenameExisting,
+        ConflictPolicy::OverwriteIfNewer,
+        ConflictPolicy::OverwriteIfSizeDiffers,
+        ConflictPolicy::Resume,
+    ];
+
+    /// Short button label, e.g. `"Keep both"`.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictPolicy::Ask => "Ask",
+            ConflictPolicy::Overwrite => "Overwrite",
+            ConflictPolicy::Skip => "Skip",
+            ConflictPolicy::RenameNew => "Keep both",
+            ConflictPolicy::RenameExisting => "Rename existing",
+            ConflictPolicy::OverwriteIfNewer => "If newer",
+            ConflictPolicy::OverwriteIfSizeDiffers => "If size differs",
+            ConflictPolicy::Resume => "Resume",
+        }
+    }
+
+    /// The decision that applies this policy to one conflict, or to every
+    /// later one with `all`. `None` for `Ask`.
+    #[must_use]
+    pub fn decision(self, all: bool) -> Option<OperationDecision> {
+        use OperationDecision::*;
+        Some(match (self, all) {
+            (ConflictPolicy::Ask, _) => return None,
+            (ConflictPolicy::Overwrite, false) => Overwrite,
+            (ConflictPolicy::Overwrite, true) => OverwriteAll,
+            (ConflictPolicy::Skip, false) => Skip,
+            (ConflictPolicy::Skip, true) => SkipAll,
+            (ConflictPolicy::RenameNew, false) => RenameNew,
+            (ConflictPolicy::RenameNew, true) => RenameNewAll,
+            (ConflictPolicy::RenameExisting, false) => RenameExisting,
+            (ConflictPolicy::RenameExisting, true) => RenameExistingAll,
+            (ConflictPolicy::OverwriteIfNewer, false) => OverwriteIfNewer,
+            (ConflictPolicy::OverwriteIfNewer, true) => OverwriteIfNewerAll,
+            (ConflictPolicy::OverwriteIfSizeDiffers, false) => OverwriteIfSizeDiffers,
+            (ConflictPolicy::OverwriteIfSizeDiffers, true) => OverwriteIfSizeDiffersAll,
+            (ConflictPolicy::Resume, false) => Resume,
+            (ConflictPolicy::Resume, true) => ResumeAll,
+        })
+    }
+}
+
 /// ProgressUpdate is sent by background workers to the UI to report
 /// progress and to request conflict resolution.
 ///
@@ -57,13 +211,17 @@ impl fmt::Display for OperationDecision {
 ///   due to an error/cancellation).
 /// - `error`: optional error message when `done == true` and an error
 ///   occurred.
-/// - `conflict`: when `Some(path)`, the worker is blocked waiting for
-///   an `OperationDecision` from the UI for that `path`.
+/// - `conflict`: when `Some(conflict)`, the worker is blocked waiting for
+///   an `OperationDecision` from the UI about `conflict.target`.
+/// - `bytes`: byte counters, throughput and ETA for workers that move file
+///   data; `None` for item-only progress.
+/// - `failed`: when `Some(item)`, an item failed and the worker is waiting
+///   for an `OperationDecision` about it (see `job_queue::ErrorPolicy`).
 ///
 /// Example sequence:
 /// 1. Worker -> ProgressUpdate { processed:0, total:N, message:Some("Starting"), done:false, conflict:None }
 /// 2. Worker -> ProgressUpdate { processed:i, total:N, message:Some("Copied ..."), done:false, conflict:None }
-/// 3. Worker -> ProgressUpdate { processed:i, total:N, message:Some("Conflict"), done:false, conflict:Some(path) }
+/// 3. Worker -> ProgressUpdate { processed:i, total:N, message:Some("Conflict"), done:false, conflict:Some(Conflict { target, .. }) }
 /// 4. UI -> OperationDecision::Skip (sent via decision channel)
 /// 5. Worker continues, eventually sending ProgressUpdate { processed:N, total:N, done:true }
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -84,30 +242,206 @@ pub struct ProgressUpdate {
     /// Optional error message when `done == true` and an error occurred.
     pub error: Option<String>,
 
-    /// If present, the worker has hit a conflict for this `PathBuf` and is
-    /// waiting for an `OperationDecision` from the UI thread.
-    pub conflict: Option<PathBuf>,
+    /// If present, the worker has found an existing target and is waiting
+    /// for an `OperationDecision` from the UI thread.
+    pub conflict: Option<Conflict>,
+
+    /// Byte-level progress, when the worker tracks it.
+    pub bytes: Option<ByteProgress>,
+
+    /// If present, this item failed and the worker is waiting for an
+    /// `OperationDecision` (retry, skip or cancel) from the UI thread.
+    pub failed: Option<FailedItem>,
+}
+
+/// Files larger than this are not hashed for `Conflict::same_content`.
+const QUICK_COMPARE_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Both sides of a conflict, so the dialog can show them side by side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The item being written; `None` when it is created rather than
+    /// copied (a new archive).
+    pub src: Option<PathBuf>,
+    /// The target that already exists.
+    pub target: PathBuf,
+    /// Metadata of each side, `None` when it could not be read.
+    pub src_entry: Option<Entry>,
+    pub target_entry: Option<Entry>,
+    /// Whether both sides hold the same bytes; `None` when not checked
+    /// (directories, or files over 16 MiB of equal size).
+    pub same_content: Option<bool>,
+}
+
+impl Conflict {
+    /// Describe writing `src` over the existing `target`. Regular files of
+    /// equal size are hashed to tell whether they are identical.
+    pub fn inspect(src_vfs: &dyn Vfs, src: &Path, dst_vfs: &dyn Vfs, target: &Path) -> Self {
+        let mut conflict = Conflict { src: Some(src.to_path_buf()), target: target.to_path_buf(), src_entry: src_vfs.stat(src).ok(), target_entry: dst_vfs.stat(target).ok(), same_content: None };
+        if let (true, Some(a), Some(b)) = (conflict.both_files(), &conflict.src_entry, &conflict.target_entry) {
+            conflict.same_content = if a.size != b.size {
+                Some(false)
+            } else if a.size > QUICK_COMPARE_LIMIT {
+                None
+            } else {
+                let digest = |vfs: &dyn Vfs, path: &Path| vfs.open_read(path).and_then(|mut r| crate::fs_op::checksum::xxh3_reader(&mut r, &mut |_| Ok(())));
+                match (digest(src_vfs, src), digest(dst_vfs, target)) {
+                    (Ok(a), Ok(b)) => Some(a == b),
+                    _ => None,
+                }
+            };
+        }
+        conflict
+    }
+
+    /// Whether both sides are regular files, which can be compared.
+    #[must_use]
+    pub fn both_files(&self) -> bool {
+        let regular = |e: &Option<Entry>| e.as_ref().is_some_and(|e| !e.is_dir && e.unix_mode.is_none_or(|m| m & 0o170000 == 0o100000));
+        regular(&self.src_entry) && regular(&self.target_entry)
+    }
+
+    /// A conflict with nothing known but the existing `target`'s metadata.
+    pub fn target_only(target: PathBuf, target_entry: Option<Entry>) -> Self {
+        Conflict { src: None, target, src_entry: None, target_entry, same_content: None }
+    }
+}
+
+/// An item an operation could not process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailedItem {
+    /// Source path of the item (a file inside a copied tree when only that
+    /// file failed).
+    pub path: PathBuf,
+    /// Where the item was being copied or moved to; `None` for deletes.
+    pub target: Option<PathBuf>,
+    /// What was being done: `"copy"`, `"move"` or `"delete"`.
+    pub operation: &'static str,
+    /// The error, rendered by `errors::render_io_error`.
+    pub error: String,
+}
+
+impl fmt::Display for FailedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.operation, self.path.display(), self.error)
+    }
+}
+
+/// Byte counters reported alongside the item counts of a `ProgressUpdate`.
+///
+/// While `scanning` is set the worker is still totalling the size of the
+/// sources: `total` grows and the other counters stay at zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ByteProgress {
+    /// Bytes written of the file currently being copied.
+    pub file_done: u64,
+    /// Size of the file currently being copied.
+    pub file_total: u64,
+    /// Bytes written for the whole operation.
+    pub done: u64,
+    /// Bytes the whole operation will write (0 when unknown).
+    pub total: u64,
+    /// Smoothed throughput in bytes per second.
+    pub rate: u64,
+    /// Estimated seconds remaining, once a rate is known.
+    pub eta_secs: Option<u64>,
+    /// Whether the pre-scan that computes `total` is still running.
+    pub scanning: bool,
+}
+
+impl ByteProgress {
+    /// Completion of the current file in percent, if its size is known.
+    #[must_use]
+    pub fn file_percent(&self) -> Option<f64> {
+        if self.file_total == 0 { None } else { Some((self.file_done.min(self.file_total) as f64 / self.file_total as f64) * 100.0) }
+    }
+
+    /// Completion of the whole operation in percent, if its size is known.
+    #[must_use]
+    pub fn percent(&self) -> Option<f64> {
+        if self.total == 0 || self.scanning { None } else { Some((self.done.min(self.total) as f64 / self.total as f64) * 100.0) }
+    }
+}
+
+/// Exponentially smoothed transfer rate computed from a running byte count.
+///
+/// Samples closer together than `Throughput::WINDOW` are folded into the
+/// next one so bursts of small chunks do not make the rate jitter.
+#[derive(Clone, Debug)]
+pub struct Throughput {
+    last: Instant,
+    last_bytes: u64,
+    rate: f64,
+}
+
+impl Throughput {
+    /// Minimum time between two rate samples.
+    pub const WINDOW: Duration = Duration::from_millis(250);
+    /// Weight of the newest sample in the moving average.
+    const ALPHA: f64 = 0.3;
+
+    /// Start measuring from zero bytes now.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    /// Start measuring from zero bytes at `now`.
+    #[must_use]
+    pub fn starting_at(now: Instant) -> Self {
+        Self { last: now, last_bytes: 0, rate: 0.0 }
+    }
+
+    /// Record that `done` bytes have been transferred in total at `now` and
+    /// return the smoothed rate in bytes per second.
+    pub fn sample_at(&mut self, done: u64, now: Instant) -> u64 {
+        let elapsed = now.saturating_duration_since(self.last);
+        if elapsed >= Self::WINDOW {
+            let instant = done.saturating_sub(self.last_bytes) as f64 / elapsed.as_secs_f64();
+            self.rate = if self.rate == 0.0 { instant } else { Self::ALPHA * instant + (1.0 - Self::ALPHA) * self.rate };
+            self.last = now;
+            self.last_bytes = done;
+        }
+        self.rate as u64
+    }
+
+    /// `sample_at` with the current time.
+    pub fn sample(&mut self, done: u64) -> u64 {
+        self.sample_at(done, Instant::now())
+    }
+
+    /// Seconds needed for `remaining` bytes at `rate` bytes per second.
+    #[must_use]
+    pub fn eta(rate: u64, remaining: u64) -> Option<u64> {
+        if rate == 0 { None } else { Some(remaining.div_ceil(rate)) }
+    }
+}
+
+impl Default for Throughput {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ProgressUpdate {
     /// Create a new progress update with minimal state.
     #[must_use]
     pub fn new(processed: usize, total: usize) -> Self {
-        Self { processed, total, message: None, done: false, error: None, conflict: None }
+        Self { processed, total, message: None, done: false, error: None, conflict: None, bytes: None, failed: None }
     }
 
     /// Create a progress update that marks the operation done with an optional
     /// error message.
     #[must_use]
     pub fn done_with_error(processed: usize, total: usize, error: Option<String>) -> Self {
-        Self { processed, total, message: error.clone(), done: true, error, conflict: None }
+        Self { processed, total, message: error.clone(), done: true, error, conflict: None, bytes: None, failed: None }
     }
 
     /// Convenience constructor for a conflict update. The returned struct has
     /// `done == false` and `error == None`.
     #[must_use]
-    pub fn conflict(path: PathBuf, processed: usize, total: usize, message: Option<String>) -> Self {
-        Self { processed, total, message, done: false, error: None, conflict: Some(path) }
+    pub fn conflict(conflict: Conflict, processed: usize, total: usize, message: Option<String>) -> Self {
+        Self { processed, total, message, done: false, error: None, conflict: Some(conflict), bytes: None, failed: None }
     }
 
     /// Returns true if the operation is finished.
@@ -135,8 +469,8 @@ impl fmt::Display for ProgressUpdate {
             } else {
                 write!(f, "done {}/{} ({})", self.processed, self.total, self.message.as_deref().unwrap_or(""))
             }
-        } else if let Some(path) = &self.conflict {
-            write!(f, "{}/{} - conflict: {} ({})", self.processed, self.total, path.display(), self.message.as_deref().unwrap_or(""))
+        } else if let Some(conflict) = &self.conflict {
+            write!(f, "{}/{} - conflict: {} ({})", self.processed, self.total, conflict.target.display(), self.message.as_deref().unwrap_or(""))
         } else {
             write!(f, "{}/{} ({})", self.processed, self.total, self.message.as_deref().unwrap_or(""))
         }
@@ -145,8 +479,9 @@ impl fmt::Display for ProgressUpdate {
 
 #[cfg(test)]
 mod tests {
-    use super::{OperationDecision, ProgressUpdate};
+    use super::{ByteProgress, Conflict, ConflictPolicy, OperationDecision, ProgressUpdate, Throughput};
     use std::path::PathBuf;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn decision_is_copy_and_display() {
@@ -157,13 +492,44 @@ mod tests {
         assert_eq!(format!("{}", d), "OverwriteAll");
     }
 
+    #[test]
+    fn conflicts_compare_both_sides() {
+        let vfs = crate::vfs::vfs_mem::MemVfs::new();
+        vfs.write_file("/a/x", "same").unwrap();
+        vfs.write_file("/b/x", "same").unwrap();
+        vfs.write_file("/b/y", "diff").unwrap();
+        vfs.write_file("/b/z", "longer").unwrap();
+        vfs.create_dir_all("/b/d").unwrap();
+        let inspect = |target: &str| Conflict::inspect(&vfs, PathBuf::from("/a/x").as_path(), &vfs, PathBuf::from(target).as_path());
+        let same = inspect("/b/x");
+        assert_eq!(same.same_content, Some(true));
+        assert_eq!(same.src_entry.unwrap().size, 4);
+        assert_eq!(inspect("/b/y").same_content, Some(false));
+        assert_eq!(inspect("/b/z").same_content, Some(false));
+        let dir = inspect("/b/d");
+        assert!(!dir.both_files());
+        assert_eq!(dir.same_content, None);
+    }
+
+    #[test]
+    fn conflict_policies_round_trip_through_decisions() {
+        for policy in ConflictPolicy::CHOICES {
+            for all in [false, true] {
+                assert_eq!(policy.decision(all).unwrap().conflict_policy(), Some((policy, all)));
+            }
+        }
+        assert_eq!(ConflictPolicy::Ask.decision(true), None);
+        assert_eq!(OperationDecision::Retry.conflict_policy(), None);
+        assert_eq!(serde_json::to_string(&ConflictPolicy::OverwriteIfSizeDiffers).unwrap(), "\"overwrite-if-size-differs\"");
+    }
+
     #[test]
     fn progress_update_helpers_and_display() {
         let p = ProgressUpdate::new(3, 10);
         assert!(!p.is_done());
         assert_eq!(p.percent().unwrap(), 30.0);
 
-        let mut q = ProgressUpdate::conflict(PathBuf::from("/tmp/foo"), 4, 10, Some("blocked".to_string()));
+        let mut q = ProgressUpdate::conflict(Conflict::target_only(PathBuf::from("/tmp/foo"), None), 4, 10, Some("blocked".to_string()));
         assert!(q.is_conflict());
         assert_eq!(format!("{}", q), "4/10 - conflict: /tmp/foo (blocked)");
 
@@ -171,4 +537,21 @@ mod tests {
         q.error = Some("oh no".to_string());
         assert_eq!(format!("{}", q), "done 4/10 (error: oh no)");
     }
+
+    #[test]
+    fn throughput_smooths_and_estimates() {
+        let t0 = Instant::now();
+        let mut t = Throughput::starting_at(t0);
+        assert_eq!(t.sample_at(1_000, t0 + Duration::from_millis(100)), 0, "inside the window");
+        assert_eq!(t.sample_at(1_000_000, t0 + Duration::from_secs(1)), 1_000_000);
+        // A burst at twice the speed only moves the average part of the way.
+        let rate = t.sample_at(3_000_000, t0 + Duration::from_secs(2));
+        assert!(rate > 1_000_000 && rate < 2_000_000, "{}", rate);
+        assert_eq!(Throughput::eta(1_000, 2_500), Some(3));
+        assert_eq!(Throughput::eta(0, 2_500), None);
+
+        let b = ByteProgress { file_done: 5, file_total: 10, done: 30, total: 40, ..Default::default() };
+        assert_eq!((b.file_percent(), b.percent()), (Some(50.0), Some(75.0)));
+        assert_eq!(ByteProgress { scanning: true, ..b }.percent(), None);
+    }
 }
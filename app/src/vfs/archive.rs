@@ -0,0 +1,479 @@
+//! Shared plumbing for read-only archive backends.
+//!
+//! Archives open "in place": members are addressed below the path of the
+//! archive file in the enclosing backend, so `/tmp/a.zip/docs/x.txt` is the
+//! member `docs/x.txt` of `/tmp/a.zip`. The panel therefore keeps its usual
+//! parent-row navigation, and walking above the archive path hands the panel
+//! back to the enclosing backend (see `Vfs::outer`).
+
+use super::Vfs;
+use crate::app::types::Entry;
+use chrono::{DateTime, Local};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+/// Size of the chunks handed from an extraction thread to its reader.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Open `path` (in `outer`) as an archive backend when its name has a
+/// supported archive extension. Returns `None` for anything else so callers
+/// can fall back to their normal handling of regular files.
+pub fn open_archive(outer: &Arc<dyn Vfs>, path: &Path) -> Option<io::Result<Arc<dyn Vfs>>> {
+    let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+    if name.ends_with(".zip") {
+        return Some(super::vfs_zip::ZipVfs::open(outer.clone(), path).map(|v| Arc::new(v) as Arc<dyn Vfs>));
+    }
+    if let Some(compression) = super::vfs_tar::Compression::from_name(&name) {
+        return Some(super::vfs_tar::TarVfs::open(outer.clone(), path, compression).map(|v| Arc::new(v) as Arc<dyn Vfs>));
+    }
+    if name.ends_with(".7z") {
+        return Some(super::vfs_7z::SevenZipVfs::open(outer.clone(), path).map(|v| Arc::new(v) as Arc<dyn Vfs>));
+    }
+    if name.ends_with(".iso") {
+        return Some(super::vfs_iso::IsoVfs::open(outer.clone(), path).map(|v| Arc::new(v) as Arc<dyn Vfs>));
+    }
+    None
+}
+
+/// The error returned by every mutating operation on an archive backend.
+pub fn read_only(archive: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::ReadOnlyFilesystem, format!("{} is a read-only archive", archive.display()))
+}
+
+/// Random-access view of an archive file that can be cloned cheaply, so
+/// each open member can read through its own cursor.
+///
+/// Local archives are read in place with positioned reads; archives that
+/// live in other backends are buffered in memory once when opened.
+#[derive(Debug, Clone)]
+pub struct ArchiveSource {
+    data: SourceData,
+    pos: u64,
+    len: u64,
+}
+
+#[derive(Debug, Clone)]
+enum SourceData {
+    File(Arc<fs::File>),
+    Memory(Arc<[u8]>),
+}
+
+impl ArchiveSource {
+    /// Open the archive at `path` in `vfs`.
+    pub fn open(vfs: &dyn Vfs, path: &Path) -> io::Result<Self> {
+        if vfs.is_local() {
+            let file = fs::File::open(path)?;
+            let len = file.metadata()?.len();
+            return Ok(ArchiveSource { data: SourceData::File(Arc::new(file)), pos: 0, len });
+        }
+        let mut buf = Vec::new();
+        vfs.open_read(path)?.read_to_end(&mut buf)?;
+        Ok(Self::from_bytes(buf))
+    }
+
+    /// Wrap an archive that is already in memory.
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Self {
+        let bytes = bytes.into();
+        let len = bytes.len() as u64;
+        ArchiveSource { data: SourceData::Memory(bytes), pos: 0, len }
+    }
+}
+
+impl Read for ArchiveSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &self.data {
+            SourceData::File(file) => read_file_at(file, buf, self.pos)?,
+            SourceData::Memory(bytes) => {
+                let start = (self.pos.min(self.len)) as usize;
+                let n = buf.len().min(bytes.len() - start);
+                buf[..n].copy_from_slice(&bytes[start..start + n]);
+                n
+            }
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+/// Read from `file` at `pos` without moving a cursor other clones of the
+/// source rely on.
+#[cfg(unix)]
+fn read_file_at(file: &fs::File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    file.read_at(buf, pos)
+}
+
+/// `seek_read` moves the shared cursor, but reads at `pos` in one call, so
+/// concurrent readers never see each other's position.
+#[cfg(windows)]
+fn read_file_at(file: &fs::File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    use std::os::windows::fs::FileExt;
+    file.seek_read(buf, pos)
+}
+
+/// Without positioned reads the shared cursor is moved to `pos` first;
+/// the lock keeps another reader from moving it between seek and read.
+#[cfg(not(any(unix, windows)))]
+fn read_file_at(mut file: &fs::File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    static CURSOR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _guard = CURSOR.lock().unwrap_or_else(|e| e.into_inner());
+    file.seek(SeekFrom::Start(pos))?;
+    file.read(buf)
+}
+
+impl Seek for ArchiveSource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(d) => self.len.checked_add_signed(d),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+        };
+        self.pos = target
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before start of archive"))?;
+        Ok(self.pos)
+    }
+}
+
+/// Directory tree built from an archive's member list.
+///
+/// Backends add one node per member (with whatever metadata the format
+/// records); parent directories that the archive does not list explicitly
+/// are created on the fly. Each node may carry a backend-specific member id
+/// used later to open its contents.
+#[derive(Debug)]
+pub struct ArchiveIndex {
+    root: PathBuf,
+    nodes: HashMap<PathBuf, ArchiveNode>,
+}
+
+#[derive(Debug)]
+struct ArchiveNode {
+    entry: Entry,
+    member: Option<usize>,
+    children: BTreeSet<String>,
+}
+
+impl ArchiveIndex {
+    /// Create an index whose root directory is the archive file at `root`.
+    pub fn new(root: PathBuf, modified: Option<DateTime<Local>>) -> Self {
+        let name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.to_string_lossy().into_owned());
+        let mut nodes = HashMap::new();
+        let entry = Entry::directory(name, root.clone(), modified);
+        nodes.insert(root.clone(), ArchiveNode { entry, member: None, children: BTreeSet::new() });
+        ArchiveIndex { root, nodes }
+    }
+
+    /// Path of the archive file; also the path of the archive's top level.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Add a member stored at `rel` inside the archive and return its entry
+    /// so the caller can fill in format-specific metadata. Returns `None`
+    /// (and records nothing) for names that are empty or try to escape the
+    /// archive with `..`.
+    pub fn insert(
+        &mut self,
+        rel: &Path,
+        is_dir: bool,
+        size: u64,
+        modified: Option<DateTime<Local>>,
+        member: Option<usize>,
+    ) -> Option<&mut Entry> {
+        let mut parts = Vec::new();
+        for component in rel.components() {
+            match component {
+                Component::Normal(p) => parts.push(p.to_string_lossy().into_owned()),
+                Component::ParentDir => return None,
+                _ => {}
+            }
+        }
+        let (name, parents) = parts.split_last()?;
+
+        let mut dir = self.root.clone();
+        for part in parents {
+            let child = dir.join(part);
+            self.link_child(&dir, part);
+            self.nodes.entry(child.clone()).or_insert_with(|| ArchiveNode {
+                entry: Entry::directory(part.clone(), child.clone(), None),
+                member: None,
+                children: BTreeSet::new(),
+            });
+            dir = child;
+        }
+
+        let path = dir.join(name);
+        self.link_child(&dir, name);
+        let entry = if is_dir {
+            Entry::directory(name.clone(), path.clone(), modified)
+        } else {
+            Entry::file(name.clone(), path.clone(), size, modified)
+        };
+        let node = self
+            .nodes
+            .entry(path)
+            .or_insert_with(|| ArchiveNode { entry: entry.clone(), member: None, children: BTreeSet::new() });
+        node.entry = entry;
+        node.member = member;
+        Some(&mut node.entry)
+    }
+
+    fn link_child(&mut self, dir: &Path, name: &str) {
+        if let Some(parent) = self.nodes.get_mut(dir) {
+            parent.children.insert(name.to_string());
+        }
+    }
+
+    fn node(&self, path: &Path) -> io::Result<&ArchiveNode> {
+        self.nodes.get(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} not found in archive", path.display()))
+        })
+    }
+
+    /// List the children of `dir`, sorted by name.
+    pub fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        let node = self.node(dir)?;
+        if !node.entry.is_dir {
+            return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} is not a directory", dir.display())));
+        }
+        Ok(node
+            .children
+            .iter()
+            .filter_map(|name| self.nodes.get(&dir.join(name)))
+            .map(|child| child.entry.clone())
+            .collect())
+    }
+
+    /// Metadata recorded for `path`.
+    pub fn stat(&self, path: &Path) -> io::Result<Entry> {
+        Ok(self.node(path)?.entry.clone())
+    }
+
+    /// Backend member id for the file at `path`.
+    pub fn member(&self, path: &Path) -> io::Result<usize> {
+        let node = self.node(path)?;
+        if node.entry.is_dir {
+            return Err(io::Error::new(io::ErrorKind::IsADirectory, format!("{} is a directory", path.display())));
+        }
+        node.member
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} has no data in archive", path.display())))
+    }
+}
+
+/// How many symlinks `ArchiveLinks::resolve` follows before giving up.
+const MAX_LINK_HOPS: usize = 8;
+
+/// Symlink targets recorded by an archive, keyed by the link's path.
+#[derive(Debug, Default)]
+pub struct ArchiveLinks {
+    targets: HashMap<PathBuf, PathBuf>,
+}
+
+impl ArchiveLinks {
+    /// Record that the member at `link` points to `target`.
+    pub fn insert(&mut self, link: PathBuf, target: PathBuf) {
+        self.targets.insert(link, target);
+    }
+
+    /// The stored target of the link at `path`.
+    pub fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.targets
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a symbolic link", path.display())))
+    }
+
+    /// Follow links at `path` until a path that is not a link is reached.
+    /// Relative targets resolve against the link's directory; the result
+    /// may name something that does not exist in the archive.
+    pub fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+        let mut current = path.to_path_buf();
+        for _ in 0..MAX_LINK_HOPS {
+            let Some(target) = self.targets.get(&current) else { return Ok(current) };
+            let base = current.parent().unwrap_or(Path::new("/"));
+            current = normalize(&base.join(target));
+        }
+        Err(io::Error::new(io::ErrorKind::InvalidData, format!("too many levels of symbolic links at {}", path.display())))
+    }
+}
+
+/// Lexically resolve `.` and `..` components.
+pub(super) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Run `produce` on a background thread and return a reader over the bytes
+/// it writes. This lets backends whose decoders borrow the archive (and so
+/// cannot be returned directly) still hand out streaming readers. Dropping
+/// the reader early stops the producer at its next write.
+pub fn spawn_reader<F>(produce: F) -> Box<dyn Read + Send>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(4);
+    thread::spawn(move || {
+        let mut writer = ChannelWriter { tx: tx.clone(), buf: Vec::with_capacity(CHUNK_SIZE) };
+        if let Err(e) = produce(&mut writer).and_then(|_| writer.flush()) {
+            let _ = tx.send(Err(e));
+        }
+    });
+    Box::new(ChannelReader { rx, chunk: Vec::new(), pos: 0 })
+}
+
+/// Counterpart of `spawn_reader`: run `consume` on a background thread over
+/// the bytes written to the returned pipe. This drives encoders that pull
+/// their input from a reader. Dropping the pipe without `finish` ends the
+/// consumer's input early.
+pub fn spawn_writer<F>(consume: F) -> PipeWriter
+where
+    F: FnOnce(&mut dyn Read) -> io::Result<()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(4);
+    let handle = thread::spawn(move || consume(&mut ChannelReader { rx, chunk: Vec::new(), pos: 0 }));
+    PipeWriter { writer: ChannelWriter { tx, buf: Vec::with_capacity(CHUNK_SIZE) }, handle }
+}
+
+/// Write end of a pipe created by `spawn_writer`.
+pub struct PipeWriter {
+    writer: ChannelWriter,
+    handle: thread::JoinHandle<io::Result<()>>,
+}
+
+impl PipeWriter {
+    /// Flush buffered bytes, close the pipe and wait for the consumer,
+    /// returning its result.
+    pub fn finish(self) -> io::Result<()> {
+        let PipeWriter { mut writer, handle } = self;
+        let flushed = writer.flush();
+        drop(writer);
+        let consumed = handle.join().map_err(|_| io::Error::other("pipe consumer panicked"))?;
+        consumed.and(flushed)
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.writer.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+struct ChannelWriter {
+    tx: SyncSender<io::Result<Vec<u8>>>,
+    buf: Vec<u8>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+        self.tx.send(Ok(chunk)).map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+struct ChannelReader {
+    rx: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.chunk.len() {
+            match self.rx.recv() {
+                Ok(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Ok(Err(e)) => return Err(e),
+                // Producer finished and hung up.
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_creates_implicit_parents_and_rejects_escapes() {
+        let root = PathBuf::from("/tmp/a.zip");
+        let mut index = ArchiveIndex::new(root.clone(), None);
+        index.insert(Path::new("docs/guide/intro.txt"), false, 5, None, Some(0)).unwrap().unix_mode = Some(0o644);
+        index.insert(Path::new("docs/"), true, 0, None, None);
+        assert!(index.insert(Path::new("../evil"), false, 1, None, Some(1)).is_none());
+
+        let top = index.list(&root).unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].name, "docs");
+        assert!(top[0].is_dir);
+
+        let file = root.join("docs/guide/intro.txt");
+        assert_eq!(index.stat(&file).unwrap().unix_mode, Some(0o644));
+        assert_eq!(index.member(&file).unwrap(), 0);
+        assert_eq!(index.list(&file).unwrap_err().kind(), io::ErrorKind::NotADirectory);
+        assert_eq!(index.member(&root.join("docs")).unwrap_err().kind(), io::ErrorKind::IsADirectory);
+        assert_eq!(index.stat(&root.join("nope")).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn spawned_reader_streams_and_reports_errors() {
+        let mut s = String::new();
+        spawn_reader(|out| out.write_all(&vec![b'x'; CHUNK_SIZE * 2 + 3])).read_to_string(&mut s).unwrap();
+        assert_eq!(s.len(), CHUNK_SIZE * 2 + 3);
+
+        let mut r = spawn_reader(|out| {
+            out.write_all(b"partial")?;
+            Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt member"))
+        });
+        let mut buf = Vec::new();
+        assert_eq!(r.read_to_end(&mut buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn memory_source_reads_and_seeks() {
+        let mut src = ArchiveSource::from_bytes(b"0123456789".to_vec());
+        src.seek(SeekFrom::End(-3)).unwrap();
+        let mut s = String::new();
+        src.clone().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "789");
+        assert!(src.seek(SeekFrom::Current(-20)).is_err());
+    }
+}
@@ -0,0 +1,748 @@
+//! Create zip, tar.* and 7z archives from paths in any backend.
+//!
+//! `pack` walks the selected paths through the source panel's `Vfs` and
+//! streams them into an archive written through the destination backend.
+//! The archive is written under a hidden `.NAME.part` name next to the
+//! target and renamed into place once complete, so a failed or cancelled
+//! run never leaves a truncated archive behind.
+
+use super::archive::{normalize, spawn_writer, PipeWriter};
+use super::transfer::is_symlink_mode;
+use super::vfs_7z::{sevenz_error, FILE_ATTRIBUTE_UNIX_EXTENSION};
+use super::vfs_tar::Compression;
+use super::vfs_zip::zip_error;
+use super::Vfs;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sevenz_rust2::encoder_options::Lzma2Options;
+use sevenz_rust2::{ArchiveEntry, ArchiveWriter, NtTime};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+use tar::EntryType;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+const S_IFDIR: u32 = 0o040000;
+const S_IFREG: u32 = 0o100000;
+const S_IFLNK: u32 = 0o120000;
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+
+/// How many symlinks are followed when `preserve_symlinks` is off.
+const MAX_LINK_HOPS: usize = 8;
+
+/// Bytes read between two progress reports.
+const PROGRESS_STEP: u64 = 1024 * 1024;
+
+/// Archive formats `pack` can write, chosen from the archive's file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackFormat {
+    Zip,
+    Tar(Compression),
+    SevenZip,
+}
+
+impl PackFormat {
+    /// Suffixes offered in the pack prompt.
+    pub const EXTENSIONS: &'static [&'static str] =
+        &[".zip", ".tar", ".tar.gz", ".tar.xz", ".tar.zst", ".tar.bz2", ".7z"];
+
+    /// Pick the format matching `name` (case-insensitive). Returns `None`
+    /// when the suffix is not a format we can write.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(PackFormat::Zip)
+        } else if name.ends_with(".7z") {
+            Some(PackFormat::SevenZip)
+        } else {
+            Compression::from_name(&name).map(PackFormat::Tar)
+        }
+    }
+}
+
+/// Tunables for `pack`, persisted as the `[pack]` table in `settings.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackOptions {
+    /// Compression level from 0 (store only) to 9 (smallest output). zstd
+    /// only distinguishes 0 from its fastest level.
+    pub level: u32,
+    /// Store Unix permission bits (and, in tarballs, owners).
+    pub preserve_permissions: bool,
+    /// Store extended attributes. Only tarballs carry them (as
+    /// `SCHILY.xattr.*` PAX records), and only from backends that report
+    /// them (`Vfs::list_xattrs`).
+    pub preserve_xattrs: bool,
+    /// Store symlinks as links; when off the files they point to are
+    /// packed instead.
+    pub preserve_symlinks: bool,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions { level: 6, preserve_permissions: true, preserve_xattrs: false, preserve_symlinks: true }
+    }
+}
+
+/// What an archive member is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MemberKind {
+    Dir,
+    File(u64),
+    Symlink(PathBuf),
+}
+
+/// One path to be written, in archive order.
+#[derive(Debug, Clone)]
+struct Member {
+    /// Where the data is read from in the source backend.
+    src: PathBuf,
+    /// Archive-relative name with `/` separators.
+    name: String,
+    kind: MemberKind,
+    /// Permission bits (without the file-type bits).
+    perms: Option<u32>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    owner: Option<String>,
+    group: Option<String>,
+    modified: Option<DateTime<Local>>,
+}
+
+/// Pack `sources` (paths in `src`) into a new archive at `archive` in
+/// `dst`. The format follows the archive's suffix (see `PackFormat`); each
+/// source is stored under its own file name. `progress` receives the bytes
+/// packed so far, the total and the member being written; setting `cancel`
+/// aborts the run. Returns the number of members written.
+pub fn pack(
+    src: &dyn Vfs,
+    sources: &[PathBuf],
+    dst: &dyn Vfs,
+    archive: &Path,
+    options: &PackOptions,
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(u64, u64, &str),
+) -> io::Result<usize> {
+    let name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a file name", archive.display())))?;
+    let format = PackFormat::from_name(&name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot create {}: use one of {}", name, PackFormat::EXTENSIONS.join(", ")),
+        )
+    })?;
+
+    let members = collect(src, sources, options.preserve_symlinks)?;
+    let total = members.iter().map(|m| if let MemberKind::File(size) = m.kind { size } else { 0 }).sum();
+    let mut meter = Meter { done: 0, reported: 0, total, cancel, progress };
+
+    let partial = archive.with_file_name(format!(".{}.part", name));
+    let written = match format {
+        PackFormat::Zip => dst.open_write(&partial).and_then(|out| write_zip(out, src, &members, options, &mut meter)),
+        PackFormat::Tar(compression) => {
+            dst.open_write(&partial).and_then(|out| write_tar(out, compression, src, &members, options, &mut meter))
+        }
+        PackFormat::SevenZip => write_7z(dst, &partial, src, &members, options, &mut meter),
+    };
+    if let Err(e) = written.and_then(|()| replace(dst, &partial, archive)) {
+        let _ = dst.remove(&partial);
+        return Err(e);
+    }
+    Ok(members.len())
+}
+
+/// Expand `sources` into the full member list, parents before children.
+fn collect(src: &dyn Vfs, sources: &[PathBuf], preserve_symlinks: bool) -> io::Result<Vec<Member>> {
+    let mut members = Vec::new();
+    for path in sources {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("cannot pack {}", path.display())))?;
+        walk(src, path, name, preserve_symlinks, &mut Vec::new(), &mut members)?;
+    }
+    Ok(members)
+}
+
+fn walk(
+    src: &dyn Vfs,
+    path: &Path,
+    name: String,
+    preserve_symlinks: bool,
+    ancestors: &mut Vec<PathBuf>,
+    out: &mut Vec<Member>,
+) -> io::Result<()> {
+    let mut entry = src.stat(path)?;
+    let mut read_from = path.to_path_buf();
+    let mut kind = None;
+    if is_symlink_mode(entry.unix_mode) {
+        if preserve_symlinks {
+            kind = Some(MemberKind::Symlink(src.read_link(path)?));
+        } else {
+            read_from = resolve_link(src, path)?;
+            entry = src.stat(&read_from)?;
+        }
+    }
+    let kind = kind.unwrap_or(if entry.is_dir { MemberKind::Dir } else { MemberKind::File(entry.size) });
+    let is_dir = kind == MemberKind::Dir;
+    // A followed link back up the tree would recurse forever.
+    if is_dir && ancestors.contains(&read_from) {
+        return Ok(());
+    }
+    out.push(Member {
+        src: read_from.clone(),
+        name: name.clone(),
+        kind,
+        perms: entry.unix_mode.map(|m| m & 0o7777),
+        uid: entry.uid,
+        gid: entry.gid,
+        owner: entry.owner,
+        group: entry.group,
+        modified: entry.modified,
+    });
+    if is_dir {
+        let mut children = src.list(&read_from)?;
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        ancestors.push(read_from);
+        for child in children {
+            walk(src, &child.path, format!("{}/{}", name, child.name), preserve_symlinks, ancestors, out)?;
+        }
+        ancestors.pop();
+    }
+    Ok(())
+}
+
+/// Follow the symlink at `path` until something that is not a link.
+fn resolve_link(src: &dyn Vfs, path: &Path) -> io::Result<PathBuf> {
+    let mut current = path.to_path_buf();
+    for _ in 0..MAX_LINK_HOPS {
+        if !is_symlink_mode(src.stat(&current)?.unix_mode) {
+            return Ok(current);
+        }
+        let target = src.read_link(&current)?;
+        current = match current.parent() {
+            Some(parent) => normalize(&parent.join(target)),
+            None => target,
+        };
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, format!("too many levels of symbolic links at {}", path.display())))
+}
+
+/// Move the finished `partial` file over `archive`.
+fn replace(dst: &dyn Vfs, partial: &Path, archive: &Path) -> io::Result<()> {
+    if dst.rename(partial, archive).is_ok() {
+        return Ok(());
+    }
+    // Some servers refuse to rename over an existing file.
+    if dst.stat(archive).is_ok() {
+        dst.remove(archive)?;
+    }
+    dst.rename(partial, archive)
+}
+
+/// Byte counter shared by every member read during one `pack` run.
+struct Meter<'a> {
+    done: u64,
+    reported: u64,
+    total: u64,
+    cancel: &'a AtomicBool,
+    progress: &'a mut dyn FnMut(u64, u64, &str),
+}
+
+impl<'a> Meter<'a> {
+    fn check(&self) -> io::Result<()> {
+        if self.cancel.load(Ordering::SeqCst) {
+            return Err(io::Error::other("cancelled"));
+        }
+        Ok(())
+    }
+
+    /// Report the start of `name` and open it for reading through the meter.
+    fn open<'m>(&'m mut self, src: &dyn Vfs, member: &'m Member) -> io::Result<Metered<'m, 'a>> {
+        self.check()?;
+        (self.progress)(self.done, self.total, &member.name);
+        self.reported = self.done;
+        Ok(Metered { inner: src.open_read(&member.src)?, meter: self, name: &member.name })
+    }
+}
+
+/// Reader that counts bytes into a `Meter` and stops once cancelled.
+struct Metered<'m, 'a> {
+    inner: Box<dyn Read + Send>,
+    meter: &'m mut Meter<'a>,
+    name: &'m str,
+}
+
+impl Read for Metered<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.meter.check()?;
+        let n = self.inner.read(buf)?;
+        let meter = &mut *self.meter;
+        meter.done += n as u64;
+        if meter.done - meter.reported >= PROGRESS_STEP {
+            meter.reported = meter.done;
+            (meter.progress)(meter.done, meter.total, self.name);
+        }
+        Ok(n)
+    }
+}
+
+/// The full `st_mode` stored for `member`, when permissions are kept.
+fn unix_mode(member: &Member, options: &PackOptions) -> Option<u32> {
+    let file_type = match member.kind {
+        MemberKind::Dir => S_IFDIR,
+        MemberKind::File(_) => S_IFREG,
+        MemberKind::Symlink(_) => S_IFLNK,
+    };
+    let perms = options.preserve_permissions.then_some(member.perms).flatten();
+    match member.kind {
+        // Links are only recognisable through their mode in zip and 7z.
+        MemberKind::Symlink(_) => Some(file_type | perms.unwrap_or(0o777)),
+        _ => perms.map(|p| file_type | p),
+    }
+}
+
+fn write_zip(
+    out: Box<dyn Write + Send>,
+    src: &dyn Vfs,
+    members: &[Member],
+    options: &PackOptions,
+    meter: &mut Meter<'_>,
+) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new_stream(out);
+    let base = if options.level == 0 {
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored)
+    } else {
+        SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(Some(options.level.min(9) as i64))
+    };
+    for member in members {
+        meter.check()?;
+        let mut opts = base;
+        if let Some(time) = member.modified.and_then(|t| zip::DateTime::try_from(t.naive_local()).ok()) {
+            opts = opts.last_modified_time(time);
+        }
+        if let Some(mode) = unix_mode(member, options) {
+            opts = opts.unix_permissions(mode & 0o7777);
+        }
+        match &member.kind {
+            MemberKind::Dir => zip.add_directory(format!("{}/", member.name), opts).map_err(zip_error)?,
+            MemberKind::Symlink(target) => {
+                zip.add_symlink(&member.name, target.to_string_lossy(), opts).map_err(zip_error)?
+            }
+            MemberKind::File(size) => {
+                zip.start_file(&member.name, opts.large_file(*size >= u32::MAX as u64)).map_err(zip_error)?;
+                io::copy(&mut meter.open(src, member)?, &mut zip)?;
+            }
+        }
+    }
+    zip.finish().map_err(zip_error)?.into_inner().flush()
+}
+
+fn write_tar(
+    out: Box<dyn Write + Send>,
+    compression: Compression,
+    src: &dyn Vfs,
+    members: &[Member],
+    options: &PackOptions,
+    meter: &mut Meter<'_>,
+) -> io::Result<()> {
+    let mut builder = tar::Builder::new(Encoder::new(out, compression, options.level)?);
+    let appended = append_tar_members(&mut builder, src, members, options, meter);
+    // Finish even after a failure so a zstd pipe thread is joined before
+    // the caller removes the partial file.
+    let finished = builder.into_inner().and_then(Encoder::finish);
+    appended.and(finished)
+}
+
+fn append_tar_members<W: Write>(
+    builder: &mut tar::Builder<W>,
+    src: &dyn Vfs,
+    members: &[Member],
+    options: &PackOptions,
+    meter: &mut Meter<'_>,
+) -> io::Result<()> {
+    for member in members {
+        meter.check()?;
+        if options.preserve_xattrs {
+            append_xattrs(builder, src, &member.src)?;
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(member.modified.map(|t| t.timestamp().max(0) as u64).unwrap_or(0));
+        header.set_mode(unix_mode(member, options).map(|m| m & 0o7777).unwrap_or(match member.kind {
+            MemberKind::File(_) => 0o644,
+            _ => 0o755,
+        }));
+        if options.preserve_permissions {
+            header.set_uid(member.uid.unwrap_or(0) as u64);
+            header.set_gid(member.gid.unwrap_or(0) as u64);
+            // Names that do not fit the header are simply left out.
+            if let Some(owner) = &member.owner {
+                let _ = header.set_username(owner);
+            }
+            if let Some(group) = &member.group {
+                let _ = header.set_groupname(group);
+            }
+        }
+        match &member.kind {
+            MemberKind::Dir => {
+                header.set_entry_type(EntryType::Directory);
+                header.set_size(0);
+                builder.append_data(&mut header, format!("{}/", member.name), io::empty())?;
+            }
+            MemberKind::Symlink(target) => {
+                header.set_entry_type(EntryType::Symlink);
+                header.set_size(0);
+                builder.append_link(&mut header, &member.name, target)?;
+            }
+            MemberKind::File(size) => {
+                header.set_entry_type(EntryType::Regular);
+                header.set_size(*size);
+                builder.append_data(&mut header, &member.name, meter.open(src, member)?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the extended attributes of `path` as a PAX header for the member
+/// that follows. Backends without extended attributes add nothing.
+fn append_xattrs<W: Write>(builder: &mut tar::Builder<W>, src: &dyn Vfs, path: &Path) -> io::Result<()> {
+    let names = match src.list_xattrs(path) {
+        Ok(names) => names,
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut records = Vec::new();
+    for name in names {
+        if let Some(value) = src.get_xattr(path, &name)? {
+            push_pax_record(&mut records, &format!("SCHILY.xattr.{}", name), &value);
+        }
+    }
+    if records.is_empty() {
+        return Ok(());
+    }
+    let mut header = tar::Header::new_ustar();
+    header.set_path("././@PaxHeader")?;
+    header.set_entry_type(EntryType::XHeader);
+    header.set_mode(0o644);
+    header.set_size(records.len() as u64);
+    header.set_cksum();
+    builder.append(&header, records.as_slice())
+}
+
+/// Append one `"<len> <key>=<value>\n"` record, where `len` counts the
+/// whole record including its own digits.
+fn push_pax_record(out: &mut Vec<u8>, key: &str, value: &[u8]) {
+    let rest = key.len() + value.len() + 3;
+    let mut len = rest + 1;
+    while len != rest + len.to_string().len() {
+        len = rest + len.to_string().len();
+    }
+    out.extend_from_slice(format!("{} {}=", len, key).as_bytes());
+    out.extend_from_slice(value);
+    out.push(b'\n');
+}
+
+/// Compressor wrapped around a tar stream.
+enum Encoder {
+    Plain(Box<dyn Write + Send>),
+    Gzip(flate2::write::GzEncoder<Box<dyn Write + Send>>),
+    Bzip2(bzip2::write::BzEncoder<Box<dyn Write + Send>>),
+    Xz(lzma_rust2::XzWriter<Box<dyn Write + Send>>),
+    /// ruzstd's encoder pulls from a reader, so it runs on its own thread.
+    Zstd(PipeWriter),
+}
+
+impl Encoder {
+    fn new(out: Box<dyn Write + Send>, compression: Compression, level: u32) -> io::Result<Self> {
+        let level = level.min(9);
+        Ok(match compression {
+            Compression::None => Encoder::Plain(out),
+            Compression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(out, flate2::Compression::new(level))),
+            Compression::Bzip2 => {
+                Encoder::Bzip2(bzip2::write::BzEncoder::new(out, bzip2::Compression::new(level.max(1))))
+            }
+            Compression::Xz => Encoder::Xz(lzma_rust2::XzWriter::new(out, lzma_rust2::XzOptions::with_preset(level))?),
+            Compression::Zstd => {
+                let level = if level == 0 {
+                    ruzstd::encoding::CompressionLevel::Uncompressed
+                } else {
+                    ruzstd::encoding::CompressionLevel::Fastest
+                };
+                Encoder::Zstd(spawn_writer(move |input| compress_zstd(input, out, level)))
+            }
+        })
+    }
+
+    /// Write the compressed stream's trailer and flush the output.
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(mut out) => out.flush(),
+            Encoder::Gzip(e) => e.finish()?.flush(),
+            Encoder::Bzip2(e) => e.finish()?.flush(),
+            Encoder::Xz(e) => e.finish()?.flush(),
+            Encoder::Zstd(pipe) => pipe.finish(),
+        }
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(w) => w.write(buf),
+            Encoder::Gzip(w) => w.write(buf),
+            Encoder::Bzip2(w) => w.write(buf),
+            Encoder::Xz(w) => w.write(buf),
+            Encoder::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(w) => w.flush(),
+            Encoder::Gzip(w) => w.flush(),
+            Encoder::Bzip2(w) => w.flush(),
+            Encoder::Xz(w) => w.flush(),
+            Encoder::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+fn compress_zstd(
+    input: &mut dyn Read,
+    out: Box<dyn Write + Send>,
+    level: ruzstd::encoding::CompressionLevel,
+) -> io::Result<()> {
+    // ruzstd panics on write errors, so hand it a sink that records the
+    // first failure instead and report that once it is done.
+    let mut sink = LatchedWriter { inner: out, error: None };
+    ruzstd::encoding::compress(input, &mut sink, level);
+    match sink.error {
+        Some(e) => Err(e),
+        None => sink.inner.flush(),
+    }
+}
+
+/// Writer that swallows everything after its first error.
+struct LatchedWriter {
+    inner: Box<dyn Write + Send>,
+    error: Option<io::Error>,
+}
+
+impl Write for LatchedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.error.is_none() {
+            if let Err(e) = self.inner.write_all(buf) {
+                self.error = Some(e);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_7z(
+    dst: &dyn Vfs,
+    partial: &Path,
+    src: &dyn Vfs,
+    members: &[Member],
+    options: &PackOptions,
+    meter: &mut Meter<'_>,
+) -> io::Result<()> {
+    // The 7z writer seeks back to patch its start header, so remote
+    // destinations get a local spool file that is uploaded afterwards.
+    if dst.is_local() {
+        return build_7z(fs::File::create(partial)?, src, members, options, meter);
+    }
+    let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let spool = std::env::temp_dir().join(format!("filezoom-pack-{}-{}.7z", std::process::id(), nanos));
+    let result = build_7z(fs::File::create(&spool)?, src, members, options, meter).and_then(|()| {
+        let mut out = dst.open_write(partial)?;
+        io::copy(&mut fs::File::open(&spool)?, &mut out)?;
+        out.flush()
+    });
+    let _ = fs::remove_file(&spool);
+    result
+}
+
+fn build_7z(
+    file: fs::File,
+    src: &dyn Vfs,
+    members: &[Member],
+    options: &PackOptions,
+    meter: &mut Meter<'_>,
+) -> io::Result<()> {
+    let mut writer = ArchiveWriter::new(file).map_err(sevenz_error)?;
+    writer.set_content_methods(vec![Lzma2Options::from_level(options.level.min(9)).into()]);
+    for member in members {
+        meter.check()?;
+        let mut entry = match member.kind {
+            MemberKind::Dir => ArchiveEntry::new_directory(&member.name),
+            _ => ArchiveEntry::new_file(&member.name),
+        };
+        if let Some(time) = member.modified.and_then(|t| NtTime::try_from(SystemTime::from(t)).ok()) {
+            entry.has_last_modified_date = true;
+            entry.last_modified_date = time;
+        }
+        let mut attributes = if member.kind == MemberKind::Dir { FILE_ATTRIBUTE_DIRECTORY } else { 0 };
+        if let Some(mode) = unix_mode(member, options) {
+            attributes |= FILE_ATTRIBUTE_UNIX_EXTENSION | (mode << 16);
+        }
+        entry.has_windows_attributes = attributes != 0;
+        entry.windows_attributes = attributes;
+        let pushed = match &member.kind {
+            MemberKind::Dir | MemberKind::File(0) => writer.push_archive_entry::<&[u8]>(entry, None).map(|_| ()),
+            // 7-Zip stores a symlink as a member whose contents are the target.
+            MemberKind::Symlink(target) => {
+                let target = target.to_string_lossy();
+                writer.push_archive_entry(entry, Some(target.as_bytes())).map(|_| ())
+            }
+            MemberKind::File(_) => writer.push_archive_entry(entry, Some(meter.open(src, member)?)).map(|_| ()),
+        };
+        pushed.map_err(sevenz_error)?;
+    }
+    writer.finish()?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::vfs_mem::MemVfs;
+    use crate::vfs::{open_archive, LocalVfs};
+    use assert_fs::prelude::*;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn fixture() -> assert_fs::TempDir {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("src/docs/readme.txt").write_str("hello pack").unwrap();
+        temp.child("src/docs/empty.txt").touch().unwrap();
+        temp.child("src/tool.sh").write_str("#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(temp.path().join("src/tool.sh"), fs::Permissions::from_mode(0o750)).unwrap();
+            std::os::unix::fs::symlink("docs/readme.txt", temp.path().join("src/latest")).unwrap();
+        }
+        temp
+    }
+
+    #[cfg(unix)]
+    fn pack_to(temp: &assert_fs::TempDir, name: &str, options: &PackOptions) -> PathBuf {
+        let archive = temp.path().join(name);
+        let sources = [temp.path().join("src/docs"), temp.path().join("src/tool.sh"), temp.path().join("src/latest")];
+        let mut reports = Vec::new();
+        let n = pack(&LocalVfs::new(), &sources, &LocalVfs::new(), &archive, options, &AtomicBool::new(false), &mut |d, t, m| {
+            reports.push((d, t, m.to_string()))
+        })
+        .unwrap();
+        assert_eq!(n, 5);
+        assert!(reports.windows(2).all(|w| w[0].1 == w[1].1 && w[0].0 <= w[1].0));
+        assert!(reports.iter().any(|(_, _, m)| m == "docs/readme.txt"));
+        assert!(!temp.path().join(format!(".{}.part", name)).exists());
+        archive
+    }
+
+    #[cfg(unix)]
+    fn read(vfs: &dyn Vfs, path: &Path) -> String {
+        let mut s = String::new();
+        vfs.open_read(path).unwrap().read_to_string(&mut s).unwrap();
+        s
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn every_format_round_trips_through_the_archive_backends() {
+        let temp = fixture();
+        let local: Arc<dyn Vfs> = Arc::new(LocalVfs::new());
+        for name in ["out.zip", "out.tar", "out.tar.gz", "out.tar.xz", "out.tar.zst", "out.tar.bz2", "out.7z"] {
+            for level in [0, 9] {
+                let archive = pack_to(&temp, name, &PackOptions { level, ..Default::default() });
+                let vfs = open_archive(&local, &archive).unwrap().unwrap();
+                assert_eq!(read(vfs.as_ref(), &archive.join("docs/readme.txt")), "hello pack", "{name} level {level}");
+                assert_eq!(vfs.stat(&archive.join("docs/empty.txt")).unwrap().size, 0, "{name}");
+                assert_eq!(vfs.stat(&archive.join("tool.sh")).unwrap().unix_mode.map(|m| m & 0o7777), Some(0o750), "{name}");
+                assert!(is_symlink_mode(vfs.stat(&archive.join("latest")).unwrap().unix_mode), "{name}");
+                match vfs.read_link(&archive.join("latest")) {
+                    Ok(target) => assert_eq!(target, PathBuf::from("docs/readme.txt"), "{name}"),
+                    Err(e) => assert_eq!(e.kind(), io::ErrorKind::Unsupported, "{name}"),
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_followed_when_not_preserved() {
+        let temp = fixture();
+        let options = PackOptions { preserve_symlinks: false, preserve_permissions: false, ..Default::default() };
+        let archive = pack_to(&temp, "follow.tar", &options);
+        let local: Arc<dyn Vfs> = Arc::new(LocalVfs::new());
+        let vfs = open_archive(&local, &archive).unwrap().unwrap();
+        assert!(vfs.read_link(&archive.join("latest")).is_err());
+        assert_eq!(read(vfs.as_ref(), &archive.join("latest")), "hello pack");
+        assert_eq!(vfs.stat(&archive.join("tool.sh")).unwrap().unix_mode.map(|m| m & 0o7777), Some(0o644));
+    }
+
+    #[test]
+    fn xattrs_are_stored_as_pax_records() {
+        let vfs = MemVfs::new();
+        vfs.write_file("/src/readme.txt", "hello pack").unwrap();
+        vfs.set_xattr(Path::new("/src/readme.txt"), "user.origin", b"vendor").unwrap();
+        let options = PackOptions { preserve_xattrs: true, ..Default::default() };
+        let archive = Path::new("/attrs.tar");
+        pack(&vfs, &[PathBuf::from("/src")], &vfs, archive, &options, &AtomicBool::new(false), &mut |_, _, _| {}).unwrap();
+
+        let mut tarball = tar::Archive::new(Cursor::new(vfs.read_file(archive).unwrap()));
+        let mut found = None;
+        for entry in tarball.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            if entry.path().unwrap() == Path::new("src/readme.txt") {
+                for ext in entry.pax_extensions().unwrap().unwrap() {
+                    let ext = ext.unwrap();
+                    if ext.key().unwrap() == "SCHILY.xattr.user.origin" {
+                        found = Some(ext.value_bytes().to_vec());
+                    }
+                }
+            }
+        }
+        assert_eq!(found.as_deref(), Some(&b"vendor"[..]));
+    }
+
+    #[test]
+    fn cancel_and_unknown_formats_leave_nothing_behind() {
+        let temp = fixture();
+        let sources = [temp.path().join("src/docs")];
+        let archive = temp.path().join("out.tar.gz");
+        let err = pack(&LocalVfs::new(), &sources, &LocalVfs::new(), &archive, &PackOptions::default(), &AtomicBool::new(true), &mut |_, _, _| {})
+            .unwrap_err();
+        assert_eq!(err.to_string(), "cancelled");
+        assert!(!archive.exists());
+        assert!(!temp.path().join(".out.tar.gz.part").exists());
+
+        let err = pack(&LocalVfs::new(), &sources, &LocalVfs::new(), &temp.path().join("out.rar"), &PackOptions::default(), &AtomicBool::new(false), &mut |_, _, _| {})
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn pax_record_length_counts_itself() {
+        let mut out = Vec::new();
+        push_pax_record(&mut out, "k", b"v");
+        assert_eq!(out, b"6 k=v\n");
+        out.clear();
+        push_pax_record(&mut out, "SCHILY.xattr.user.a", &[b'x'; 80]);
+        assert_eq!(out.len(), 105);
+        assert!(out.starts_with(b"105 "));
+    }
+}
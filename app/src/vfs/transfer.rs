@@ -0,0 +1,246 @@
+//! Backend-agnostic tree operations built on the `Vfs` primitives.
+//!
+//! These helpers are used whenever at least one side of an operation is not
+//! the local filesystem (for example copying from an SFTP panel into a local
+//! one). Local-to-local operations keep using the `fs_op` helpers, which
+//! preserve more metadata and write atomically.
+
+use super::Vfs;
+use crate::app::types::Entry;
+use crate::fs_op::helpers::{CopyCheckpoint, FailureHook};
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+
+/// Buffer size used when streaming file contents between backends.
+const COPY_CHUNK: usize = 64 * 1024;
+
+/// Recursively copy `from` (in `src`) to `to` (in `dst`) by streaming file
+/// contents. Existing directories at the destination are merged into and
+/// existing files are overwritten. Permission bits are carried over on a
+/// best-effort basis. Returns the number of bytes copied.
+pub fn copy_tree(src: &dyn Vfs, from: &Path, dst: &dyn Vfs, to: &Path) -> io::Result<u64> {
+    copy_tree_with_progress(src, from, dst, to, &mut |_| {})
+}
+
+/// Like `copy_tree`, calling `progress` with the source path of every file,
+/// link or directory once it has been written. Extended attributes are
+/// carried over when both backends support them.
+///
+/// Symbolic links reported by the source (through the file-type bits of
+/// `Entry::unix_mode`) are recreated as links when the destination supports
+/// them; otherwise the link's contents are copied.
+pub fn copy_tree_with_progress(
+    src: &dyn Vfs,
+    from: &Path,
+    dst: &dyn Vfs,
+    to: &Path,
+    progress: &mut dyn FnMut(&Path),
+) -> io::Result<u64> {
+    copy_tree_with(src, from, dst, to, progress, &mut |_, _, _| Ok(()), &mut |_, e| Err(e))
+}
+
+/// Like `copy_tree_with_progress`, streaming file contents in chunks and
+/// calling `checkpoint` around each chunk written (see
+/// `fs_op::helpers::CopyCheckpoint`). An error from
+/// `checkpoint` aborts the copy and is returned unchanged. Trees exported
+/// natively by the source backend (`Vfs::export_tree`) are not interrupted.
+///
+/// Entries that fail are passed to `on_error`, which retries, skips or
+/// stops (see `fs_op::helpers::FailureHook`); a directory that cannot be
+/// created or listed is skipped as a whole.
+pub fn copy_tree_with(
+    src: &dyn Vfs,
+    from: &Path,
+    dst: &dyn Vfs,
+    to: &Path,
+    progress: &mut dyn FnMut(&Path),
+    checkpoint: &mut CopyCheckpoint<'_>,
+    on_error: &mut FailureHook<'_>,
+) -> io::Result<u64> {
+    if let Some(result) = src.export_tree(from, dst, to, progress) {
+        return result;
+    }
+    let (entry, children) = loop {
+        let res = src.stat(from).and_then(|entry| {
+            if !entry.is_dir {
+                return Ok((entry, Vec::new()));
+            }
+            make_dir(dst, to)?;
+            let children = src.list(from)?;
+            Ok((entry, children))
+        });
+        match res {
+            Ok(found) => break found,
+            Err(e) => {
+                if !on_error(from, e)? {
+                    return Ok(0);
+                }
+            }
+        }
+    };
+    let copied = if entry.is_dir {
+        let mut total = 0;
+        for child in children {
+            total += copy_tree_with(src, &child.path, dst, &to.join(child_name(&child)), progress, checkpoint, on_error)?;
+        }
+        total
+    } else {
+        loop {
+            match copy_leaf(src, from, &entry, dst, to, checkpoint) {
+                Ok(Some(n)) => break n,
+                Ok(None) => {
+                    progress(from);
+                    return Ok(0);
+                }
+                Err(e) => {
+                    if !on_error(from, e)? {
+                        return Ok(0);
+                    }
+                }
+            }
+        }
+    };
+    if let Some(mode) = entry.unix_mode {
+        let _ = dst.set_mode(to, mode & 0o7777);
+    }
+    if let Ok(names) = src.list_xattrs(from) {
+        for name in names {
+            if let Ok(Some(value)) = src.get_xattr(from, &name) {
+                let _ = dst.set_xattr(to, &name, &value);
+            }
+        }
+    }
+    progress(from);
+    Ok(copied)
+}
+
+/// Create directory `to`, merging into one that already exists.
+fn make_dir(dst: &dyn Vfs, to: &Path) -> io::Result<()> {
+    match dst.mkdir(to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+        Err(e) => {
+            // Some servers report a generic failure for an existing
+            // directory; only treat it as fatal if nothing is there.
+            if dst.stat(to).map(|d| d.is_dir).unwrap_or(false) {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Copy the file or link `from` to `to`. Returns the bytes copied, or
+/// `None` when a link was recreated as a link.
+fn copy_leaf(src: &dyn Vfs, from: &Path, entry: &Entry, dst: &dyn Vfs, to: &Path, checkpoint: &mut CopyCheckpoint<'_>) -> io::Result<Option<u64>> {
+    if is_symlink_mode(entry.unix_mode) {
+        if let Ok(target) = src.read_link(from) {
+            match dst.symlink(&target, to) {
+                Ok(()) => return Ok(None),
+                Err(e) if e.kind() == io::ErrorKind::Unsupported => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    let mut reader = src.open_read(from)?;
+    let mut writer = dst.open_write(to)?;
+    let mut buf = vec![0u8; COPY_CHUNK];
+    let mut n = 0u64;
+    checkpoint(from, 0, entry.size)?;
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..read])?;
+        n += read as u64;
+        checkpoint(from, n, entry.size)?;
+    }
+    writer.flush()?;
+    Ok(Some(n))
+}
+
+/// Name of `entry` within its directory, taken from its path so a name
+/// that is not valid UTF-8 (which `Entry::name` shows lossily) is kept.
+pub(crate) fn child_name(entry: &Entry) -> &OsStr {
+    entry.path.file_name().unwrap_or(OsStr::new(&entry.name))
+}
+
+/// Whether `mode` carries the `S_IFLNK` file-type bits.
+pub(crate) fn is_symlink_mode(mode: Option<u32>) -> bool {
+    mode.is_some_and(|m| m & 0o170000 == 0o120000)
+}
+
+/// Recursively remove `path` from `vfs`. Missing paths are an error so
+/// callers notice stale listings.
+pub fn remove_tree(vfs: &dyn Vfs, path: &Path) -> io::Result<()> {
+    remove_tree_with(vfs, path, &mut || Ok(()))
+}
+
+/// Like `remove_tree`, calling `checkpoint` before each entry is removed.
+pub fn remove_tree_with(vfs: &dyn Vfs, path: &Path, checkpoint: &mut dyn FnMut() -> io::Result<()>) -> io::Result<()> {
+    let entry = vfs.stat(path)?;
+    if entry.is_dir {
+        for child in vfs.list(path)? {
+            remove_tree_with(vfs, &child.path, checkpoint)?;
+        }
+    }
+    checkpoint()?;
+    vfs.remove(path)
+}
+
+/// Total size of the files under `path` in `vfs` (the file's own size when
+/// `path` is a file). Symlinks are not followed; unreadable parts count as
+/// empty.
+pub fn tree_size(vfs: &dyn Vfs, path: &Path) -> u64 {
+    let Ok(entry) = vfs.stat(path) else { return 0 };
+    if is_symlink_mode(entry.unix_mode) {
+        0
+    } else if entry.is_dir {
+        vfs.list(path).unwrap_or_default().iter().map(|child| tree_size(vfs, &child.path)).sum()
+    } else {
+        entry.size
+    }
+}
+
+/// Move `from` (in `src`) to `to` (in `dst`). When both sides are the same
+/// backend a rename is attempted first; otherwise, or if the rename fails,
+/// the tree is copied and the source removed afterwards.
+pub fn move_tree(src: &dyn Vfs, from: &Path, dst: &dyn Vfs, to: &Path, same_backend: bool) -> io::Result<()> {
+    if same_backend && src.rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_tree(src, from, dst, to)?;
+    remove_tree(src, from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::LocalVfs;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn copy_move_and_remove_trees() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("src/a.txt").write_str("aaa").unwrap();
+        temp.child("src/sub/b.txt").write_str("bb").unwrap();
+        let vfs = LocalVfs::new();
+
+        let copied = copy_tree(&vfs, &temp.path().join("src"), &vfs, &temp.path().join("copy")).unwrap();
+        assert_eq!(copied, 5);
+        temp.child("copy/sub/b.txt").assert("bb");
+
+        move_tree(&vfs, &temp.path().join("copy"), &vfs, &temp.path().join("moved"), false).unwrap();
+        assert!(!temp.path().join("copy").exists());
+        temp.child("moved/a.txt").assert("aaa");
+
+        remove_tree(&vfs, &temp.path().join("moved")).unwrap();
+        assert!(!temp.path().join("moved").exists());
+        assert!(remove_tree(&vfs, &temp.path().join("moved")).is_err());
+    }
+}
@@ -1 +1,441 @@
-//place holder for vfs module
\ No newline at end of file
+//! Virtual filesystem abstraction.
+//!
+//! A `Vfs` is the seam between the panel model and whatever storage a panel
+//! is browsing. The core only ever talks to a panel's backend through this
+//! trait, so remote (SFTP/SMB) and archive backends can be added without
+//! touching the listing code in `Panel`.
+//!
+//! Paths passed to a backend are always interpreted in that backend's own
+//! namespace: for `LocalVfs` they are ordinary host paths, for a remote
+//! backend they are paths on the remote side. Every operation reports
+//! failures as `std::io::Error` so callers can reuse the existing error
+//! rendering in `crate::errors`.
+
+use crate::app::settings::write_settings::Settings;
+use crate::app::types::{Entry, EntryKind};
+use chrono::{DateTime, Local};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub mod archive;
+pub mod pack;
+pub mod transfer;
+pub mod vfs_7z;
+pub mod vfs_iso;
+pub mod vfs_mem;
+#[cfg(feature = "smb")]
+pub mod vfs_smb;
+#[cfg(feature = "sftp")]
+pub mod vfs_ssh;
+pub mod vfs_tar;
+pub mod vfs_zip;
+
+pub use archive::open_archive;
+pub use transfer::{copy_tree, copy_tree_with, copy_tree_with_progress, move_tree, remove_tree, remove_tree_with, tree_size};
+
+/// Operations a storage backend must provide for a panel to browse it.
+///
+/// Implementations must be cheap to share between threads: panels hold the
+/// backend behind an `Arc` and background workers may clone that handle.
+pub trait Vfs: Send + Sync + fmt::Debug {
+    /// Short URL-style scheme identifying the backend (e.g. `"file"`).
+    fn scheme(&self) -> &'static str;
+
+    /// Whether paths in this backend are ordinary host paths. Callers use
+    /// this to keep the `fs_op` fast paths for local-to-local operations.
+    fn is_local(&self) -> bool {
+        self.scheme() == "file"
+    }
+
+    /// Render `path` for display, including any scheme/host prefix.
+    fn display_path(&self, path: &Path) -> String {
+        path.display().to_string()
+    }
+
+    /// For backends nested inside another one (archives), the enclosing
+    /// backend and the path of the container file within it. A panel that
+    /// walks above that path switches back to the enclosing backend.
+    fn outer(&self) -> Option<(&Arc<dyn Vfs>, &Path)> {
+        None
+    }
+
+    /// List the immediate children of `dir`. The returned entries carry
+    /// full paths in the backend's namespace and are not sorted.
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>>;
+
+    /// Return metadata for `path` without following a final symlink.
+    fn stat(&self, path: &Path) -> io::Result<Entry>;
+
+    /// Open `path` for reading.
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+
+    /// Create or truncate `path` and open it for writing.
+    fn open_write(&self, path: &Path) -> io::Result<Box<dyn Write + Send>>;
+
+    /// Create a single directory. The parent must already exist.
+    fn mkdir(&self, path: &Path) -> io::Result<()>;
+
+    /// Rename `from` to `to` within this backend.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Remove a file, symlink or empty directory. Directories are not
+    /// removed recursively; callers walk the tree themselves.
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Create a symbolic link at `link` pointing to `target`.
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    /// Return the target of the symbolic link at `path`.
+    fn read_link(&self, _path: &Path) -> io::Result<PathBuf> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} backend cannot read symbolic links", self.scheme())))
+    }
+
+    /// Copy the directory tree at `from` into `dst` at `to` in one pass,
+    /// calling `progress` for each member written. Backends that can only
+    /// be read sequentially (compressed tarballs) override this so that
+    /// extracting a directory does not rescan the archive once per file;
+    /// the default returns `None` and `copy_tree` walks the tree itself.
+    fn export_tree(
+        &self,
+        _from: &Path,
+        _dst: &dyn Vfs,
+        _to: &Path,
+        _progress: &mut dyn FnMut(&Path),
+    ) -> Option<io::Result<u64>> {
+        None
+    }
+
+    /// Change the Unix permission bits of `path`. Backends without a
+    /// notion of Unix modes keep the default, which reports `Unsupported`.
+    fn set_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} backend cannot change permissions", self.scheme())))
+    }
+
+    /// Names of the extended attributes of `path`, without following a
+    /// final symlink.
+    fn list_xattrs(&self, _path: &Path) -> io::Result<Vec<String>> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} backend has no extended attributes", self.scheme())))
+    }
+
+    /// Value of the extended attribute `name` of `path`, or `None` when it
+    /// is not set.
+    fn get_xattr(&self, _path: &Path, _name: &str) -> io::Result<Option<Vec<u8>>> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} backend has no extended attributes", self.scheme())))
+    }
+
+    /// Set the extended attribute `name` of `path` to `value`.
+    fn set_xattr(&self, _path: &Path, _name: &str, _value: &[u8]) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} backend has no extended attributes", self.scheme())))
+    }
+}
+
+/// A `scheme://[user@]host[:port]/path` location typed by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    pub scheme: String,
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    /// Path on the remote side; empty when the URL has no path.
+    pub path: PathBuf,
+}
+
+impl RemoteUrl {
+    /// Parse `input` as a remote URL. Returns `None` for plain paths and
+    /// for malformed URLs (missing host, non-numeric port).
+    pub fn parse(input: &str) -> Option<Self> {
+        let (scheme, rest) = input.trim().split_once("://")?;
+        if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        let (user, hostport) = match authority.rsplit_once('@') {
+            Some((u, h)) => (Some(u.to_string()), h),
+            None => (None, authority),
+        };
+        let (host, port) = match hostport.rsplit_once(':') {
+            Some((h, p)) => (h, Some(p.parse().ok()?)),
+            None => (hostport, None),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(RemoteUrl {
+            scheme: scheme.to_ascii_lowercase(),
+            user: user.filter(|u| !u.is_empty()),
+            host: host.to_string(),
+            port,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Resolve a location typed into the change-path prompt into a backend and
+/// a starting directory. Plain paths (and `file://` URLs) open on the local
+/// filesystem; `sftp://` URLs connect to the remote host and `smb://` URLs
+/// connect to a share using the per-host credentials in `settings`.
+#[cfg_attr(not(feature = "smb"), allow(unused_variables))]
+pub fn open_location(input: &str, settings: &Settings) -> io::Result<(Arc<dyn Vfs>, PathBuf)> {
+    let trimmed = input.trim();
+    if let Some(local) = trimmed.strip_prefix("file://") {
+        return Ok((Arc::new(LocalVfs::new()), PathBuf::from(local)));
+    }
+    let Some(url) = RemoteUrl::parse(trimmed) else {
+        return Ok((Arc::new(LocalVfs::new()), PathBuf::from(trimmed)));
+    };
+    match url.scheme.as_str() {
+        #[cfg(feature = "sftp")]
+        "sftp" | "ssh" => {
+            let (vfs, start) = vfs_ssh::SftpVfs::connect(&url)?;
+            Ok((Arc::new(vfs), start))
+        }
+        #[cfg(feature = "smb")]
+        "smb" => {
+            let (vfs, start) = vfs_smb::SmbVfs::connect(&url, settings.smb_credentials(&url.host))?;
+            Ok((Arc::new(vfs), start))
+        }
+        other => Err(io::Error::new(io::ErrorKind::Unsupported, format!("unsupported location scheme: {}://", other))),
+    }
+}
+
+/// Backend for the host filesystem.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalVfs;
+
+impl LocalVfs {
+    /// Create a new local backend handle.
+    pub fn new() -> Self {
+        LocalVfs
+    }
+}
+
+impl Vfs for LocalVfs {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(dir)? {
+            let dir_entry = dir_entry?;
+            let metadata = fs::symlink_metadata(dir_entry.path())?;
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            entries.push(local_entry(name, &dir_entry.path(), &metadata));
+        }
+        Ok(entries)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        let metadata = fs::symlink_metadata(path)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned());
+        Ok(local_entry(name, path, &metadata))
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(fs::File::open(path)?))
+    }
+
+    fn open_write(&self, path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        Ok(Box::new(fs::File::create(path)?))
+    }
+
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path)?;
+        if metadata.is_dir() {
+            fs::remove_dir(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        crate::fs_op::symlink::create_symlink(target, link)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        crate::fs_op::permissions::change_permissions(path, mode).map_err(io::Error::other)
+    }
+
+    fn list_xattrs(&self, path: &Path) -> io::Result<Vec<String>> {
+        Ok(xattr::list(path)?.map(|n| n.to_string_lossy().into_owned()).collect())
+    }
+
+    fn get_xattr(&self, path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+        xattr::get(path, name)
+    }
+
+    fn set_xattr(&self, path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+        xattr::set(path, name, value)
+    }
+}
+
+/// Build an `Entry` for a host path from its (symlink) metadata.
+///
+/// Permission flags and ownership are filled in on a best-effort basis;
+/// failure to inspect them leaves the corresponding fields as `None`.
+fn local_entry(name: String, path: &Path, metadata: &fs::Metadata) -> Entry {
+    let modified_time = metadata.modified().ok().map(DateTime::<Local>::from);
+    let mut entry = if metadata.is_dir() {
+        Entry::directory(name, path.to_path_buf(), modified_time)
+    } else {
+        Entry::file(name, path.to_path_buf(), metadata.len(), modified_time)
+    };
+
+    if metadata.file_type().is_symlink() {
+        entry.kind = EntryKind::Symlink;
+        entry.link_target = fs::read_link(path).ok();
+        match fs::metadata(path) {
+            Ok(target) => entry.points_to_dir = target.is_dir(),
+            Err(_) => entry.broken_link = true,
+        }
+    }
+
+    if let Ok(perms) = crate::fs_op::permissions::inspect_permissions(path, false) {
+        entry.unix_mode = perms.unix_mode;
+        entry.can_read = Some(perms.can_read);
+        entry.can_write = Some(perms.can_write);
+        entry.can_execute = Some(perms.can_execute);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // `inspect_permissions` follows symlinks; keep the link's own
+        // file-type bits so callers can tell links apart.
+        entry.unix_mode = Some(metadata.mode());
+        entry.kind = EntryKind::from_mode(metadata.mode()).unwrap_or(entry.kind);
+        entry.uid = Some(metadata.uid());
+        entry.gid = Some(metadata.gid());
+
+        // Resolve uid/gid to names for display.
+        if let Some(u) = users::get_user_by_uid(metadata.uid()) {
+            entry.owner = Some(u.name().to_string_lossy().into_owned());
+        }
+        if let Some(g) = users::get_group_by_gid(metadata.gid()) {
+            entry.group = Some(g.name().to_string_lossy().into_owned());
+        }
+    }
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn local_list_returns_children_with_metadata() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("a.txt").write_str("abc").unwrap();
+        temp.child("sub").create_dir_all().unwrap();
+
+        let mut entries = LocalVfs::new().list(temp.path()).unwrap();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "a.txt");
+        assert_eq!(entries[0].size, 3);
+        assert!(!entries[0].is_dir);
+        assert_eq!(entries[0].path, temp.path().join("a.txt"));
+        assert!(entries[1].is_dir);
+        assert!(entries[0].can_read.is_some());
+    }
+
+    #[test]
+    fn local_read_write_mkdir_rename_remove_roundtrip() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let vfs = LocalVfs::new();
+        let dir = temp.path().join("d");
+        vfs.mkdir(&dir).unwrap();
+
+        let file = dir.join("f.txt");
+        vfs.open_write(&file).unwrap().write_all(b"hello").unwrap();
+        let mut s = String::new();
+        vfs.open_read(&file).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "hello");
+
+        let moved = dir.join("g.txt");
+        vfs.rename(&file, &moved).unwrap();
+        assert_eq!(vfs.stat(&moved).unwrap().size, 5);
+        assert!(vfs.stat(&file).is_err());
+
+        // Non-empty directories are not removed recursively.
+        assert!(vfs.remove(&dir).is_err());
+        vfs.remove(&moved).unwrap();
+        vfs.remove(&dir).unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn remote_url_parsing() {
+        let u = RemoteUrl::parse("sftp://admin@nas.lan:2222/mnt/tank").unwrap();
+        assert_eq!(u.scheme, "sftp");
+        assert_eq!(u.user.as_deref(), Some("admin"));
+        assert_eq!(u.host, "nas.lan");
+        assert_eq!(u.port, Some(2222));
+        assert_eq!(u.path, PathBuf::from("/mnt/tank"));
+
+        let u = RemoteUrl::parse("SFTP://nas").unwrap();
+        assert_eq!(u.scheme, "sftp");
+        assert_eq!(u.user, None);
+        assert_eq!(u.port, None);
+        assert!(u.path.as_os_str().is_empty());
+
+        assert!(RemoteUrl::parse("/home/user").is_none());
+        assert!(RemoteUrl::parse("sftp:///path").is_none());
+        assert!(RemoteUrl::parse("sftp://host:port/x").is_none());
+    }
+
+    #[test]
+    fn open_location_plain_and_unknown_schemes() {
+        let settings = Settings::default();
+        let (vfs, path) = open_location("/tmp", &settings).unwrap();
+        assert!(vfs.is_local());
+        assert_eq!(path, PathBuf::from("/tmp"));
+
+        let (vfs, path) = open_location("file:///var", &settings).unwrap();
+        assert!(vfs.is_local());
+        assert_eq!(path, PathBuf::from("/var"));
+
+        let err = open_location("gopher://host/", &settings).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local_symlink_is_not_followed_by_stat() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("target").create_dir_all().unwrap();
+        let vfs = LocalVfs::new();
+        let link = temp.path().join("link");
+        vfs.symlink(&temp.path().join("target"), &link).unwrap();
+
+        let e = vfs.stat(&link).unwrap();
+        assert_eq!(e.name, "link");
+        assert!(!e.is_dir, "stat must not follow the final symlink");
+        assert_eq!(vfs.read_link(&link).unwrap(), temp.path().join("target"));
+        vfs.remove(&link).unwrap();
+        assert!(temp.path().join("target").exists());
+    }
+}
@@ -0,0 +1,264 @@
+//! Read-only 7z backend built on the pure-Rust `sevenz-rust2` decoder.
+//!
+//! The archive header is parsed once when opened. Members are decoded on
+//! demand; in solid archives that means decompressing the block from its
+//! start up to the wanted member. Password-protected archives and methods
+//! the decoder lacks are reported as `ErrorKind::Unsupported`.
+
+use super::archive::{read_only, spawn_reader, ArchiveIndex, ArchiveSource};
+use super::transfer::is_symlink_mode;
+use super::Vfs;
+use crate::app::types::Entry;
+use chrono::{DateTime, Local};
+use sevenz_rust2::{Archive, BlockDecoder, EncoderMethod, Error as SevenZipError, Password};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Set in the Windows attributes when the high 16 bits hold a Unix mode.
+pub(super) const FILE_ATTRIBUTE_UNIX_EXTENSION: u32 = 0x8000;
+
+/// Longest symlink target read back from a member's contents.
+const MAX_LINK_LEN: u64 = 4096;
+
+/// A 7z archive opened as a directory tree.
+pub struct SevenZipVfs {
+    outer: Arc<dyn Vfs>,
+    index: ArchiveIndex,
+    source: ArchiveSource,
+    archive: Arc<Archive>,
+}
+
+impl fmt::Debug for SevenZipVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SevenZipVfs")
+            .field("archive", &self.index.root())
+            .field("members", &self.archive.files.len())
+            .field("solid", &self.archive.is_solid)
+            .finish()
+    }
+}
+
+impl SevenZipVfs {
+    /// Open the archive at `path` inside `outer` and index its members.
+    pub fn open(outer: Arc<dyn Vfs>, path: &Path) -> io::Result<Self> {
+        let source = ArchiveSource::open(outer.as_ref(), path)?;
+        let archive = Archive::read(&mut source.clone(), &Password::empty()).map_err(sevenz_error)?;
+        let modified = outer.stat(path).ok().and_then(|e| e.modified);
+        let mut index = ArchiveIndex::new(path.to_path_buf(), modified);
+
+        for (i, file) in archive.files.iter().enumerate() {
+            // Anti-items mark deletions in update archives; they have no data.
+            if file.is_anti_item() {
+                continue;
+            }
+            let name = PathBuf::from(file.name().replace('\\', "/"));
+            let modified = file
+                .has_last_modified_date
+                .then(|| DateTime::<Local>::from(SystemTime::from(file.last_modified_date())));
+            let is_dir = file.is_directory();
+            let data = (!is_dir).then_some(i);
+            if let Some(entry) = index.insert(&name, is_dir, file.size(), modified, data) {
+                let attrs = file.windows_attributes();
+                if file.has_windows_attributes && attrs & FILE_ATTRIBUTE_UNIX_EXTENSION != 0 {
+                    entry.unix_mode = Some(attrs >> 16);
+                }
+            }
+        }
+
+        Ok(SevenZipVfs { outer, index, source, archive: Arc::new(archive) })
+    }
+}
+
+impl Vfs for SevenZipVfs {
+    fn scheme(&self) -> &'static str {
+        "7z"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        self.outer.display_path(path)
+    }
+
+    fn outer(&self) -> Option<(&Arc<dyn Vfs>, &Path)> {
+        Some((&self.outer, self.index.root()))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        self.index.list(dir)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        self.index.stat(path)
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let member = self.index.member(path)?;
+        let Some(block) = self.archive.stream_map.file_block_index[member] else {
+            // Empty files have no block to decode.
+            return Ok(Box::new(io::empty()));
+        };
+        // Fail fast on encrypted blocks instead of surfacing the error on
+        // the first read.
+        let encrypted = self.archive.blocks[block]
+            .coders
+            .iter()
+            .any(|c| c.encoder_method_id() == EncoderMethod::ID_AES256_SHA256);
+        if encrypted {
+            return Err(sevenz_error(SevenZipError::PasswordRequired));
+        }
+
+        let archive = self.archive.clone();
+        let mut source = self.source.clone();
+        Ok(spawn_reader(move |out| {
+            let password = Password::empty();
+            let wanted = &archive.files[member];
+            BlockDecoder::new(1, block, &archive, &password, &mut source)
+                .for_each_entries(&mut |file, reader| {
+                    if std::ptr::eq(file, wanted) {
+                        io::copy(reader, out)?;
+                        return Ok(false);
+                    }
+                    // Solid blocks must be decoded in order; skip earlier members.
+                    io::copy(reader, &mut io::sink())?;
+                    Ok(true)
+                })
+                .map_err(sevenz_error)?;
+            Ok(())
+        }))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        // 7-Zip stores a symlink as a member whose contents are the target.
+        if !is_symlink_mode(self.index.stat(path)?.unix_mode) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a symbolic link", path.display())));
+        }
+        let mut target = String::new();
+        self.open_read(path)?.take(MAX_LINK_LEN).read_to_string(&mut target)?;
+        Ok(PathBuf::from(target))
+    }
+
+    fn open_write(&self, _path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn mkdir(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn remove(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn set_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+}
+
+pub(super) fn sevenz_error(err: SevenZipError) -> io::Error {
+    match err {
+        SevenZipError::Io(e, _) | SevenZipError::FileOpen(e, _) => e,
+        SevenZipError::PasswordRequired | SevenZipError::MaybeBadPassword(_) => {
+            io::Error::new(io::ErrorKind::Unsupported, "password-protected 7z archives are not supported")
+        }
+        SevenZipError::UnsupportedCompressionMethod(method) => {
+            io::Error::new(io::ErrorKind::Unsupported, format!("7z compression method {method} is not supported"))
+        }
+        SevenZipError::UnsupportedVersion { major, minor } => {
+            io::Error::new(io::ErrorKind::Unsupported, format!("7z format version {major}.{minor} is not supported"))
+        }
+        SevenZipError::ExternalUnsupported | SevenZipError::Unsupported(_) | SevenZipError::MaxMemLimited { .. } => {
+            io::Error::new(io::ErrorKind::Unsupported, format!("unsupported 7z feature: {err}"))
+        }
+        SevenZipError::FileNotFound => io::Error::new(io::ErrorKind::NotFound, "member not found in 7z archive"),
+        _ => io::Error::new(io::ErrorKind::InvalidData, format!("corrupt 7z archive: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::LocalVfs;
+    use sevenz_rust2::encoder_options::AesEncoderOptions;
+    use sevenz_rust2::{ArchiveEntry, ArchiveWriter, EncoderConfiguration, SourceReader};
+
+    fn unix_entry(name: &str, mode: u32) -> ArchiveEntry {
+        let mut entry = ArchiveEntry::new_file(name);
+        entry.has_windows_attributes = true;
+        entry.windows_attributes = FILE_ATTRIBUTE_UNIX_EXTENSION | (mode << 16);
+        entry
+    }
+
+    #[test]
+    fn solid_archive_lists_and_reads_members() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("a.7z");
+        let mut w = ArchiveWriter::create(&path).unwrap();
+        w.push_archive_entries(
+            vec![
+                unix_entry("src/lib.rs", 0o100644),
+                unix_entry("src/main.rs", 0o100755),
+                unix_entry("link", 0o120777),
+            ],
+            [&b"pub fn lib() {}"[..], &b"fn main() {}"[..], &b"src/main.rs"[..]].into_iter().map(SourceReader::from).collect(),
+        )
+        .unwrap();
+        w.finish().unwrap();
+
+        let vfs = SevenZipVfs::open(Arc::new(LocalVfs::new()), &path).unwrap();
+        let names: Vec<String> = vfs.list(&path).unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["link", "src"]);
+        let main = vfs.stat(&path.join("src/main.rs")).unwrap();
+        assert_eq!(main.size, 12);
+        assert_eq!(main.unix_mode, Some(0o100755));
+
+        // The second member of the solid block decodes past the first.
+        let mut s = String::new();
+        vfs.open_read(&path.join("src/main.rs")).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "fn main() {}");
+        assert_eq!(vfs.read_link(&path.join("link")).unwrap(), PathBuf::from("src/main.rs"));
+        assert_eq!(vfs.read_link(&path.join("src/lib.rs")).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(vfs.rename(&path.join("link"), &path.join("l2")).unwrap_err().kind(), io::ErrorKind::ReadOnlyFilesystem);
+    }
+
+    #[test]
+    fn encrypted_archives_are_reported_as_unsupported() {
+        let temp = assert_fs::TempDir::new().unwrap();
+
+        // Encrypted header: nothing can be listed without the password.
+        let sealed = temp.path().join("sealed.7z");
+        let mut w = ArchiveWriter::create(&sealed).unwrap();
+        w.set_content_methods(vec![
+            AesEncoderOptions::new(Password::new("secret")).into(),
+            EncoderConfiguration::new(EncoderMethod::LZMA2),
+        ]);
+        w.push_archive_entry(ArchiveEntry::new_file("x.txt"), Some(&b"x"[..])).unwrap();
+        w.finish().unwrap();
+        let err = SevenZipVfs::open(Arc::new(LocalVfs::new()), &sealed).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains("password-protected"), "{err}");
+
+        // Encrypted contents only: the listing works, reading does not.
+        let locked = temp.path().join("locked.7z");
+        let mut w = ArchiveWriter::create(&locked).unwrap();
+        w.set_encrypt_header(false);
+        w.set_content_methods(vec![
+            AesEncoderOptions::new(Password::new("secret")).into(),
+            EncoderConfiguration::new(EncoderMethod::LZMA2),
+        ]);
+        w.push_archive_entry(ArchiveEntry::new_file("x.txt"), Some(&b"x"[..])).unwrap();
+        w.finish().unwrap();
+        let vfs = SevenZipVfs::open(Arc::new(LocalVfs::new()), &locked).unwrap();
+        assert_eq!(vfs.list(&locked).unwrap().len(), 1);
+        assert_eq!(vfs.open_read(&locked.join("x.txt")).err().unwrap().kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,746 @@
+//! Read-only ISO 9660 backend with Rock Ridge and Joliet name support.
+//!
+//! The whole directory tree is walked once when the image is opened. Rock
+//! Ridge entries (POSIX names, modes, owners, timestamps, symlinks and
+//! relocated directories) are preferred when present; otherwise the Joliet
+//! tree supplies Unicode names, and plain ISO 9660 names are the fallback.
+//! File data is stored uncompressed in contiguous extents, so members are
+//! read straight from the image without a decoding thread.
+
+use super::archive::{read_only, ArchiveIndex, ArchiveLinks, ArchiveSource};
+use super::Vfs;
+use crate::app::types::Entry;
+use chrono::{DateTime, FixedOffset, Local, TimeZone};
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const SECTOR: u64 = 2048;
+/// Volume descriptors start after the 32 KiB system area.
+const FIRST_DESCRIPTOR: u64 = 16;
+/// Give up looking for the descriptor set terminator after this many sectors.
+const MAX_DESCRIPTORS: u64 = 64;
+/// Bound on chained `CE` continuation areas per directory record.
+const MAX_CONTINUATIONS: usize = 16;
+/// Largest directory or continuation area read in one piece; their sizes
+/// come from the image, so a damaged one could ask for anything.
+const MAX_AREA: usize = 16 * 1024 * 1024;
+
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+const FLAG_DIRECTORY: u8 = 0x02;
+const FLAG_MULTI_EXTENT: u8 = 0x80;
+
+/// A run of file data inside the image.
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    offset: u64,
+    len: u64,
+}
+
+/// Which directory tree names are taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Names {
+    RockRidge { skip: usize },
+    Joliet,
+    Plain,
+}
+
+/// An ISO 9660 image opened as a directory tree.
+pub struct IsoVfs {
+    outer: Arc<dyn Vfs>,
+    index: ArchiveIndex,
+    source: ArchiveSource,
+    files: Vec<Vec<Extent>>,
+    links: ArchiveLinks,
+    names: Names,
+}
+
+impl fmt::Debug for IsoVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IsoVfs")
+            .field("image", &self.index.root())
+            .field("names", &self.names)
+            .field("files", &self.files.len())
+            .finish()
+    }
+}
+
+impl IsoVfs {
+    /// Open the image at `path` inside `outer` and index its directory tree.
+    pub fn open(outer: Arc<dyn Vfs>, path: &Path) -> io::Result<Self> {
+        let source = ArchiveSource::open(outer.as_ref(), path)?;
+        let (block_size, primary, joliet) = read_descriptors(&source)?;
+        let modified = outer.stat(path).ok().and_then(|e| e.modified);
+
+        // Rock Ridge announces itself with an `SP` entry in the system use
+        // area of the root directory's "." record.
+        let dot = read_directory(&source, primary.offset(block_size), SECTOR)?.into_iter().next();
+        let names = match (dot.and_then(|d| susp_skip(&d.system_use)), joliet.is_some()) {
+            (Some(skip), _) => Names::RockRidge { skip },
+            (None, true) => Names::Joliet,
+            (None, false) => Names::Plain,
+        };
+        let root = match names {
+            Names::Joliet => joliet.unwrap_or(primary),
+            _ => primary,
+        };
+
+        let mut vfs = IsoVfs {
+            outer,
+            index: ArchiveIndex::new(path.to_path_buf(), modified),
+            source,
+            files: Vec::new(),
+            links: ArchiveLinks::default(),
+            names,
+        };
+        vfs.walk(root, block_size)?;
+        Ok(vfs)
+    }
+
+    /// Index every directory reachable from `root`.
+    fn walk(&mut self, root: Record, block_size: u64) -> io::Result<()> {
+        let mut pending = VecDeque::from([(PathBuf::new(), root.offset(block_size), root.size)]);
+        let mut seen = HashSet::new();
+        while let Some((rel, offset, size)) = pending.pop_front() {
+            if !seen.insert(offset) {
+                continue;
+            }
+            let records = read_directory(&self.source, offset, size)?;
+            // Skip the "." and ".." records.
+            let mut records = records.into_iter().skip(2);
+            while let Some(record) = records.next() {
+                let rr = match self.names {
+                    Names::RockRidge { skip } => self.rock_ridge(&record.system_use, skip, block_size)?,
+                    _ => RockRidge::default(),
+                };
+                // Relocated directories show up again through their `CL`
+                // placeholder at the original location.
+                if rr.relocated {
+                    continue;
+                }
+                let name = match (&rr.name, self.names) {
+                    (Some(name), _) => name.clone(),
+                    (None, Names::Joliet) => strip_version(&decode_ucs2(&record.name)),
+                    (None, _) => strip_version(&String::from_utf8_lossy(&record.name)),
+                };
+                if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+                    continue;
+                }
+
+                // Files larger than 4 GiB are split over several records.
+                let mut extents = vec![Extent { offset: record.offset(block_size), len: record.size }];
+                let mut multi = record.flags & FLAG_MULTI_EXTENT != 0;
+                while multi {
+                    let Some(next) = records.next() else { break };
+                    extents.push(Extent { offset: next.offset(block_size), len: next.size });
+                    multi = next.flags & FLAG_MULTI_EXTENT != 0;
+                }
+                let size = extents.iter().map(|e| e.len).sum();
+
+                let modified = rr.modified.or_else(|| record_time(&record.recorded));
+                let child = rel.join(&name);
+                let is_link = rr.mode.is_some_and(|m| m & S_IFMT == S_IFLNK) || rr.link.is_some();
+                let is_dir = !is_link && (record.flags & FLAG_DIRECTORY != 0 || rr.child_link.is_some());
+
+                let member = if is_dir {
+                    match rr.child_link {
+                        Some(lba) => {
+                            let offset = lba as u64 * block_size;
+                            let dot = read_directory(&self.source, offset, SECTOR)?.into_iter().next();
+                            pending.push_back((child.clone(), offset, dot.map_or(SECTOR, |d| d.size)));
+                        }
+                        None => pending.push_back((child.clone(), extents[0].offset, record.size)),
+                    }
+                    None
+                } else if is_link {
+                    None
+                } else {
+                    self.files.push(extents);
+                    Some(self.files.len() - 1)
+                };
+
+                let size = if is_dir || is_link { 0 } else { size };
+                let Some(entry) = self.index.insert(&child, is_dir, size, modified, member) else { continue };
+                entry.unix_mode = rr.mode;
+                entry.uid = rr.uid;
+                entry.gid = rr.gid;
+                let path = entry.path.clone();
+                if let Some(target) = rr.link {
+                    self.links.insert(path, PathBuf::from(target));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the Rock Ridge entries in a record's system use area,
+    /// following `CE` continuation areas.
+    fn rock_ridge(&self, system_use: &[u8], skip: usize, block_size: u64) -> io::Result<RockRidge> {
+        let mut rr = RockRidge::default();
+        let mut areas = VecDeque::from([system_use.get(skip..).unwrap_or_default().to_vec()]);
+        let mut continuations = 0;
+        let mut name = String::new();
+        let mut link = SymlinkBuilder::default();
+
+        while let Some(area) = areas.pop_front() {
+            let mut pos = 0;
+            while pos + 4 <= area.len() {
+                let len = area[pos + 2] as usize;
+                if len < 4 || pos + len > area.len() {
+                    break;
+                }
+                let data = &area[pos + 4..pos + len];
+                match &area[pos..pos + 2] {
+                    // Flags 0x02/0x04 name "." and ".."; those records are
+                    // skipped anyway.
+                    b"NM" if !data.is_empty() && data[0] & 0x06 == 0 => {
+                        name.push_str(&String::from_utf8_lossy(&data[1..]));
+                    }
+                    b"PX" if data.len() >= 32 => {
+                        rr.mode = Some(le32(data, 0));
+                        rr.uid = Some(le32(data, 16));
+                        rr.gid = Some(le32(data, 24));
+                    }
+                    b"SL" if !data.is_empty() => link.push_record(&data[1..]),
+                    b"TF" if !data.is_empty() => rr.modified = tf_modified(data),
+                    b"CL" if data.len() >= 8 => rr.child_link = Some(le32(data, 0)),
+                    b"RE" => rr.relocated = true,
+                    b"CE" if data.len() >= 24 && continuations < MAX_CONTINUATIONS => {
+                        continuations += 1;
+                        let offset = le32(data, 0) as u64 * block_size + le32(data, 8) as u64;
+                        areas.push_back(read_at(&self.source, offset, le32(data, 16) as usize)?);
+                    }
+                    b"ST" => break,
+                    _ => {}
+                }
+                pos += len;
+            }
+        }
+
+        rr.name = (!name.is_empty()).then_some(name);
+        rr.link = link.finish();
+        Ok(rr)
+    }
+}
+
+impl Vfs for IsoVfs {
+    fn scheme(&self) -> &'static str {
+        "iso"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        self.outer.display_path(path)
+    }
+
+    fn outer(&self) -> Option<(&Arc<dyn Vfs>, &Path)> {
+        Some((&self.outer, self.index.root()))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        self.index.list(&self.links.resolve(dir)?)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        self.index.stat(path)
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let member = self.index.member(&self.links.resolve(path)?)?;
+        let extents = self.files[member].iter().copied().collect();
+        Ok(Box::new(ExtentReader { source: self.source.clone(), extents, left: 0 }))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.links.read_link(path)
+    }
+
+    fn open_write(&self, _path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn mkdir(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn remove(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn set_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+}
+
+/// Reads a member's extents one after another.
+struct ExtentReader {
+    source: ArchiveSource,
+    extents: VecDeque<Extent>,
+    left: u64,
+}
+
+impl Read for ExtentReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.left == 0 {
+            let Some(extent) = self.extents.pop_front() else { return Ok(0) };
+            self.source.seek(SeekFrom::Start(extent.offset))?;
+            self.left = extent.len;
+        }
+        let want = buf.len().min(self.left as usize);
+        let n = self.source.read(&mut buf[..want])?;
+        if n == 0 && want > 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "ISO image is truncated"));
+        }
+        self.left -= n as u64;
+        Ok(n)
+    }
+}
+
+/// One directory record, with the fields this backend uses.
+#[derive(Debug, Clone)]
+struct Record {
+    extent: u32,
+    size: u64,
+    flags: u8,
+    recorded: [u8; 7],
+    name: Vec<u8>,
+    system_use: Vec<u8>,
+}
+
+impl Record {
+    fn parse(buf: &[u8]) -> Option<Record> {
+        let len = *buf.first()? as usize;
+        if len < 34 || len > buf.len() {
+            return None;
+        }
+        let name_len = buf[32] as usize;
+        if 33 + name_len > len {
+            return None;
+        }
+        // A padding byte follows names of even length.
+        let system_use = (33 + name_len + name_len.is_multiple_of(2) as usize).min(len);
+        Some(Record {
+            extent: le32(buf, 2),
+            size: le32(buf, 10) as u64,
+            flags: buf[25],
+            recorded: buf[18..25].try_into().ok()?,
+            name: buf[33..33 + name_len].to_vec(),
+            system_use: buf[system_use..len].to_vec(),
+        })
+    }
+
+    fn offset(&self, block_size: u64) -> u64 {
+        self.extent as u64 * block_size
+    }
+}
+
+/// What the Rock Ridge entries of one record say.
+#[derive(Debug, Default)]
+struct RockRidge {
+    name: Option<String>,
+    mode: Option<u32>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    modified: Option<DateTime<Local>>,
+    link: Option<String>,
+    child_link: Option<u32>,
+    relocated: bool,
+}
+
+/// Joins the component records of one or more `SL` entries.
+#[derive(Debug, Default)]
+struct SymlinkBuilder {
+    target: String,
+    started: bool,
+    /// The previous component continues in the next one (no separator).
+    joined: bool,
+}
+
+impl SymlinkBuilder {
+    fn push_record(&mut self, mut components: &[u8]) {
+        while components.len() >= 2 {
+            let (flags, len) = (components[0], components[1] as usize);
+            let Some(content) = components.get(2..2 + len) else { break };
+            let part: Cow<str> = match flags {
+                f if f & 0x02 != 0 => ".".into(),
+                f if f & 0x04 != 0 => "..".into(),
+                f if f & 0x08 != 0 => {
+                    self.target.clear();
+                    self.target.push('/');
+                    self.started = true;
+                    self.joined = true;
+                    components = &components[2 + len..];
+                    continue;
+                }
+                _ => String::from_utf8_lossy(content),
+            };
+            if self.started && !self.joined {
+                self.target.push('/');
+            }
+            self.target.push_str(&part);
+            self.started = true;
+            self.joined = flags & 0x01 != 0;
+            components = &components[2 + len..];
+        }
+    }
+
+    fn finish(self) -> Option<String> {
+        self.started.then_some(self.target)
+    }
+}
+
+/// Read the volume descriptor set and return the block size together with
+/// the root directory records of the primary and Joliet volumes.
+fn read_descriptors(source: &ArchiveSource) -> io::Result<(u64, Record, Option<Record>)> {
+    let mut primary = None;
+    let mut joliet = None;
+    let mut block_size = SECTOR;
+    for sector in FIRST_DESCRIPTOR..FIRST_DESCRIPTOR + MAX_DESCRIPTORS {
+        let buf = match read_at(source, sector * SECTOR, SECTOR as usize) {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        };
+        match &buf[1..6] {
+            b"CD001" => {}
+            b"BEA01" | b"NSR02" | b"NSR03" | b"TEA01" if primary.is_none() => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "UDF-only disc images are not supported"));
+            }
+            _ => break,
+        }
+        match buf[0] {
+            1 => {
+                block_size = u16::from_le_bytes([buf[128], buf[129]]) as u64;
+                primary = Record::parse(&buf[156..190]);
+            }
+            // Joliet is a supplementary descriptor with a UCS-2 escape sequence.
+            2 if matches!(&buf[88..91], b"%/@" | b"%/C" | b"%/E") => joliet = Record::parse(&buf[156..190]),
+            255 => break,
+            _ => {}
+        }
+    }
+    let primary = primary.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not an ISO 9660 image"))?;
+    if !matches!(block_size, 512 | 1024 | 2048) {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, format!("ISO block size {block_size} is not supported")));
+    }
+    Ok((block_size, primary, joliet))
+}
+
+/// Parse the records of the directory stored at `offset`. Records never
+/// straddle a sector boundary; a zero length byte pads to the next sector.
+fn read_directory(source: &ArchiveSource, offset: u64, size: u64) -> io::Result<Vec<Record>> {
+    let data = read_at(source, offset, usize::try_from(size).unwrap_or(usize::MAX))?;
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        if data[pos] == 0 {
+            pos = (pos / SECTOR as usize + 1) * SECTOR as usize;
+            continue;
+        }
+        let Some(record) = Record::parse(&data[pos..]) else { break };
+        pos += data[pos] as usize;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Length of the SUSP header to skip in every record, if the `SP` entry is
+/// present.
+fn susp_skip(system_use: &[u8]) -> Option<usize> {
+    (system_use.len() >= 7 && &system_use[..2] == b"SP" && system_use[4..6] == [0xBE, 0xEF]).then(|| system_use[6] as usize)
+}
+
+/// Read `len` bytes at `offset`, refusing areas larger than `MAX_AREA` or
+/// reaching past the end of the image.
+fn read_at(source: &ArchiveSource, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut source = source.clone();
+    let image_len = source.seek(SeekFrom::End(0))?;
+    if len > MAX_AREA || offset.checked_add(len as u64).is_none_or(|end| end > image_len) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} bytes at offset {} do not fit in the image", len, offset)));
+    }
+    source.seek(SeekFrom::Start(offset))?;
+    let mut buf = vec![0; len];
+    source.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Little-endian half of a both-endian (or plain LE) 32-bit field.
+fn le32(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]])
+}
+
+fn decode_ucs2(bytes: &[u8]) -> String {
+    let units = bytes.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]]));
+    char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+}
+
+/// Drop the `;1` version suffix and the trailing dot of extension-less
+/// ISO 9660 file names.
+fn strip_version(name: &str) -> String {
+    let name = name.split(';').next().unwrap_or(name);
+    name.strip_suffix('.').unwrap_or(name).to_string()
+}
+
+/// The 7-byte recording date of a directory record.
+fn record_time(b: &[u8; 7]) -> Option<DateTime<Local>> {
+    let offset = FixedOffset::east_opt(b[6] as i8 as i32 * 15 * 60)?;
+    let dt = offset
+        .with_ymd_and_hms(1900 + b[0] as i32, b[1] as u32, b[2] as u32, b[3] as u32, b[4] as u32, b[5] as u32)
+        .single()?;
+    Some(dt.with_timezone(&Local))
+}
+
+/// The 17-byte ASCII date format used by volume descriptors and long-form
+/// `TF` entries.
+fn long_time(b: &[u8]) -> Option<DateTime<Local>> {
+    let digits = std::str::from_utf8(b.get(..14)?).ok()?;
+    let field = |r: std::ops::Range<usize>| digits.get(r)?.parse::<u32>().ok();
+    let offset = FixedOffset::east_opt(*b.get(16)? as i8 as i32 * 15 * 60)?;
+    let dt = offset
+        .with_ymd_and_hms(field(0..4)? as i32, field(4..6)?, field(6..8)?, field(8..10)?, field(10..12)?, field(12..14)?)
+        .single()?;
+    Some(dt.with_timezone(&Local))
+}
+
+/// Modification time from a `TF` entry. Timestamps appear in flag-bit
+/// order (creation, modify, access, ...), so the modify stamp follows the
+/// creation stamp when one is present.
+fn tf_modified(data: &[u8]) -> Option<DateTime<Local>> {
+    let flags = data[0];
+    if flags & 0x02 == 0 {
+        return None;
+    }
+    let width = if flags & 0x80 != 0 { 17 } else { 7 };
+    let start = 1 + (flags & 0x01) as usize * width;
+    let stamp = data.get(start..start + width)?;
+    if width == 7 {
+        record_time(stamp.try_into().ok()?)
+    } else {
+        long_time(stamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::LocalVfs;
+
+    fn both16(v: u16) -> Vec<u8> {
+        [v.to_le_bytes(), v.to_be_bytes()].concat()
+    }
+
+    fn both32(v: u32) -> Vec<u8> {
+        [v.to_le_bytes(), v.to_be_bytes()].concat()
+    }
+
+    /// 2024-05-17 10:30:00 UTC in directory record format.
+    const STAMP: [u8; 7] = [124, 5, 17, 10, 30, 0, 0];
+
+    fn record(name: &[u8], lba: u32, len: u32, flags: u8, su: &[u8]) -> Vec<u8> {
+        let pad = name.len().is_multiple_of(2) as usize;
+        let mut total = 33 + name.len() + pad + su.len();
+        total += total % 2;
+        let mut r = vec![0u8; total];
+        r[0] = total as u8;
+        r[2..10].copy_from_slice(&both32(lba));
+        r[10..18].copy_from_slice(&both32(len));
+        r[18..25].copy_from_slice(&STAMP);
+        r[25] = flags;
+        r[28..32].copy_from_slice(&both16(1));
+        r[32] = name.len() as u8;
+        r[33..33 + name.len()].copy_from_slice(name);
+        r[33 + name.len() + pad..33 + name.len() + pad + su.len()].copy_from_slice(su);
+        r
+    }
+
+    fn susp(sig: &[u8; 2], data: &[u8]) -> Vec<u8> {
+        [&sig[..], &[4 + data.len() as u8, 1], data].concat()
+    }
+
+    fn rr(name: &str, mode: u32, uid: u32) -> Vec<u8> {
+        let px = [both32(mode), both32(1), both32(uid), both32(100)].concat();
+        [susp(b"NM", &[&[0u8][..], name.as_bytes()].concat()), susp(b"PX", &px)].concat()
+    }
+
+    fn ucs2(name: &str) -> Vec<u8> {
+        name.encode_utf16().flat_map(|u| u.to_be_bytes()).collect()
+    }
+
+    fn descriptor(kind: u8, root_lba: u32, joliet: bool) -> Vec<u8> {
+        let mut d = vec![0u8; SECTOR as usize];
+        d[0] = kind;
+        d[1..6].copy_from_slice(b"CD001");
+        d[6] = 1;
+        if joliet {
+            d[88..91].copy_from_slice(b"%/E");
+        }
+        d[128..132].copy_from_slice(&both16(SECTOR as u16));
+        d[156..190].copy_from_slice(&record(&[0], root_lba, SECTOR as u32, FLAG_DIRECTORY, &[]));
+        d
+    }
+
+    fn directory(records: Vec<Vec<u8>>) -> Vec<u8> {
+        records.concat()
+    }
+
+    /// Layout: descriptors at 16..=18, primary tree at 20/21, Joliet tree
+    /// at 22/23, file data from 24.
+    fn build_iso(rock_ridge: bool, joliet: bool) -> Vec<u8> {
+        let mut img = vec![0u8; 28 * SECTOR as usize];
+        let mut put = |sector: u64, bytes: Vec<u8>| {
+            let at = (sector * SECTOR) as usize;
+            img[at..at + bytes.len()].copy_from_slice(&bytes);
+        };
+        let su = |data: Vec<u8>| if rock_ridge { data } else { Vec::new() };
+
+        put(16, descriptor(1, 20, false));
+        put(17, if joliet { descriptor(2, 22, true) } else { vec![0u8; SECTOR as usize] });
+        put(if joliet { 18 } else { 17 }, [&[255u8][..], b"CD001", &[1]].concat());
+
+        let tf = susp(b"TF", &[&[0x02u8][..], &[123, 1, 2, 3, 4, 5, 0]].concat());
+        let link = susp(b"SL", &[0, 0, 4, b'd', b'o', b'c', b's', 0, 9, b'g', b'u', b'i', b'd', b'e', b'.', b't', b'x', b't']);
+        put(
+            20,
+            directory(vec![
+                record(&[0], 20, 2048, FLAG_DIRECTORY, &su(susp(b"SP", &[0xBE, 0xEF, 0]))),
+                record(&[1], 20, 2048, FLAG_DIRECTORY, &[]),
+                record(b"DOCS", 21, 2048, FLAG_DIRECTORY, &su(rr("docs", 0o040750, 0))),
+                record(b"LATEST.;1", 0, 0, 0, &su([rr("latest", 0o120777, 0), link].concat())),
+                record(b"README.TXT;1", 24, 9, 0, &su([rr("readme.txt", 0o100640, 1000), tf].concat())),
+            ]),
+        );
+        put(
+            21,
+            directory(vec![
+                record(&[0], 21, 2048, FLAG_DIRECTORY, &[]),
+                record(&[1], 20, 2048, FLAG_DIRECTORY, &[]),
+                record(b"BIG.BIN;1", 26, 2048, FLAG_MULTI_EXTENT, &su(rr("big.bin", 0o100644, 0))),
+                record(b"BIG.BIN;1", 27, 3, 0, &[]),
+                record(b"GUIDE.TXT;1", 25, 5, 0, &su(rr("guide.txt", 0o100644, 0))),
+            ]),
+        );
+        put(
+            22,
+            directory(vec![
+                record(&[0], 22, 2048, FLAG_DIRECTORY, &[]),
+                record(&[1], 22, 2048, FLAG_DIRECTORY, &[]),
+                record(&ucs2("Docs"), 23, 2048, FLAG_DIRECTORY, &[]),
+                record(&ucs2("readme.txt;1"), 24, 9, 0, &[]),
+            ]),
+        );
+        put(
+            23,
+            directory(vec![
+                record(&[0], 23, 2048, FLAG_DIRECTORY, &[]),
+                record(&[1], 22, 2048, FLAG_DIRECTORY, &[]),
+                record(&ucs2("Guide – long name.txt;1"), 25, 5, 0, &[]),
+            ]),
+        );
+        put(24, b"hello iso".to_vec());
+        put(25, b"guide".to_vec());
+        put(26, vec![b'a'; SECTOR as usize]);
+        put(27, b"end".to_vec());
+        img
+    }
+
+    fn open(temp: &assert_fs::TempDir, name: &str, bytes: Vec<u8>) -> (IsoVfs, PathBuf) {
+        let path = temp.path().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        (IsoVfs::open(Arc::new(LocalVfs::new()), &path).unwrap(), path)
+    }
+
+    fn read(vfs: &IsoVfs, path: &Path) -> String {
+        let mut s = String::new();
+        vfs.open_read(path).unwrap().read_to_string(&mut s).unwrap();
+        s
+    }
+
+    fn names(vfs: &IsoVfs, dir: &Path) -> Vec<String> {
+        vfs.list(dir).unwrap().into_iter().map(|e| e.name).collect()
+    }
+
+    #[test]
+    fn rock_ridge_names_modes_links_and_multi_extent_files() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let (vfs, path) = open(&temp, "rr.iso", build_iso(true, true));
+        assert_eq!(names(&vfs, &path), vec!["docs", "latest", "readme.txt"]);
+
+        let readme = vfs.stat(&path.join("readme.txt")).unwrap();
+        assert_eq!(readme.size, 9);
+        assert_eq!(readme.unix_mode, Some(0o100640));
+        assert_eq!((readme.uid, readme.gid), (Some(1000), Some(100)));
+        assert_eq!(readme.modified.unwrap().timestamp(), 1_672_628_645); // 2023-01-02 03:04:05 UTC
+        assert_eq!(read(&vfs, &path.join("readme.txt")), "hello iso");
+
+        assert_eq!(vfs.read_link(&path.join("latest")).unwrap(), PathBuf::from("docs/guide.txt"));
+        assert_eq!(read(&vfs, &path.join("latest")), "guide");
+
+        let big = path.join("docs/big.bin");
+        assert_eq!(vfs.stat(&big).unwrap().size, 2051);
+        let data = read(&vfs, &big);
+        assert_eq!(data.len(), 2051);
+        assert!(data.ends_with("aend"));
+        assert_eq!(vfs.open_write(&big).err().unwrap().kind(), io::ErrorKind::ReadOnlyFilesystem);
+    }
+
+    #[test]
+    fn joliet_then_plain_names_without_rock_ridge() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let (vfs, path) = open(&temp, "joliet.iso", build_iso(false, true));
+        assert_eq!(names(&vfs, &path), vec!["Docs", "readme.txt"]);
+        assert_eq!(names(&vfs, &path.join("Docs")), vec!["Guide – long name.txt"]);
+        let readme = vfs.stat(&path.join("readme.txt")).unwrap();
+        assert_eq!(readme.unix_mode, None);
+        assert_eq!(readme.modified.unwrap().timestamp(), 1_715_941_800); // 2024-05-17 10:30 UTC
+
+        let (vfs, path) = open(&temp, "plain.iso", build_iso(false, false));
+        assert_eq!(names(&vfs, &path), vec!["DOCS", "LATEST", "README.TXT"]);
+        assert_eq!(read(&vfs, &path.join("DOCS/GUIDE.TXT")), "guide");
+    }
+
+    #[test]
+    fn oversized_directories_and_continuation_areas_are_rejected() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let mut img = build_iso(true, false);
+        // DOCS claims an extent of almost 4 GiB.
+        let docs = img[(20 * SECTOR) as usize..].windows(4).position(|w| w == b"DOCS").unwrap() + (20 * SECTOR) as usize - 33;
+        img[docs + 10..docs + 18].copy_from_slice(&both32(u32::MAX - 1));
+        let path = temp.path().join("huge.iso");
+        std::fs::write(&path, &img).unwrap();
+        let err = IsoVfs::open(Arc::new(LocalVfs::new()), &path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // `CE` areas go through the same check.
+        let source = ArchiveSource::from_bytes(img);
+        assert_eq!(read_at(&source, 24 * SECTOR, u32::MAX as usize).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(read_at(&source, u64::MAX, 1).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(read_at(&source, 24 * SECTOR, 9).unwrap(), b"hello iso");
+    }
+
+    #[test]
+    fn non_iso_and_udf_images_are_rejected() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("junk.iso");
+        std::fs::write(&path, vec![7u8; 40 * SECTOR as usize]).unwrap();
+        let err = IsoVfs::open(Arc::new(LocalVfs::new()), &path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut udf = vec![0u8; 40 * SECTOR as usize];
+        udf[(16 * SECTOR) as usize + 1..(16 * SECTOR) as usize + 6].copy_from_slice(b"BEA01");
+        std::fs::write(&path, udf).unwrap();
+        let err = IsoVfs::open(Arc::new(LocalVfs::new()), &path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,751 @@
+//! In-memory backend for deterministic tests.
+//!
+//! `MemVfs` keeps a whole tree (files, directories and symlinks, with
+//! modes, owners, mtimes and extended attributes) behind a mutex, so panels,
+//! copy/move workers and previews can be exercised without touching disk
+//! and without depending on what the host filesystem supports.
+//!
+//! Permissions are enforced the way a Unix kernel would for the backend's
+//! current identity (`set_identity`, uid/gid 1000 by default; uid 0
+//! bypasses every check). Failures can be injected per operation and path
+//! with `fail`, or part-way through a write with `fail_write_after`;
+//! `corrupt_writes` makes writes succeed with the wrong bytes.
+//! Setup helpers (`write_file`, `create_dir_all`, `set_owner`, ...) bypass
+//! both so tests can build trees the identity itself could not.
+
+use super::archive::normalize;
+use super::Vfs;
+use crate::app::types::Entry;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Symlinks followed while resolving one path before giving up (`ELOOP`).
+const MAX_LINKS: usize = 40;
+
+const S_IFREG: u32 = 0o100000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+
+/// Operations that can be made to fail with `MemVfs::fail`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemOp {
+    List,
+    Stat,
+    Read,
+    Write,
+    Mkdir,
+    Rename,
+    Remove,
+    Symlink,
+    ReadLink,
+    SetMode,
+    Xattr,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    File(Vec<u8>),
+    Dir,
+    Symlink(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    kind: Kind,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    modified: DateTime<Local>,
+    xattrs: BTreeMap<String, Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+struct Failure {
+    op: MemOp,
+    path: PathBuf,
+    kind: io::ErrorKind,
+    /// For writes: bytes accepted before the error is returned.
+    after: Option<u64>,
+}
+
+#[derive(Debug)]
+struct State {
+    nodes: BTreeMap<PathBuf, Node>,
+    uid: u32,
+    gid: u32,
+    clock: DateTime<Local>,
+    users: BTreeMap<u32, String>,
+    groups: BTreeMap<u32, String>,
+    failures: Vec<Failure>,
+    /// Files whose writes store every byte inverted.
+    corrupt: Vec<PathBuf>,
+}
+
+enum Walk {
+    Done(PathBuf),
+    Link(PathBuf),
+}
+
+/// An in-memory filesystem. Clones share the same tree, so a test can keep
+/// one handle for assertions while panels and workers use another.
+#[derive(Clone)]
+pub struct MemVfs {
+    state: Arc<Mutex<State>>,
+}
+
+impl fmt::Debug for MemVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("MemVfs").field("nodes", &state.nodes.len()).field("uid", &state.uid).finish()
+    }
+}
+
+impl Default for MemVfs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemVfs {
+    /// Create a tree holding only `/`, owned by the default identity
+    /// (uid/gid 1000) with mode `0755`. Every timestamp comes from a fixed
+    /// clock (2024-01-01 00:00 UTC) until `set_clock` is called.
+    pub fn new() -> Self {
+        let clock = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().with_timezone(&Local);
+        let mut state = State {
+            nodes: BTreeMap::new(),
+            uid: 1000,
+            gid: 1000,
+            clock,
+            users: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            failures: Vec::new(),
+            corrupt: Vec::new(),
+        };
+        let root = state.node(Kind::Dir, 0o755);
+        state.nodes.insert(PathBuf::from("/"), root);
+        MemVfs { state: Arc::new(Mutex::new(state)) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Act as `uid`/`gid` for permission checks and for ownership of
+    /// newly created nodes.
+    pub fn set_identity(&self, uid: u32, gid: u32) {
+        let mut state = self.lock();
+        state.uid = uid;
+        state.gid = gid;
+    }
+
+    /// Timestamp given to nodes created or written from now on.
+    pub fn set_clock(&self, now: DateTime<Local>) {
+        self.lock().clock = now;
+    }
+
+    /// Name reported as `Entry::owner` for `uid`.
+    pub fn add_user(&self, uid: u32, name: &str) {
+        self.lock().users.insert(uid, name.to_string());
+    }
+
+    /// Name reported as `Entry::group` for `gid`.
+    pub fn add_group(&self, gid: u32, name: &str) {
+        self.lock().groups.insert(gid, name.to_string());
+    }
+
+    /// Create `path` and any missing parents as directories.
+    pub fn create_dir_all(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut state = self.lock();
+        let key = state.resolve(path.as_ref(), true, false)?;
+        state.create_dirs(&key)
+    }
+
+    /// Create or replace the file at `path` with `contents`, creating
+    /// missing parent directories.
+    pub fn write_file(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        let mut state = self.lock();
+        let key = state.resolve(path.as_ref(), true, false)?;
+        if let Some(parent) = key.parent() {
+            state.create_dirs(parent)?;
+        }
+        match state.nodes.get_mut(&key) {
+            Some(Node { kind: Kind::Dir, .. }) => Err(is_a_directory(&key)),
+            Some(node) => {
+                node.kind = Kind::File(contents.as_ref().to_vec());
+                Ok(())
+            }
+            None => {
+                let node = state.node(Kind::File(contents.as_ref().to_vec()), 0o644);
+                state.nodes.insert(key, node);
+                Ok(())
+            }
+        }
+    }
+
+    /// Contents of the file at `path`, following symlinks.
+    pub fn read_file(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        let state = self.lock();
+        let key = state.resolve(path.as_ref(), true, false)?;
+        match &state.get(&key)?.kind {
+            Kind::File(data) => Ok(data.clone()),
+            _ => Err(is_a_directory(&key)),
+        }
+    }
+
+    /// Whether anything (including a dangling symlink) exists at `path`.
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        let state = self.lock();
+        state.resolve(path.as_ref(), false, false).is_ok_and(|key| state.nodes.contains_key(&key))
+    }
+
+    /// Change the owner of `path` (not following a final symlink).
+    pub fn set_owner(&self, path: impl AsRef<Path>, uid: u32, gid: u32) -> io::Result<()> {
+        let mut state = self.lock();
+        let node = state.get_mut_unchecked(path.as_ref())?;
+        node.uid = uid;
+        node.gid = gid;
+        Ok(())
+    }
+
+    /// Change the modification time of `path` (not following a final
+    /// symlink).
+    pub fn set_modified(&self, path: impl AsRef<Path>, modified: DateTime<Local>) -> io::Result<()> {
+        self.lock().get_mut_unchecked(path.as_ref())?.modified = modified;
+        Ok(())
+    }
+
+    /// Make every `op` on `path` fail with `kind` until `heal` is called.
+    /// `path` is matched as given by the caller, before symlinks are
+    /// resolved.
+    pub fn fail(&self, op: MemOp, path: impl AsRef<Path>, kind: io::ErrorKind) {
+        let path = absolute(path.as_ref());
+        self.lock().failures.push(Failure { op, path, kind, after: None });
+    }
+
+    /// Let writes to `path` accept `bytes` bytes and then fail with `kind`,
+    /// leaving the partial contents behind.
+    pub fn fail_write_after(&self, path: impl AsRef<Path>, bytes: u64, kind: io::ErrorKind) {
+        let path = absolute(path.as_ref());
+        self.lock().failures.push(Failure { op: MemOp::Write, path, kind, after: Some(bytes) });
+    }
+
+    /// Let writes to `path` succeed but store every byte inverted, like a
+    /// target that silently corrupts data.
+    pub fn corrupt_writes(&self, path: impl AsRef<Path>) {
+        let path = absolute(path.as_ref());
+        self.lock().corrupt.push(path);
+    }
+
+    /// Remove every injected failure and corruption for `path`.
+    pub fn heal(&self, path: impl AsRef<Path>) {
+        let path = absolute(path.as_ref());
+        let mut state = self.lock();
+        state.failures.retain(|f| f.path != path);
+        state.corrupt.retain(|p| *p != path);
+    }
+}
+
+impl State {
+    fn node(&self, kind: Kind, mode: u32) -> Node {
+        Node { kind, mode, uid: self.uid, gid: self.gid, modified: self.clock, xattrs: BTreeMap::new() }
+    }
+
+    fn get(&self, key: &Path) -> io::Result<&Node> {
+        self.nodes.get(key).ok_or_else(|| not_found(key))
+    }
+
+    fn get_mut_unchecked(&mut self, path: &Path) -> io::Result<&mut Node> {
+        let key = self.resolve(path, false, false)?;
+        self.nodes.get_mut(&key).ok_or_else(|| not_found(&key))
+    }
+
+    /// Return the injected error for `op` on `path`, if any. Write
+    /// failures with a byte budget are reported by the writer instead.
+    fn injected(&self, op: MemOp, path: &Path) -> io::Result<()> {
+        let path = absolute(path);
+        match self.failures.iter().find(|f| f.op == op && f.path == path && f.after.is_none()) {
+            Some(f) => Err(io::Error::new(f.kind, format!("injected {:?} failure: {}", op, path.display()))),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the current identity has `want` (4 = read, 2 = write,
+    /// 1 = execute) on `node`.
+    fn permits(&self, node: &Node, want: u32) -> bool {
+        if self.uid == 0 {
+            return true;
+        }
+        let shift = if node.uid == self.uid {
+            6
+        } else if node.gid == self.gid {
+            3
+        } else {
+            0
+        };
+        (node.mode >> shift) & want == want
+    }
+
+    fn require(&self, key: &Path, want: u32) -> io::Result<&Node> {
+        let node = self.get(key)?;
+        if self.permits(node, want) {
+            Ok(node)
+        } else {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("permission denied: {}", key.display())))
+        }
+    }
+
+    /// Resolve `path` to the key of the node it names. Symlinks in parent
+    /// components are always followed, a final symlink only when
+    /// `follow_final` is set. The final component need not exist; its
+    /// parent must be a searchable directory unless `check` is off, in
+    /// which case permissions are ignored and missing parents are kept
+    /// as written.
+    fn resolve(&self, path: &Path, follow_final: bool, check: bool) -> io::Result<PathBuf> {
+        let mut pending = absolute(path);
+        for _ in 0..MAX_LINKS {
+            match self.walk(&pending, follow_final, check)? {
+                Walk::Done(key) => return Ok(key),
+                Walk::Link(next) => pending = next,
+            }
+        }
+        Err(io::Error::other(format!("too many levels of symbolic links: {}", path.display())))
+    }
+
+    fn walk(&self, path: &Path, follow_final: bool, check: bool) -> io::Result<Walk> {
+        let components: Vec<_> = path.components().skip(1).collect();
+        let mut cur = PathBuf::from("/");
+        for (i, component) in components.iter().enumerate() {
+            match self.get(&cur)?.kind {
+                Kind::Dir => {}
+                _ => return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} is not a directory", cur.display()))),
+            }
+            if check {
+                self.require(&cur, 1)?;
+            }
+            cur.push(component);
+            let last = i + 1 == components.len();
+            match self.nodes.get(&cur) {
+                None if last => break,
+                // Setup helpers create missing parents themselves.
+                None if !check => {
+                    cur.extend(&components[i + 1..]);
+                    break;
+                }
+                None => return Err(not_found(&cur)),
+                Some(Node { kind: Kind::Symlink(target), .. }) if !last || follow_final => {
+                    let mut next = cur.parent().unwrap_or(Path::new("/")).join(target);
+                    next.extend(&components[i + 1..]);
+                    return Ok(Walk::Link(normalize(&next)));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(Walk::Done(cur))
+    }
+
+    /// Check that a child of `key`'s parent may be created or removed.
+    fn require_parent_writable(&self, key: &Path) -> io::Result<()> {
+        let parent = key.parent().ok_or_else(|| io::Error::new(io::ErrorKind::PermissionDenied, "cannot modify /"))?;
+        match self.get(parent)?.kind {
+            Kind::Dir => self.require(parent, 3).map(|_| ()),
+            _ => Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} is not a directory", parent.display()))),
+        }
+    }
+
+    fn create_dirs(&mut self, key: &Path) -> io::Result<()> {
+        let mut cur = PathBuf::new();
+        for component in key.components() {
+            cur.push(component);
+            match self.nodes.get(&cur).map(|n| &n.kind) {
+                Some(Kind::Dir) => {}
+                Some(_) => return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} is not a directory", cur.display()))),
+                None => {
+                    let node = self.node(Kind::Dir, 0o755);
+                    self.nodes.insert(cur.clone(), node);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn has_children(&self, key: &Path) -> bool {
+        self.nodes.range(key.to_path_buf()..).nth(1).is_some_and(|(p, _)| p.starts_with(key))
+    }
+
+    fn entry(&self, name: String, path: PathBuf, node: &Node) -> Entry {
+        let (mut entry, type_bits) = match &node.kind {
+            Kind::Dir => (Entry::directory(name, path, Some(node.modified)), S_IFDIR),
+            Kind::File(data) => (Entry::file(name, path, data.len() as u64, Some(node.modified)), S_IFREG),
+            Kind::Symlink(target) => {
+                (Entry::file(name, path, target.as_os_str().len() as u64, Some(node.modified)), S_IFLNK)
+            }
+        };
+        entry.unix_mode = Some(type_bits | node.mode);
+        entry.uid = Some(node.uid);
+        entry.gid = Some(node.gid);
+        entry.owner = self.users.get(&node.uid).cloned();
+        entry.group = self.groups.get(&node.gid).cloned();
+        entry.can_read = Some(self.permits(node, 4));
+        entry.can_write = Some(self.permits(node, 2));
+        entry.can_execute = Some(self.permits(node, 1));
+        entry
+    }
+}
+
+impl Vfs for MemVfs {
+    fn scheme(&self) -> &'static str {
+        "mem"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        format!("mem://{}", path.display())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        let state = self.lock();
+        state.injected(MemOp::List, dir)?;
+        let key = state.resolve(dir, true, true)?;
+        match state.get(&key)?.kind {
+            Kind::Dir => {}
+            _ => return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} is not a directory", dir.display()))),
+        }
+        state.require(&key, 4)?;
+        let children = state.nodes.range(key.clone()..).skip(1).take_while(|(p, _)| p.starts_with(&key));
+        Ok(children
+            .filter(|(p, _)| p.parent() == Some(key.as_path()))
+            .map(|(p, node)| {
+                let name = p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                state.entry(name.clone(), dir.join(&name), node)
+            })
+            .collect())
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        let state = self.lock();
+        state.injected(MemOp::Stat, path)?;
+        let key = state.resolve(path, false, true)?;
+        let node = state.get(&key)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned());
+        Ok(state.entry(name, path.to_path_buf(), node))
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let state = self.lock();
+        state.injected(MemOp::Read, path)?;
+        let key = state.resolve(path, true, true)?;
+        match &state.require(&key, 4)?.kind {
+            Kind::File(data) => Ok(Box::new(Cursor::new(data.clone()))),
+            _ => Err(is_a_directory(path)),
+        }
+    }
+
+    fn open_write(&self, path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        let mut state = self.lock();
+        state.injected(MemOp::Write, path)?;
+        let key = state.resolve(path, true, true)?;
+        if state.nodes.contains_key(&key) {
+            if let Kind::Dir = state.require(&key, 2)?.kind {
+                return Err(is_a_directory(path));
+            }
+        } else {
+            state.require_parent_writable(&key)?;
+        }
+        let clock = state.clock;
+        match state.nodes.get_mut(&key) {
+            Some(node) => {
+                node.kind = Kind::File(Vec::new());
+                node.modified = clock;
+            }
+            None => {
+                let node = state.node(Kind::File(Vec::new()), 0o644);
+                state.nodes.insert(key.clone(), node);
+            }
+        }
+        let budget = state
+            .failures
+            .iter()
+            .find(|f| f.op == MemOp::Write && f.path == absolute(path))
+            .and_then(|f| f.after.map(|after| (after, f.kind)));
+        let corrupt = state.corrupt.contains(&absolute(path));
+        Ok(Box::new(MemWriter { state: self.state.clone(), key, budget, corrupt, written: 0 }))
+    }
+
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state.injected(MemOp::Mkdir, path)?;
+        let key = state.resolve(path, false, true)?;
+        if state.nodes.contains_key(&key) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display())));
+        }
+        state.require_parent_writable(&key)?;
+        let node = state.node(Kind::Dir, 0o755);
+        state.nodes.insert(key, node);
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state.injected(MemOp::Rename, from)?;
+        let from_key = state.resolve(from, false, true)?;
+        let to_key = state.resolve(to, false, true)?;
+        let from_is_dir = matches!(state.get(&from_key)?.kind, Kind::Dir);
+        state.require_parent_writable(&from_key)?;
+        state.require_parent_writable(&to_key)?;
+        if from_key == to_key {
+            return Ok(());
+        }
+        if to_key.starts_with(&from_key) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("cannot move {} into itself", from.display())));
+        }
+        if let Some(existing) = state.nodes.get(&to_key) {
+            match (from_is_dir, matches!(existing.kind, Kind::Dir)) {
+                (true, false) => return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("{} is not a directory", to.display()))),
+                (false, true) => return Err(is_a_directory(to)),
+                (true, true) if state.has_children(&to_key) => {
+                    return Err(io::Error::new(io::ErrorKind::DirectoryNotEmpty, format!("{} is not empty", to.display())));
+                }
+                _ => {}
+            }
+            state.nodes.remove(&to_key);
+        }
+        let moved: Vec<PathBuf> = state.nodes.range(from_key.clone()..).map(|(p, _)| p.clone()).take_while(|p| p.starts_with(&from_key)).collect();
+        for old in moved {
+            let node = state.nodes.remove(&old).expect("listed above");
+            let rest = old.strip_prefix(&from_key).expect("same prefix");
+            let new = if rest.as_os_str().is_empty() { to_key.clone() } else { to_key.join(rest) };
+            state.nodes.insert(new, node);
+        }
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state.injected(MemOp::Remove, path)?;
+        let key = state.resolve(path, false, true)?;
+        state.get(&key)?;
+        state.require_parent_writable(&key)?;
+        if state.has_children(&key) {
+            return Err(io::Error::new(io::ErrorKind::DirectoryNotEmpty, format!("{} is not empty", path.display())));
+        }
+        state.nodes.remove(&key);
+        Ok(())
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state.injected(MemOp::Symlink, link)?;
+        let key = state.resolve(link, false, true)?;
+        if state.nodes.contains_key(&key) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", link.display())));
+        }
+        state.require_parent_writable(&key)?;
+        let node = state.node(Kind::Symlink(target.to_path_buf()), 0o777);
+        state.nodes.insert(key, node);
+        Ok(())
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        let state = self.lock();
+        state.injected(MemOp::ReadLink, path)?;
+        let key = state.resolve(path, false, true)?;
+        match &state.get(&key)?.kind {
+            Kind::Symlink(target) => Ok(target.clone()),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a symbolic link", path.display()))),
+        }
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        let mut state = self.lock();
+        state.injected(MemOp::SetMode, path)?;
+        let key = state.resolve(path, true, true)?;
+        let uid = state.uid;
+        let node = state.nodes.get_mut(&key).ok_or_else(|| not_found(path))?;
+        if uid != 0 && node.uid != uid {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("not the owner of {}", path.display())));
+        }
+        node.mode = mode & 0o7777;
+        Ok(())
+    }
+
+    fn list_xattrs(&self, path: &Path) -> io::Result<Vec<String>> {
+        let state = self.lock();
+        state.injected(MemOp::Xattr, path)?;
+        let key = state.resolve(path, false, true)?;
+        Ok(state.get(&key)?.xattrs.keys().cloned().collect())
+    }
+
+    fn get_xattr(&self, path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+        let state = self.lock();
+        state.injected(MemOp::Xattr, path)?;
+        let key = state.resolve(path, false, true)?;
+        Ok(state.require(&key, 4)?.xattrs.get(name).cloned())
+    }
+
+    fn set_xattr(&self, path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+        let mut state = self.lock();
+        state.injected(MemOp::Xattr, path)?;
+        let key = state.resolve(path, false, true)?;
+        state.require(&key, 2)?;
+        state.nodes.get_mut(&key).ok_or_else(|| not_found(path))?.xattrs.insert(name.to_string(), value.to_vec());
+        Ok(())
+    }
+}
+
+/// Appends to a file node as bytes arrive, so a failed write leaves the
+/// partial contents behind like a real file would.
+struct MemWriter {
+    state: Arc<Mutex<State>>,
+    key: PathBuf,
+    budget: Option<(u64, io::ErrorKind)>,
+    corrupt: bool,
+    written: u64,
+}
+
+impl Write for MemWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut accepted = buf;
+        if let Some((after, kind)) = self.budget {
+            let left = after.saturating_sub(self.written);
+            if left == 0 {
+                return Err(io::Error::new(kind, format!("injected Write failure: {}", self.key.display())));
+            }
+            accepted = &buf[..buf.len().min(left as usize)];
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let clock = state.clock;
+        match state.nodes.get_mut(&self.key) {
+            Some(Node { kind: Kind::File(data), modified, .. }) => {
+                if self.corrupt {
+                    data.extend(accepted.iter().map(|b| !b));
+                } else {
+                    data.extend_from_slice(accepted);
+                }
+                *modified = clock;
+            }
+            _ => return Err(not_found(&self.key)),
+        }
+        self.written += accepted.len() as u64;
+        Ok(accepted.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Interpret `path` relative to `/` and drop `.`/`..` components.
+fn absolute(path: &Path) -> PathBuf {
+    normalize(&Path::new("/").join(path))
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display()))
+}
+
+fn is_a_directory(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::IsADirectory, format!("{} is a directory", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> MemVfs {
+        let vfs = MemVfs::new();
+        vfs.write_file("/home/a.txt", "alpha").unwrap();
+        vfs.write_file("/home/sub/b.txt", "beta").unwrap();
+        vfs
+    }
+
+    fn read(vfs: &MemVfs, path: &str) -> String {
+        let mut s = String::new();
+        vfs.open_read(Path::new(path)).unwrap().read_to_string(&mut s).unwrap();
+        s
+    }
+
+    #[test]
+    fn list_stat_write_and_rename_subtrees() {
+        let vfs = tree();
+        let mut names: Vec<String> = vfs.list(Path::new("/home")).unwrap().into_iter().map(|e| e.name).collect();
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "sub"]);
+
+        let e = vfs.stat(Path::new("/home/a.txt")).unwrap();
+        assert_eq!((e.size, e.unix_mode, e.uid), (5, Some(0o100644), Some(1000)));
+        assert_eq!(e.modified, Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().with_timezone(&Local)));
+
+        vfs.open_write(Path::new("/home/sub/c.txt")).unwrap().write_all(b"gamma").unwrap();
+        vfs.rename(Path::new("/home/sub"), Path::new("/home/moved")).unwrap();
+        assert_eq!(read(&vfs, "/home/moved/c.txt"), "gamma");
+        assert!(!vfs.exists("/home/sub/b.txt"));
+
+        assert_eq!(vfs.remove(Path::new("/home/moved")).unwrap_err().kind(), io::ErrorKind::DirectoryNotEmpty);
+        assert_eq!(vfs.rename(Path::new("/home"), Path::new("/home/x")).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn symlinks_resolve_in_parents_but_not_for_stat() {
+        let vfs = tree();
+        vfs.symlink(Path::new("sub"), Path::new("/home/link")).unwrap();
+        vfs.symlink(Path::new("/loop"), Path::new("/loop")).unwrap();
+
+        assert_eq!(read(&vfs, "/home/link/b.txt"), "beta");
+        let e = vfs.stat(Path::new("/home/link")).unwrap();
+        assert!(!e.is_dir);
+        assert_eq!(e.unix_mode, Some(0o120777));
+        assert_eq!(vfs.read_link(Path::new("/home/link")).unwrap(), PathBuf::from("sub"));
+        assert!(vfs.open_read(Path::new("/loop")).err().unwrap().to_string().contains("symbolic links"));
+    }
+
+    #[test]
+    fn permissions_follow_owner_group_other_bits() {
+        let vfs = tree();
+        vfs.set_mode(Path::new("/home/a.txt"), 0o600).unwrap();
+        vfs.set_owner("/home/sub", 0, 0).unwrap();
+        vfs.set_mode(Path::new("/home/sub"), 0o700).unwrap_err();
+
+        vfs.set_identity(2000, 2000);
+        assert_eq!(vfs.open_read(Path::new("/home/a.txt")).err().unwrap().kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(vfs.stat(Path::new("/home/a.txt")).unwrap().can_read, Some(false));
+        assert_eq!(vfs.mkdir(Path::new("/home/new")).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        vfs.set_identity(0, 0);
+        vfs.set_mode(Path::new("/home/sub"), 0o700).unwrap();
+        vfs.set_identity(1000, 1000);
+        assert_eq!(vfs.stat(Path::new("/home/sub/b.txt")).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn injected_failures_and_partial_writes() {
+        let vfs = tree();
+        vfs.fail(MemOp::Read, "/home/a.txt", io::ErrorKind::TimedOut);
+        assert_eq!(vfs.open_read(Path::new("/home/a.txt")).err().unwrap().kind(), io::ErrorKind::TimedOut);
+        vfs.heal("/home/a.txt");
+        assert_eq!(read(&vfs, "/home/a.txt"), "alpha");
+
+        vfs.fail_write_after("/home/big.bin", 3, io::ErrorKind::StorageFull);
+        let mut w = vfs.open_write(Path::new("/home/big.bin")).unwrap();
+        assert_eq!(w.write_all(b"abcdef").unwrap_err().kind(), io::ErrorKind::StorageFull);
+        assert_eq!(vfs.read_file("/home/big.bin").unwrap(), b"abc");
+    }
+
+    #[test]
+    fn xattrs_and_names_are_reported() {
+        let vfs = tree();
+        vfs.add_user(1000, "alice");
+        vfs.add_group(1000, "staff");
+        vfs.set_xattr(Path::new("/home/a.txt"), "user.origin", b"vendor").unwrap();
+        assert_eq!(vfs.list_xattrs(Path::new("/home/a.txt")).unwrap(), vec!["user.origin"]);
+        assert_eq!(vfs.get_xattr(Path::new("/home/a.txt"), "user.origin").unwrap().as_deref(), Some(&b"vendor"[..]));
+        let e = vfs.stat(Path::new("/home/a.txt")).unwrap();
+        assert_eq!((e.owner.as_deref(), e.group.as_deref()), (Some("alice"), Some("staff")));
+    }
+}
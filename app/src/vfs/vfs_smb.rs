@@ -1 +1,374 @@
-//place holder for smb vfs module
\ No newline at end of file
+//! SMB2/3 backend for `smb://server/share/path` locations.
+//!
+//! The share is reached with a user-space client, so no kernel mount (and
+//! no root) is needed. Credentials come from the `smb_hosts` table in the
+//! settings file, keyed by server name; servers without an entry are tried
+//! as guest. Inside the backend, paths are rooted at the share: `/` is the
+//! top of the share and `/docs/a.txt` maps to `docs\a.txt` on the wire.
+
+use super::{RemoteUrl, Vfs};
+use crate::app::settings::write_settings::SmbCredentials;
+use crate::app::types::Entry;
+use chrono::{DateTime, Local};
+use smb::{
+    Client, ClientConfig, CreateOptions, DirAccessMask, File, FileAccessMask, FileAttributes, FileCreateArgs,
+    FileDirectoryInformation, FileDispositionInformation, FileNetworkOpenInformation, FileRenameInformation,
+    Resource, ResourceHandle, Status, UncPath,
+};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// Default SMB port used when the URL does not name one.
+pub const DEFAULT_PORT: u16 = 445;
+
+/// Timeout applied to connecting and to every request on the connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A connected SMB share.
+///
+/// The client multiplexes requests over one connection and is safe to use
+/// from several threads, so the backend can be shared between the UI thread
+/// and background workers.
+pub struct SmbVfs {
+    user: String,
+    server: String,
+    share: String,
+    port: Option<u16>,
+    root: UncPath,
+    client: Client,
+}
+
+impl fmt::Debug for SmbVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmbVfs")
+            .field("user", &self.user)
+            .field("server", &self.server)
+            .field("share", &self.share)
+            .field("port", &self.port)
+            .finish()
+    }
+}
+
+impl SmbVfs {
+    /// Connect to the share named by `url` using `creds` (or as guest when
+    /// `None`). A user name in the URL overrides the configured one. Returns
+    /// the backend together with the starting directory inside the share.
+    pub fn connect(url: &RemoteUrl, creds: Option<&SmbCredentials>) -> io::Result<(Self, PathBuf)> {
+        let (share, start) = split_share(&url.path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "smb:// locations must name a share, e.g. smb://server/share")
+        })?;
+        let user = url
+            .user
+            .clone()
+            .or_else(|| creds.map(|c| c.user.clone()))
+            .unwrap_or_else(|| "guest".to_string());
+        let login = match creds.and_then(|c| c.domain.as_deref()) {
+            Some(domain) if !domain.is_empty() => format!("{}\\{}", domain, user),
+            _ => user.clone(),
+        };
+        let password = creds.map(|c| c.password.clone()).unwrap_or_default();
+
+        let mut config = ClientConfig::default();
+        config.connection.port = url.port;
+        config.connection.timeout = Some(CONNECT_TIMEOUT);
+        config.connection.allow_unsigned_guest_access = creds.is_none();
+        let client = Client::new(config);
+
+        let root = UncPath::from_str(&format!(r"\\{}\{}", url.host, share)).map_err(smb_error)?;
+        client.share_connect(&root, &login, password).map_err(smb_error)?;
+
+        let vfs = SmbVfs { user, server: url.host.clone(), share, port: url.port, root, client };
+        Ok((vfs, start))
+    }
+
+    /// UNC path for `path` inside the share.
+    fn unc(&self, path: &Path) -> UncPath {
+        let rel = share_relative(path);
+        if rel.is_empty() {
+            self.root.clone().with_no_path()
+        } else {
+            self.root.clone().with_path(&rel)
+        }
+    }
+
+    fn open(&self, path: &Path, args: &FileCreateArgs) -> io::Result<Resource> {
+        self.client.create_file(&self.unc(path), args).map_err(smb_error)
+    }
+
+    /// Open `path` with `access`, run `f` on the handle and close it again,
+    /// reporting the first error.
+    fn with_handle<T>(
+        &self,
+        path: &Path,
+        access: FileAccessMask,
+        f: impl FnOnce(&ResourceHandle) -> smb::Result<T>,
+    ) -> io::Result<T> {
+        let resource = self.open(path, &FileCreateArgs::make_open_existing(access))?;
+        let handle = resource_handle(&resource);
+        let result = f(handle);
+        let closed = handle.close();
+        let value = result.map_err(smb_error)?;
+        closed.map_err(smb_error)?;
+        Ok(value)
+    }
+}
+
+impl Vfs for SmbVfs {
+    fn scheme(&self) -> &'static str {
+        "smb"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        let port = match self.port {
+            Some(p) if p != DEFAULT_PORT => format!(":{}", p),
+            _ => String::new(),
+        };
+        format!("smb://{}{}/{}{}", self.server, port, self.share, path.display())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        let access = DirAccessMask::new().with_list_directory(true).with_synchronize(true);
+        let resource = self.open(dir, &FileCreateArgs::make_open_existing(access.into()))?;
+        let Resource::Directory(handle) = resource else {
+            let _ = resource_handle(&resource).close();
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a directory", dir.display())));
+        };
+        let mut entries = Vec::new();
+        let listed = handle.query::<FileDirectoryInformation>("*").and_then(|items| {
+            for item in items {
+                let info = item?;
+                let name = info.file_name.to_string();
+                if name == "." || name == ".." {
+                    continue;
+                }
+                let path = dir.join(&name);
+                entries.push(smb_entry(
+                    name,
+                    path,
+                    info.file_attributes.directory(),
+                    info.end_of_file,
+                    info.last_write_time.into(),
+                ));
+            }
+            Ok(())
+        });
+        let closed = handle.close();
+        listed.map_err(smb_error)?;
+        closed.map_err(smb_error)?;
+        Ok(entries)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        let info = self.with_handle(path, FileAccessMask::new().with_file_read_attributes(true), |h| {
+            h.query_info::<FileNetworkOpenInformation>()
+        })?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.share.clone());
+        Ok(smb_entry(
+            name,
+            path.to_path_buf(),
+            info.file_attributes.directory(),
+            info.end_of_file,
+            info.last_write_time.into(),
+        ))
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let access = FileAccessMask::new().with_generic_read(true);
+        match self.open(path, &FileCreateArgs::make_open_existing(access))? {
+            Resource::File(file) => Ok(Box::new(SmbStream(Some(file)))),
+            other => {
+                let _ = resource_handle(&other).close();
+                Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a regular file", path.display())))
+            }
+        }
+    }
+
+    fn open_write(&self, path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        let args = FileCreateArgs::make_overwrite(FileAttributes::new(), CreateOptions::new());
+        match self.open(path, &args)? {
+            Resource::File(file) => Ok(Box::new(SmbStream(Some(file)))),
+            other => {
+                let _ = resource_handle(&other).close();
+                Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a regular file", path.display())))
+            }
+        }
+    }
+
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        let args = FileCreateArgs::make_create_new(
+            FileAttributes::new().with_directory(true),
+            CreateOptions::new().with_directory_file(true),
+        );
+        let resource = self.open(path, &args)?;
+        resource_handle(&resource).close().map_err(smb_error)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let target = share_relative(to);
+        let access = FileAccessMask::new().with_generic_read(true).with_delete(true);
+        self.with_handle(from, access, |h| {
+            h.set_info(FileRenameInformation {
+                replace_if_exists: false.into(),
+                root_directory: 0,
+                file_name: target.as_str().into(),
+            })
+        })
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let access = FileAccessMask::new().with_generic_read(true).with_delete(true);
+        self.with_handle(path, access, |h| h.set_info(FileDispositionInformation::default()))
+    }
+
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "smb backend cannot create symbolic links"))
+    }
+}
+
+/// A remote file handle that is closed when the stream is dropped. The SMB
+/// client only logs a warning for handles that are dropped while open.
+struct SmbStream(Option<File>);
+
+impl Read for SmbStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.as_mut() {
+            Some(f) => f.read(buf),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Write for SmbStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.0.as_mut() {
+            Some(f) => f.write(buf),
+            None => Err(io::Error::from(io::ErrorKind::BrokenPipe)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0.as_mut() {
+            Some(f) => f.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for SmbStream {
+    fn drop(&mut self) {
+        if let Some(file) = self.0.take() {
+            let _ = file.close();
+        }
+    }
+}
+
+fn resource_handle(resource: &Resource) -> &ResourceHandle {
+    match resource {
+        Resource::File(f) => f,
+        Resource::Directory(d) => d,
+        Resource::Pipe(p) => p,
+    }
+}
+
+/// Split the path of an `smb://server/share/rest` URL into the share name
+/// and the starting directory inside it (`/rest`, or `/`).
+fn split_share(url_path: &Path) -> Option<(String, PathBuf)> {
+    let mut parts = url_path.components().filter_map(|c| match c {
+        Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+        _ => None,
+    });
+    let share = parts.next()?;
+    let mut start = PathBuf::from("/");
+    start.extend(parts);
+    Some((share, start))
+}
+
+/// Convert a share-rooted path (`/docs/a.txt`) into the backslash form the
+/// protocol expects (`docs\a.txt`). The share root maps to an empty name.
+fn share_relative(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\\")
+}
+
+/// Build an `Entry` from directory or file information. SMB does not expose
+/// Unix modes or ownership, so those fields are left unset.
+fn smb_entry(name: String, path: PathBuf, is_dir: bool, size: u64, modified: SystemTime) -> Entry {
+    let modified = Some(DateTime::<Local>::from(modified));
+    if is_dir {
+        Entry::directory(name, path, modified)
+    } else {
+        Entry::file(name, path, size, modified)
+    }
+}
+
+/// Map a client error to an `io::Error`, translating the NT status codes
+/// callers care about into matching `io::ErrorKind`s.
+fn smb_error(err: smb::Error) -> io::Error {
+    let status = match &err {
+        smb::Error::IoError(e) => return io::Error::new(e.kind(), err.to_string()),
+        smb::Error::ReceivedErrorMessage(status, _) | smb::Error::UnexpectedMessageStatus(status) => Some(*status),
+        _ => None,
+    };
+    io::Error::new(status.map(status_kind).unwrap_or(io::ErrorKind::Other), err.to_string())
+}
+
+fn status_kind(status: u32) -> io::ErrorKind {
+    match status {
+        Status::U32_OBJECT_NAME_NOT_FOUND | Status::U32_OBJECT_PATH_NOT_FOUND | Status::U32_BAD_NETWORK_NAME => {
+            io::ErrorKind::NotFound
+        }
+        Status::U32_ACCESS_DENIED | Status::U32_LOGON_FAILURE | Status::U32_USER_ACCOUNT_LOCKED_OUT => {
+            io::ErrorKind::PermissionDenied
+        }
+        Status::U32_OBJECT_NAME_COLLISION => io::ErrorKind::AlreadyExists,
+        Status::U32_DIRECTORY_NOT_EMPTY => io::ErrorKind::DirectoryNotEmpty,
+        Status::U32_FILE_IS_A_DIRECTORY => io::ErrorKind::IsADirectory,
+        Status::U32_NOT_SUPPORTED | Status::U32_NOT_IMPLEMENTED => io::ErrorKind::Unsupported,
+        Status::U32_IO_TIMEOUT => io::ErrorKind::TimedOut,
+        _ => io::ErrorKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_and_path_translation() {
+        let (share, start) = split_share(Path::new("/media/films/2024")).unwrap();
+        assert_eq!(share, "media");
+        assert_eq!(start, PathBuf::from("/films/2024"));
+
+        let (share, start) = split_share(Path::new("/media")).unwrap();
+        assert_eq!(share, "media");
+        assert_eq!(start, PathBuf::from("/"));
+        assert!(split_share(Path::new("")).is_none());
+        assert!(split_share(Path::new("/")).is_none());
+
+        assert_eq!(share_relative(Path::new("/films/2024/a.mkv")), "films\\2024\\a.mkv");
+        assert_eq!(share_relative(Path::new("/")), "");
+    }
+
+    #[test]
+    fn status_codes_map_to_io_kinds() {
+        assert_eq!(status_kind(0xC0000034), io::ErrorKind::NotFound);
+        assert_eq!(status_kind(0xC000003A), io::ErrorKind::NotFound);
+        assert_eq!(status_kind(0xC0000022), io::ErrorKind::PermissionDenied);
+        assert_eq!(status_kind(0xC0000035), io::ErrorKind::AlreadyExists);
+        assert_eq!(status_kind(0xC0000101), io::ErrorKind::DirectoryNotEmpty);
+        assert_eq!(status_kind(0xDEADBEEF), io::ErrorKind::Other);
+        let e = smb_error(smb::Error::UnexpectedMessageStatus(0xC0000034));
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -1 +1,305 @@
-//place holder for ssh vfs module
\ No newline at end of file
+//! SFTP backend for `sftp://[user@]host[:port]/path` locations.
+//!
+//! The connection is authenticated with the user's SSH agent or the default
+//! key files in `~/.ssh` (`id_ed25519`, `id_ecdsa`, `id_rsa`). The server's
+//! host key must already be present in `~/.ssh/known_hosts`; unknown or
+//! changed keys abort the connection rather than being trusted silently.
+//! Passphrase-protected keys are only usable through the agent.
+
+use super::{RemoteUrl, Vfs};
+use crate::app::types::Entry;
+use chrono::{DateTime, Local};
+use ssh2::{CheckResult, FileStat, KnownHostFileKind, Session, Sftp};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default SSH port used when the URL does not name one.
+pub const DEFAULT_PORT: u16 = 22;
+
+/// Timeout applied to the TCP connect and to every blocking libssh2 call.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Private key file names tried, in order, when the agent cannot authenticate.
+const DEFAULT_KEY_FILES: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// A connected SFTP session.
+///
+/// All operations go through one libssh2 session which serialises calls
+/// internally, so the backend can be shared between the UI thread and
+/// background workers.
+pub struct SftpVfs {
+    user: String,
+    host: String,
+    port: u16,
+    sftp: Sftp,
+    // Keep the session alive for as long as the SFTP channel is in use.
+    _session: Session,
+}
+
+impl fmt::Debug for SftpVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SftpVfs")
+            .field("user", &self.user)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .finish()
+    }
+}
+
+impl SftpVfs {
+    /// Connect to the server named by `url`, verify its host key and
+    /// authenticate. Returns the backend together with the starting
+    /// directory: the URL path, or the remote home directory when the URL
+    /// has no path.
+    pub fn connect(url: &RemoteUrl) -> io::Result<(Self, PathBuf)> {
+        let ssh_dir = default_ssh_dir()?;
+        let user = match &url.user {
+            Some(u) => u.clone(),
+            None => std::env::var("USER")
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "no user in URL and $USER is not set"))?,
+        };
+        let port = url.port.unwrap_or(DEFAULT_PORT);
+
+        let addr = (url.host.as_str(), port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve host {}", url.host)))?;
+        let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+
+        let mut session = Session::new()?;
+        session.set_timeout(CONNECT_TIMEOUT.as_millis() as u32);
+        session.set_tcp_stream(tcp);
+        session.handshake()?;
+
+        verify_host_key(&session, &url.host, port, &ssh_dir.join("known_hosts"))?;
+        authenticate(&session, &user, &ssh_dir)?;
+
+        let sftp = session.sftp()?;
+        let start = if url.path.as_os_str().is_empty() {
+            sftp.realpath(Path::new("."))?
+        } else {
+            url.path.clone()
+        };
+
+        Ok((SftpVfs { user, host: url.host.clone(), port, sftp, _session: session }, start))
+    }
+}
+
+impl Vfs for SftpVfs {
+    fn scheme(&self) -> &'static str {
+        "sftp"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        let port = if self.port == DEFAULT_PORT { String::new() } else { format!(":{}", self.port) };
+        format!("sftp://{}@{}{}{}", self.user, self.host, port, path.display())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        // `readdir` joins names onto `dir` and filters out `.` and `..`.
+        let items = self.sftp.readdir(dir)?;
+        Ok(items.into_iter().map(|(path, stat)| sftp_entry(path, &stat)).collect())
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        let stat = self.sftp.lstat(path)?;
+        Ok(sftp_entry(path.to_path_buf(), &stat))
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(self.sftp.open(path)?))
+    }
+
+    fn open_write(&self, path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        Ok(Box::new(self.sftp.create(path)?))
+    }
+
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        Ok(self.sftp.mkdir(path, 0o755)?)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        Ok(self.sftp.rename(from, to, None)?)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        if self.sftp.lstat(path)?.is_dir() {
+            Ok(self.sftp.rmdir(path)?)
+        } else {
+            Ok(self.sftp.unlink(path)?)
+        }
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        Ok(self.sftp.symlink(target, link)?)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(self.sftp.readlink(path)?)
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        let stat = FileStat { size: None, uid: None, gid: None, perm: Some(mode & 0o7777), atime: None, mtime: None };
+        Ok(self.sftp.setstat(path, stat)?)
+    }
+}
+
+/// Build an `Entry` from the attributes returned by the server. Remote
+/// owner names are not resolved; only the numeric ids are recorded.
+fn sftp_entry(path: PathBuf, stat: &FileStat) -> Entry {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned());
+    let modified = stat
+        .mtime
+        .and_then(|secs| DateTime::from_timestamp(secs as i64, 0))
+        .map(|dt| dt.with_timezone(&Local));
+    let mut entry = if stat.is_dir() {
+        Entry::directory(name, path, modified)
+    } else {
+        Entry::file(name, path, stat.size.unwrap_or(0), modified)
+    };
+    entry.unix_mode = stat.perm;
+    entry.uid = stat.uid;
+    entry.gid = stat.gid;
+    entry
+}
+
+/// Locate `~/.ssh`.
+fn default_ssh_dir() -> io::Result<PathBuf> {
+    directories_next::BaseDirs::new()
+        .map(|b| b.home_dir().join(".ssh"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cannot determine home directory"))
+}
+
+/// Check the server's host key against `known_hosts`.
+fn verify_host_key(session: &Session, host: &str, port: u16, known_hosts: &Path) -> io::Result<()> {
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| io::Error::other("server did not present a host key"))?;
+    check_host_key(session, host, port, key, known_hosts)
+}
+
+/// Look up `key`, the raw host key blob presented for `host:port`, in
+/// `known_hosts`. Anything other than an exact match is an error.
+fn check_host_key(session: &Session, host: &str, port: u16, key: &[u8], known_hosts: &Path) -> io::Result<()> {
+    let mut kh = session.known_hosts()?;
+    kh.read_file(known_hosts, KnownHostFileKind::OpenSSH).map_err(|e| {
+        io::Error::new(io::ErrorKind::NotFound, format!("cannot read {}: {}", known_hosts.display(), e))
+    })?;
+    match kh.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("host key for {} is not in {}; connect once with ssh to verify it", host, known_hosts.display()),
+        )),
+        CheckResult::Mismatch => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("host key for {} does not match {}; refusing to connect", host, known_hosts.display()),
+        )),
+        CheckResult::Failure => Err(io::Error::other(format!("failed to check host key for {}", host))),
+    }
+}
+
+/// Try the SSH agent first, then each default key file in `ssh_dir`.
+fn authenticate(session: &Session, user: &str, ssh_dir: &Path) -> io::Result<()> {
+    if std::env::var_os("SSH_AUTH_SOCK").is_some() && session.userauth_agent(user).is_ok() && session.authenticated() {
+        return Ok(());
+    }
+    for name in DEFAULT_KEY_FILES {
+        let private = ssh_dir.join(name);
+        if !private.is_file() {
+            continue;
+        }
+        let public = private.with_extension("pub");
+        let public = public.is_file().then_some(public);
+        if session.userauth_pubkey_file(user, public.as_deref(), &private, None).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("no usable SSH key for {} (tried the agent and {})", user, ssh_dir.display()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sftp_entry_maps_attributes() {
+        let stat = FileStat { size: Some(42), uid: Some(1000), gid: Some(100), perm: Some(0o100644), atime: None, mtime: Some(0) };
+        let e = sftp_entry(PathBuf::from("/srv/data/a.txt"), &stat);
+        assert_eq!(e.name, "a.txt");
+        assert_eq!(e.size, 42);
+        assert!(!e.is_dir);
+        assert_eq!(e.unix_mode, Some(0o100644));
+        assert_eq!(e.uid, Some(1000));
+        assert!(e.modified.is_some());
+
+        let dir = FileStat { perm: Some(0o040755), ..stat };
+        assert!(sftp_entry(PathBuf::from("/srv/data"), &dir).is_dir);
+    }
+
+    /// The wire form of an Ed25519 public key with the given key bytes.
+    fn ed25519_blob(key: [u8; 32]) -> Vec<u8> {
+        let mut blob = Vec::new();
+        blob.extend_from_slice(&11u32.to_be_bytes());
+        blob.extend_from_slice(b"ssh-ed25519");
+        blob.extend_from_slice(&32u32.to_be_bytes());
+        blob.extend_from_slice(&key);
+        blob
+    }
+
+    #[test]
+    fn only_a_known_matching_host_key_is_accepted() {
+        let dir = tempfile::tempdir().unwrap();
+        let known_hosts = dir.path().join("known_hosts");
+        let session = Session::new().unwrap();
+        let trusted = ed25519_blob([1; 32]);
+        let mut kh = session.known_hosts().unwrap();
+        kh.add("files.example", &trusted, "", ssh2::KnownHostKeyFormat::Ed25519).unwrap();
+        kh.add("[backup.example]:2222", &trusted, "", ssh2::KnownHostKeyFormat::Ed25519).unwrap();
+        kh.write_file(&known_hosts, KnownHostFileKind::OpenSSH).unwrap();
+
+        check_host_key(&session, "files.example", DEFAULT_PORT, &trusted, &known_hosts).unwrap();
+        check_host_key(&session, "backup.example", 2222, &trusted, &known_hosts).unwrap();
+
+        let changed = check_host_key(&session, "files.example", DEFAULT_PORT, &ed25519_blob([2; 32]), &known_hosts).unwrap_err();
+        assert_eq!(changed.kind(), io::ErrorKind::PermissionDenied);
+        assert!(changed.to_string().contains("does not match"), "{}", changed);
+
+        let unknown = check_host_key(&session, "other.example", DEFAULT_PORT, &trusted, &known_hosts).unwrap_err();
+        assert_eq!(unknown.kind(), io::ErrorKind::PermissionDenied);
+        assert!(unknown.to_string().contains("is not in"), "{}", unknown);
+        // A key known for one port is not trusted on another.
+        assert!(check_host_key(&session, "backup.example", DEFAULT_PORT, &trusted, &known_hosts).is_err());
+
+        let missing = check_host_key(&session, "files.example", DEFAULT_PORT, &trusted, &dir.path().join("nope")).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn connection_failures_surface_as_errors() {
+        let url = |port: u16| RemoteUrl::parse(&format!("sftp://tester@127.0.0.1:{}", port)).unwrap();
+
+        // Nothing listening.
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert_eq!(SftpVfs::connect(&url(port)).unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+
+        // A peer that is not an SSH server fails the handshake.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let peer = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+        });
+        assert!(SftpVfs::connect(&url(port)).is_err());
+        peer.join().unwrap();
+    }
+}
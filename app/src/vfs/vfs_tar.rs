@@ -0,0 +1,503 @@
+//! Read-only tar backend for `.tar`, `.tar.gz`, `.tar.xz`, `.tar.zst` and
+//! `.tar.bz2` archives.
+//!
+//! The archive is scanned once when opened to build the directory index;
+//! modes, owners, mtimes, symlinks and hardlinks are taken from the headers.
+//! Compressed archives are never unpacked to disk: member contents are read
+//! by decompressing the stream again up to the wanted member, and copying a
+//! whole directory out of the archive extracts it in a single pass (see
+//! `Vfs::export_tree`).
+
+use super::archive::{read_only, spawn_reader, ArchiveIndex, ArchiveLinks, ArchiveSource};
+use super::transfer::is_symlink_mode;
+use super::Vfs;
+use crate::app::types::Entry;
+use chrono::{DateTime, Local};
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tar::EntryType;
+
+const S_IFDIR: u32 = 0o040000;
+const S_IFREG: u32 = 0o100000;
+const S_IFLNK: u32 = 0o120000;
+
+/// Compression wrapped around the tar stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detect the compression from a (lower-case) file name. Returns `None`
+    /// for names that are not tarballs.
+    pub fn from_name(name: &str) -> Option<Self> {
+        const SUFFIXES: &[(&str, Compression)] = &[
+            (".tar", Compression::None),
+            (".tar.gz", Compression::Gzip),
+            (".tgz", Compression::Gzip),
+            (".tar.xz", Compression::Xz),
+            (".txz", Compression::Xz),
+            (".tar.zst", Compression::Zstd),
+            (".tzst", Compression::Zstd),
+            (".tar.bz2", Compression::Bzip2),
+            (".tbz2", Compression::Bzip2),
+            (".tbz", Compression::Bzip2),
+        ];
+        SUFFIXES.iter().find(|(suffix, _)| name.ends_with(suffix)).map(|(_, c)| *c)
+    }
+
+    /// Wrap `source` in the matching streaming decoder.
+    fn decoder(self, source: ArchiveSource) -> io::Result<Box<dyn Read + Send>> {
+        let buffered = BufReader::new(source);
+        Ok(match self {
+            Compression::None => Box::new(buffered),
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(buffered)),
+            Compression::Xz => Box::new(lzma_rust2::XzReader::new(buffered, true)),
+            Compression::Zstd => Box::new(
+                ruzstd::decoding::StreamingDecoder::new(buffered)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+            ),
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(buffered)),
+        })
+    }
+}
+
+/// What a header describes, as far as extraction is concerned.
+#[derive(Debug, Clone)]
+enum MemberKind {
+    Dir,
+    File,
+    Symlink(PathBuf),
+    /// Hard link to the member stored at this archive path.
+    Hardlink(PathBuf),
+    /// Devices, FIFOs and other entries that carry no data.
+    Special,
+}
+
+/// One header of the archive, in stream order.
+#[derive(Debug, Clone)]
+struct Member {
+    path: PathBuf,
+    kind: MemberKind,
+    mode: u32,
+}
+
+/// A tarball opened as a directory tree.
+pub struct TarVfs {
+    outer: Arc<dyn Vfs>,
+    source: ArchiveSource,
+    compression: Compression,
+    index: ArchiveIndex,
+    /// Headers in stream order; the position is the member id stored in
+    /// the index.
+    members: Vec<Member>,
+    links: ArchiveLinks,
+}
+
+impl fmt::Debug for TarVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TarVfs")
+            .field("archive", &self.index.root())
+            .field("compression", &self.compression)
+            .field("members", &self.members.len())
+            .finish()
+    }
+}
+
+impl TarVfs {
+    /// Open the tarball at `path` inside `outer` and index its headers.
+    pub fn open(outer: Arc<dyn Vfs>, path: &Path, compression: Compression) -> io::Result<Self> {
+        let source = ArchiveSource::open(outer.as_ref(), path)?;
+        let modified = outer.stat(path).ok().and_then(|e| e.modified);
+        let mut index = ArchiveIndex::new(path.to_path_buf(), modified);
+        let mut members = Vec::new();
+        let mut links = ArchiveLinks::default();
+
+        let mut archive = tar::Archive::new(compression.decoder(source.clone())?);
+        for (ordinal, item) in archive.entries()?.enumerate() {
+            let item = item?;
+            let header = item.header();
+            let rel = item.path()?.into_owned();
+            let link = item.link_name()?.map(|l| l.into_owned());
+            let perms = header.mode().unwrap_or(0o644) & 0o7777;
+            let modified = header
+                .mtime()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs as i64, 0))
+                .map(|dt| dt.with_timezone(&Local));
+
+            let (kind, mode) = match (header.entry_type(), link) {
+                (EntryType::Directory, _) => (MemberKind::Dir, S_IFDIR | perms),
+                (EntryType::Symlink, Some(target)) => (MemberKind::Symlink(target), S_IFLNK | perms),
+                (EntryType::Link, Some(target)) => (MemberKind::Hardlink(index.root().join(target)), S_IFREG | perms),
+                (EntryType::Regular | EntryType::Continuous | EntryType::GNUSparse, _) => (MemberKind::File, S_IFREG | perms),
+                (EntryType::Char | EntryType::Block | EntryType::Fifo, _) => (MemberKind::Special, perms),
+                // Pax/GNU extension headers are folded into the next entry
+                // by the reader; anything else is not shown.
+                _ => continue,
+            };
+
+            // Hard links share the data (and size) of the member they
+            // point at, which always appears earlier in the stream.
+            let (size, data) = match &kind {
+                MemberKind::File => (item.size(), Some(ordinal)),
+                MemberKind::Hardlink(target) => match index.stat(target) {
+                    Ok(e) => (e.size, index.member(target).ok()),
+                    Err(_) => (0, None),
+                },
+                _ => (0, None),
+            };
+            let is_dir = matches!(kind, MemberKind::Dir);
+            let Some(entry) = index.insert(&rel, is_dir, size, modified, data) else { continue };
+            entry.unix_mode = Some(mode);
+            entry.uid = header.uid().ok().map(|id| id as u32);
+            entry.gid = header.gid().ok().map(|id| id as u32);
+            entry.owner = header.username().ok().flatten().filter(|s| !s.is_empty()).map(str::to_string);
+            entry.group = header.groupname().ok().flatten().filter(|s| !s.is_empty()).map(str::to_string);
+            let path = entry.path.clone();
+
+            if let MemberKind::Symlink(target) = &kind {
+                links.insert(path.clone(), target.clone());
+            }
+            members.resize_with(ordinal, || Member { path: PathBuf::new(), kind: MemberKind::Special, mode: 0 });
+            members.push(Member { path, kind, mode });
+        }
+
+        Ok(TarVfs { outer, source, compression, index, members, links })
+    }
+
+    /// Stream the archive once, writing every member below `from` into
+    /// `dst` under `to`.
+    fn extract(&self, from: &Path, dst: &dyn Vfs, to: &Path, progress: &mut dyn FnMut(&Path)) -> io::Result<u64> {
+        let mut made_dirs = HashSet::new();
+        ensure_dir(dst, to, &mut made_dirs)?;
+        let mut dir_modes = vec![(to.to_path_buf(), self.index.stat(from)?.unix_mode)];
+        let mut deferred_links = Vec::new();
+        // Symlinks are created after all data is written, and no member may
+        // sit at or below one, so a crafted archive cannot write through a
+        // link it planted itself.
+        let mut deferred_symlinks = Vec::new();
+        let mut symlinks = HashSet::new();
+        let mut total = 0;
+
+        let mut archive = tar::Archive::new(self.compression.decoder(self.source.clone())?);
+        for (ordinal, item) in archive.entries()?.enumerate() {
+            let mut item = item?;
+            let Some(member) = self.members.get(ordinal) else { continue };
+            let Ok(rel) = member.path.strip_prefix(from) else { continue };
+            if rel.as_os_str().is_empty() {
+                continue;
+            }
+            if let Some(link) = member.path.ancestors().find(|a| symlinks.contains(*a)) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} lies behind the symbolic link {}", self.display_path(&member.path), self.display_path(link)),
+                ));
+            }
+            let target = to.join(rel);
+            if let Some(parent) = target.parent() {
+                ensure_dir(dst, parent, &mut made_dirs)?;
+            }
+            match &member.kind {
+                MemberKind::Dir => {
+                    ensure_dir(dst, &target, &mut made_dirs)?;
+                    dir_modes.push((target, Some(member.mode)));
+                }
+                MemberKind::File => {
+                    let mut writer = dst.open_write(&target)?;
+                    total += io::copy(&mut item, &mut writer)?;
+                    writer.flush()?;
+                    drop(writer);
+                    let _ = dst.set_mode(&target, member.mode & 0o7777);
+                }
+                MemberKind::Symlink(link) => {
+                    symlinks.insert(member.path.clone());
+                    deferred_symlinks.push((link.clone(), target, &member.path));
+                    continue;
+                }
+                MemberKind::Hardlink(original) => {
+                    deferred_links.push((original.clone(), target, member.mode));
+                    continue;
+                }
+                MemberKind::Special => continue,
+            }
+            progress(&member.path);
+        }
+
+        // Hard links become independent copies: take the data from the
+        // copy already extracted, or from the archive if the original lies
+        // outside the extracted subtree.
+        for (original, target, mode) in deferred_links {
+            let mut reader = match original.strip_prefix(from) {
+                Ok(rel) if dst.stat(&to.join(rel)).is_ok() => dst.open_read(&to.join(rel))?,
+                _ => self.open_read(&original)?,
+            };
+            let mut writer = dst.open_write(&target)?;
+            total += io::copy(&mut reader, &mut writer)?;
+            writer.flush()?;
+            drop(writer);
+            let _ = dst.set_mode(&target, mode & 0o7777);
+            progress(&original);
+        }
+
+        for (link, target, path) in deferred_symlinks {
+            match dst.symlink(&link, &target) {
+                Ok(()) => progress(path),
+                Err(e) if e.kind() == io::ErrorKind::Unsupported => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Apply directory modes last so read-only directories can be filled.
+        for (dir, mode) in dir_modes.into_iter().rev() {
+            if let Some(mode) = mode {
+                let _ = dst.set_mode(&dir, mode & 0o7777);
+            }
+        }
+        Ok(total)
+    }
+}
+
+impl Vfs for TarVfs {
+    fn scheme(&self) -> &'static str {
+        "tar"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        self.outer.display_path(path)
+    }
+
+    fn outer(&self) -> Option<(&Arc<dyn Vfs>, &Path)> {
+        Some((&self.outer, self.index.root()))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        self.index.list(&self.links.resolve(dir)?)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        self.index.stat(path)
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let member = self.index.member(&self.links.resolve(path)?)?;
+        let source = self.source.clone();
+        let compression = self.compression;
+        Ok(spawn_reader(move |out| {
+            let mut archive = tar::Archive::new(compression.decoder(source)?);
+            for (ordinal, item) in archive.entries()?.enumerate() {
+                let mut item = item?;
+                if ordinal == member {
+                    io::copy(&mut item, out)?;
+                    return Ok(());
+                }
+            }
+            Err(io::Error::new(io::ErrorKind::UnexpectedEof, "archive ended before member"))
+        }))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.links.read_link(path)
+    }
+
+    fn export_tree(
+        &self,
+        from: &Path,
+        dst: &dyn Vfs,
+        to: &Path,
+        progress: &mut dyn FnMut(&Path),
+    ) -> Option<io::Result<u64>> {
+        // Single files and links go through the generic per-file path.
+        let entry = self.index.stat(from).ok()?;
+        if !entry.is_dir || is_symlink_mode(entry.unix_mode) {
+            return None;
+        }
+        Some(self.extract(from, dst, to, progress))
+    }
+
+    fn open_write(&self, _path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn mkdir(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn remove(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn set_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+}
+
+/// Create `dir` in `dst` (and any missing parents) unless it was already
+/// made during this extraction.
+fn ensure_dir(dst: &dyn Vfs, dir: &Path, made: &mut HashSet<PathBuf>) -> io::Result<()> {
+    if made.contains(dir) {
+        return Ok(());
+    }
+    if !dst.stat(dir).map(|e| e.is_dir).unwrap_or(false) {
+        if let Some(parent) = dir.parent() {
+            ensure_dir(dst, parent, made)?;
+        }
+        match dst.mkdir(dir) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    made.insert(dir.to_path_buf());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::LocalVfs;
+
+    #[test]
+    fn compression_is_detected_from_the_name() {
+        assert_eq!(Compression::from_name("backup.tar"), Some(Compression::None));
+        assert_eq!(Compression::from_name("backup.tgz"), Some(Compression::Gzip));
+        assert_eq!(Compression::from_name("backup.tar.xz"), Some(Compression::Xz));
+        assert_eq!(Compression::from_name("backup.tar.zst"), Some(Compression::Zstd));
+        assert_eq!(Compression::from_name("backup.tar.bz2"), Some(Compression::Bzip2));
+        assert_eq!(Compression::from_name("backup.zip"), None);
+        assert_eq!(Compression::from_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn headers_populate_entries_links_and_contents() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("t.tar.gz");
+        let gz = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::fast());
+        let mut b = tar::Builder::new(gz);
+        let mut h = tar::Header::new_gnu();
+        h.set_entry_type(EntryType::Regular);
+        h.set_size(5);
+        h.set_mode(0o640);
+        h.set_uid(1234);
+        h.set_gid(42);
+        h.set_username("alice").unwrap();
+        h.set_groupname("staff").unwrap();
+        h.set_mtime(1_700_000_000);
+        b.append_data(&mut h, "bin/tool", &b"hello"[..]).unwrap();
+        let mut l = tar::Header::new_gnu();
+        l.set_entry_type(EntryType::Symlink);
+        l.set_size(0);
+        l.set_mode(0o777);
+        b.append_link(&mut l, "latest", "bin/tool").unwrap();
+        let mut hl = tar::Header::new_gnu();
+        hl.set_entry_type(EntryType::Link);
+        hl.set_size(0);
+        hl.set_mode(0o640);
+        b.append_link(&mut hl, "bin/tool-copy", "bin/tool").unwrap();
+        b.into_inner().unwrap().finish().unwrap();
+
+        let vfs = TarVfs::open(Arc::new(LocalVfs::new()), &path, Compression::Gzip).unwrap();
+        let tool = vfs.stat(&path.join("bin/tool")).unwrap();
+        assert_eq!(tool.size, 5);
+        assert_eq!(tool.unix_mode, Some(S_IFREG | 0o640));
+        assert_eq!((tool.uid, tool.gid), (Some(1234), Some(42)));
+        assert_eq!(tool.owner.as_deref(), Some("alice"));
+        assert_eq!(tool.group.as_deref(), Some("staff"));
+        assert_eq!(tool.modified.unwrap().timestamp(), 1_700_000_000);
+
+        let latest = path.join("latest");
+        assert!(is_symlink_mode(vfs.stat(&latest).unwrap().unix_mode));
+        assert_eq!(vfs.read_link(&latest).unwrap(), PathBuf::from("bin/tool"));
+
+        for p in [path.join("bin/tool"), latest, path.join("bin/tool-copy")] {
+            let mut s = String::new();
+            vfs.open_read(&p).unwrap().read_to_string(&mut s).unwrap();
+            assert_eq!(s, "hello", "reading {}", p.display());
+        }
+        assert_eq!(vfs.stat(&path.join("bin/tool-copy")).unwrap().size, 5);
+    }
+
+    #[test]
+    fn zstd_stream_is_decoded_without_unpacking() {
+        let mut b = tar::Builder::new(Vec::new());
+        let mut h = tar::Header::new_ustar();
+        h.set_size(4);
+        h.set_mode(0o644);
+        b.append_data(&mut h, "a/b/c.txt", &b"zstd"[..]).unwrap();
+        let plain = b.into_inner().unwrap();
+        let packed = ruzstd::encoding::compress_to_vec(&plain[..], ruzstd::encoding::CompressionLevel::Fastest);
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("t.tar.zst");
+        std::fs::write(&path, packed).unwrap();
+        let vfs = TarVfs::open(Arc::new(LocalVfs::new()), &path, Compression::Zstd).unwrap();
+        // Parent directories missing from the stream are synthesised.
+        assert!(vfs.stat(&path.join("a/b")).unwrap().is_dir);
+        let mut s = String::new();
+        vfs.open_read(&path.join("a/b/c.txt")).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "zstd");
+        assert_eq!(vfs.mkdir(&path.join("new")).unwrap_err().kind(), io::ErrorKind::ReadOnlyFilesystem);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn members_behind_an_extracted_symlink_are_refused() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let outside = temp.path().join("outside");
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::write(outside.join("victim"), "keep").unwrap();
+        let dest = temp.path().join("dest");
+
+        // `pkg/link` points outside, then `pkg/link/pwned` tries to write
+        // through it.
+        let through = temp.path().join("through.tar");
+        let mut b = tar::Builder::new(std::fs::File::create(&through).unwrap());
+        let mut l = tar::Header::new_gnu();
+        l.set_entry_type(EntryType::Symlink);
+        l.set_size(0);
+        l.set_mode(0o777);
+        b.append_link(&mut l, "pkg/link", &outside).unwrap();
+        let mut h = tar::Header::new_gnu();
+        h.set_size(5);
+        h.set_mode(0o644);
+        b.append_data(&mut h, "pkg/link/pwned", &b"owned"[..]).unwrap();
+        b.into_inner().unwrap();
+        let vfs = TarVfs::open(Arc::new(LocalVfs::new()), &through, Compression::None).unwrap();
+        let err = vfs.export_tree(&through.join("pkg"), &LocalVfs::new(), &dest.join("a"), &mut |_| {}).unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", err);
+        assert!(!outside.join("pwned").exists());
+
+        // A regular member named like an earlier link must not truncate
+        // what the link points at.
+        let over = temp.path().join("over.tar");
+        let mut b = tar::Builder::new(std::fs::File::create(&over).unwrap());
+        let mut l = tar::Header::new_gnu();
+        l.set_entry_type(EntryType::Symlink);
+        l.set_size(0);
+        l.set_mode(0o777);
+        b.append_link(&mut l, "pkg/link", outside.join("victim")).unwrap();
+        let mut h = tar::Header::new_gnu();
+        h.set_size(0);
+        h.set_mode(0o644);
+        b.append_data(&mut h, "pkg/link", &b""[..]).unwrap();
+        b.into_inner().unwrap();
+        let vfs = TarVfs::open(Arc::new(LocalVfs::new()), &over, Compression::None).unwrap();
+        assert!(vfs.export_tree(&over.join("pkg"), &LocalVfs::new(), &dest.join("b"), &mut |_| {}).unwrap().is_err());
+        assert_eq!(std::fs::read_to_string(outside.join("victim")).unwrap(), "keep");
+    }
+}
@@ -0,0 +1,173 @@
+//! Read-only ZIP backend.
+//!
+//! The central directory is read once when the archive is opened; listings
+//! and `stat` are served from that index with the sizes, modification times
+//! and Unix modes recorded there. Member contents are decompressed on demand
+//! and streamed to the caller.
+
+use super::archive::{read_only, spawn_reader, ArchiveIndex, ArchiveSource};
+use super::Vfs;
+use crate::app::types::Entry;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// A ZIP archive opened as a directory tree.
+pub struct ZipVfs {
+    outer: Arc<dyn Vfs>,
+    index: ArchiveIndex,
+    archive: ZipArchive<ArchiveSource>,
+}
+
+impl fmt::Debug for ZipVfs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipVfs").field("archive", &self.index.root()).field("members", &self.archive.len()).finish()
+    }
+}
+
+impl ZipVfs {
+    /// Open the archive at `path` inside `outer` and index its members.
+    pub fn open(outer: Arc<dyn Vfs>, path: &Path) -> io::Result<Self> {
+        let source = ArchiveSource::open(outer.as_ref(), path)?;
+        let archive = ZipArchive::new(source).map_err(zip_error)?;
+        let modified = outer.stat(path).ok().and_then(|e| e.modified);
+        let mut index = ArchiveIndex::new(path.to_path_buf(), modified);
+
+        for i in 0..archive.len() {
+            let member = archive.by_index_data(i).map_err(zip_error)?;
+            let Some(name) = member.enclosed_name() else { continue };
+            let modified = member.last_modified().and_then(zip_time);
+            let is_dir = member.is_dir();
+            let data = (!is_dir).then_some(i);
+            if let Some(entry) = index.insert(&name, is_dir, member.size(), modified, data) {
+                entry.unix_mode = member.unix_mode();
+            }
+        }
+
+        Ok(ZipVfs { outer, index, archive })
+    }
+}
+
+impl Vfs for ZipVfs {
+    fn scheme(&self) -> &'static str {
+        "zip"
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        self.outer.display_path(path)
+    }
+
+    fn outer(&self) -> Option<(&Arc<dyn Vfs>, &Path)> {
+        Some((&self.outer, self.index.root()))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        self.index.list(dir)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        self.index.stat(path)
+    }
+
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let member = self.index.member(path)?;
+        let mut archive = self.archive.clone();
+        // Fail fast on members we cannot decode (encrypted, unknown method)
+        // instead of surfacing the error on the first read.
+        archive.by_index(member).map_err(zip_error)?;
+        Ok(spawn_reader(move |out| {
+            let mut file = archive.by_index(member).map_err(zip_error)?;
+            io::copy(&mut file, out)?;
+            Ok(())
+        }))
+    }
+
+    fn open_write(&self, _path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn mkdir(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn remove(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn symlink(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+
+    fn set_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Err(read_only(self.index.root()))
+    }
+}
+
+/// ZIP timestamps are local wall-clock times without a zone.
+fn zip_time(dt: zip::DateTime) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::try_from(dt).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+pub(super) fn zip_error(err: ZipError) -> io::Error {
+    match err {
+        ZipError::Io(e) => e,
+        ZipError::FileNotFound => io::Error::new(io::ErrorKind::NotFound, err.to_string()),
+        ZipError::UnsupportedArchive(_) | ZipError::CompressionMethodNotSupported(_) | ZipError::InvalidPassword => {
+            io::Error::new(io::ErrorKind::Unsupported, err.to_string())
+        }
+        _ => io::Error::new(io::ErrorKind::InvalidData, err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::LocalVfs;
+    use zip::write::SimpleFileOptions;
+
+    fn build_zip(path: &Path) {
+        let mut w = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let opts = SimpleFileOptions::default()
+            .unix_permissions(0o640)
+            .last_modified_time(zip::DateTime::from_date_and_time(2024, 5, 17, 10, 30, 0).unwrap());
+        w.add_directory("docs/", opts).unwrap();
+        w.start_file("docs/readme.txt", opts).unwrap();
+        w.write_all(b"inside the zip").unwrap();
+        w.start_file("top.txt", opts.unix_permissions(0o755)).unwrap();
+        w.write_all(b"top").unwrap();
+        w.finish().unwrap();
+    }
+
+    #[test]
+    fn lists_stats_and_reads_members() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.path().join("a.zip");
+        build_zip(&path);
+
+        let vfs = ZipVfs::open(Arc::new(LocalVfs::new()), &path).unwrap();
+        let names: Vec<String> = vfs.list(&path).unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["docs", "top.txt"]);
+
+        let readme = vfs.stat(&path.join("docs/readme.txt")).unwrap();
+        assert_eq!(readme.size, 14);
+        assert_eq!(readme.unix_mode.map(|m| m & 0o777), Some(0o640));
+        assert_eq!(readme.modified.unwrap().format("%Y-%m-%d %H:%M").to_string(), "2024-05-17 10:30");
+        assert_eq!(vfs.stat(&path.join("top.txt")).unwrap().unix_mode.map(|m| m & 0o777), Some(0o755));
+
+        let mut s = String::new();
+        vfs.open_read(&path.join("docs/readme.txt")).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "inside the zip");
+
+        assert_eq!(vfs.remove(&path.join("top.txt")).unwrap_err().kind(), io::ErrorKind::ReadOnlyFilesystem);
+        assert!(vfs.open_read(&path.join("docs")).is_err());
+    }
+}
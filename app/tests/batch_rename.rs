@@ -0,0 +1,75 @@
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use std::fs;
+use std::path::Path;
+
+fn app_in(dir: &Path) -> App {
+    App::with_options(&StartOptions { start_dir: Some(dir.to_path_buf()), ..Default::default() }).unwrap()
+}
+
+fn key(app: &mut App, code: KeyCode) {
+    handlers::handle_key(app, code, 10).unwrap();
+}
+
+/// Replace the focused field of the dialog with `text`.
+fn set_field(app: &mut App, text: &str) {
+    let Mode::BatchRename { fields, focus, .. } = &app.mode else { panic!("{:?}", app.mode) };
+    for _ in 0..fields[*focus].chars().count() {
+        key(app, KeyCode::Backspace);
+    }
+    for c in text.chars() {
+        key(app, KeyCode::Char(c));
+    }
+}
+
+fn new_names(app: &App) -> Vec<(String, Option<String>)> {
+    let Mode::BatchRename { preview: Ok(rows), .. } = &app.mode else { panic!("{:?}", app.mode) };
+    rows.iter().map(|r| (r.new.clone(), r.problem.clone())).collect()
+}
+
+#[test]
+fn the_dialog_previews_templates_and_renames_chains_safely() {
+    let td = tempfile::tempdir().unwrap();
+    for name in ["1.jpg", "2.jpg", "3.jpg", "notes.TXT"] {
+        fs::write(td.path().join(name), name).unwrap();
+    }
+    let mut app = app_in(td.path());
+    for idx in 0..app.active_panel().entries.len() {
+        app.active_panel_mut().selections.insert(idx);
+    }
+    key(&mut app, KeyCode::Char('R'));
+
+    // Shift every photo up by one: 3→4 has to happen before 2→3, and so on.
+    set_field(&mut app, "[N]");
+    key(&mut app, KeyCode::Tab);
+    set_field(&mut app, r"^(\d)$");
+    key(&mut app, KeyCode::Tab);
+    set_field(&mut app, "x$1");
+    assert_eq!(new_names(&app)[0], ("x1".to_string(), None));
+
+    key(&mut app, KeyCode::Tab);
+    set_field(&mut app, "[C2].[E]");
+    key(&mut app, KeyCode::Tab);
+    set_field(&mut app, "");
+    assert_eq!(new_names(&app), [("2.jpg".to_string(), None), ("3.jpg".to_string(), None), ("4.jpg".to_string(), None), ("5.TXT".to_string(), None)]);
+
+    // A bad token or a duplicate name blocks Enter.
+    key(&mut app, KeyCode::Tab);
+    key(&mut app, KeyCode::Tab);
+    set_field(&mut app, "[Q]");
+    assert!(matches!(&app.mode, Mode::BatchRename { preview: Err(e), .. } if e == "unknown token: [Q]"));
+    key(&mut app, KeyCode::Enter);
+    set_field(&mut app, "same");
+    assert!(new_names(&app).iter().all(|(_, problem)| problem.as_deref() == Some("another item gets the same name")));
+    key(&mut app, KeyCode::Enter);
+    assert!(matches!(app.mode, Mode::BatchRename { .. }));
+
+    set_field(&mut app, "[C2].[L][E]");
+    key(&mut app, KeyCode::Enter);
+    assert!(matches!(app.mode, Mode::Normal), "{:?}", app.mode);
+    for (name, was) in [("2.jpg", "1.jpg"), ("3.jpg", "2.jpg"), ("4.jpg", "3.jpg"), ("5.txt", "notes.TXT")] {
+        assert_eq!(fs::read_to_string(td.path().join(name)).unwrap(), was);
+    }
+    assert_eq!(fs::read_dir(td.path()).unwrap().count(), 4);
+}
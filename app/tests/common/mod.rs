@@ -0,0 +1,52 @@
+//! Fixtures shared by the integration tests. Each test crate pulls them in
+//! with `mod common;` and uses only what it needs.
+#![allow(dead_code)]
+
+use fileZoom::app::{App, Mode};
+use fileZoom::runner::progress::ProgressUpdate;
+use std::time::{Duration, Instant};
+
+/// How long a test waits for a background job before giving up.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Put the cursor of the active panel on `name`.
+pub fn select_entry(app: &mut App, name: &str) {
+    let idx = app.active_panel().entries.iter().position(|e| e.name == name).expect("entry present");
+    let parent_rows = app.active_panel().cwd.parent().is_some() as usize;
+    app.active_panel_mut().selected = 1 + parent_rows + idx;
+}
+
+/// Wait until `cond` holds.
+pub fn wait_until(what: &str, mut cond: impl FnMut() -> bool) {
+    let deadline = Instant::now() + TIMEOUT;
+    while !cond() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Poll the followed job until `cond` holds for the app mode.
+pub fn poll_until(app: &mut App, what: &str, cond: impl Fn(&Mode) -> bool) {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        app.poll_progress();
+        if cond(&app.mode) {
+            return;
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for {}: {:?}", what, app.mode);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Drain progress until the background worker reports completion, then
+/// return to normal mode so the next key press is handled by the panels.
+pub fn wait_done(app: &mut App) -> ProgressUpdate {
+    let rx = app.op_progress_rx.take().expect("progress channel");
+    loop {
+        let upd = rx.recv_timeout(TIMEOUT).expect("worker finished");
+        if upd.done {
+            app.mode = Mode::Normal;
+            return upd;
+        }
+    }
+}
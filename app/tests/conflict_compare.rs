@@ -0,0 +1,67 @@
+mod common;
+use common::poll_until;
+
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::vfs::vfs_mem::MemVfs;
+use fileZoom::vfs::Vfs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[test]
+fn conflicts_show_both_files_and_their_diff() {
+    let vfs = MemVfs::new();
+    vfs.write_file("/left/notes.txt", "one\ntwo\nnew\n").unwrap();
+    vfs.write_file("/right/notes.txt", "one\ntwo\nold\n").unwrap();
+
+    let mut app = App::with_options(&StartOptions::default()).unwrap();
+    let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+    app.left.vfs = shared.clone();
+    app.left.cwd = PathBuf::from("/left");
+    app.right.vfs = shared;
+    app.right.cwd = PathBuf::from("/right");
+    app.refresh().unwrap();
+    let idx = app.left.entries.iter().position(|e| e.name == "notes.txt").unwrap();
+    app.left.selections.insert(idx);
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+
+    poll_until(&mut app, "the conflict", |m| matches!(m, Mode::Conflict { .. }));
+    match &app.mode {
+        Mode::Conflict { conflict, .. } => {
+            assert_eq!(conflict.target, PathBuf::from("/right/notes.txt"));
+            assert_eq!(conflict.same_content, Some(false));
+            assert!(conflict.both_files());
+        }
+        other => panic!("{:?}", other),
+    }
+
+    handlers::handle_key(&mut app, KeyCode::Char('v'), 10).unwrap();
+    match &app.mode {
+        Mode::Diff { text, .. } => {
+            assert!(text.contains("\n-old\n") && text.contains("\n+new\n"), "{}", text);
+            assert!(text.contains(" two\n"), "{}", text);
+        }
+        other => panic!("{:?}", other),
+    }
+    handlers::handle_key(&mut app, KeyCode::Esc, 10).unwrap();
+    assert!(matches!(app.mode, Mode::Conflict { .. }));
+
+    handlers::handle_key(&mut app, KeyCode::Char('k'), 10).unwrap();
+    poll_until(&mut app, "the copy", |m| matches!(m, Mode::Message { .. }));
+    assert_eq!(vfs.read_file("/right/notes.txt").unwrap(), b"one\ntwo\nold\n");
+    assert_eq!(vfs.read_file("/right/notes (1).txt").unwrap(), b"one\ntwo\nnew\n");
+}
+
+#[test]
+fn binary_files_are_not_diffed() {
+    use fileZoom::runner::handlers::conflict::text_diff;
+    use std::path::Path;
+
+    let vfs = MemVfs::new();
+    vfs.write_file("/a", b"\0\x01").unwrap();
+    vfs.write_file("/b", "text").unwrap();
+    vfs.write_file("/c", "text").unwrap();
+    assert_eq!(text_diff(&vfs, Path::new("/a"), &vfs, Path::new("/b")), Err("mem:///a is not a text file".to_string()));
+    assert_eq!(text_diff(&vfs, Path::new("/c"), &vfs, Path::new("/b")), Err("The files are identical".to_string()));
+}
@@ -1,5 +1,5 @@
 use assert_fs::prelude::*;
-use fileZoom::app::{App, Panel, Side, SortKey};
+use fileZoom::app::{App, Panel};
 use fileZoom::input::KeyCode;
 use fileZoom::runner::progress::OperationDecision;
 use std::time::Duration;
@@ -18,30 +18,8 @@ fn conflict_overwrite() {
     let left_path = left.path().to_path_buf();
     let right_path = right.path().to_path_buf();
 
-    let mut app = App {
-        left: Panel::new(left_path.clone()),
-        right: Panel::new(right_path.clone()),
-        active: Side::Left,
-        mode: fileZoom::app::Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(left_path.clone());
+    app.right = Panel::new(right_path.clone());
     app.refresh().unwrap();
 
     let mut idx = None;
@@ -99,30 +77,8 @@ fn conflict_skip() {
     let left_path = left.path().to_path_buf();
     let right_path = right.path().to_path_buf();
 
-    let mut app = App {
-        left: Panel::new(left_path.clone()),
-        right: Panel::new(right_path.clone()),
-        active: Side::Left,
-        mode: fileZoom::app::Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(left_path.clone());
+    app.right = Panel::new(right_path.clone());
     app.refresh().unwrap();
 
     let mut idx = None;
@@ -1,8 +1,6 @@
 use assert_fs::prelude::*;
-use fileZoom::app::core::panel::Panel;
 use fileZoom::app::core::App;
-use fileZoom::app::settings::write_settings::Settings;
-use fileZoom::app::types::{Mode, Side, SortKey};
+use fileZoom::app::types::Side;
 
 #[test]
 fn selected_index_reflects_active_panel_unit() {
@@ -12,30 +10,7 @@ fn selected_index_reflects_active_panel_unit() {
     temp.child("c.txt").write_str("3").unwrap();
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // find index of a.txt
@@ -85,30 +60,7 @@ fn panel_mut_match() {
     let temp = assert_fs::TempDir::new().unwrap();
     temp.child("a.txt").write_str("1").unwrap();
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
     // modify left via panel_mut and check read through panel
     let left_name_before = app.left.cwd.clone();
@@ -1,5 +1,5 @@
 use assert_fs::prelude::*;
-use fileZoom::app::{App, Mode, Panel, Side, SortKey};
+use fileZoom::app::{App, Side};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
@@ -27,30 +27,7 @@ fn sort_name_puts_dirs_first() {
     temp.child("a.txt").write_str("hello").unwrap();
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // `entries` is domain-only after refactor; start at 0.
@@ -70,30 +47,7 @@ fn preview_truncates_large_file() {
     big.write_str(&large).unwrap();
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // find index of big.txt in entries
@@ -131,30 +85,7 @@ fn preview_shows_directory_entries_limited() {
     }
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // find index of d in entries
@@ -195,30 +126,7 @@ fn preview_resets_preview_offset() {
     f.write_str("hello\nthere\n").unwrap();
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // find index of small.txt
@@ -257,30 +165,7 @@ fn preview_handles_very_long_filename() {
     f.write_str("hello").unwrap();
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // find index of long filename
@@ -323,30 +208,7 @@ fn preview_unreadable_file_shows_message() {
     fs::set_permissions(p, perms.clone()).unwrap();
 
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-        preview_visible: false,
-        file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     // find index of cannot_read.txt
@@ -30,7 +30,21 @@ fn settings_defaults_snapshot() {
           "mouse_enabled": true,
           "mouse_double_click_ms": 500,
           "show_cli_listing": true,
-          "prefer_integrated_vim": false
+          "prefer_integrated_vim": false,
+          "error_policy": "abort",
+          "conflict_policy": "ask",
+          "verify_copies": false,
+          "copy_method": "reflink",
+          "pack": {
+            "level": 6,
+            "preserve_permissions": true,
+            "preserve_xattrs": false,
+            "preserve_symlinks": true
+          },
+          "shred": {
+            "method": "random",
+            "passes": 3
+          }
         }
         "###
     );
@@ -30,30 +30,7 @@ fn main_chunks(term_rect: Rect) -> [ratatui::layout::Rect; 2] {
 #[test]
 fn drag_does_not_select_across_panels() {
     let cwd = PathBuf::from("/");
-    let mut app = App {
-        left: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        right: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        active: fileZoom::app::types::Side::Left,
-        mode: fileZoom::app::types::Mode::Normal,
-        sort: fileZoom::app::types::SortKey::Name,
-            sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
 
     // populate entries for both panels
     app.left.entries = (0..6)
@@ -113,30 +90,8 @@ fn drag_does_not_select_across_panels() {
 #[test]
 fn drag_with_parent_row_present_selects_correct_domain_indices() {
     let cwd = PathBuf::from("/tmp/somewhere"); // has a parent
-    let mut app = App {
-        left: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        right: fileZoom::app::core::panel::Panel::new(PathBuf::from("/")),
-        active: fileZoom::app::types::Side::Left,
-        mode: fileZoom::app::types::Mode::Normal,
-        sort: fileZoom::app::types::SortKey::Name,
-            sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
+    app.right = fileZoom::app::core::panel::Panel::new(PathBuf::from("/"));
 
     // populate left entries
     app.left.entries = (0..8)
@@ -190,30 +145,7 @@ fn drag_with_parent_row_present_selects_correct_domain_indices() {
 #[test]
 fn drag_with_panel_offset_respects_offset() {
     let cwd = PathBuf::from("/");
-    let mut app = App {
-        left: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        right: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        active: fileZoom::app::types::Side::Left,
-        mode: fileZoom::app::types::Mode::Normal,
-        sort: fileZoom::app::types::SortKey::Name,
-            sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
 
     // many entries so offset matters
     app.left.entries = (0..20)
@@ -0,0 +1,87 @@
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::runner::handlers::edit_names::edit_names_with;
+use std::fs;
+use std::path::Path;
+
+fn app_in(dir: &Path) -> App {
+    App::with_options(&StartOptions { start_dir: Some(dir.to_path_buf()), ..Default::default() }).unwrap()
+}
+
+fn mark_all(app: &mut App) {
+    for idx in 0..app.active_panel().entries.len() {
+        app.active_panel_mut().selections.insert(idx);
+    }
+}
+
+/// Run the edit with `change` applied to the listing in place of an editor.
+fn edit(app: &mut App, change: impl Fn(&str) -> String) {
+    edit_names_with(app, |path| {
+        let text = fs::read_to_string(path)?;
+        fs::write(path, change(&text))
+    });
+}
+
+#[test]
+fn edited_names_are_confirmed_then_renamed_and_deleted() {
+    let td = tempfile::tempdir().unwrap();
+    // Keep the real trash out of this.
+    let data = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_DATA_HOME", data.path());
+    for name in ["a", "b", "c"] {
+        fs::write(td.path().join(name), name).unwrap();
+    }
+    fs::create_dir(td.path().join("dir")).unwrap();
+    fs::write(td.path().join("dir/inner"), "x").unwrap();
+    let mut app = app_in(td.path());
+    mark_all(&mut app);
+
+    // Swap a and b, rename c to a name dir gives up, and drop dir.
+    edit(&mut app, |text| {
+        assert_eq!(text, "1\tdir\n2\ta\n3\tb\n4\tc\n");
+        "3\ta\n2\tb\n4\tdir\n".to_string()
+    });
+    let Mode::EditNames { text, .. } = &app.mode else { panic!("{:?}", app.mode) };
+    assert_eq!(text, "@@ 3 renamed\n-a\n+b\n-b\n+a\n-c\n+dir\n@@ 1 moved to the trash\n-dir\n");
+    assert!(td.path().join("dir/inner").exists(), "nothing happens before Enter");
+
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert!(matches!(app.mode, Mode::Normal), "{:?}", app.mode);
+    assert_eq!(fs::read_to_string(td.path().join("a")).unwrap(), "b");
+    assert_eq!(fs::read_to_string(td.path().join("b")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(td.path().join("dir")).unwrap(), "c");
+    assert_eq!(fs::read_dir(td.path()).unwrap().count(), 3);
+    assert_eq!(fs::read_to_string(data.path().join("Trash/files/dir/inner")).unwrap(), "x");
+    assert!(app.active_panel().selections.is_empty());
+}
+
+#[test]
+fn bad_edits_and_cancelled_confirmations_change_nothing() {
+    let td = tempfile::tempdir().unwrap();
+    for name in ["a", "b", "other"] {
+        fs::write(td.path().join(name), name).unwrap();
+    }
+    let mut app = app_in(td.path());
+    mark_all(&mut app);
+    let other = app.active_panel().entries.iter().position(|e| e.name == "other").unwrap();
+    app.active_panel_mut().selections.remove(&other);
+
+    edit(&mut app, |_| "1\tx\n2\tx\n".to_string());
+    assert!(matches!(&app.mode, Mode::Message { content, .. } if content == "Nothing was changed: line 2: x is also given on line 1"), "{:?}", app.mode);
+
+    edit(&mut app, |_| "1\tother\n2\tb\n".to_string());
+    assert!(matches!(&app.mode, Mode::Message { content, .. } if content.ends_with("other already exists")), "{:?}", app.mode);
+
+    edit(&mut app, |text| text.to_string());
+    assert!(matches!(app.mode, Mode::Normal), "an unchanged listing does nothing: {:?}", app.mode);
+
+    edit(&mut app, |_| String::new());
+    assert!(matches!(&app.mode, Mode::EditNames { deletes, .. } if deletes.len() == 2));
+    handlers::handle_key(&mut app, KeyCode::Esc, 10).unwrap();
+    assert!(matches!(app.mode, Mode::Normal));
+    assert_eq!(fs::read_dir(td.path()).unwrap().count(), 3);
+
+    edit_names_with(&mut app, |_| Err(std::io::Error::other("no editor")));
+    assert!(matches!(&app.mode, Mode::Message { content, .. } if content == "Could not edit the names: no editor"));
+}
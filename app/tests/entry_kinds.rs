@@ -0,0 +1,97 @@
+mod common;
+use common::select_entry;
+
+use fileZoom::app::{App, Entry, EntryKind, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::ui::UIState;
+use fileZoom::vfs::vfs_mem::MemVfs;
+use fileZoom::vfs::Vfs;
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn entry<'a>(app: &'a App, name: &str) -> &'a Entry {
+    app.active_panel().entries.iter().find(|e| e.name == name).expect("entry present")
+}
+
+fn mkfifo(path: &Path) {
+    let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o644) }, 0);
+}
+
+#[test]
+fn local_entries_carry_their_type_and_link_details() {
+    let td = tempfile::tempdir().unwrap();
+    let dir = td.path();
+    fs::write(dir.join("plain"), "p").unwrap();
+    fs::write(dir.join("run.sh"), "#!/bin/sh").unwrap();
+    fs::set_permissions(dir.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+    fs::create_dir(dir.join("sub")).unwrap();
+    symlink("plain", dir.join("to_file")).unwrap();
+    symlink("sub", dir.join("to_dir")).unwrap();
+    symlink("gone", dir.join("stale")).unwrap();
+    mkfifo(&dir.join("pipe"));
+    let _listener = std::os::unix::net::UnixListener::bind(dir.join("sock")).unwrap();
+    let app = App::with_options(&StartOptions { start_dir: Some(dir.to_path_buf()), ..Default::default() }).unwrap();
+
+    assert_eq!(entry(&app, "plain").kind, EntryKind::File);
+    assert_eq!(entry(&app, "sub").kind, EntryKind::Dir);
+    assert_eq!(entry(&app, "pipe").kind, EntryKind::Fifo);
+    assert_eq!(entry(&app, "sock").kind, EntryKind::Socket);
+    let to_file = entry(&app, "to_file");
+    assert_eq!((to_file.kind, to_file.link_target.clone(), to_file.points_to_dir, to_file.broken_link), (EntryKind::Symlink, Some(PathBuf::from("plain")), false, false));
+    let to_dir = entry(&app, "to_dir");
+    assert_eq!((to_dir.kind, to_dir.points_to_dir, to_dir.broken_link), (EntryKind::Symlink, true, false));
+    assert!(!to_dir.is_dir, "a link to a directory is not a directory itself");
+    let stale = entry(&app, "stale");
+    assert_eq!((stale.kind, stale.points_to_dir, stale.broken_link), (EntryKind::Symlink, false, true));
+
+    let lines = UIState::from_core(&app).left_list;
+    for expected in ["plain  1  ", "*run.sh  ", "sub  <dir>  ", "@to_file -> plain  ", "~to_dir -> sub  <dir>  ", "!stale -> gone  ", "|pipe  ", "=sock  "] {
+        assert!(lines.iter().any(|line| line.starts_with(expected)), "{:?} in {:?}", expected, lines);
+    }
+}
+
+#[test]
+fn enter_follows_a_directory_symlink_and_fifos_preview_without_blocking() {
+    let td = tempfile::tempdir().unwrap();
+    fs::create_dir(td.path().join("sub")).unwrap();
+    fs::write(td.path().join("sub/inner.txt"), "x").unwrap();
+    symlink("sub", td.path().join("to_dir")).unwrap();
+    mkfifo(&td.path().join("pipe"));
+    let mut app = App::with_options(&StartOptions { start_dir: Some(td.path().to_path_buf()), ..Default::default() }).unwrap();
+
+    select_entry(&mut app, "pipe");
+    app.update_preview_for(app.active);
+    assert!(app.active_panel().preview.contains("FIFO"), "{:?}", app.active_panel().preview);
+
+    select_entry(&mut app, "to_dir");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert_eq!(app.active_panel().cwd, td.path().join("to_dir"), "the link's path is kept");
+    assert!(app.active_panel().entries.iter().any(|e| e.name == "inner.txt"));
+}
+
+#[test]
+fn other_backends_get_their_kinds_from_the_mode() {
+    let vfs = MemVfs::new();
+    vfs.write_file("/mem/file.txt", "hello").unwrap();
+    vfs.create_dir_all("/mem/sub").unwrap();
+    vfs.symlink(Path::new("sub"), Path::new("/mem/to_dir")).unwrap();
+    vfs.symlink(Path::new("/mem/file.txt"), Path::new("/mem/to_file")).unwrap();
+    vfs.symlink(Path::new("nowhere"), Path::new("/mem/stale")).unwrap();
+    let mut app = App::with_options(&StartOptions::default()).unwrap();
+    app.left.vfs = Arc::new(vfs);
+    app.left.cwd = PathBuf::from("/mem");
+    app.active = fileZoom::app::Side::Left;
+    app.refresh().unwrap();
+
+    assert_eq!(entry(&app, "file.txt").kind, EntryKind::File);
+    assert_eq!(entry(&app, "sub").kind, EntryKind::Dir);
+    let to_dir = entry(&app, "to_dir");
+    assert_eq!((to_dir.kind, to_dir.link_target.clone(), to_dir.points_to_dir), (EntryKind::Symlink, Some(PathBuf::from("sub")), true));
+    let to_file = entry(&app, "to_file");
+    assert_eq!((to_file.kind, to_file.points_to_dir, to_file.broken_link), (EntryKind::Symlink, false, false));
+    assert!(entry(&app, "stale").broken_link);
+}
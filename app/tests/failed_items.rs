@@ -0,0 +1,86 @@
+mod common;
+use common::{select_entry, poll_until};
+
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::runner::job_queue::ErrorPolicy;
+use fileZoom::vfs::vfs_mem::{MemOp, MemVfs};
+use fileZoom::vfs::Vfs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[test]
+fn failed_items_are_asked_about_listed_and_retried() {
+    let vfs = MemVfs::new();
+    vfs.write_file("/left/docs/a.txt", "a").unwrap();
+    vfs.write_file("/left/docs/b.txt", "b").unwrap();
+    vfs.create_dir_all("/right").unwrap();
+    vfs.fail(MemOp::Read, "/left/docs/a.txt", io::ErrorKind::PermissionDenied);
+
+    let mut app = App::with_options(&StartOptions::default()).unwrap();
+    let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+    app.left.vfs = shared.clone();
+    app.left.cwd = PathBuf::from("/left");
+    app.right.vfs = shared;
+    app.right.cwd = PathBuf::from("/right");
+    app.settings.error_policy = ErrorPolicy::Ask;
+    app.refresh().unwrap();
+    select_entry(&mut app, "docs");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+
+    poll_until(&mut app, "the failed item", |m| matches!(m, Mode::ItemError { .. }));
+    match &app.mode {
+        Mode::ItemError { item, .. } => assert_eq!(item.to_string(), "copy /left/docs/a.txt: Permission denied: mem:///left/docs/a.txt — check file permissions"),
+        other => panic!("{:?}", other),
+    }
+    handlers::handle_key(&mut app, KeyCode::Char('s'), 10).unwrap();
+    assert!(matches!(app.mode, Mode::Progress { .. }));
+
+    poll_until(&mut app, "the report", |m| matches!(m, Mode::Message { .. }));
+    match &app.mode {
+        Mode::Message { title, content, buttons, .. } => {
+            assert_eq!(title, "Failed items");
+            assert_eq!(content, "1 items processed, 1 failed:\n\ncopy /left/docs/a.txt: Permission denied: mem:///left/docs/a.txt — check file permissions");
+            assert_eq!(buttons[0], "Retry failed");
+        }
+        other => panic!("{:?}", other),
+    }
+    assert!(vfs.exists("/right/docs/b.txt") && !vfs.exists("/right/docs/a.txt"));
+
+    // One key runs just the failed file again.
+    vfs.heal("/left/docs/a.txt");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert!(app.op_job.is_some());
+    poll_until(&mut app, "the retry", |m| matches!(m, Mode::Message { title, .. } if title == "Done"));
+    assert_eq!(vfs.read_file("/right/docs/a.txt").unwrap(), b"a");
+}
+
+#[test]
+fn verified_copies_report_what_was_checked() {
+    let vfs = MemVfs::new();
+    vfs.write_file("/left/docs/a.txt", "aaaa").unwrap();
+    vfs.write_file("/left/docs/b.txt", "bb").unwrap();
+    vfs.create_dir_all("/right").unwrap();
+
+    let mut app = App::with_options(&StartOptions::default()).unwrap();
+    let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+    app.left.vfs = shared.clone();
+    app.left.cwd = PathBuf::from("/left");
+    app.right.vfs = shared;
+    app.right.cwd = PathBuf::from("/right");
+    app.settings.verify_copies = true;
+    app.refresh().unwrap();
+    select_entry(&mut app, "docs");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+
+    poll_until(&mut app, "the report", |m| matches!(m, Mode::Message { .. }));
+    match &app.mode {
+        Mode::Message { title, content, .. } => {
+            assert_eq!(title, "Done");
+            assert_eq!(content, &format!("1 items processed\n\nVerified 2 files ({})", fileZoom::ui::widgets::progress_bar::format_bytes(6)));
+        }
+        other => panic!("{:?}", other),
+    }
+}
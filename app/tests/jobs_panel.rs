@@ -0,0 +1,85 @@
+mod common;
+use common::{select_entry, wait_until};
+
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::runner::job_queue::JobStatus;
+use fileZoom::ui::widgets::jobs::{format_row, indicator};
+use std::fs;
+use std::path::Path;
+
+/// Copy `a.txt` onto an existing file so the job waits on a conflict.
+fn app_with_waiting_copy(root: &Path) -> App {
+    fs::create_dir_all(root.join("left")).unwrap();
+    fs::create_dir_all(root.join("right")).unwrap();
+    fs::write(root.join("left/a.txt"), b"new").unwrap();
+    fs::write(root.join("right/a.txt"), b"old").unwrap();
+    let mut app = App::with_options(&StartOptions { start_dir: Some(root.join("left")), ..Default::default() }).unwrap();
+    app.right.cwd = root.join("right");
+    app.refresh().unwrap();
+    select_entry(&mut app, "a.txt");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    let id = app.op_job.expect("copy follows a queued job");
+    wait_until("the conflict", || app.jobs.state(id).is_some_and(|s| s.message == "Conflict"));
+    app
+}
+
+#[test]
+fn background_job_can_be_paused_followed_and_finished_from_the_jobs_view() {
+    let td = tempfile::tempdir().unwrap();
+    let mut app = app_with_waiting_copy(td.path());
+
+    // `b` returns to browsing; the footer reports the waiting job.
+    handlers::handle_key(&mut app, KeyCode::Char('b'), 10).unwrap();
+    assert!(matches!(app.mode, Mode::Normal));
+    assert!(app.op_job.is_none() && app.op_progress_rx.is_none());
+    assert_eq!(indicator(&app.jobs.list()).as_deref(), Some("Jobs: 1 active · 0% · 1 waiting"));
+
+    handlers::handle_key(&mut app, KeyCode::Char('J'), 10).unwrap();
+    assert!(matches!(app.mode, Mode::Jobs { selected: 0 }));
+    let (id, kind, state) = app.jobs.list().remove(0);
+    assert!(format_row(&kind, &state).starts_with("Running     0%  Copy 1 item to "), "{}", format_row(&kind, &state));
+
+    handlers::handle_key(&mut app, KeyCode::Char('p'), 10).unwrap();
+    assert_eq!(app.jobs.state(id).unwrap().status, JobStatus::Paused);
+    handlers::handle_key(&mut app, KeyCode::Char('p'), 10).unwrap();
+    assert_eq!(app.jobs.state(id).unwrap().status, JobStatus::Running);
+
+    // Following the job again brings back its pending conflict.
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert_eq!(app.op_job, Some(id));
+    app.poll_progress();
+    assert!(matches!(app.mode, Mode::Conflict { .. }), "{:?}", app.mode);
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    wait_until("the copy", || app.jobs.state(id).is_some_and(|s| s.status == JobStatus::Completed));
+    assert_eq!(fs::read(td.path().join("right/a.txt")).unwrap(), b"new");
+    assert_eq!(indicator(&app.jobs.list()), None);
+}
+
+#[test]
+fn cancelled_jobs_can_be_retried_from_the_jobs_view() {
+    let td = tempfile::tempdir().unwrap();
+    let mut app = app_with_waiting_copy(td.path());
+    handlers::handle_key(&mut app, KeyCode::Char('b'), 10).unwrap();
+    handlers::handle_key(&mut app, KeyCode::Char('J'), 10).unwrap();
+    let id = app.jobs.list()[0].0;
+
+    handlers::handle_key(&mut app, KeyCode::Char('c'), 10).unwrap();
+    wait_until("the cancel", || app.jobs.state(id).is_some_and(|s| s.status == JobStatus::Cancelled));
+
+    handlers::handle_key(&mut app, KeyCode::Char('r'), 10).unwrap();
+    assert!(matches!(app.mode, Mode::Jobs { selected: 1 }));
+    let retried = app.jobs.list()[1].0;
+    wait_until("the retry", || app.jobs.state(retried).is_some_and(|s| s.message == "Conflict"));
+
+    // Move the retry above the cancelled job, then close the view.
+    handlers::handle_key(&mut app, KeyCode::Char('+'), 10).unwrap();
+    assert_eq!(app.jobs.list()[0].0, retried);
+    assert!(matches!(app.mode, Mode::Jobs { selected: 0 }));
+    handlers::handle_key(&mut app, KeyCode::Char('c'), 10).unwrap();
+    handlers::handle_key(&mut app, KeyCode::Esc, 10).unwrap();
+    assert!(matches!(app.mode, Mode::Normal));
+    wait_until("the second cancel", || app.jobs.state(retried).is_some_and(|s| s.status.is_finished()));
+    assert_eq!(fs::read(td.path().join("right/a.txt")).unwrap(), b"old");
+}
@@ -0,0 +1,105 @@
+mod common;
+use common::select_entry;
+
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::ui::UIState;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An app browsing `src` on the left and `dst` on the right.
+fn app_between(src: &Path, dst: &Path) -> App {
+    let mut app = App::with_options(&StartOptions { start_dir: Some(src.to_path_buf()), ..Default::default() }).unwrap();
+    app.right.cwd = dst.to_path_buf();
+    app.refresh().unwrap();
+    app
+}
+
+fn key(app: &mut App, code: KeyCode) {
+    handlers::handle_key(app, code, 10).unwrap();
+}
+
+/// Open the Link dialog and press the `button`-th button.
+fn link(app: &mut App, button: usize) {
+    key(app, KeyCode::Char('L'));
+    assert!(matches!(&app.mode, Mode::Message { title, .. } if title == "Link"), "{:?}", app.mode);
+    for _ in 0..button {
+        key(app, KeyCode::Right);
+    }
+    key(app, KeyCode::Enter);
+}
+
+#[test]
+fn marked_entries_are_linked_into_the_other_panel() {
+    let td = tempfile::tempdir().unwrap();
+    let (src, dst) = (td.path().join("src"), td.path().join("dst/deep"));
+    fs::create_dir_all(src.join("dir")).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    fs::write(src.join("a.txt"), "a").unwrap();
+    let mut app = app_between(&src, &dst);
+
+    select_entry(&mut app, "a.txt");
+    link(&mut app, 1);
+    assert!(matches!(app.mode, Mode::Normal), "{:?}", app.mode);
+    assert_eq!(fs::read_link(dst.join("a.txt")).unwrap(), PathBuf::from("../../src/a.txt"));
+    assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "a");
+    assert!(app.right.entries.iter().any(|e| e.name == "a.txt"), "the other panel is refreshed");
+
+    // The name is taken now, so nothing is linked.
+    link(&mut app, 0);
+    assert!(matches!(&app.mode, Mode::Message { content, .. } if content.contains("already taken")), "{:?}", app.mode);
+    app.mode = Mode::Normal;
+
+    select_entry(&mut app, "dir");
+    link(&mut app, 2);
+    assert!(matches!(&app.mode, Mode::Message { content, .. } if content.contains("cannot be hard-linked")), "{:?}", app.mode);
+    app.mode = Mode::Normal;
+    link(&mut app, 0);
+    assert_eq!(fs::read_link(dst.join("dir")).unwrap(), src.join("dir"));
+
+    fs::remove_file(dst.join("a.txt")).unwrap();
+    select_entry(&mut app, "a.txt");
+    link(&mut app, 2);
+    fs::write(src.join("a.txt"), "changed").unwrap();
+    assert!(!fs::symlink_metadata(dst.join("a.txt")).unwrap().file_type().is_symlink());
+    assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "changed");
+}
+
+#[test]
+fn symlinks_can_be_retargeted_and_broken_ones_are_flagged() {
+    let td = tempfile::tempdir().unwrap();
+    fs::write(td.path().join("one"), "1").unwrap();
+    fs::write(td.path().join("two"), "2").unwrap();
+    std::os::unix::fs::symlink("one", td.path().join("link")).unwrap();
+    std::os::unix::fs::symlink("missing", td.path().join("stale")).unwrap();
+    let mut app = app_between(td.path(), td.path());
+
+    let state = UIState::from_core(&app);
+    assert!(state.left_list.iter().any(|line| line.starts_with("!stale -> missing  ")), "{:?}", state.left_list);
+    assert!(state.left_list.iter().any(|line| line.starts_with("@link -> one  ")), "{:?}", state.left_list);
+
+    select_entry(&mut app, "link");
+    key(&mut app, KeyCode::F(4));
+    let Mode::ContextMenu { options, .. } = &app.mode else { panic!("{:?}", app.mode) };
+    let at = options.iter().position(|o| o == "Edit symlink").expect("offered for symlinks");
+    for _ in 0..at {
+        key(&mut app, KeyCode::Down);
+    }
+    key(&mut app, KeyCode::Enter);
+    assert!(matches!(&app.mode, Mode::Input { prompt, buffer, .. } if prompt == "Symlink link points to:" && buffer == "one"), "{:?}", app.mode);
+    for _ in 0..3 {
+        key(&mut app, KeyCode::Backspace);
+    }
+    for c in "two".chars() {
+        key(&mut app, KeyCode::Char(c));
+    }
+    key(&mut app, KeyCode::Enter);
+    assert!(matches!(app.mode, Mode::Normal), "{:?}", app.mode);
+    assert_eq!(fs::read_to_string(td.path().join("link")).unwrap(), "2");
+    assert_eq!(fs::read_dir(td.path()).unwrap().count(), 4, "no temporary link left behind");
+
+    select_entry(&mut app, "one");
+    key(&mut app, KeyCode::F(4));
+    assert!(matches!(&app.mode, Mode::ContextMenu { options, .. } if !options.iter().any(|o| o == "Edit symlink")));
+}
@@ -0,0 +1,158 @@
+mod common;
+use common::poll_until;
+
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+fn app_in(dir: &Path) -> App {
+    App::with_options(&StartOptions { start_dir: Some(dir.to_path_buf()), ..Default::default() }).unwrap()
+}
+
+fn mark(app: &mut App, names: &[&str]) {
+    for name in names {
+        let idx = app.active_panel().entries.iter().position(|e| e.name == *name).expect("entry present");
+        app.active_panel_mut().selections.insert(idx);
+    }
+}
+
+fn key(app: &mut App, code: KeyCode) {
+    handlers::handle_key(app, code, 10).unwrap();
+}
+
+fn type_line(app: &mut App, text: &str) {
+    for c in text.chars() {
+        key(app, KeyCode::Char(c));
+    }
+    key(app, KeyCode::Enter);
+}
+
+#[test]
+fn deleting_marked_entries_runs_a_job_after_a_summary() {
+    let td = tempfile::tempdir().unwrap();
+    fs::write(td.path().join("a.txt"), vec![0u8; 1000]).unwrap();
+    fs::create_dir_all(td.path().join("dir/sub")).unwrap();
+    fs::write(td.path().join("dir/sub/b.bin"), vec![0u8; 2048]).unwrap();
+    fs::write(td.path().join("keep.txt"), b"k").unwrap();
+    let mut app = app_in(td.path());
+    mark(&mut app, &["a.txt", "dir"]);
+
+    key(&mut app, KeyCode::Char('D'));
+    match &app.mode {
+        Mode::Confirm { msg, .. } => assert_eq!(msg, "Delete 2 items (1000 B + 1 directory) permanently? (y/n)"),
+        other => panic!("expected a confirmation, got {:?}", other),
+    }
+    key(&mut app, KeyCode::Char('y'));
+    assert!(matches!(&app.mode, Mode::Progress { title, .. } if title == "Deleting"), "{:?}", app.mode);
+    poll_until(&mut app, "the delete job", |m| matches!(m, Mode::Message { title, .. } if title == "Done"));
+
+    assert!(!td.path().join("a.txt").exists());
+    assert!(!td.path().join("dir").exists());
+    assert!(td.path().join("keep.txt").exists());
+    assert!(app.active_panel().selections.is_empty());
+}
+
+#[test]
+fn a_single_file_is_deleted_at_once() {
+    let td = tempfile::tempdir().unwrap();
+    fs::write(td.path().join("a.txt"), b"a").unwrap();
+    fs::write(td.path().join("b.txt"), b"b").unwrap();
+    let mut app = app_in(td.path());
+    mark(&mut app, &["b.txt"]);
+
+    key(&mut app, KeyCode::Char('D'));
+    key(&mut app, KeyCode::Char('y'));
+    assert!(matches!(app.mode, Mode::Normal), "{:?}", app.mode);
+    assert!(td.path().join("a.txt").exists());
+    assert!(!td.path().join("b.txt").exists());
+}
+
+#[test]
+fn renaming_marked_entries_uses_a_mask_and_refuses_collisions() {
+    let td = tempfile::tempdir().unwrap();
+    for name in ["a.txt", "b.txt", "c.txt", "b.txt.bak"] {
+        fs::write(td.path().join(name), name).unwrap();
+    }
+    let mut app = app_in(td.path());
+
+    // `b.txt.bak` is taken, so the preview flags it and Enter does nothing.
+    mark(&mut app, &["a.txt", "b.txt"]);
+    key(&mut app, KeyCode::Char('R'));
+    assert!(matches!(&app.mode, Mode::BatchRename { fields, .. } if fields[0] == "*"), "{:?}", app.mode);
+    type_line(&mut app, ".bak");
+    let Mode::BatchRename { preview: Ok(rows), .. } = &app.mode else { panic!("{:?}", app.mode) };
+    assert_eq!(rows[1].problem.as_deref(), Some("name already exists"));
+    assert!(td.path().join("a.txt").exists() && !td.path().join("a.txt.bak").exists());
+
+    // The same goes for the mask API behind it.
+    app.mode = Mode::Normal;
+    let err = app.rename_selected_to("same.txt".to_string()).unwrap_err();
+    assert!(err.to_string().contains("another item gets the same name"), "{}", err);
+
+    app.active_panel_mut().selections.clear();
+    mark(&mut app, &["a.txt", "c.txt"]);
+    key(&mut app, KeyCode::Char('R'));
+    key(&mut app, KeyCode::Backspace);
+    type_line(&mut app, "old-*");
+    assert!(matches!(app.mode, Mode::Normal), "{:?}", app.mode);
+    assert_eq!(fs::read_to_string(td.path().join("old-a.txt")).unwrap(), "a.txt");
+    assert_eq!(fs::read_to_string(td.path().join("old-c.txt")).unwrap(), "c.txt");
+    assert!(td.path().join("b.txt").exists());
+    assert!(app.active_panel().selections.is_empty());
+}
+
+#[test]
+fn permissions_apply_to_every_marked_entry() {
+    let td = tempfile::tempdir().unwrap();
+    for name in ["a.sh", "b.sh", "c.sh"] {
+        fs::write(td.path().join(name), b"").unwrap();
+        fs::set_permissions(td.path().join(name), fs::Permissions::from_mode(0o644)).unwrap();
+    }
+    let mut app = app_in(td.path());
+    mark(&mut app, &["a.sh", "c.sh"]);
+
+    app.mode = Mode::ContextMenu { title: String::new(), options: vec!["Permissions".to_string()], selected: 0, path: td.path().join("a.sh") };
+    key(&mut app, KeyCode::Enter);
+    match &app.mode {
+        Mode::Input { prompt, buffer, .. } => {
+            assert_eq!(prompt, "Permissions for 2 items (octal):");
+            assert_eq!(buffer, "644");
+        }
+        other => panic!("expected the permissions prompt, got {:?}", other),
+    }
+    for _ in 0..3 {
+        key(&mut app, KeyCode::Backspace);
+    }
+    type_line(&mut app, "755");
+
+    let mode = |name: &str| fs::metadata(td.path().join(name)).unwrap().permissions().mode() & 0o7777;
+    assert_eq!((mode("a.sh"), mode("b.sh"), mode("c.sh")), (0o755, 0o644, 0o755));
+}
+
+#[test]
+fn shredding_asks_first_and_runs_a_job() {
+    let td = tempfile::tempdir().unwrap();
+    fs::write(td.path().join("key.pem"), vec![b'k'; 512]).unwrap();
+    fs::write(td.path().join("keep.txt"), b"k").unwrap();
+    let mut app = app_in(td.path());
+    app.settings.shred.passes = 2;
+    mark(&mut app, &["key.pem"]);
+
+    key(&mut app, KeyCode::Char('X'));
+    match &app.mode {
+        Mode::Confirm { msg, .. } => {
+            assert!(msg.starts_with("Shred key.pem (512 B)? The data is overwritten 2 times and cannot be recovered."), "{}", msg);
+            assert!(msg.ends_with(" (y/n)"));
+        }
+        other => panic!("expected a confirmation, got {:?}", other),
+    }
+    key(&mut app, KeyCode::Char('y'));
+    assert!(matches!(&app.mode, Mode::Progress { title, .. } if title == "Shredding"), "{:?}", app.mode);
+    poll_until(&mut app, "the shred job", |m| matches!(m, Mode::Message { title, .. } if title == "Done"));
+
+    let names: Vec<_> = fs::read_dir(td.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+    assert_eq!(names, vec![std::ffi::OsString::from("keep.txt")]);
+}
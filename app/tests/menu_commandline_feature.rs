@@ -1,6 +1,6 @@
 use assert_fs::prelude::*;
 use fileZoom::app::{App, Panel};
-use fileZoom::app::{Mode, Side};
+use fileZoom::app::Mode;
 use fileZoom::input::KeyCode;
 use fileZoom::runner::handlers::context_menu;
 use fileZoom::ui::panels::{compute_scrollbar_thumb, format_entry_line};
@@ -48,30 +48,7 @@ fn context_menu_enter_opens_preview() {
     let temp = assert_fs::TempDir::new().unwrap();
     temp.child("a").write_str("x").unwrap();
     let cwd = temp.path().to_path_buf();
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: fileZoom::app::SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.refresh().unwrap();
 
     app.mode = Mode::ContextMenu {
@@ -9,30 +9,7 @@ use std::path::PathBuf;
 #[test]
 fn drag_start_outside_panel_creates_no_selection() {
     let cwd = PathBuf::from("/");
-    let mut app = App {
-        left: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        right: fileZoom::app::core::panel::Panel::new(cwd.clone()),
-        active: fileZoom::app::types::Side::Left,
-        mode: fileZoom::app::types::Mode::Normal,
-        sort: fileZoom::app::types::SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-            preview_visible: false,
-            file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
 
     // populate left entries
     app.left.entries = (0..6)
@@ -1,4 +1,4 @@
-use fileZoom::app::{App, Mode, Panel, Side, SortKey};
+use fileZoom::app::{App, Mode, Side};
 use fileZoom::runner::handlers;
 use fileZoom::Entry;
 use std::path::PathBuf;
@@ -6,30 +6,7 @@ use std::path::PathBuf;
 #[test]
 fn app_navigation_next_prev_and_paging() {
     let cwd = PathBuf::from("/");
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-        preview_visible: false,
-        file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     // populate left entries with mock (directory) entries so preview doesn't try to read
     app.left.entries = (0..10)
         .map(|i| Entry::directory(format!("f{}", i), PathBuf::from(format!("/f{}", i)), None))
@@ -108,30 +85,7 @@ fn help_key_opens_help_message() {
 #[test]
 fn app_navigation_ensure_selection_visible() {
     let cwd = PathBuf::from("/");
-    let mut app = App {
-        left: Panel::new(cwd.clone()),
-        right: Panel::new(cwd.clone()),
-        active: Side::Left,
-        mode: Mode::Normal,
-        sort: SortKey::Name,
-        sort_order: fileZoom::app::types::SortOrder::Ascending,
-        menu_index: 0,
-        menu_focused: false,
-        menu_state: fileZoom::ui::menu_model::MenuState::default(),
-        preview_visible: false,
-        file_stats_visible: false,
-        command_line: None,
-        settings: fileZoom::app::settings::write_settings::Settings::default(),
-        op_progress_rx: None,
-        op_cancel_flag: None,
-        op_decision_tx: None,
-        last_mouse_click_time: None,
-        last_mouse_click_pos: None,
-        drag_active: false,
-        drag_start: None,
-        drag_current: None,
-        drag_button: None,
-    };
+    let mut app = App::with_cwd(cwd.clone());
     app.left.entries = (0..10)
         .map(|i| Entry::directory(format!("f{}", i), PathBuf::from(format!("/f{}", i)), None))
         .collect();
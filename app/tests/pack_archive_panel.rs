@@ -0,0 +1,102 @@
+mod common;
+use common::{select_entry, wait_done};
+
+use assert_fs::prelude::*;
+use fileZoom::app::{App, InputKind, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use std::path::Path;
+use std::time::Duration;
+
+fn app_in(left: &Path, right: &Path) -> App {
+    let opts = StartOptions { start_dir: Some(left.to_path_buf()), ..Default::default() };
+    let mut app = App::with_options(&opts).unwrap();
+    app.right.cwd = right.to_path_buf();
+    app.refresh().unwrap();
+    app
+}
+
+fn submit(app: &mut App, name: &str) {
+    if let Mode::Input { buffer, .. } = &mut app.mode {
+        buffer.clear();
+    }
+    for c in name.chars() {
+        handlers::handle_key(app, KeyCode::Char(c), 10).unwrap();
+    }
+    handlers::handle_key(app, KeyCode::Enter, 10).unwrap();
+}
+
+#[test]
+fn pack_prompt_defaults_to_zip_and_writes_into_other_panel() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let left = tmp.child("left");
+    let right = tmp.child("right");
+    left.child("docs/readme.txt").write_str("read me").unwrap();
+    right.create_dir_all().unwrap();
+    let mut app = app_in(left.path(), right.path());
+
+    select_entry(&mut app, "docs");
+    handlers::handle_key(&mut app, KeyCode::Char('P'), 10).unwrap();
+    match &app.mode {
+        Mode::Input { buffer, kind, prompt } => {
+            assert_eq!(*kind, InputKind::Pack);
+            assert_eq!(buffer, "docs.zip");
+            assert!(prompt.contains(".tar.zst"), "prompt lists formats: {}", prompt);
+        }
+        other => panic!("expected pack prompt, got {:?}", other),
+    }
+
+    submit(&mut app, "docs.tar.gz");
+    let upd = wait_done(&mut app);
+    assert!(upd.error.is_none(), "pack failed: {:?}", upd.error);
+    assert_eq!(upd.processed, 2);
+    assert!(right.path().join("docs.tar.gz").is_file());
+    assert!(!right.path().join(".docs.tar.gz.part").exists());
+
+    // Browsing the new archive shows what was packed.
+    app.mode = Mode::Normal;
+    app.active = fileZoom::app::Side::Right;
+    app.refresh().unwrap();
+    select_entry(&mut app, "docs.tar.gz");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    let names: Vec<&str> = app.right.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["readme.txt"]);
+}
+
+#[test]
+fn unknown_suffix_is_rejected_and_existing_archive_asks_first() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let left = tmp.child("left");
+    let right = tmp.child("right");
+    left.child("a.txt").write_str("a").unwrap();
+    right.child("a.zip").write_str("old").unwrap();
+    let mut app = app_in(left.path(), right.path());
+
+    select_entry(&mut app, "a.txt");
+    handlers::handle_key(&mut app, KeyCode::Char('P'), 10).unwrap();
+    submit(&mut app, "a.rar");
+    match &app.mode {
+        Mode::Message { title, content, .. } => {
+            assert_eq!(title, "Error");
+            assert!(content.contains("a.rar"), "{}", content);
+        }
+        other => panic!("expected error dialog, got {:?}", other),
+    }
+    assert!(app.op_progress_rx.is_none());
+
+    app.mode = Mode::Normal;
+    handlers::handle_key(&mut app, KeyCode::Char('P'), 10).unwrap();
+    submit(&mut app, "a.zip");
+    let rx = app.op_progress_rx.take().expect("progress channel");
+    let upd = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(upd.conflict.is_some(), "existing archive raises a conflict");
+    app.op_decision_tx
+        .as_ref()
+        .unwrap()
+        .send(fileZoom::runner::progress::OperationDecision::Skip)
+        .unwrap();
+    let upd = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(upd.done && upd.error.is_none());
+    right.child("a.zip").assert("old");
+}
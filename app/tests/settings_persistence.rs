@@ -1,6 +1,11 @@
 use fileZoom::app::settings::read_settings::load_settings;
 use fileZoom::app::settings::write_settings::save_settings;
-use fileZoom::app::settings::write_settings::Settings;
+use fileZoom::app::settings::write_settings::{Settings, SmbCredentials};
+use fileZoom::fs_op::fastcopy::CopyMethod;
+use fileZoom::fs_op::shred::{ShredMethod, ShredOptions};
+use fileZoom::runner::job_queue::ErrorPolicy;
+use fileZoom::runner::progress::ConflictPolicy;
+use fileZoom::vfs::pack::PackOptions;
 use std::env;
 use tempfile::tempdir;
 
@@ -22,9 +27,25 @@ fn save_and_load_settings_roundtrip() {
         mouse_enabled: true,
         mouse_double_click_ms: 500,
         prefer_integrated_vim: false,
+        error_policy: ErrorPolicy::Skip,
+        conflict_policy: ConflictPolicy::RenameNew,
+        verify_copies: true,
+        copy_method: CopyMethod::Sparse,
+        smb_hosts: [(
+            "nas.lan".to_string(),
+            SmbCredentials { user: "media".into(), password: "hunter2".into(), domain: Some("HOME".into()) },
+        )]
+        .into_iter()
+        .collect(),
+        pack: PackOptions { level: 9, preserve_xattrs: true, ..Default::default() },
+        shred: ShredOptions { method: ShredMethod::Dod, passes: 1 },
     };
 
     save_settings(&s).expect("save should succeed");
     let loaded = load_settings().expect("load should succeed");
     assert_eq!(loaded, s);
+    assert_eq!(loaded.smb_credentials("NAS.lan").map(|c| c.user.as_str()), Some("media"));
+    assert!(loaded.smb_credentials("other").is_none());
+    let debug = format!("{:?}", loaded);
+    assert!(debug.contains("media") && !debug.contains("hunter2"), "{}", debug);
 }
@@ -0,0 +1,98 @@
+mod common;
+use common::select_entry;
+
+use assert_fs::prelude::*;
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use sevenz_rust2::encoder_options::AesEncoderOptions;
+use sevenz_rust2::{ArchiveEntry, ArchiveWriter, EncoderConfiguration, EncoderMethod, Password};
+use std::path::Path;
+use std::time::Duration;
+
+fn build_7z(path: &Path) {
+    let mut w = ArchiveWriter::create(path).unwrap();
+    w.push_archive_entry::<&[u8]>(ArchiveEntry::new_directory("bundle"), None).unwrap();
+    w.push_archive_entry(ArchiveEntry::new_file("bundle/setup.txt"), Some(&b"run setup.exe"[..])).unwrap();
+    w.push_archive_entry(ArchiveEntry::new_file("bundle/notes.txt"), Some(&b"vendor notes"[..])).unwrap();
+    w.finish().unwrap();
+}
+
+fn build_sealed_7z(path: &Path) {
+    let mut w = ArchiveWriter::create(path).unwrap();
+    w.set_content_methods(vec![
+        AesEncoderOptions::new(Password::new("vendor")).into(),
+        EncoderConfiguration::new(EncoderMethod::LZMA2),
+    ]);
+    w.push_archive_entry(ArchiveEntry::new_file("secret.txt"), Some(&b"hidden"[..])).unwrap();
+    w.finish().unwrap();
+}
+
+fn app_in(left: &Path, right: &Path) -> App {
+    let opts = StartOptions { start_dir: Some(left.to_path_buf()), ..Default::default() };
+    let mut app = App::with_options(&opts).unwrap();
+    app.right.cwd = right.to_path_buf();
+    app.refresh().unwrap();
+    app
+}
+
+#[test]
+fn browse_preview_and_copy_out_of_7z() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let left = tmp.child("left");
+    let right = tmp.child("right");
+    left.create_dir_all().unwrap();
+    right.create_dir_all().unwrap();
+    let archive = left.path().join("vendor.7z");
+    build_7z(&archive);
+    let mut app = app_in(left.path(), right.path());
+
+    select_entry(&mut app, "vendor.7z");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert_eq!(app.left.vfs.scheme(), "7z");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    let names: Vec<&str> = app.left.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["notes.txt", "setup.txt"]);
+    select_entry(&mut app, "setup.txt");
+    app.update_preview_for(app.active);
+    assert_eq!(app.left.preview, "run setup.exe");
+
+    handlers::handle_key(&mut app, KeyCode::Backspace, 10).unwrap();
+    select_entry(&mut app, "bundle");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    let rx = app.op_progress_rx.take().expect("progress channel");
+    while let Ok(upd) = rx.recv_timeout(Duration::from_secs(2)) {
+        if upd.done {
+            assert!(upd.error.is_none(), "copy failed: {:?}", upd.error);
+            break;
+        }
+    }
+    right.child("bundle/setup.txt").assert("run setup.exe");
+    right.child("bundle/notes.txt").assert("vendor notes");
+}
+
+#[test]
+fn unsupported_archives_show_a_clean_error() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    build_sealed_7z(&tmp.path().join("sealed.7z"));
+    tmp.child("broken.iso").write_binary(&[0u8; 40 * 2048]).unwrap();
+    let mut app = app_in(tmp.path(), tmp.path());
+
+    select_entry(&mut app, "sealed.7z");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    match &app.mode {
+        Mode::Message { title, content, .. } => {
+            assert_eq!(title, "Error");
+            assert!(content.starts_with("Not supported:"), "{content}");
+            assert!(content.contains("password-protected"), "{content}");
+        }
+        other => panic!("expected an error message, got {other:?}"),
+    }
+    assert!(app.left.vfs.is_local());
+    app.mode = Mode::Normal;
+
+    select_entry(&mut app, "broken.iso");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert!(matches!(&app.mode, Mode::Message { title, .. } if title == "Error"));
+    assert_eq!(app.left.cwd, tmp.path());
+}
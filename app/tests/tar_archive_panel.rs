@@ -0,0 +1,91 @@
+mod common;
+use common::select_entry;
+
+use assert_fs::prelude::*;
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use std::path::Path;
+use std::time::Duration;
+
+fn build_tarball(path: &Path) {
+    let bz = bzip2::write::BzEncoder::new(std::fs::File::create(path).unwrap(), bzip2::Compression::fast());
+    let mut b = tar::Builder::new(bz);
+    let mut dir = tar::Header::new_gnu();
+    dir.set_entry_type(tar::EntryType::Directory);
+    dir.set_size(0);
+    dir.set_mode(0o750);
+    b.append_data(&mut dir, "project/", std::io::empty()).unwrap();
+    let mut file = tar::Header::new_gnu();
+    file.set_entry_type(tar::EntryType::Regular);
+    file.set_size(12);
+    file.set_mode(0o600);
+    file.set_uid(501);
+    file.set_gid(20);
+    file.set_username("builder").unwrap();
+    file.set_mtime(1_600_000_000);
+    b.append_data(&mut file, "project/main.rs", &b"fn main() {}"[..]).unwrap();
+    let mut link = tar::Header::new_gnu();
+    link.set_entry_type(tar::EntryType::Symlink);
+    link.set_size(0);
+    link.set_mode(0o777);
+    b.append_link(&mut link, "project/entry.rs", "main.rs").unwrap();
+    b.into_inner().unwrap().finish().unwrap();
+}
+
+#[test]
+fn browse_tarball_and_extract_directory_with_progress() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let left = tmp.child("left");
+    let right = tmp.child("right");
+    left.create_dir_all().unwrap();
+    right.create_dir_all().unwrap();
+    let archive = left.path().join("src.tar.bz2");
+    build_tarball(&archive);
+
+    let opts = StartOptions { start_dir: Some(left.path().to_path_buf()), ..Default::default() };
+    let mut app = App::with_options(&opts).unwrap();
+    app.right.cwd = right.path().to_path_buf();
+    app.refresh().unwrap();
+
+    select_entry(&mut app, "src.tar.bz2");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert_eq!(app.left.vfs.scheme(), "tar");
+    assert_eq!(app.left.cwd, archive);
+
+    // Header metadata is carried into the listing.
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert_eq!(app.left.cwd, archive.join("project"));
+    let main = app.left.entries.iter().find(|e| e.name == "main.rs").unwrap();
+    assert_eq!(main.size, 12);
+    assert_eq!(main.unix_mode, Some(0o100600));
+    assert_eq!((main.uid, main.gid), (Some(501), Some(20)));
+    assert_eq!(main.owner.as_deref(), Some("builder"));
+    assert_eq!(main.modified.unwrap().timestamp(), 1_600_000_000);
+    let entry = app.left.entries.iter().find(|e| e.name == "entry.rs").unwrap();
+    assert_eq!(entry.unix_mode.map(|m| m & 0o170000), Some(0o120000));
+
+    // Copying the directory out extracts it in one pass, reporting each member.
+    handlers::handle_key(&mut app, KeyCode::Backspace, 10).unwrap();
+    select_entry(&mut app, "project");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    assert!(matches!(app.mode, Mode::Progress { .. }));
+    let rx = app.op_progress_rx.take().expect("progress channel");
+    let mut messages = Vec::new();
+    while let Ok(upd) = rx.recv_timeout(Duration::from_secs(2)) {
+        if upd.done {
+            assert!(upd.error.is_none(), "extract failed: {:?}", upd.error);
+            break;
+        }
+        messages.extend(upd.message);
+    }
+    assert!(messages.iter().any(|m| m.starts_with("Copied") && m.ends_with("main.rs")), "{messages:?}");
+    assert!(messages.iter().any(|m| m.starts_with("Copied") && m.ends_with("entry.rs")), "{messages:?}");
+
+    right.child("project/main.rs").assert("fn main() {}");
+    let link = right.path().join("project/entry.rs");
+    assert_eq!(std::fs::read_link(&link).unwrap(), Path::new("main.rs"));
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(right.path().join("project/main.rs")).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
@@ -0,0 +1,78 @@
+mod common;
+use common::select_entry;
+
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::ui::widgets::trash::format_row;
+use std::fs;
+
+fn key(app: &mut App, code: KeyCode) {
+    handlers::handle_key(app, code, 10).unwrap();
+}
+
+#[test]
+fn deleted_files_go_to_the_trash_and_can_be_restored_purged_or_emptied() {
+    let td = tempfile::tempdir().unwrap();
+    // Keep the real trash out of this; everything below uses the user trash.
+    std::env::set_var("XDG_DATA_HOME", td.path().join("data"));
+    let dir = td.path().join("files");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.txt"), b"a").unwrap();
+    fs::write(dir.join("b.txt"), b"b").unwrap();
+    fs::write(dir.join("c.txt"), b"c").unwrap();
+    let mut app = App::with_options(&StartOptions { start_dir: Some(dir.clone()), ..Default::default() }).unwrap();
+
+    select_entry(&mut app, "a.txt");
+    key(&mut app, KeyCode::Char('d'));
+    match &app.mode {
+        Mode::Confirm { msg, .. } => assert_eq!(msg, "Move a.txt (1 B) to the trash? (y/n)"),
+        other => panic!("expected a confirmation, got {:?}", other),
+    }
+    key(&mut app, KeyCode::Char('y'));
+    assert!(!dir.join("a.txt").exists());
+    assert!(td.path().join("data/Trash/files/a.txt").exists());
+    assert!(td.path().join("data/Trash/info/a.txt.trashinfo").exists());
+
+    // `D` skips the trash.
+    select_entry(&mut app, "b.txt");
+    key(&mut app, KeyCode::Char('D'));
+    match &app.mode {
+        Mode::Confirm { msg, .. } => assert_eq!(msg, "Delete b.txt (1 B) permanently? (y/n)"),
+        other => panic!("expected a confirmation, got {:?}", other),
+    }
+    key(&mut app, KeyCode::Char('y'));
+    assert!(!dir.join("b.txt").exists());
+    assert!(!td.path().join("data/Trash/files/b.txt").exists());
+
+    key(&mut app, KeyCode::Char('T'));
+    let Mode::Trash { items, selected: 0 } = &app.mode else { panic!("expected the trash view, got {:?}", app.mode) };
+    assert_eq!(items.len(), 1);
+    assert!(format_row(&items[0]).ends_with(&format!("  {}", dir.join("a.txt").display())), "{}", format_row(&items[0]));
+
+    key(&mut app, KeyCode::Char('r'));
+    assert_eq!(fs::read(dir.join("a.txt")).unwrap(), b"a");
+    assert!(matches!(&app.mode, Mode::Trash { items, .. } if items.is_empty()));
+    assert!(app.active_panel().entries.iter().any(|e| e.name == "a.txt"));
+    key(&mut app, KeyCode::Esc);
+    assert!(matches!(app.mode, Mode::Normal));
+
+    // Purge one item, then empty the rest.
+    for name in ["a.txt", "c.txt"] {
+        select_entry(&mut app, name);
+        key(&mut app, KeyCode::Char('d'));
+        key(&mut app, KeyCode::Char('y'));
+    }
+    key(&mut app, KeyCode::Char('T'));
+    key(&mut app, KeyCode::Char('x'));
+    assert!(matches!(app.mode, Mode::Confirm { .. }));
+    key(&mut app, KeyCode::Char('y'));
+    let Mode::Trash { items, .. } = &app.mode else { panic!("expected the trash view, got {:?}", app.mode) };
+    assert_eq!(items.len(), 1);
+    key(&mut app, KeyCode::Char('E'));
+    key(&mut app, KeyCode::Char('y'));
+    assert!(matches!(&app.mode, Mode::Trash { items, .. } if items.is_empty()));
+    assert_eq!(fs::read_dir(td.path().join("data/Trash/files")).unwrap().count(), 0);
+    assert_eq!(fs::read_dir(td.path().join("data/Trash/info")).unwrap().count(), 0);
+    assert!(!dir.join("a.txt").exists() && !dir.join("c.txt").exists());
+}
@@ -23,7 +23,7 @@ fn main_menu_renders_smoke() {
     let buf = term.backend_mut().buffer();
     // The content line is the second row (index 1) when bordered block
     // with height 3 is rendered.
-    let width = buf.area().width as u16;
+    let width = buf.area().width;
     let mut content_row = String::new();
     for x in 0..width {
         if let Some(c) = buf.cell((x, 1)) { content_row.push_str(c.symbol()); }
@@ -25,7 +25,7 @@ fn runtime_ui_respects_menu_state() -> Result<()> {
     terminal.draw(|f| fileZoom::ui::ui(f, &app))?;
 
     let buf = terminal.backend_mut().buffer();
-    let width = buf.area().width as u16;
+    let width = buf.area().width;
     // The menu is rendered as a bordered block 3 rows tall, so the
     // content (including the bracketed active item) appears on the
     // second row (index 1) rather than the top border row.
@@ -33,7 +33,7 @@ fn runtime_ui_respects_menu_state() -> Result<()> {
     // fixed row index, check that the backend buffer contains the
     // expected active label anywhere in the visible area.
     let mut full = String::new();
-    for y in 0..buf.area().height as u16 {
+    for y in 0..buf.area().height {
         for x in 0..width {
             if let Some(c) = buf.cell((x, y)) { full.push_str(c.symbol()); }
         }
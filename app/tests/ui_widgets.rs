@@ -20,3 +20,70 @@ fn header_and_panels_render() {
         fileZoom::ui::widgets::file_list::render(f, area, &state.left_list, state.left_selected, &theme);
     }).unwrap();
 }
+
+#[test]
+fn operation_dialog_shows_bytes_rate_and_eta() {
+    use fileZoom::runner::progress::ByteProgress;
+    use fileZoom::ui::widgets::progress_bar::{format_bytes, format_eta, render_operation, summary};
+
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(40 * 1024 * 1024 * 1024), "40.0 GiB");
+    assert_eq!(format_eta(462), "7m 42s");
+    assert_eq!(format_eta(3900), "1h 05m");
+
+    let bytes = ByteProgress {
+        file_done: 512 * 1024 * 1024,
+        file_total: 1024 * 1024 * 1024,
+        done: 3 * 1024 * 1024 * 1024,
+        total: 4 * 1024 * 1024 * 1024,
+        rate: 100 * 1024 * 1024,
+        eta_secs: Some(10),
+        scanning: false,
+    };
+    assert_eq!(summary(0, 1, Some(&bytes)), "0/1 items · 3.0 GiB / 4.0 GiB · 100.0 MiB/s · ETA 10s");
+    assert_eq!(summary(2, 5, None), "2/5 items");
+    assert_eq!(summary(0, 1, Some(&ByteProgress { scanning: true, total: 2048, ..Default::default() })), "Scanning · 2.0 KiB found");
+
+    let mut t = Terminal::new(TestBackend::new(70, 7)).unwrap();
+    t.draw(|f| render_operation(f, Rect::new(0, 0, 70, 7), "Copying", "Copying /vm/disk.img", 0, 1, Some(&bytes))).unwrap();
+    let text: String = t.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(text.contains("Copying /vm/disk.img"), "{}", text);
+    assert!(text.contains("file 512.0 MiB / 1.0 GiB"), "{}", text);
+    assert!(text.contains("total 75%"), "{}", text);
+    assert!(text.contains("ETA 10s"), "{}", text);
+}
+
+#[test]
+fn jobs_view_shows_hints_and_an_empty_state() {
+    use fileZoom::ui::widgets::jobs::{render, HINTS};
+
+    let mut t = Terminal::new(TestBackend::new(90, 6)).unwrap();
+    t.draw(|f| render(f, Rect::new(0, 0, 90, 6), &[], 0)).unwrap();
+    let text: String = t.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(text.contains("No jobs"), "{}", text);
+    assert!(text.contains(HINTS), "{}", text);
+}
+
+#[test]
+fn conflict_dialog_shows_both_sides() {
+    use fileZoom::runner::progress::Conflict;
+    use fileZoom::ui::widgets::conflict::{buttons, render, rows, HEIGHT};
+    use fileZoom::vfs::vfs_mem::MemVfs;
+    use std::path::Path;
+
+    let vfs = MemVfs::new();
+    vfs.write_file("/src/a.txt", "new contents").unwrap();
+    vfs.write_file("/dst/a.txt", "old").unwrap();
+    let conflict = Conflict::inspect(&vfs, Path::new("/src/a.txt"), &vfs, Path::new("/dst/a.txt"));
+    let rows = rows(&conflict);
+    assert_eq!(rows[0], ["Size".to_string(), "12 B".to_string(), "3 B".to_string()]);
+    assert_eq!(rows.last().unwrap()[1], "different");
+    assert_eq!(buttons(&conflict).last(), Some(&"Compare"));
+    assert_eq!(buttons(&Conflict::target_only("/dst/a.txt".into(), None)).last(), Some(&"Cancel"));
+
+    let mut t = Terminal::new(TestBackend::new(90, HEIGHT)).unwrap();
+    t.draw(|f| render(f, Rect::new(0, 0, 90, HEIGHT), &conflict, 2, true)).unwrap();
+    let text: String = t.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(text.contains("/dst/a.txt"), "{}", text);
+    assert!(text.contains("12 B") && text.contains("[Keep both]") && text.contains("[x] apply to all"), "{}", text);
+}
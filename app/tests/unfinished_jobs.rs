@@ -0,0 +1,80 @@
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::fs_op::helpers::partial_path;
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::runner::job_journal::{JobJournal, JournalEntry, PartialFile};
+use fileZoom::runner::job_queue::{JobKind, JobQueue};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// An app whose queue journals into `root/jobs`, which holds a copy of
+/// `src/a.txt` and `src/big.bin` interrupted 64 KiB into the second file.
+fn app_with_unfinished_copy(root: &Path) -> App {
+    let (src, dst) = (root.join("src"), root.join("dst"));
+    fs::create_dir_all(&src).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    fs::write(src.join("a.txt"), b"a").unwrap();
+    fs::write(src.join("big.bin"), vec![3u8; 200 * 1024]).unwrap();
+    fs::write(dst.join("a.txt"), b"a").unwrap();
+    fs::write(partial_path(&dst.join("big.bin")), vec![3u8; 64 * 1024]).unwrap();
+
+    let journal = JobJournal::new(root.join("jobs"));
+    let mut entry = JournalEntry::new(1, JobKind::Copy { sources: vec![src.join("a.txt"), src.join("big.bin")], dst_dir: dst.clone() });
+    entry.pid = i32::MAX as u32;
+    entry.done[0] = true;
+    entry.current = Some(1);
+    entry.partial = Some(PartialFile { src: src.join("big.bin"), dst: dst.join("big.bin"), offset: 64 * 1024 });
+    journal.save(&entry).unwrap();
+
+    let mut app = App::with_options(&StartOptions { start_dir: Some(root.to_path_buf()), ..Default::default() }).unwrap();
+    app.jobs = JobQueue::with_journal(journal);
+    handlers::unfinished::offer_unfinished(&mut app);
+    app
+}
+
+#[test]
+fn start_offers_unfinished_jobs_and_resumes_them() {
+    let td = tempfile::tempdir().unwrap();
+    let mut app = app_with_unfinished_copy(td.path());
+    match &app.mode {
+        Mode::Message { title, content, .. } => {
+            assert_eq!(title, "Unfinished jobs");
+            assert!(content.contains("Copy 2 items to"), "{}", content);
+            assert!(content.contains("(1/2 done)"), "{}", content);
+        }
+        other => panic!("expected the resume prompt, got {:?}", other),
+    }
+
+    // Inspect lists the items, then Resume from the details dialog.
+    handlers::handle_key(&mut app, KeyCode::Right, 10).unwrap();
+    handlers::handle_key(&mut app, KeyCode::Right, 10).unwrap();
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    match &app.mode {
+        Mode::Message { content, .. } => assert!(content.contains("64.0 KiB of"), "{}", content),
+        other => panic!("expected job details, got {:?}", other),
+    }
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert!(matches!(app.mode, Mode::Progress { total: 1, .. }), "{:?}", app.mode);
+
+    let rx = app.op_progress_rx.take().unwrap();
+    let last = loop {
+        let upd = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        if upd.done {
+            break upd;
+        }
+    };
+    assert!(last.error.is_none(), "{:?}", last.error);
+    assert_eq!(fs::read(td.path().join("dst/big.bin")).unwrap(), vec![3u8; 200 * 1024]);
+}
+
+#[test]
+fn discarding_unfinished_jobs_clears_the_journal() {
+    let td = tempfile::tempdir().unwrap();
+    let mut app = app_with_unfinished_copy(td.path());
+    handlers::handle_key(&mut app, KeyCode::Right, 10).unwrap();
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert!(matches!(app.mode, Mode::Normal));
+    assert!(app.jobs.unfinished().is_empty());
+    assert!(!partial_path(&td.path().join("dst/big.bin")).exists());
+}
@@ -0,0 +1,100 @@
+mod common;
+use common::{select_entry, wait_done};
+
+use fileZoom::app::{App, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::vfs::vfs_mem::{MemOp, MemVfs};
+use fileZoom::vfs::Vfs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Both panels browse the same in-memory tree: `/left` and `/right`.
+fn mem_app(vfs: &MemVfs) -> App {
+    let mut app = App::with_options(&StartOptions::default()).unwrap();
+    let shared: Arc<dyn Vfs> = Arc::new(vfs.clone());
+    app.left.vfs = shared.clone();
+    app.left.cwd = PathBuf::from("/left");
+    app.right.vfs = shared;
+    app.right.cwd = PathBuf::from("/right");
+    app.refresh().unwrap();
+    app
+}
+
+fn tree() -> MemVfs {
+    let vfs = MemVfs::new();
+    vfs.add_user(1000, "alice");
+    vfs.add_group(1000, "staff");
+    vfs.write_file("/left/docs/readme.txt", "hello memory").unwrap();
+    vfs.write_file("/left/tool.sh", "#!/bin/sh\n").unwrap();
+    vfs.set_mode(Path::new("/left/tool.sh"), 0o750).unwrap();
+    vfs.set_xattr(Path::new("/left/tool.sh"), "user.origin", b"vendor").unwrap();
+    vfs.create_dir_all("/right").unwrap();
+    vfs
+}
+
+#[test]
+fn panels_list_preview_and_copy_with_metadata() {
+    let vfs = tree();
+    let mut app = mem_app(&vfs);
+
+    let tool = app.left.entries.iter().find(|e| e.name == "tool.sh").unwrap();
+    assert_eq!(tool.unix_mode, Some(0o100750));
+    assert_eq!((tool.owner.as_deref(), tool.group.as_deref()), (Some("alice"), Some("staff")));
+
+    select_entry(&mut app, "docs");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    select_entry(&mut app, "readme.txt");
+    app.update_preview_for(app.active);
+    assert_eq!(app.left.preview, "hello memory");
+
+    handlers::handle_key(&mut app, KeyCode::Backspace, 10).unwrap();
+    select_entry(&mut app, "tool.sh");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    let upd = wait_done(&mut app);
+    assert!(upd.error.is_none(), "copy failed: {:?}", upd.error);
+
+    let copied = vfs.stat(Path::new("/right/tool.sh")).unwrap();
+    assert_eq!(copied.unix_mode, Some(0o100750));
+    assert_eq!(vfs.get_xattr(Path::new("/right/tool.sh"), "user.origin").unwrap().as_deref(), Some(&b"vendor"[..]));
+
+    select_entry(&mut app, "docs");
+    handlers::handle_key(&mut app, KeyCode::F(6), 10).unwrap();
+    let upd = wait_done(&mut app);
+    assert!(upd.error.is_none(), "move failed: {:?}", upd.error);
+    assert_eq!(vfs.read_file("/right/docs/readme.txt").unwrap(), b"hello memory");
+    assert!(!vfs.exists("/left/docs"));
+}
+
+#[test]
+fn injected_failures_and_permissions_surface_as_errors() {
+    let vfs = tree();
+    let mut app = mem_app(&vfs);
+
+    vfs.fail_write_after("/right/tool.sh", 4, io::ErrorKind::StorageFull);
+    select_entry(&mut app, "tool.sh");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    let upd = wait_done(&mut app);
+    assert!(upd.error.is_some_and(|e| e.contains("/right/tool.sh")));
+    assert_eq!(vfs.read_file("/right/tool.sh").unwrap(), b"#!/b");
+
+    // Another user cannot read a private file; the preview says so.
+    vfs.set_mode(Path::new("/left/docs/readme.txt"), 0o600).unwrap();
+    vfs.set_identity(2000, 2000);
+    app.left.cwd = PathBuf::from("/left/docs");
+    app.refresh().unwrap();
+    select_entry(&mut app, "readme.txt");
+    app.update_preview_for(app.active);
+    assert!(app.left.preview.contains("(unreadable)"), "{}", app.left.preview);
+
+    // A new file never truncates one that is already there.
+    vfs.set_identity(1000, 1000);
+    let err = app.new_file("readme.txt".to_string()).unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+    vfs.set_identity(0, 0);
+    assert_eq!(vfs.read_file("/left/docs/readme.txt").unwrap(), b"hello memory");
+
+    vfs.fail(MemOp::List, "/right", io::ErrorKind::TimedOut);
+    assert_eq!(app.refresh().unwrap_err().kind(), io::ErrorKind::TimedOut);
+}
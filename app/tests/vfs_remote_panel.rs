@@ -0,0 +1,177 @@
+mod common;
+use common::{select_entry, wait_done};
+
+use assert_fs::prelude::*;
+use fileZoom::app::{App, Entry, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::vfs::{LocalVfs, Vfs};
+use predicates::prelude::*;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Delegates to the local filesystem but reports a non-local scheme so the
+/// app takes the same code paths it uses for remote (e.g. SFTP) panels.
+#[derive(Debug)]
+struct PretendRemote(LocalVfs);
+
+impl Vfs for PretendRemote {
+    fn scheme(&self) -> &'static str {
+        "pretend"
+    }
+    fn list(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        self.0.list(dir)
+    }
+    fn stat(&self, path: &Path) -> io::Result<Entry> {
+        self.0.stat(path)
+    }
+    fn open_read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        self.0.open_read(path)
+    }
+    fn open_write(&self, path: &Path) -> io::Result<Box<dyn Write + Send>> {
+        self.0.open_write(path)
+    }
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        self.0.mkdir(path)
+    }
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.0.rename(from, to)
+    }
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.0.remove(path)
+    }
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.0.symlink(target, link)
+    }
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.0.set_mode(path, mode)
+    }
+}
+
+#[test]
+fn remote_panel_copy_move_delete_preview_and_chmod() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let remote = tmp.child("remote");
+    let local = tmp.child("local");
+    remote.child("docs/readme.txt").write_str("hello remote").unwrap();
+    remote.child("big.bin").write_str("payload").unwrap();
+    local.create_dir_all().unwrap();
+
+    let opts = StartOptions { start_dir: Some(remote.path().to_path_buf()), ..Default::default() };
+    let mut app = App::with_options(&opts).unwrap();
+    app.left.vfs = Arc::new(PretendRemote(LocalVfs::new()));
+    app.right.cwd = local.path().to_path_buf();
+    app.refresh().unwrap();
+
+    // Preview of a remote file goes through the backend.
+    select_entry(&mut app, "big.bin");
+    app.update_preview_for(app.active);
+    assert_eq!(app.left.preview, "payload");
+
+    // F5 copies a remote directory into the local panel.
+    select_entry(&mut app, "docs");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    let upd = wait_done(&mut app);
+    assert!(upd.error.is_none(), "operation failed: {:?}", upd.error);
+    local.child("docs/readme.txt").assert("hello remote");
+
+    // F6 moves a remote file into the local panel.
+    app.refresh().unwrap();
+    select_entry(&mut app, "big.bin");
+    handlers::handle_key(&mut app, KeyCode::F(6), 10).unwrap();
+    let upd = wait_done(&mut app);
+    assert!(upd.error.is_none(), "operation failed: {:?}", upd.error);
+    local.child("big.bin").assert("payload");
+    remote.child("big.bin").assert(predicate::path::missing());
+
+    // chmod goes through the backend.
+    app.refresh().unwrap();
+    select_entry(&mut app, "docs");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        app.chmod_selected(0o700).unwrap();
+        let mode = std::fs::metadata(remote.child("docs").path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    // Recursive delete of a remote directory.
+    select_entry(&mut app, "docs");
+    app.delete_selected().unwrap();
+    remote.child("docs").assert(predicate::path::missing());
+}
+
+/// Manual check against a real SFTP server; the automated suite only
+/// covers host key checks and connection errors (see `vfs_ssh.rs`). Run it with `cargo test -- --ignored sftp` and
+/// `FILEZOOM_SFTP_URL=sftp://user@host:port/writable/dir` pointing at a
+/// throwaway `sshd` whose host key is in `~/.ssh/known_hosts` and which
+/// accepts one of the default keys in `~/.ssh`.
+#[cfg(feature = "sftp")]
+#[test]
+#[ignore = "needs an SFTP server; set FILEZOOM_SFTP_URL"]
+fn sftp_roundtrip_against_real_server() {
+    let url = std::env::var("FILEZOOM_SFTP_URL").expect("FILEZOOM_SFTP_URL=sftp://user@host/dir names the server to test against");
+    let settings = fileZoom::app::settings::write_settings::Settings::default();
+    let (vfs, root) = fileZoom::vfs::open_location(&url, &settings).expect("connect");
+    assert_eq!(vfs.scheme(), "sftp");
+
+    let dir = root.join(format!("filezoom_test_{}", std::process::id()));
+    vfs.mkdir(&dir).unwrap();
+    let file = dir.join("a.txt");
+    vfs.open_write(&file).unwrap().write_all(b"over the wire").unwrap();
+
+    let names: Vec<String> = vfs.list(&dir).unwrap().into_iter().map(|e| e.name).collect();
+    assert_eq!(names, vec!["a.txt".to_string()]);
+    let mut s = String::new();
+    vfs.open_read(&file).unwrap().read_to_string(&mut s).unwrap();
+    assert_eq!(s, "over the wire");
+
+    vfs.set_mode(&file, 0o600).unwrap();
+    assert_eq!(vfs.stat(&file).unwrap().unix_mode.map(|m| m & 0o777), Some(0o600));
+
+    let renamed = dir.join("b.txt");
+    vfs.rename(&file, &renamed).unwrap();
+    fileZoom::vfs::remove_tree(vfs.as_ref(), &dir).unwrap();
+    assert!(vfs.stat(&dir).is_err());
+}
+
+/// Manual check against a real SMB server, e.g. a throwaway Samba
+/// container; the automated suite does not cover SMB. Run it with
+/// `cargo test -- --ignored smb` and
+/// `FILEZOOM_SMB_URL=smb://host[:port]/share/writable/dir` and, for
+/// non-guest shares, `FILEZOOM_SMB_USER` / `FILEZOOM_SMB_PASSWORD`.
+#[cfg(feature = "smb")]
+#[test]
+#[ignore = "needs an SMB server; set FILEZOOM_SMB_URL"]
+fn smb_roundtrip_against_real_server() {
+    use fileZoom::app::settings::write_settings::{Settings, SmbCredentials};
+
+    let url = std::env::var("FILEZOOM_SMB_URL").expect("FILEZOOM_SMB_URL=smb://host/share/dir names the server to test against");
+    let mut settings = Settings::default();
+    if let Ok(user) = std::env::var("FILEZOOM_SMB_USER") {
+        let host = fileZoom::vfs::RemoteUrl::parse(&url).expect("valid url").host;
+        let password = std::env::var("FILEZOOM_SMB_PASSWORD").unwrap_or_default();
+        settings.smb_hosts.insert(host, SmbCredentials { user, password, domain: None });
+    }
+    let (vfs, root) = fileZoom::vfs::open_location(&url, &settings).expect("connect");
+    assert_eq!(vfs.scheme(), "smb");
+
+    let dir = root.join(format!("filezoom_test_{}", std::process::id()));
+    vfs.mkdir(&dir).unwrap();
+    let file = dir.join("a.txt");
+    vfs.open_write(&file).unwrap().write_all(b"over the wire").unwrap();
+
+    let names: Vec<String> = vfs.list(&dir).unwrap().into_iter().map(|e| e.name).collect();
+    assert_eq!(names, vec!["a.txt".to_string()]);
+    let mut s = String::new();
+    vfs.open_read(&file).unwrap().read_to_string(&mut s).unwrap();
+    assert_eq!(s, "over the wire");
+    assert_eq!(vfs.stat(&file).unwrap().size, 13);
+
+    let renamed = dir.join("b.txt");
+    vfs.rename(&file, &renamed).unwrap();
+    assert!(vfs.stat(&file).is_err());
+    fileZoom::vfs::remove_tree(vfs.as_ref(), &dir).unwrap();
+    assert_eq!(vfs.stat(&dir).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
@@ -0,0 +1,85 @@
+mod common;
+use common::select_entry;
+
+use assert_fs::prelude::*;
+use fileZoom::app::{App, Mode, StartOptions};
+use fileZoom::input::KeyCode;
+use fileZoom::runner::handlers;
+use fileZoom::runner::progress::OperationDecision;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use zip::write::SimpleFileOptions;
+
+fn build_zip(path: &Path) {
+    let mut w = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    let opts = SimpleFileOptions::default().unix_permissions(0o600);
+    w.start_file("docs/readme.txt", opts).unwrap();
+    w.write_all(b"hello from the archive").unwrap();
+    w.start_file("notes.txt", opts).unwrap();
+    w.write_all(b"archived notes").unwrap();
+    w.finish().unwrap();
+}
+
+#[test]
+fn enter_zip_preview_copy_out_and_leave() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let left = tmp.child("left");
+    let right = tmp.child("right");
+    left.create_dir_all().unwrap();
+    right.create_dir_all().unwrap();
+    right.child("notes.txt").write_str("local notes").unwrap();
+    let archive = left.path().join("bundle.zip");
+    build_zip(&archive);
+
+    let opts = StartOptions { start_dir: Some(left.path().to_path_buf()), ..Default::default() };
+    let mut app = App::with_options(&opts).unwrap();
+    app.right.cwd = right.path().to_path_buf();
+    app.refresh().unwrap();
+
+    // Enter opens the archive in place.
+    select_entry(&mut app, "bundle.zip");
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    assert_eq!(app.left.vfs.scheme(), "zip");
+    assert_eq!(app.left.cwd, archive);
+    let names: Vec<&str> = app.left.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["docs", "notes.txt"]);
+    let notes = app.left.entries.iter().find(|e| e.name == "notes.txt").unwrap();
+    assert_eq!(notes.size, 14);
+    assert_eq!(notes.unix_mode.map(|m| m & 0o777), Some(0o600));
+
+    // Members preview like regular files.
+    handlers::handle_key(&mut app, KeyCode::Enter, 10).unwrap();
+    select_entry(&mut app, "readme.txt");
+    app.update_preview_for(app.active);
+    assert_eq!(app.left.preview, "hello from the archive");
+
+    // Back at the archive root, copy a member out; the existing file in the
+    // other panel raises the usual conflict prompt.
+    handlers::handle_key(&mut app, KeyCode::Backspace, 10).unwrap();
+    assert_eq!(app.left.cwd, archive);
+    select_entry(&mut app, "notes.txt");
+    handlers::handle_key(&mut app, KeyCode::F(5), 10).unwrap();
+    assert!(matches!(app.mode, Mode::Progress { .. }));
+    let rx = app.op_progress_rx.take().expect("progress channel");
+    let mut saw_conflict = false;
+    while let Ok(upd) = rx.recv_timeout(Duration::from_secs(2)) {
+        if upd.conflict.is_some() {
+            saw_conflict = true;
+            app.op_decision_tx.as_ref().unwrap().send(OperationDecision::Overwrite).unwrap();
+        }
+        if upd.done {
+            assert!(upd.error.is_none(), "copy failed: {:?}", upd.error);
+            break;
+        }
+    }
+    assert!(saw_conflict, "expected a conflict for the existing notes.txt");
+    right.child("notes.txt").assert("archived notes");
+    app.mode = Mode::Normal;
+
+    // Going up from the archive root returns to the local filesystem.
+    handlers::handle_key(&mut app, KeyCode::Backspace, 10).unwrap();
+    assert!(app.left.vfs.is_local());
+    assert_eq!(app.left.cwd, left.path());
+    assert!(app.left.entries.iter().any(|e| e.name == "bundle.zip"));
+}